@@ -0,0 +1,14 @@
+-- Transactional outbox for indexer events
+-- Rows are written in the same transaction that advances indexer_state.last_block
+-- and are published to Kafka (then marked sent) by a separate drain loop.
+CREATE TABLE IF NOT EXISTS event_outbox (
+    id BIGSERIAL PRIMARY KEY,
+    topic VARCHAR(255) NOT NULL,
+    kafka_key VARCHAR(255) NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    sent_at TIMESTAMP
+);
+
+-- Pending rows are drained in insertion order
+CREATE INDEX IF NOT EXISTS idx_event_outbox_pending ON event_outbox(id) WHERE sent_at IS NULL;
@@ -0,0 +1,4 @@
+-- Quality multiplier applied to an interaction's preference weight
+-- (e.g. repeated comments are down-weighted). NULL means unweighted (1.0).
+ALTER TABLE user_interactions
+  ADD COLUMN IF NOT EXISTS signal_quality REAL;
@@ -0,0 +1,25 @@
+-- Shares received by a recipient, kept so later engagement by the recipient
+-- with the same NFT can be credited back to the sharer
+CREATE TABLE IF NOT EXISTS content_shares (
+    id BIGSERIAL PRIMARY KEY,
+    sharer_address VARCHAR(42) NOT NULL,
+    recipient_address VARCHAR(42) NOT NULL,
+    nft_id UUID NOT NULL,
+    shared_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    engaged_at TIMESTAMP
+);
+
+-- Engagement lookups only care about shares not yet credited
+CREATE INDEX IF NOT EXISTS idx_content_shares_pending
+    ON content_shares(recipient_address, nft_id) WHERE engaged_at IS NULL;
+
+-- Sharer -> recipient relationship strength (0.0 to 1.0)
+CREATE TABLE IF NOT EXISTS share_affinity (
+    sharer_address VARCHAR(42) NOT NULL,
+    recipient_address VARCHAR(42) NOT NULL,
+    shares INTEGER NOT NULL DEFAULT 0,
+    engaged_shares INTEGER NOT NULL DEFAULT 0,
+    affinity REAL NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (sharer_address, recipient_address)
+);
@@ -0,0 +1,8 @@
+-- On-chain daily post/follow limits from DailyLimitsUpdated, used to
+-- calibrate per-user interaction caps in preference learning
+CREATE TABLE IF NOT EXISTS daily_limits (
+    contract_address VARCHAR(42) PRIMARY KEY,
+    max_posts BIGINT NOT NULL,
+    max_follows BIGINT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
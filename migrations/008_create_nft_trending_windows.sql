@@ -0,0 +1,12 @@
+-- Short-window trending variants, kept apart from nft_features.trending_score:
+-- hot_score is engagement in the latest window, rising_score its growth over
+-- the window before
+CREATE TABLE IF NOT EXISTS nft_trending_windows (
+    nft_id UUID PRIMARY KEY,
+    hot_score REAL NOT NULL DEFAULT 0,
+    rising_score REAL NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_nft_trending_windows_hot ON nft_trending_windows(hot_score DESC);
+CREATE INDEX IF NOT EXISTS idx_nft_trending_windows_rising ON nft_trending_windows(rising_score DESC);
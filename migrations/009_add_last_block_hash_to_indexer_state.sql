@@ -0,0 +1,3 @@
+-- Canonical hash of last_block, compared against the chain on the next poll
+-- to detect reorgs
+ALTER TABLE indexer_state ADD COLUMN IF NOT EXISTS last_block_hash VARCHAR(66);
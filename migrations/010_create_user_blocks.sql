@@ -0,0 +1,8 @@
+-- Users blocked from UserBlocked events; their content is kept out of the
+-- blocker's enhanced feed until a UserUnblocked event removes the row
+CREATE TABLE IF NOT EXISTS user_blocks (
+    blocker_address VARCHAR(42) NOT NULL,
+    blocked_address VARCHAR(42) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (blocker_address, blocked_address)
+);
@@ -0,0 +1,13 @@
+-- On-chain follows from Follow/Unfollow events, keyed by address. Unfollows
+-- flip is_active rather than deleting so a replayed Follow stays idempotent
+CREATE TABLE IF NOT EXISTS user_follows (
+    follower_address VARCHAR(42) NOT NULL,
+    target_address VARCHAR(42) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (follower_address, target_address)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_follows_active
+    ON user_follows(follower_address) WHERE is_active = true;
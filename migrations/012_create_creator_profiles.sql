@@ -0,0 +1,10 @@
+-- Creator display info from ProfileUpdated/ProfileUpdatedExtended events,
+-- joined onto feed items so clients don't need a separate profile lookup
+CREATE TABLE IF NOT EXISTS creator_profiles (
+    address VARCHAR(42) PRIMARY KEY,
+    username TEXT,
+    bio TEXT,
+    website TEXT,
+    profile_hash TEXT,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,17 @@
+-- Per-user tracking opt-out. Opted-out users' interactions skip
+-- user_interactions and user_preferences and only bump the anonymous
+-- per-NFT counters below
+CREATE TABLE IF NOT EXISTS user_privacy (
+    user_address VARCHAR(42) PRIMARY KEY,
+    opt_out_tracking BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Global engagement counts per NFT and interaction type, with no user attached
+CREATE TABLE IF NOT EXISTS nft_engagement_counts (
+    nft_id UUID NOT NULL,
+    interaction_type VARCHAR(20) NOT NULL,
+    count BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (nft_id, interaction_type)
+);
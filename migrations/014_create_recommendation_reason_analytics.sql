@@ -0,0 +1,13 @@
+-- How often each primary reason drives a generated feed: one row per cached
+-- generation with counts keyed by reason (tag_match, trending, discovery, ...)
+CREATE TABLE IF NOT EXISTS recommendation_reason_analytics (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_address VARCHAR(42) NOT NULL,
+    feed_type VARCHAR(50) NOT NULL,
+    reason_counts JSONB NOT NULL,
+    total INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_recommendation_reason_analytics_created
+    ON recommendation_reason_analytics(created_at);
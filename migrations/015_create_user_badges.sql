@@ -0,0 +1,13 @@
+-- Badges from BadgeAwarded/BadgeRemoved events. Removals flip is_active so an
+-- award and its removal net out; active badges lift the holder's creator quality
+CREATE TABLE IF NOT EXISTS user_badges (
+    user_address VARCHAR(42) NOT NULL,
+    badge VARCHAR(100) NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_address, badge)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_badges_active
+    ON user_badges(user_address) WHERE is_active = true;
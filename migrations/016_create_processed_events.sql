@@ -0,0 +1,10 @@
+-- Events the processor has already applied, keyed by their position on chain.
+-- Claimed in the same transaction as the event's interaction writes so a
+-- redelivered Kafka message is skipped instead of counted twice
+CREATE TABLE IF NOT EXISTS processed_events (
+    transaction_hash VARCHAR(66) NOT NULL,
+    log_index BIGINT NOT NULL,
+    event_type VARCHAR(100) NOT NULL,
+    processed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (transaction_hash, log_index)
+);
@@ -0,0 +1,4 @@
+-- Cached taste vector over the canonical type/tag space, refreshed when a
+-- user's preferences move noticeably. NULL until their next interaction.
+ALTER TABLE user_preferences
+  ADD COLUMN IF NOT EXISTS taste_vector REAL[];
@@ -0,0 +1,4 @@
+-- Cache pruning evicts the least recently computed entries once the table
+-- is over its configured size
+CREATE INDEX IF NOT EXISTS idx_recommendation_cache_computed
+  ON recommendation_cache(computed_at);
@@ -0,0 +1,4 @@
+-- Set by UserVerified events; verified senders' tips weigh more toward the
+-- recipient's creator affinity
+ALTER TABLE creator_profiles
+  ADD COLUMN IF NOT EXISTS is_verified BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,5 @@
+-- Strongest signal class (purchase, engagement or view) behind each content
+-- type affinity, so affinities built on purchases decay slower than ones
+-- built on views
+ALTER TABLE user_preferences
+  ADD COLUMN IF NOT EXISTS affinity_signals JSONB NOT NULL DEFAULT '{}'::jsonb;
@@ -0,0 +1,10 @@
+-- Progress of block range backfills, so an interrupted reindex resumes
+-- after its last completed chunk instead of starting over
+CREATE TABLE IF NOT EXISTS backfill_checkpoints (
+    contract_address VARCHAR(42) NOT NULL,
+    from_block BIGINT NOT NULL,
+    to_block BIGINT NOT NULL,
+    completed_through BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (contract_address, from_block, to_block)
+);
@@ -0,0 +1,4 @@
+-- Cached feeds are keyed per content type filter (e.g. 'personalized:music'),
+-- which can outgrow the original 20 characters
+ALTER TABLE recommendation_cache
+  ALTER COLUMN feed_type TYPE VARCHAR(50);
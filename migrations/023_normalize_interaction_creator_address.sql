@@ -0,0 +1,8 @@
+-- Creator addresses are stored lowercase so per-creator lookups can use a
+-- plain equality (and this index) instead of LOWER() on every row
+UPDATE user_interactions
+  SET nft_creator_address = LOWER(nft_creator_address)
+  WHERE nft_creator_address <> LOWER(nft_creator_address);
+
+CREATE INDEX IF NOT EXISTS idx_user_interactions_creator
+  ON user_interactions(nft_creator_address);
@@ -0,0 +1,7 @@
+-- Replay reads stored events by the block number in their payload
+CREATE INDEX IF NOT EXISTS idx_event_outbox_block_number
+  ON event_outbox(((payload->>'block_number')::bigint));
+
+-- Retention deletes sent rows by age
+CREATE INDEX IF NOT EXISTS idx_event_outbox_sent_at
+  ON event_outbox(sent_at) WHERE sent_at IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Purchases already recorded, keyed by token/buyer/block. A single buy can
+-- emit both PurchaseProcessed and ContentCopyMinted; the first one to claim
+-- the purchase, in the same transaction as its interaction, records it
+CREATE TABLE IF NOT EXISTS processed_purchases (
+    contract_address VARCHAR(42) NOT NULL,
+    token_id VARCHAR(78) NOT NULL,
+    buyer_address VARCHAR(42) NOT NULL,
+    block_number BIGINT NOT NULL,
+    processed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (contract_address, token_id, buyer_address, block_number)
+);
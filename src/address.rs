@@ -0,0 +1,65 @@
+//! Address normalization
+//!
+//! Addresses arrive in whatever case the client or contract used (e.g. EIP-55
+//! checksums) but are stored and looked up lowercase. Everything that keys on
+//! an address goes through [`normalize_address`] so malformed input is
+//! rejected rather than stored.
+
+use crate::error::{Error, Result};
+use ethers::types::Address;
+
+/// Validate an address and return it in the canonical lowercase form used
+/// for storage and lookups, whatever case (e.g. EIP-55 checksum) it came in
+pub fn normalize_address(addr: &str) -> Result<String> {
+    let trimmed = addr.trim();
+    let parsed: Option<Address> = trimmed
+        .starts_with("0x")
+        .then(|| trimmed.parse().ok())
+        .flatten();
+    match parsed {
+        Some(address) => Ok(format!("{:?}", address)),
+        None => Err(Error::InvalidAddress {
+            address: addr.to_string(),
+        }),
+    }
+}
+
+/// A random well-formed address, so database tests don't share rows
+#[cfg(test)]
+pub(crate) fn random_address() -> String {
+    format!(
+        "0x{}",
+        &uuid::Uuid::new_v4().simple().to_string()[..8].repeat(5)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_address() {
+        let lower = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+        assert_eq!(
+            normalize_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap(),
+            lower
+        );
+        assert_eq!(normalize_address(lower).unwrap(), lower);
+
+        for invalid in [
+            "",
+            "0xabc",
+            "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+            "0xzzaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        ] {
+            assert!(
+                matches!(
+                    normalize_address(invalid),
+                    Err(Error::InvalidAddress { .. })
+                ),
+                "{:?} should be rejected",
+                invalid
+            );
+        }
+    }
+}
@@ -4,21 +4,39 @@
 
 use anyhow::Result;
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
-    Router,
+    error_handling::HandleErrorLayer,
+    extract::{ConnectInfo, Path, Query, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::{from_fn_with_state, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post, put},
+    BoxError, Router,
 };
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tower::limit::GlobalConcurrencyLimitLayer;
+use tower::ServiceBuilder;
 use tower_http::cors::{Any, CorsLayer};
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::address::normalize_address;
+use crate::config::ApiConfig;
+use crate::database::PoolStats;
+use crate::error::Error;
+use crate::event_processor::{DeadLetter, ProcessorCounters};
+use crate::indexer::IndexerCounters;
+use crate::kafka::{KafkaProducer, PeekedMessage, ProducerStats, TopicPeek};
 
 use crate::recommendation::{
-    engine::RecommendationEngine,
+    engine::{ExplainedNft, FeedCursor, RecommendationEngine, TrendingMode},
     preferences::{record_interaction, InteractionEvent, InteractionType},
+    privacy::{get_engagement_counts, set_privacy, PrivacySettings},
     ScoredNft,
 };
 
@@ -26,6 +44,138 @@ use crate::recommendation::{
 pub struct AppState {
     pub pool: PgPool,
     pub engine: RecommendationEngine,
+    /// Process start, for `/info` uptime
+    pub started_at: Instant,
+    /// Configured chain ID, reported by `/info`
+    pub chain_id: u64,
+    /// Shared producer, for `/metrics`
+    pub producer: KafkaProducer,
+    /// Engine database holding `indexer_state`, for `/metrics`
+    pub indexer_pool: PgPool,
+    /// Bearer token for admin routes; `None` disables them
+    pub admin_token: Option<String>,
+    /// Cleared while the Elixir database is unreachable; routes that read it
+    /// answer 503 until it comes back
+    pub database_ready: Arc<AtomicBool>,
+    /// Per-user limit on `/api/v1/interactions/view`
+    pub view_limiter: ViewRateLimiter,
+    /// Reader for `/api/v1/admin/deadletter`; `None` when dead-lettering is off
+    pub dead_letters: Option<Arc<dyn TopicPeek>>,
+    /// Budget for all of `/feeds/home`; sections not ready by then fall back
+    pub home_feed_timeout: Duration,
+    /// Per-client request budget for everything outside `/health`
+    pub rate_limiter: RateLimiter,
+}
+
+/// Token-bucket limit on API requests per client IP and, optionally, per
+/// user address in the path. Each bucket holds up to `burst` tokens and
+/// refills at `rate_per_sec`; a request spends one.
+pub struct RateLimiter {
+    rate_per_sec: f64,
+    burst: f64,
+    per_address: bool,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// How often buckets that have refilled are dropped
+const BUCKET_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+impl RateLimiter {
+    /// Allow bursts of `burst` requests refilling at `rate_per_sec`; a rate
+    /// of 0 disables the limit
+    pub fn new(rate_per_sec: f64, burst: u32, per_address: bool) -> Self {
+        Self {
+            rate_per_sec,
+            burst: f64::from(burst.max(1)),
+            per_address,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Spend a token from `key`'s bucket, or reject the request with the
+    /// seconds until one is available
+    fn admit(&self, key: &str) -> Result<(), Error> {
+        if self.rate_per_sec <= 0.0 {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        let bucket = buckets.entry(key.to_string()).or_insert(TokenBucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * self.rate_per_sec;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens < 1.0 {
+            let wait = (1.0 - bucket.tokens) / self.rate_per_sec;
+            return Err(Error::TooManyRequests {
+                retry_after_secs: (wait.ceil() as u64).max(1),
+            });
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+
+    /// Drop buckets that have refilled to `burst`; their clients would get a
+    /// full bucket again anyway
+    fn sweep_idle(&self) {
+        let now = Instant::now();
+        let (rate, burst) = (self.rate_per_sec, self.burst);
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        buckets
+            .retain(|_, b| b.tokens + now.duration_since(b.updated).as_secs_f64() * rate < burst);
+    }
+}
+
+/// Fixed-window limit on recorded views per user. Counts reset with each
+/// window, so memory is bounded by the users active within one.
+pub struct ViewRateLimiter {
+    max_per_window: u32,
+    window: Duration,
+    counts: Mutex<(Instant, HashMap<String, u32>)>,
+}
+
+impl ViewRateLimiter {
+    /// Allow `max_per_window` views per user per `window`; 0 disables the limit
+    pub fn new(max_per_window: u32, window: Duration) -> Self {
+        Self {
+            max_per_window,
+            window,
+            counts: Mutex::new((Instant::now(), HashMap::new())),
+        }
+    }
+
+    /// Count a view by `user`, or reject it until the window rolls over
+    fn admit(&self, user: &str) -> Result<(), Error> {
+        if self.max_per_window == 0 {
+            return Ok(());
+        }
+
+        let mut counts = self.counts.lock().unwrap_or_else(PoisonError::into_inner);
+        let (started, users) = &mut *counts;
+        if started.elapsed() >= self.window {
+            *started = Instant::now();
+            users.clear();
+        }
+
+        let count = users.entry(user.to_lowercase()).or_default();
+        if *count >= self.max_per_window {
+            let remaining = self.window.saturating_sub(started.elapsed());
+            return Err(Error::TooManyRequests {
+                retry_after_secs: remaining.as_secs().max(1),
+            });
+        }
+        *count += 1;
+        Ok(())
+    }
 }
 
 /// Query params for feed endpoints
@@ -36,6 +186,25 @@ pub struct FeedQuery {
     #[serde(default)]
     pub offset: usize,
     pub contract_type: Option<String>,
+    /// Keyset cursor from a previous page's `next_cursor` (enhanced feed
+    /// only). Present but empty requests the first page; replaces `offset`.
+    pub cursor: Option<String>,
+    /// Include each item's top contributing `reasons`
+    #[serde(default)]
+    pub verbose: bool,
+}
+
+/// Query params for the trending endpoint
+#[derive(Debug, Deserialize)]
+pub struct TrendingQuery {
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub offset: usize,
+    pub contract_type: Option<String>,
+    /// `trending` (default), `hot` or `rising`
+    #[serde(default)]
+    pub mode: TrendingMode,
 }
 
 /// Query params for recommendations endpoint
@@ -46,18 +215,65 @@ pub struct RecommendationsQuery {
     pub contract_type: Option<String>,
     #[serde(default)]
     pub exclude_seen: bool,
+    /// Include each item's top contributing `reasons`
+    #[serde(default)]
+    pub verbose: bool,
+}
+
+/// Query params for the explain endpoint
+#[derive(Debug, Deserialize)]
+pub struct ExplainQuery {
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+/// Query params for the home feed, one limit per section
+#[derive(Debug, Deserialize)]
+pub struct HomeFeedQuery {
+    #[serde(default = "default_limit")]
+    pub personalized_limit: usize,
+    #[serde(default = "default_section_limit")]
+    pub trending_limit: usize,
+    #[serde(default = "default_section_limit")]
+    pub following_limit: usize,
+}
+
+fn default_section_limit() -> usize {
+    10
 }
 
 fn default_limit() -> usize {
     20
 }
 
+/// Largest page the following feed serves
+const MAX_FEED_LIMIT: usize = 100;
+
 /// Response for feed endpoints
 #[derive(Debug, Serialize)]
 pub struct FeedResponse {
     pub items: Vec<ScoredNft>,
     pub total: usize,
     pub has_more: bool,
+    /// Cursor for the next page, when paging by cursor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Response for `/feeds/home`
+#[derive(Debug, Serialize)]
+pub struct HomeFeedResponse {
+    pub personalized: HomeFeedSection,
+    pub trending: HomeFeedSection,
+    pub following: HomeFeedSection,
+}
+
+/// One section of the home feed
+#[derive(Debug, Serialize)]
+pub struct HomeFeedSection {
+    pub items: Vec<ScoredNft>,
+    /// Set when the feed failed or ran out of time; `items` is then empty
+    pub degraded: bool,
 }
 
 /// Request body for recording interactions
@@ -73,28 +289,162 @@ pub struct InteractionRequest {
     pub nft_tags: Option<Vec<String>>,
 }
 
+/// Request body for `/api/v1/interactions/view`
+#[derive(Debug, Deserialize)]
+pub struct ViewRequest {
+    pub user_address: String,
+    pub nft_id: String,
+    pub view_duration_ms: Option<i64>,
+    pub source: Option<String>,
+}
+
+/// Recommendation engine kill switch state
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EngineStatus {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+/// Most dead letters a single peek returns
+const MAX_DEAD_LETTER_PEEK: usize = 100;
+/// Characters of the original payload shown per dead letter
+const PAYLOAD_PREVIEW_CHARS: usize = 256;
+
+/// A dead-lettered message. Fields from the dead-letter record are `None`
+/// if the message on the topic isn't one.
+#[derive(Debug, Serialize)]
+pub struct DeadLetterEntry {
+    /// Where the failed message was originally consumed from
+    pub topic: Option<String>,
+    pub partition: Option<i32>,
+    pub offset: Option<i64>,
+    pub error: Option<String>,
+    pub attempts: Option<u32>,
+    pub failed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Start of the original payload
+    pub payload_preview: String,
+    /// Position on the dead-letter topic itself
+    pub dlq_partition: i32,
+    pub dlq_offset: i64,
+}
+
+impl From<PeekedMessage> for DeadLetterEntry {
+    fn from(message: PeekedMessage) -> Self {
+        let preview = |payload: &str| payload.chars().take(PAYLOAD_PREVIEW_CHARS).collect();
+        match serde_json::from_slice::<DeadLetter>(&message.payload) {
+            Ok(dead) => Self {
+                topic: Some(dead.topic),
+                partition: Some(dead.partition),
+                offset: Some(dead.offset),
+                error: Some(dead.error),
+                attempts: Some(dead.attempts),
+                failed_at: Some(dead.failed_at),
+                payload_preview: preview(&dead.payload),
+                dlq_partition: message.partition,
+                dlq_offset: message.offset,
+            },
+            Err(_) => Self {
+                topic: None,
+                partition: None,
+                offset: None,
+                error: None,
+                attempts: None,
+                failed_at: None,
+                payload_preview: preview(&String::from_utf8_lossy(&message.payload)),
+                dlq_partition: message.partition,
+                dlq_offset: message.offset,
+            },
+        }
+    }
+}
+
 /// Health check response
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
+    /// Whether the Kafka brokers answered (always true with Kafka disabled)
+    pub kafka_reachable: bool,
+}
+
+/// Build and runtime details for fleet debugging
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InfoResponse {
+    pub version: String,
+    /// Commit the binary was built from (`GIT_SHA` at build time)
+    pub git_sha: String,
+    pub uptime_secs: f64,
+    pub chain_id: u64,
 }
 
 /// Start the API server
-pub async fn start_server(pool: PgPool, port: u16) -> Result<()> {
-    let engine = RecommendationEngine::new(pool.clone());
+#[allow(clippy::too_many_arguments)]
+pub async fn start_server(
+    pool: PgPool,
+    engine: RecommendationEngine,
+    config: ApiConfig,
+    chain_id: u64,
+    started_at: Instant,
+    producer: KafkaProducer,
+    indexer_pool: PgPool,
+    database_ready: Arc<AtomicBool>,
+    dead_letters: Option<Arc<dyn TopicPeek>>,
+) -> Result<()> {
+    let admin_token = config.admin_token.clone();
 
-    let state = Arc::new(AppState { pool, engine });
+    let state = Arc::new(AppState {
+        pool,
+        engine,
+        started_at,
+        chain_id,
+        producer,
+        indexer_pool,
+        admin_token,
+        database_ready,
+        view_limiter: ViewRateLimiter::new(
+            config.view_rate_limit_per_minute,
+            Duration::from_secs(60),
+        ),
+        dead_letters,
+        home_feed_timeout: config.home_feed_timeout,
+        rate_limiter: RateLimiter::new(
+            config.rate_limit_rps,
+            config.rate_limit_burst,
+            config.rate_limit_per_address,
+        ),
+    });
+
+    if config.rate_limit_rps > 0.0 {
+        let state = Arc::downgrade(&state);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(BUCKET_SWEEP_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Some(state) = state.upgrade() else { break };
+                state.rate_limiter.sweep_idle();
+            }
+        });
+    }
 
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
         .allow_headers(Any);
 
-    let app = Router::new()
-        // Health check
+    // Route groups get their own timeouts: feeds are allowed longer than the
+    // default, health checks much less.
+    let health = Router::new()
         .route("/health", get(health_check))
-        // Feed endpoints
+        .route("/ready", get(readiness_check))
+        .route("/info", get(info))
+        .route("/metrics", get(metrics));
+
+    let mut feeds = Router::new()
         .route("/api/v1/feed/:user_address", get(get_following_feed))
         .route(
             "/api/v1/enhanced-feed/:user_address",
@@ -105,56 +455,416 @@ pub async fn start_server(pool: PgPool, port: u16) -> Result<()> {
             get(get_recommendations),
         )
         .route("/api/v1/trending", get(get_trending))
+        .route("/feeds/home/:user_address", get(get_home_feed));
+    if config.debug_explain_enabled {
+        feeds = feeds.route("/debug/explain/:user_address", get(explain_recommendations));
+    }
+    let feeds = feeds
+        .route_layer(from_fn_with_state(state.clone(), require_database))
+        .route_layer(from_fn_with_state(state.clone(), rate_limit));
+
+    let other = Router::new()
         // Interaction tracking
         .route("/api/v1/interactions", post(record_user_interaction))
+        .route("/api/v1/interactions/view", post(record_view))
         // User preferences
         .route(
             "/api/v1/preferences/:user_address",
             get(get_user_preferences),
         )
+        // Anonymous engagement totals
+        .route("/api/v1/nfts/:nft_id/engagement", get(get_nft_engagement))
+        // Tracking opt-out, set on the user's behalf by the backend
+        .route("/api/v1/privacy/:user_address", put(set_user_privacy))
+        .route_layer(from_fn_with_state(state.clone(), require_database))
+        // Operator controls
+        .route(
+            "/api/v1/admin/engine",
+            get(get_engine_status).put(set_engine_status),
+        )
+        .route("/api/v1/admin/deadletter", get(peek_dead_letters))
+        .route_layer(from_fn_with_state(state.clone(), rate_limit));
+
+    // Health checks stay outside the concurrency limit so probes still
+    // answer while the API is shedding load
+    let limited = Router::new()
+        .merge(with_timeout(feeds, config.feed_timeout))
+        .merge(with_timeout(other, config.request_timeout));
+
+    let app = Router::new()
+        .merge(with_timeout(health, config.health_timeout))
+        .merge(with_concurrency_limit(
+            limited,
+            config.max_concurrent_requests,
+        ))
         .layer(cors)
         .with_state(state);
 
-    let addr = format!("0.0.0.0:{}", port);
+    let addr = format!("0.0.0.0:{}", config.port);
     info!("🚀 Starting recommendation API server on {}", addr);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
 
-/// Health check endpoint
-async fn health_check() -> Json<HealthResponse> {
-    Json(HealthResponse {
-        status: "healthy".to_string(),
+/// Apply a request timeout to every route in `router`, answering with
+/// `Error::Timeout` (504) when it elapses
+fn with_timeout<S>(router: Router<S>, timeout: Duration) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(move |err: BoxError| async move {
+                timeout_error_response(err, timeout)
+            }))
+            .timeout(timeout),
+    )
+}
+
+/// Reject requests with 503 while the Elixir database is unreachable
+async fn require_database(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.database_ready.load(Ordering::Relaxed) {
+        return Error::ServiceUnavailable {
+            service: "database",
+        }
+        .into_response();
+    }
+    next.run(request).await
+}
+
+/// Answer with 429 once the client's IP, or the user address in the path
+/// when per-address limiting is on, has spent its token bucket
+async fn rate_limit(
+    State(state): State<Arc<AppState>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    path: Option<Path<HashMap<String, String>>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = connect_info.map_or_else(
+        || "unknown".to_string(),
+        |ConnectInfo(addr)| addr.ip().to_string(),
+    );
+    if let Err(e) = state.rate_limiter.admit(&format!("ip:{}", ip)) {
+        return e.into_response();
+    }
+
+    if state.rate_limiter.per_address {
+        let address =
+            path.and_then(|Path(params)| params.get("user_address").map(|a| a.to_lowercase()));
+        if let Some(address) = address {
+            if let Err(e) = state.rate_limiter.admit(&format!("address:{}", address)) {
+                return e.into_response();
+            }
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Shed requests beyond `max` in flight across all of `router`'s routes with
+/// 503 rather than queueing them; 0 leaves the router unlimited
+fn with_concurrency_limit<S>(router: Router<S>, max: usize) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    if max == 0 {
+        return router;
+    }
+
+    router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(|err: BoxError| async move {
+                overload_error_response(err)
+            }))
+            .load_shed()
+            .layer(GlobalConcurrencyLimitLayer::new(max)),
+    )
+}
+
+fn overload_error_response(err: BoxError) -> Response {
+    if err.is::<tower::load_shed::error::Overloaded>() {
+        Error::ServiceUnavailable { service: "api" }.into_response()
+    } else {
+        error!("Unhandled middleware error: {:?}", err);
+        Error::Internal { source: Some(err) }.into_response()
+    }
+}
+
+fn timeout_error_response(err: BoxError, timeout: Duration) -> Response {
+    if err.is::<tower::timeout::error::Elapsed>() {
+        Error::Timeout {
+            timeout_ms: timeout.as_millis() as u64,
+        }
+        .into_response()
+    } else {
+        error!("Unhandled middleware error: {:?}", err);
+        Error::Internal { source: Some(err) }.into_response()
+    }
+}
+
+/// Build a feed response, filling in creator display names
+/// Verbose reason lists are dropped unless `verbose` is set, so the default
+/// payload stays unchanged
+async fn feed_response(
+    state: &AppState,
+    mut items: Vec<ScoredNft>,
+    total: usize,
+    has_more: bool,
+    verbose: bool,
+) -> Json<FeedResponse> {
+    if !verbose {
+        items.iter_mut().for_each(|item| item.reasons.clear());
+    }
+    state.engine.apply_creator_profiles(&mut items);
+    Json(FeedResponse {
+        items,
+        total,
+        has_more,
+        next_cursor: None,
+    })
+}
+
+/// Health check endpoint. Always 200 so a broker outage doesn't get the
+/// process restarted; `kafka_reachable` reports the broker probe.
+async fn health_check(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
+    Json(health_response(&state).await)
+}
+
+/// Readiness check; 503 while the Kafka brokers are unreachable
+async fn readiness_check(State(state): State<Arc<AppState>>) -> (StatusCode, Json<HealthResponse>) {
+    let health = health_response(&state).await;
+    let code = if health.kafka_reachable {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (code, Json(health))
+}
+
+async fn health_response(state: &AppState) -> HealthResponse {
+    let kafka_reachable = state.producer.is_healthy();
+    HealthResponse {
+        status: if kafka_reachable {
+            "healthy"
+        } else {
+            "degraded"
+        }
+        .to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
+        kafka_reachable,
+    }
+}
+
+/// Version, build commit, uptime and chain
+async fn info(State(state): State<Arc<AppState>>) -> Json<InfoResponse> {
+    Json(InfoResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: option_env!("GIT_SHA").unwrap_or("unknown").to_string(),
+        uptime_secs: state.started_at.elapsed().as_secs_f64(),
+        chain_id: state.chain_id,
     })
 }
 
-/// Get following feed - NFTs from creators user follows
+/// Last indexed block for one contract, from `indexer_state`
+#[derive(Debug, sqlx::FromRow)]
+pub struct IndexerProgress {
+    pub contract_address: String,
+    pub contract_type: String,
+    pub last_block: i64,
+}
+
+/// Producer counters and indexer progress in Prometheus text format
+async fn metrics(State(state): State<Arc<AppState>>) -> Response {
+    let indexers = sqlx::query_as::<_, IndexerProgress>(
+        "SELECT contract_address, contract_type, last_block FROM indexer_state ORDER BY contract_type",
+    )
+    .fetch_all(&state.indexer_pool)
+    .await
+    .unwrap_or_else(|e| {
+        error!("Failed to read indexer state for metrics: {:?}", e);
+        Vec::new()
+    });
+
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        render_metrics(
+            &state.producer.stats(),
+            &ProcessorCounters::current(),
+            &IndexerCounters::current(),
+            &indexers,
+            &[
+                ("recommendations", PoolStats::of(&state.pool)),
+                ("engine", PoolStats::of(&state.indexer_pool)),
+            ],
+        ),
+    )
+        .into_response()
+}
+
+fn render_metrics(
+    stats: &ProducerStats,
+    processor: &ProcessorCounters,
+    indexer_counters: &IndexerCounters,
+    indexers: &[IndexerProgress],
+    pools: &[(&str, PoolStats)],
+) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    let mut metric = |name: &str, kind: &str, help: &str, value: u64| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        let _ = writeln!(out, "{} {}", name, value);
+    };
+    metric(
+        "kafka_messages_sent",
+        "counter",
+        "Messages delivered to Kafka",
+        stats.messages_sent,
+    );
+    metric(
+        "kafka_messages_failed",
+        "counter",
+        "Messages that failed delivery after all retries",
+        stats.messages_failed,
+    );
+    metric(
+        "kafka_bytes_sent",
+        "counter",
+        "Payload bytes delivered to Kafka",
+        stats.bytes_sent,
+    );
+    metric(
+        "kafka_in_flight",
+        "gauge",
+        "Messages awaiting delivery",
+        stats.in_flight,
+    );
+    metric(
+        "event_processor_message_timeouts",
+        "counter",
+        "Consumed messages whose processing timed out",
+        processor.message_timeouts,
+    );
+    metric(
+        "event_processor_timed_out_skips",
+        "counter",
+        "Messages skipped after timing out on every redelivery (no dead-letter topic)",
+        processor.timed_out_skips,
+    );
+    metric(
+        "event_processor_invalid_creator_mints",
+        "counter",
+        "Mints seen with a zero or malformed creator address",
+        processor.invalid_creator_mints,
+    );
+    metric(
+        "indexer_timestamp_divergences",
+        "counter",
+        "Events whose embedded timestamp diverged from their block time",
+        indexer_counters.timestamp_divergences,
+    );
+
+    let _ = writeln!(
+        out,
+        "# HELP indexer_last_block Last block indexed per contract"
+    );
+    let _ = writeln!(out, "# TYPE indexer_last_block gauge");
+    for indexer in indexers {
+        let _ = writeln!(
+            out,
+            "indexer_last_block{{contract_address=\"{}\",contract_type=\"{}\"}} {}",
+            escape_label(&indexer.contract_address),
+            escape_label(&indexer.contract_type),
+            indexer.last_block
+        );
+    }
+    let _ = writeln!(
+        out,
+        "# HELP indexer_blocks_behind Blocks the indexer trails the chain head"
+    );
+    let _ = writeln!(out, "# TYPE indexer_blocks_behind gauge");
+    for (name, behind) in &indexer_counters.blocks_behind {
+        let _ = writeln!(
+            out,
+            "indexer_blocks_behind{{indexer=\"{}\"}} {}",
+            escape_label(name),
+            behind
+        );
+    }
+
+    let mut pool_gauge = |name: &str, help: &str, value: &dyn Fn(&PoolStats) -> u64| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        for (pool, pool_stats) in pools {
+            let _ = writeln!(
+                out,
+                "{}{{pool=\"{}\"}} {}",
+                name,
+                escape_label(pool),
+                value(pool_stats)
+            );
+        }
+    };
+    pool_gauge("db_pool_size", "Connections open per pool", &|p| {
+        p.size as u64
+    });
+    pool_gauge("db_pool_idle", "Idle connections per pool", &|p| {
+        p.idle as u64
+    });
+    pool_gauge("db_pool_active", "Connections checked out per pool", &|p| {
+        p.active as u64
+    });
+    pool_gauge("db_pool_max", "Connection cap per pool", &|p| p.max as u64);
+    out
+}
+
+/// Escape a Prometheus label value
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Get following feed - NFTs from creators user follows, newest first with
+/// an engagement boost. Empty when the user follows no one.
 async fn get_following_feed(
     State(state): State<Arc<AppState>>,
     Path(user_address): Path<String>,
     Query(query): Query<FeedQuery>,
-) -> Result<Json<FeedResponse>, StatusCode> {
+) -> Result<Json<FeedResponse>, Response> {
+    validate_address(&user_address).map_err(IntoResponse::into_response)?;
+    let limit = query.limit.clamp(1, MAX_FEED_LIMIT);
+
     match state
         .engine
-        .get_following_feed(&user_address, query.limit, query.offset)
+        .get_following_feed(&user_address, limit, query.offset)
         .await
     {
         Ok(items) => {
             let total = items.len();
-            let has_more = total == query.limit;
-            Ok(Json(FeedResponse {
-                items,
-                total,
-                has_more,
-            }))
+            let has_more = total == limit;
+            Ok(feed_response(&state, items, total, has_more, query.verbose).await)
         }
         Err(e) => {
             error!("Failed to get following feed: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
         }
     }
 }
@@ -164,58 +874,150 @@ async fn get_enhanced_feed(
     State(state): State<Arc<AppState>>,
     Path(user_address): Path<String>,
     Query(query): Query<FeedQuery>,
-) -> Result<Json<FeedResponse>, StatusCode> {
-    // Check cache first
-    if let Ok(Some(cached)) = crate::recommendation::engine::get_cached_recommendations(
-        &state.pool,
-        &user_address,
-        "enhanced",
-    )
-    .await
-    {
+) -> Result<Json<FeedResponse>, Response> {
+    validate_address(&user_address).map_err(IntoResponse::into_response)?;
+    if let Some(cursor) = query.cursor.as_deref() {
+        return get_enhanced_feed_page(&state, &user_address, &query, cursor)
+            .await
+            .map_err(IntoResponse::into_response);
+    }
+
+    // Check cache first, unless the engine is off and the cache may hold
+    // the output being switched away from
+    let engine_enabled = state.engine.is_enabled();
+    let feed_type =
+        crate::recommendation::engine::cache_feed_type("enhanced", query.contract_type.as_deref());
+    let cached = if engine_enabled {
+        crate::recommendation::engine::get_cached_recommendations(
+            &state.pool,
+            &user_address,
+            &feed_type,
+        )
+        .await
+    } else {
+        Ok(None)
+    };
+    if let Ok(Some(cached)) = cached {
         let total = cached.len();
-        return Ok(Json(FeedResponse {
-            items: cached
-                .into_iter()
-                .skip(query.offset)
-                .take(query.limit)
-                .collect(),
+        let items = cached
+            .into_iter()
+            .skip(query.offset)
+            .take(query.limit)
+            .collect();
+        return Ok(feed_response(
+            &state,
+            items,
             total,
-            has_more: total > query.offset + query.limit,
-        }));
+            total > query.offset + query.limit,
+            query.verbose,
+        )
+        .await);
     }
 
+    // Concurrent cold-cache requests for the same page share one computation
+    let key = format!(
+        "{}:enhanced:{}:{}:{}",
+        user_address.to_lowercase(),
+        query.limit,
+        query.offset,
+        query.contract_type.as_deref().unwrap_or("")
+    );
+    let computed = state
+        .engine
+        .single_flight(&key, async {
+            let items = state
+                .engine
+                .get_enhanced_feed(
+                    &user_address,
+                    query.limit,
+                    query.offset,
+                    query.contract_type.as_deref(),
+                )
+                .await?;
+
+            // Cache for 5 minutes
+            if engine_enabled {
+                let _ = crate::recommendation::engine::cache_recommendations(
+                    &state.pool,
+                    &user_address,
+                    &feed_type,
+                    &items,
+                    5,
+                )
+                .await;
+            }
+            Ok(items)
+        })
+        .await;
+
+    match computed {
+        Ok(items) => {
+            let total = items.len();
+            let has_more = total == query.limit;
+            Ok(feed_response(&state, items, total, has_more, query.verbose).await)
+        }
+        Err(e) => {
+            error!(
+                "Failed to get enhanced feed, serving degraded feed: {:?}",
+                e
+            );
+            match state
+                .engine
+                .get_degraded_feed(
+                    &user_address,
+                    "enhanced",
+                    query.limit,
+                    query.offset,
+                    query.contract_type.as_deref(),
+                )
+                .await
+            {
+                Ok(items) => {
+                    let total = items.len();
+                    let has_more = total == query.limit;
+                    Ok(feed_response(&state, items, total, has_more, query.verbose).await)
+                }
+                Err(e) => {
+                    error!("Degraded feed failed: {:?}", e);
+                    Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+                }
+            }
+        }
+    }
+}
+
+/// One keyset-paged page of the enhanced feed. Not cached: pages are
+/// positioned by cursor, not by the user's cached ranking.
+async fn get_enhanced_feed_page(
+    state: &AppState,
+    user_address: &str,
+    query: &FeedQuery,
+    cursor: &str,
+) -> Result<Json<FeedResponse>, StatusCode> {
+    let cursor = match cursor {
+        "" => None,
+        raw => Some(FeedCursor::decode(raw).ok_or(StatusCode::BAD_REQUEST)?),
+    };
+
     match state
         .engine
-        .get_enhanced_feed(
-            &user_address,
+        .get_enhanced_feed_cursor(
+            user_address,
             query.limit,
-            query.offset,
             query.contract_type.as_deref(),
+            cursor.as_ref(),
         )
         .await
     {
-        Ok(items) => {
-            // Cache for 5 minutes
-            let _ = crate::recommendation::engine::cache_recommendations(
-                &state.pool,
-                &user_address,
-                "enhanced",
-                &items,
-                5,
-            )
-            .await;
-
+        Ok((items, next)) => {
             let total = items.len();
-            let has_more = total == query.limit;
-            Ok(Json(FeedResponse {
-                items,
-                total,
-                has_more,
-            }))
+            let mut response =
+                feed_response(state, items, total, next.is_some(), query.verbose).await;
+            response.next_cursor = next.map(|c| c.encode());
+            Ok(response)
         }
         Err(e) => {
-            error!("Failed to get enhanced feed: {:?}", e);
+            error!("Failed to get enhanced feed page: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
@@ -226,7 +1028,8 @@ async fn get_recommendations(
     State(state): State<Arc<AppState>>,
     Path(user_address): Path<String>,
     Query(query): Query<RecommendationsQuery>,
-) -> Result<Json<FeedResponse>, StatusCode> {
+) -> Result<Json<FeedResponse>, Response> {
+    validate_address(&user_address).map_err(IntoResponse::into_response)?;
     match state
         .engine
         .get_recommendations(
@@ -240,44 +1043,128 @@ async fn get_recommendations(
         Ok(items) => {
             let total = items.len();
             // For recommendations, we don't have a concept of "has_more" since it's personalized
-            Ok(Json(FeedResponse {
-                items,
-                total,
-                has_more: false,
-            }))
+            Ok(feed_response(&state, items, total, false, query.verbose).await)
         }
         Err(e) => {
             error!("Failed to get recommendations: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+}
+
+/// Personalized, trending and following feeds in one response, computed
+/// concurrently within `home_feed_timeout`
+async fn get_home_feed(
+    State(state): State<Arc<AppState>>,
+    Path(user_address): Path<String>,
+    Query(query): Query<HomeFeedQuery>,
+) -> Result<Json<HomeFeedResponse>, Response> {
+    validate_address(&user_address).map_err(IntoResponse::into_response)?;
+    let engine = &state.engine;
+    let mut home = home_feed(
+        state.home_feed_timeout,
+        engine.get_recommendations(&user_address, query.personalized_limit, None, false),
+        engine.get_trending(query.trending_limit, 0, None, TrendingMode::default()),
+        engine.get_following_feed(&user_address, query.following_limit, 0),
+    )
+    .await;
+
+    for section in [
+        &mut home.personalized,
+        &mut home.trending,
+        &mut home.following,
+    ] {
+        section
+            .items
+            .iter_mut()
+            .for_each(|item| item.reasons.clear());
+        engine.apply_creator_profiles(&mut section.items);
+    }
+    Ok(Json(home))
+}
+
+/// Run the three home feed sections side by side, each falling back to an
+/// empty, degraded section if it errors or isn't done within `timeout`
+async fn home_feed(
+    timeout: Duration,
+    personalized: impl std::future::Future<Output = Result<Vec<ScoredNft>>>,
+    trending: impl std::future::Future<Output = Result<Vec<ScoredNft>>>,
+    following: impl std::future::Future<Output = Result<Vec<ScoredNft>>>,
+) -> HomeFeedResponse {
+    let (personalized, trending, following) = tokio::join!(
+        home_feed_section("personalized", timeout, personalized),
+        home_feed_section("trending", timeout, trending),
+        home_feed_section("following", timeout, following),
+    );
+    HomeFeedResponse {
+        personalized,
+        trending,
+        following,
+    }
+}
+
+async fn home_feed_section(
+    name: &str,
+    timeout: Duration,
+    feed: impl std::future::Future<Output = Result<Vec<ScoredNft>>>,
+) -> HomeFeedSection {
+    match tokio::time::timeout(timeout, feed).await {
+        Ok(Ok(items)) => HomeFeedSection {
+            items,
+            degraded: false,
+        },
+        Ok(Err(e)) => {
+            error!("Home feed {} section failed: {:?}", name, e);
+            HomeFeedSection {
+                items: Vec::new(),
+                degraded: true,
+            }
+        }
+        Err(_) => {
+            warn!("Home feed {} section timed out after {:?}", name, timeout);
+            HomeFeedSection {
+                items: Vec::new(),
+                degraded: true,
+            }
+        }
+    }
+}
+
+/// Score breakdown of a user's top candidates, for tuning weights
+async fn explain_recommendations(
+    State(state): State<Arc<AppState>>,
+    Path(user_address): Path<String>,
+    Query(query): Query<ExplainQuery>,
+) -> Result<Json<Vec<ExplainedNft>>, Response> {
+    validate_address(&user_address).map_err(IntoResponse::into_response)?;
+    match state.engine.explain(&user_address, query.limit).await {
+        Ok(items) => Ok(Json(items)),
+        Err(e) => {
+            error!("Failed to explain recommendations: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
         }
     }
 }
 
-/// Get trending NFTs
+/// Get trending NFTs, hot now or rising per `mode`
 async fn get_trending(
     State(state): State<Arc<AppState>>,
-    Query(query): Query<FeedQuery>,
+    Query(query): Query<TrendingQuery>,
 ) -> Result<Json<FeedResponse>, StatusCode> {
-    // For trending, we use the engine with a generic "user" that has neutral preferences
-    // This gives us engagement-weighted results
     match state
         .engine
-        .get_enhanced_feed(
-            "0x0000000000000000000000000000000000000000",
+        .get_trending(
             query.limit,
             query.offset,
             query.contract_type.as_deref(),
+            query.mode,
         )
         .await
     {
         Ok(items) => {
             let total = items.len();
             let has_more = total == query.limit;
-            Ok(Json(FeedResponse {
-                items,
-                total,
-                has_more,
-            }))
+            Ok(feed_response(&state, items, total, has_more, false).await)
         }
         Err(e) => {
             error!("Failed to get trending: {:?}", e);
@@ -286,11 +1173,57 @@ async fn get_trending(
     }
 }
 
+/// Reject malformed NFT ids before they reach a query
+fn validate_nft_id(nft_id: &str) -> Result<Uuid, Error> {
+    Uuid::parse_str(nft_id)
+        .map_err(|_| Error::bad_request(format!("nft_id '{}' is not a valid UUID", nft_id)))
+}
+
+/// Reject anything but a 0x-prefixed 20-byte hex address
+fn validate_address(address: &str) -> Result<(), Error> {
+    normalize_address(address)
+        .map(|_| ())
+        .map_err(|_| Error::bad_request(format!("'{}' is not a valid address", address)))
+}
+
+/// Record that a user viewed an NFT
+async fn record_view(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<ViewRequest>,
+) -> Result<StatusCode, Error> {
+    validate_address(&req.user_address)?;
+    validate_nft_id(&req.nft_id)?;
+    state.view_limiter.admit(&req.user_address)?;
+
+    let event = InteractionEvent {
+        user_address: req.user_address.to_lowercase(),
+        nft_id: req.nft_id,
+        interaction_type: InteractionType::View,
+        view_duration_ms: req.view_duration_ms,
+        source: req.source,
+        nft_contract_type: None,
+        nft_creator_address: None,
+        nft_tags: vec![],
+    };
+
+    record_interaction(
+        &state.pool,
+        event,
+        state.engine.first_contact_boost(),
+        state.engine.taste_space(),
+    )
+    .await?;
+    Ok(StatusCode::CREATED)
+}
+
 /// Record a user interaction
 async fn record_user_interaction(
     State(state): State<Arc<AppState>>,
     Json(req): Json<InteractionRequest>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode, Response> {
+    validate_address(&req.user_address).map_err(IntoResponse::into_response)?;
+    validate_nft_id(&req.nft_id).map_err(IntoResponse::into_response)?;
+
     let interaction_type = match req.interaction_type.as_str() {
         "view" => InteractionType::View,
         "like" => InteractionType::Like,
@@ -300,7 +1233,7 @@ async fn record_user_interaction(
         "save" => InteractionType::Save,
         "comment" => InteractionType::Comment,
         "unsave" => InteractionType::Unsave,
-        _ => return Err(StatusCode::BAD_REQUEST),
+        _ => return Err(StatusCode::BAD_REQUEST.into_response()),
     };
 
     let event = InteractionEvent {
@@ -314,15 +1247,128 @@ async fn record_user_interaction(
         nft_tags: req.nft_tags.unwrap_or_default(),
     };
 
-    match record_interaction(&state.pool, event).await {
+    match record_interaction(
+        &state.pool,
+        event,
+        state.engine.first_contact_boost(),
+        state.engine.taste_space(),
+    )
+    .await
+    {
         Ok(_) => Ok(StatusCode::CREATED),
         Err(e) => {
             error!("Failed to record interaction: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
         }
     }
 }
 
+/// Opt a user in or out of personalized interaction tracking. Admin-token
+/// only: the caller vouches for the user's choice.
+async fn set_user_privacy(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(user_address): Path<String>,
+    Json(req): Json<PrivacySettings>,
+) -> Result<Json<PrivacySettings>, Response> {
+    authorize_admin(&state, &headers).map_err(IntoResponse::into_response)?;
+    match set_privacy(&state.pool, &user_address, &req).await {
+        Ok(()) => Ok(Json(req)),
+        Err(e) => {
+            error!("Failed to set privacy for {}: {:?}", user_address, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+}
+
+/// Anonymous interaction counts on an NFT, by interaction type
+#[derive(Debug, Serialize)]
+pub struct NftEngagementResponse {
+    pub nft_id: String,
+    pub counts: BTreeMap<String, i64>,
+}
+
+/// Interaction totals on an NFT, counting users opted out of tracking
+async fn get_nft_engagement(
+    State(state): State<Arc<AppState>>,
+    Path(nft_id): Path<String>,
+) -> Result<Json<NftEngagementResponse>, Response> {
+    validate_nft_id(&nft_id).map_err(IntoResponse::into_response)?;
+    match get_engagement_counts(&state.pool, &nft_id).await {
+        Ok(counts) => Ok(Json(NftEngagementResponse { nft_id, counts })),
+        Err(e) => {
+            error!("Failed to get engagement counts for {}: {:?}", nft_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR.into_response())
+        }
+    }
+}
+
+/// Require `Authorization: Bearer <API_ADMIN_TOKEN>` on admin routes
+fn authorize_admin(state: &AppState, headers: &HeaderMap) -> Result<(), Error> {
+    let Some(expected) = state.admin_token.as_deref() else {
+        return Err(Error::Forbidden {
+            message: "admin routes are disabled; set API_ADMIN_TOKEN".into(),
+        });
+    };
+
+    let provided = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if provided != Some(expected) {
+        return Err(Error::Unauthorized {
+            message: "missing or invalid admin token".into(),
+        });
+    }
+    Ok(())
+}
+
+/// Whether the recommendation engine is serving personalized feeds
+async fn get_engine_status(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<EngineStatus>, Error> {
+    authorize_admin(&state, &headers)?;
+    Ok(Json(EngineStatus {
+        enabled: state.engine.is_enabled(),
+    }))
+}
+
+/// Toggle the recommendation engine; off serves trending to every feed
+async fn set_engine_status(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(req): Json<EngineStatus>,
+) -> Result<Json<EngineStatus>, Error> {
+    authorize_admin(&state, &headers)?;
+    state.engine.set_enabled(req.enabled);
+    Ok(Json(EngineStatus {
+        enabled: state.engine.is_enabled(),
+    }))
+}
+
+/// Newest messages on the dead-letter topic, read without consuming them
+async fn peek_dead_letters(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<Json<Vec<DeadLetterEntry>>, Error> {
+    authorize_admin(&state, &headers)?;
+    let Some(dead_letters) = state.dead_letters.clone() else {
+        return Err(Error::ServiceUnavailable {
+            service: "dead-letter topic",
+        });
+    };
+
+    let limit = query.limit.clamp(1, MAX_DEAD_LETTER_PEEK);
+    let messages = tokio::task::spawn_blocking(move || dead_letters.peek(limit))
+        .await
+        .map_err(Error::internal)??;
+    Ok(Json(
+        messages.into_iter().map(DeadLetterEntry::from).collect(),
+    ))
+}
+
 /// Get user preferences (for debugging/admin)
 async fn get_user_preferences(
     State(state): State<Arc<AppState>>,
@@ -338,3 +1384,808 @@ async fn get_user_preferences(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::random_address;
+    use crate::recommendation::engine::ScoringWeights;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::Service;
+
+    async fn slow_feed() -> &'static str {
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        "feed"
+    }
+
+    /// State over `pool` with test defaults everywhere else; tests override
+    /// fields with struct update syntax
+    fn test_state(pool: PgPool) -> AppState {
+        AppState {
+            engine: RecommendationEngine::new(pool.clone(), ScoringWeights::default()),
+            pool: pool.clone(),
+            started_at: Instant::now(),
+            chain_id: 100,
+            producer: KafkaProducer::noop(),
+            indexer_pool: pool,
+            admin_token: None,
+            database_ready: Arc::new(AtomicBool::new(true)),
+            view_limiter: ViewRateLimiter::new(60, Duration::from_secs(60)),
+            dead_letters: None,
+            home_feed_timeout: Duration::from_secs(2),
+            rate_limiter: RateLimiter::new(0.0, 1, false),
+        }
+    }
+
+    /// A pool that never connects
+    fn offline_pool() -> PgPool {
+        PgPool::connect_lazy("postgres://localhost/unused").unwrap()
+    }
+
+    /// State with a no-op producer and a pool that never connects
+    fn offline_state() -> Arc<AppState> {
+        offline_state_with(KafkaProducer::noop())
+    }
+
+    /// Offline state publishing through `producer`
+    fn offline_state_with(producer: KafkaProducer) -> Arc<AppState> {
+        Arc::new(AppState {
+            producer,
+            ..test_state(offline_pool())
+        })
+    }
+
+    #[tokio::test]
+    async fn test_broker_outage_fails_readiness_not_health() {
+        // The real health routes and timeout, with nothing cached yet
+        let health_timeout = crate::config::ApiConfig::from_env().unwrap().health_timeout;
+        let health = Router::new()
+            .route("/health", get(health_check))
+            .route("/ready", get(readiness_check))
+            .with_state(offline_state_with(KafkaProducer::unreachable()));
+        let mut app = Router::new().merge(with_timeout(health, health_timeout));
+
+        let started = Instant::now();
+        let response = app
+            .call(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let health: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(health["kafka_reachable"], false);
+        assert_eq!(health["status"], "degraded");
+
+        assert_eq!(
+            status(app.clone(), "/ready").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert!(
+            started.elapsed() < health_timeout,
+            "{:?}",
+            started.elapsed()
+        );
+
+        // Kafka disabled counts as reachable
+        let app = Router::new()
+            .route("/ready", get(readiness_check))
+            .with_state(offline_state());
+        assert_eq!(status(app, "/ready").await, StatusCode::OK);
+    }
+
+    fn app(feed_timeout: Duration, health_timeout: Duration) -> Router {
+        let health = Router::new()
+            .route("/health", get(health_check))
+            .with_state(offline_state());
+        let feeds = Router::new().route("/api/v1/trending", get(slow_feed));
+        Router::new()
+            .merge(with_timeout(health, health_timeout))
+            .merge(with_timeout(feeds, feed_timeout))
+    }
+
+    async fn status(mut app: Router, uri: &str) -> StatusCode {
+        // Router is always ready, so calling without poll_ready is fine
+        app.call(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_route_groups_use_their_own_timeouts() {
+        // Feed timeout shorter than the handler: times out
+        let short = app(Duration::from_millis(50), Duration::from_secs(1));
+        assert_eq!(
+            status(short.clone(), "/api/v1/trending").await,
+            StatusCode::GATEWAY_TIMEOUT
+        );
+        assert_eq!(status(short, "/health").await, StatusCode::OK);
+
+        // Feed timeout longer than the handler, even with a tight health timeout
+        let long = app(Duration::from_secs(1), Duration::from_millis(50));
+        assert_eq!(
+            status(long.clone(), "/api/v1/trending").await,
+            StatusCode::OK
+        );
+        assert_eq!(status(long, "/health").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_requests_beyond_concurrency_limit_are_shed() {
+        let gate = Arc::new(tokio::sync::Notify::new());
+        let held = gate.clone();
+        let feeds = Router::new().route(
+            "/api/v1/trending",
+            get(move || {
+                let held = held.clone();
+                async move {
+                    held.notified().await;
+                    "feed"
+                }
+            }),
+        );
+        let app = Router::new()
+            .route("/health", get(health_check))
+            .with_state(offline_state())
+            .merge(with_concurrency_limit(feeds, 2));
+
+        // Fill the limit with requests parked on the gate
+        let in_flight: Vec<_> = (0..2)
+            .map(|_| tokio::spawn(status(app.clone(), "/api/v1/trending")))
+            .collect();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(
+            status(app.clone(), "/api/v1/trending").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(status(app.clone(), "/health").await, StatusCode::OK);
+
+        gate.notify_waiters();
+        for request in in_flight {
+            assert_eq!(request.await.unwrap(), StatusCode::OK);
+        }
+
+        // Capacity frees up once the parked requests finish
+        let next = tokio::spawn(status(app.clone(), "/api/v1/trending"));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        gate.notify_waiters();
+        assert_eq!(next.await.unwrap(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_info_reports_version_and_uptime() {
+        // Lazy pool: `/info` never touches the database
+        let state = Arc::new(AppState {
+            started_at: Instant::now() - Duration::from_secs(5),
+            ..test_state(offline_pool())
+        });
+        let mut app = Router::new().route("/info", get(info)).with_state(state);
+
+        let response = app
+            .call(Request::get("/info").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let info: InfoResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(info.uptime_secs >= 5.0);
+        assert_eq!(info.chain_id, 100);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_nft_id_is_rejected_before_querying() {
+        // Lazy pool: validation fails before any query is attempted
+        let state = offline_state();
+        let mut app = Router::new()
+            .route("/api/v1/interactions", post(record_user_interaction))
+            .with_state(state);
+
+        let body = serde_json::json!({
+            "user_address": "0x0000000000000000000000000000000000000abc",
+            "nft_id": "not-a-uuid",
+            "interaction_type": "like",
+        });
+        let response = app
+            .call(
+                Request::post("/api/v1/interactions")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["error"]["code"], "BAD_REQUEST");
+        assert!(error["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("'not-a-uuid' is not a valid UUID"));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_user_address_is_rejected_before_querying() {
+        let app = Router::new()
+            .route(
+                "/api/v1/enhanced-feed/:user_address",
+                get(get_enhanced_feed),
+            )
+            .route(
+                "/api/v1/recommendations/:user_address",
+                get(get_recommendations),
+            )
+            .route("/feeds/home/:user_address", get(get_home_feed))
+            .route("/debug/explain/:user_address", get(explain_recommendations))
+            .with_state(offline_state());
+
+        for uri in [
+            "/api/v1/enhanced-feed/not-an-address",
+            "/api/v1/recommendations/0xabc",
+            "/feeds/home/not-an-address",
+            "/debug/explain/0xzz00000000000000000000000000000000000000",
+        ] {
+            assert_eq!(
+                status(app.clone(), uri).await,
+                StatusCode::BAD_REQUEST,
+                "{}",
+                uri
+            );
+        }
+    }
+
+    #[test]
+    fn test_metrics_render_prometheus_text() {
+        let stats = ProducerStats {
+            messages_sent: 12,
+            messages_failed: 1,
+            bytes_sent: 2048,
+            in_flight: 3,
+        };
+        let indexers = vec![IndexerProgress {
+            contract_address: "0xabc".to_string(),
+            contract_type: "friend".to_string(),
+            last_block: 4200,
+        }];
+        let pools = [(
+            "recommendations",
+            PoolStats {
+                size: 4,
+                idle: 1,
+                active: 3,
+                max: 10,
+            },
+        )];
+        let processor = ProcessorCounters {
+            message_timeouts: 2,
+            timed_out_skips: 1,
+            invalid_creator_mints: 4,
+        };
+        let indexer_counters = IndexerCounters {
+            blocks_behind: vec![("friend".to_string(), 12)],
+            timestamp_divergences: 5,
+        };
+        let body = render_metrics(&stats, &processor, &indexer_counters, &indexers, &pools);
+
+        for (name, kind) in [
+            ("kafka_messages_sent", "counter"),
+            ("kafka_messages_failed", "counter"),
+            ("kafka_bytes_sent", "counter"),
+            ("kafka_in_flight", "gauge"),
+            ("event_processor_message_timeouts", "counter"),
+            ("event_processor_timed_out_skips", "counter"),
+            ("event_processor_invalid_creator_mints", "counter"),
+            ("indexer_last_block", "gauge"),
+            ("indexer_blocks_behind", "gauge"),
+            ("indexer_timestamp_divergences", "counter"),
+            ("db_pool_active", "gauge"),
+            ("db_pool_max", "gauge"),
+        ] {
+            assert!(
+                body.contains(&format!("# HELP {} ", name)),
+                "missing HELP for {}",
+                name
+            );
+            assert!(
+                body.contains(&format!("# TYPE {} {}\n", name, kind)),
+                "missing TYPE for {}",
+                name
+            );
+        }
+        assert!(body.contains("kafka_messages_sent 12\n"));
+        assert!(body.contains("kafka_in_flight 3\n"));
+        assert!(body.contains("event_processor_message_timeouts 2\n"));
+        assert!(body.contains("event_processor_timed_out_skips 1\n"));
+        assert!(body.contains("event_processor_invalid_creator_mints 4\n"));
+        assert!(body.contains(
+            "indexer_last_block{contract_address=\"0xabc\",contract_type=\"friend\"} 4200\n"
+        ));
+        assert!(body.contains("indexer_blocks_behind{indexer=\"friend\"} 12\n"));
+        assert!(body.contains("indexer_timestamp_divergences 5\n"));
+        assert!(body.contains("db_pool_active{pool=\"recommendations\"} 3\n"));
+        // Every sample line is `name[{labels}] value`
+        for line in body.lines().filter(|l| !l.starts_with('#')) {
+            let (_, value) = line.rsplit_once(' ').unwrap();
+            assert!(value.parse::<f64>().is_ok(), "bad sample: {}", line);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_privacy_update_requires_admin_token() {
+        let app = Router::new()
+            .route("/api/v1/privacy/:user_address", put(set_user_privacy))
+            .with_state(offline_state());
+        let request = Request::put("/api/v1/privacy/0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"opt_out_tracking":true}"#))
+            .unwrap();
+
+        // Without API_ADMIN_TOKEN no one may change a user's tracking
+        let response = app.clone().call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_admin_toggle_switches_engine_off() {
+        let state = Arc::new(AppState {
+            admin_token: Some("secret".to_string()),
+            ..test_state(offline_pool())
+        });
+        let app = Router::new()
+            .route(
+                "/api/v1/admin/engine",
+                get(get_engine_status).put(set_engine_status),
+            )
+            .with_state(state.clone());
+        let toggle = |token: &str, enabled: bool| {
+            Request::put("/api/v1/admin/engine")
+                .header("content-type", "application/json")
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::from(
+                    serde_json::json!({ "enabled": enabled }).to_string(),
+                ))
+                .unwrap()
+        };
+
+        assert!(state.engine.is_enabled());
+        let response = app.clone().call(toggle("wrong", false)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(state.engine.is_enabled());
+
+        let response = app.clone().call(toggle("secret", false)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!state.engine.is_enabled());
+
+        let response = app.clone().call(toggle("secret", true)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(state.engine.is_enabled());
+    }
+
+    #[tokio::test]
+    async fn test_dead_letters_are_readable_via_peek_endpoint() {
+        use crate::kafka::{EventPublisher, InMemoryPublisher, InMemoryTopicPeek};
+
+        let publisher = InMemoryPublisher::new();
+        for offset in [41, 42] {
+            let dead_letter = DeadLetter {
+                topic: "blockchain.events".to_string(),
+                partition: 2,
+                offset,
+                payload: format!(
+                    "{{\"event_type\": \"ContentLiked\", \"pad\": \"{}\"}}",
+                    "x".repeat(500)
+                ),
+                error: "JSON error: EOF while parsing".to_string(),
+                attempts: 3,
+                failed_at: chrono::Utc::now(),
+            };
+            publisher
+                .send_event("events.dlq", "blockchain.events", &dead_letter)
+                .await
+                .unwrap();
+        }
+
+        let state = Arc::new(AppState {
+            admin_token: Some("secret".to_string()),
+            dead_letters: Some(Arc::new(InMemoryTopicPeek::new(
+                publisher.clone(),
+                "events.dlq",
+            ))),
+            ..test_state(offline_pool())
+        });
+        let app = Router::new()
+            .route("/api/v1/admin/deadletter", get(peek_dead_letters))
+            .with_state(state);
+        let peek = |token: &str, uri: &str| {
+            Request::get(uri)
+                .header("authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .call(peek("wrong", "/api/v1/admin/deadletter"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .clone()
+            .call(peek("secret", "/api/v1/admin/deadletter?limit=1"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let entries: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let entries = entries.as_array().unwrap();
+
+        // Newest first, with the original position and a bounded preview
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["topic"], "blockchain.events");
+        assert_eq!(entries[0]["offset"], 42);
+        assert_eq!(entries[0]["attempts"], 3);
+        assert_eq!(entries[0]["error"], "JSON error: EOF while parsing");
+        let preview = entries[0]["payload_preview"].as_str().unwrap();
+        assert!(preview.starts_with("{\"event_type\": \"ContentLiked\""));
+        assert_eq!(preview.chars().count(), PAYLOAD_PREVIEW_CHARS);
+
+        // Peeking doesn't consume
+        assert_eq!(publisher.messages().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_database_routes_return_503_until_ready() {
+        let state = Arc::new(AppState {
+            database_ready: Arc::new(AtomicBool::new(false)),
+            ..test_state(offline_pool())
+        });
+        let app = Router::new()
+            .route("/api/v1/trending", get(|| async { "ok" }))
+            .route_layer(from_fn_with_state(state.clone(), require_database))
+            .route("/health", get(|| async { "ok" }))
+            .with_state(state.clone());
+        let status = |uri: &'static str| {
+            let mut app = app.clone();
+            async move {
+                app.call(Request::get(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap()
+                    .status()
+            }
+        };
+
+        assert_eq!(
+            status("/api/v1/trending").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(status("/health").await, StatusCode::OK);
+
+        state.database_ready.store(true, Ordering::Relaxed);
+        assert_eq!(status("/api/v1/trending").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_long_view_is_recorded_and_shifts_preferences() {
+        let pool = crate::database::test_pool().await;
+
+        let user = "0x00000000000000000000000000000000000f1e35";
+        let state = Arc::new(AppState {
+            view_limiter: ViewRateLimiter::new(1, Duration::from_secs(60)),
+            ..test_state(pool.clone())
+        });
+        let app = Router::new()
+            .route("/api/v1/interactions/view", post(record_view))
+            .with_state(state);
+        let nft_id = Uuid::new_v4().to_string();
+        let view = |user: &str| {
+            let body = serde_json::json!({
+                "user_address": user,
+                "nft_id": nft_id,
+                "view_duration_ms": 12_000,
+                "source": "feed",
+            });
+            Request::post("/api/v1/interactions/view")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let views = |pool: PgPool| async move {
+            let prefs = crate::recommendation::preferences::get_or_create_preferences(&pool, user)
+                .await
+                .unwrap();
+            prefs.total_views
+        };
+
+        let before = views(pool.clone()).await;
+        let response = app.clone().call(view(user)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let duration = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT view_duration_ms FROM user_interactions WHERE user_address = $1 AND interaction_type = 'view'",
+        )
+        .bind(user)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(duration, Some(12_000));
+        assert_eq!(views(pool.clone()).await, before + 1);
+
+        // Over the per-user limit, and a malformed address
+        let response = app.clone().call(view(user)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let response = app.clone().call(view("0xnope")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        for table in ["user_interactions", "user_preferences"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE user_address = $1", table))
+                .bind(user)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("DELETE FROM nft_engagement_counts WHERE nft_id = $1::uuid")
+            .bind(&nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_home_feed_returns_every_section_and_degrades_per_feed() {
+        let item = ScoredNft {
+            nft_id: "nft-1".to_string(),
+            token_id: 1,
+            contract_address: "0xabc".to_string(),
+            score: 0.9,
+            reason: crate::recommendation::engine::RecommendationReason::Discovery,
+            reasons: Vec::new(),
+            contract_type: "art".to_string(),
+            creator_address: "0xcreator".to_string(),
+            tags: Vec::new(),
+            creator_username: None,
+        };
+
+        let home = home_feed(
+            Duration::from_millis(50),
+            std::future::ready(Ok(vec![item.clone()])),
+            std::future::ready(Err(anyhow::anyhow!("trending query failed"))),
+            async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(vec![item.clone()])
+            },
+        )
+        .await;
+
+        assert!(!home.personalized.degraded);
+        assert_eq!(home.personalized.items.len(), 1);
+        assert!(home.trending.degraded);
+        assert!(home.trending.items.is_empty());
+        assert!(home.following.degraded);
+
+        let body = serde_json::to_value(&home).unwrap();
+        for section in ["personalized", "trending", "following"] {
+            assert!(body[section]["items"].is_array(), "missing {section}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_rejects_past_burst_with_retry_after() {
+        let state = Arc::new(AppState {
+            rate_limiter: RateLimiter::new(0.5, 3, true),
+            ..test_state(offline_pool())
+        });
+        let app = Router::new()
+            .route(
+                "/api/v1/feed/:user_address",
+                get(|| async { StatusCode::OK }),
+            )
+            .route_layer(from_fn_with_state(state.clone(), rate_limit))
+            .with_state(state);
+        let request = |ip: [u8; 4], user: &str| {
+            let mut request = Request::get(format!("/api/v1/feed/{}", user))
+                .body(Body::empty())
+                .unwrap();
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from((ip, 4000))));
+            request
+        };
+
+        for _ in 0..3 {
+            let response = app
+                .clone()
+                .call(request([10, 0, 0, 1], "0xaaa"))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        // Burst spent: one token takes 2s to refill at 0.5/s
+        let response = app
+            .clone()
+            .call(request([10, 0, 0, 1], "0xbbb"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "2");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "RATE_LIMIT_EXCEEDED");
+        assert_eq!(body["error"]["retry_after"], 2);
+
+        // Another IP has its own bucket, but not for an address whose
+        // bucket is already spent
+        let response = app
+            .clone()
+            .call(request([10, 0, 0, 2], "0xccc"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app
+            .clone()
+            .call(request([10, 0, 0, 3], "0xAAA"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn test_rate_limiter_sweep_drops_only_refilled_buckets() {
+        let limiter = RateLimiter::new(0.001, 1, false);
+        limiter.admit("ip:10.0.0.1").unwrap();
+        limiter.buckets.lock().unwrap().insert(
+            "ip:10.0.0.2".to_string(),
+            TokenBucket {
+                tokens: 1.0,
+                updated: Instant::now(),
+            },
+        );
+
+        limiter.sweep_idle();
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert!(buckets.contains_key("ip:10.0.0.1"));
+        assert!(!buckets.contains_key("ip:10.0.0.2"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_following_feed_endpoint_orders_by_recency_with_engagement_boost() {
+        let pool = crate::database::test_pool().await;
+        for ddl in [
+            "CREATE TABLE IF NOT EXISTS nfts (id UUID PRIMARY KEY, token_id BIGINT NOT NULL, contract_address TEXT NOT NULL, contract_type TEXT, creator_address TEXT NOT NULL, creation_time TIMESTAMP NOT NULL DEFAULT NOW(), likes_count BIGINT NOT NULL DEFAULT 0, buys_count BIGINT NOT NULL DEFAULT 0, is_deleted BOOLEAN NOT NULL DEFAULT false, is_original BOOLEAN NOT NULL DEFAULT true)",
+            "CREATE TABLE IF NOT EXISTS social_users (id BIGSERIAL PRIMARY KEY, address TEXT NOT NULL)",
+            "CREATE TABLE IF NOT EXISTS follows (follower_id BIGINT NOT NULL, followee_id BIGINT NOT NULL, is_active BOOLEAN NOT NULL DEFAULT true)",
+        ] {
+            sqlx::query(ddl).execute(&pool).await.unwrap();
+        }
+
+        let (user, loner, creator_a, creator_b) = (
+            random_address(),
+            random_address(),
+            random_address(),
+            random_address(),
+        );
+        for creator in [&creator_a, &creator_b] {
+            sqlx::query(
+                "INSERT INTO user_follows (follower_address, target_address) VALUES ($1, $2)",
+            )
+            .bind(&user)
+            .bind(creator)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        // (creator, hours old, engagement)
+        let mints = [
+            (&creator_a, 0, 0.0),
+            (&creator_a, 30, 0.0),
+            (&creator_a, 72, 1.0),
+            (&creator_b, 12, 0.0),
+        ];
+        let mut ids = Vec::new();
+        for (token_id, (creator, hours, engagement)) in mints.iter().enumerate() {
+            let id = Uuid::new_v4();
+            sqlx::query(
+                "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address, creation_time) VALUES ($1, $2, '0xfeed', 'art', $3, NOW() - make_interval(hours => $4))",
+            )
+            .bind(id)
+            .bind(token_id as i64)
+            .bind(creator.as_str())
+            .bind(*hours)
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO nft_features (nft_id, contract_address, token_id, engagement_score) VALUES ($1, '0xfeed', $2, $3)",
+            )
+            .bind(id)
+            .bind(token_id as i64)
+            .bind(*engagement as f32)
+            .execute(&pool)
+            .await
+            .unwrap();
+            ids.push(id.to_string());
+        }
+
+        let state = Arc::new(test_state(pool.clone()));
+        let app = Router::new()
+            .route("/api/v1/feed/:user_address", get(get_following_feed))
+            .with_state(state);
+        let get_feed = |path: String| {
+            let mut app = app.clone();
+            async move {
+                let response = app
+                    .call(Request::get(path).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                (
+                    status,
+                    serde_json::from_slice::<serde_json::Value>(&body).unwrap_or_default(),
+                )
+            }
+        };
+
+        // Newest first, except the 3-day-old hit outranks a quiet day-old mint
+        let (status, body) =
+            get_feed(format!("/api/v1/feed/{}?limit=500&verbose=true", user)).await;
+        assert_eq!(status, StatusCode::OK);
+        let order: Vec<&str> = body["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["nft_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(order, vec![&ids[0], &ids[3], &ids[2], &ids[1]]);
+        assert_eq!(
+            body["items"][0]["reason"]["following"]["followee"],
+            creator_a.as_str()
+        );
+
+        // No follows: an empty feed, not an error; bad addresses are rejected
+        let (status, body) = get_feed(format!("/api/v1/feed/{}", loner)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["items"], serde_json::json!([]));
+        let (status, _) = get_feed("/api/v1/feed/0xnope".to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        sqlx::query("DELETE FROM user_follows WHERE follower_address = $1")
+            .bind(&user)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for table in ["nfts", "nft_features"] {
+            let column = if table == "nfts" { "id" } else { "nft_id" };
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE {}::text = ANY($1)",
+                table, column
+            ))
+            .bind(&ids)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+    }
+}
@@ -12,8 +12,9 @@
 //! ```
 
 use crate::error::{Error, Result};
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
-use tracing::info;
+use tracing::{info, warn};
 
 /// Main application configuration
 #[derive(Debug, Clone)]
@@ -26,12 +27,17 @@ pub struct Config {
     pub database: DatabaseConfig,
     /// Elixir database configuration (for NFT count updates)
     pub elixir_database: DatabaseConfig,
+    /// Read replica of the Elixir database for feed reads, from
+    /// `DATABASE_READ_URL`; `None` reads from the primary
+    pub read_database: Option<DatabaseConfig>,
     /// API server configuration
     pub api: ApiConfig,
     /// Contract addresses
     pub contracts: ContractAddresses,
     /// Recommendation engine configuration
     pub recommendation: RecommendationConfig,
+    /// Kafka event processor configuration
+    pub processor: ProcessorConfig,
 }
 
 /// Blockchain RPC configuration
@@ -51,6 +57,52 @@ pub struct BlockchainConfig {
     pub max_retries: u32,
     /// Base delay for exponential backoff
     pub retry_delay: Duration,
+    /// Allowed drift between an event's embedded timestamp and its block time.
+    /// `None` disables the check (it costs one block lookup per log).
+    pub timestamp_tolerance_secs: Option<u64>,
+    /// Write events to `event_outbox` in the same transaction as `last_block`
+    /// and publish them from a separate drain loop
+    pub outbox_enabled: bool,
+    /// How long sent outbox rows are kept for replay before they are deleted
+    /// (`INDEXER_OUTBOX_RETENTION_DAYS`, 0 keeps them forever)
+    pub outbox_retention: Option<Duration>,
+    /// Warn when `last_block` trails the chain head by more than this many
+    /// blocks (0 disables the warning)
+    pub lag_warn_blocks: u64,
+    /// Blocks to rewind when the stored hash of `last_block` no longer
+    /// matches the chain (a reorg)
+    pub reorg_rewind_depth: u64,
+    /// At startup, fetch and store the hash of each indexer's `last_block`
+    /// where none has been recorded yet
+    pub backfill_block_hashes: bool,
+    /// Publish each indexer's progress to `KafkaTopics::indexer_progress`
+    pub progress_enabled: bool,
+    /// Time between progress reports
+    pub progress_interval: Duration,
+    /// Inclusive block range to re-emit and then exit, from
+    /// `BACKFILL_FROM` and `BACKFILL_TO`
+    pub backfill_range: Option<(u64, u64)>,
+    /// Inclusive block range whose stored events to republish from
+    /// `event_outbox` and then exit; only with `REPLAY_EVENTS_ENABLED`, from
+    /// `REPLAY_FROM` and `REPLAY_TO`. Requires `outbox_enabled`.
+    pub replay_range: Option<(u64, u64)>,
+    /// Chunks a backfill fetches at once (`BACKFILL_PARALLELISM`), capped
+    /// at `backfill_max_parallelism`
+    pub backfill_parallelism: usize,
+    /// Ceiling on `backfill_parallelism`, to keep a backfill within what the
+    /// RPC provider allows in flight (`BACKFILL_MAX_PARALLELISM`)
+    pub backfill_max_parallelism: usize,
+    /// Consecutive failed RPC calls that open an indexer's circuit breaker
+    /// (`RPC_BREAKER_THRESHOLD`, 0 disables)
+    pub rpc_breaker_threshold: u32,
+    /// How long an open breaker fails calls fast before probing again
+    /// (`RPC_BREAKER_COOLDOWN_SECS`)
+    pub rpc_breaker_cooldown: Duration,
+    /// Handling of logs not yet mined (`INDEXER_PENDING_LOGS`: reject|flag)
+    pub pending_logs: PendingLogPolicy,
+    /// Emit decoded topics as `PartiallyDecoded` when a known event's data
+    /// doesn't decode, rather than bare `Raw` (`EVENT_PARTIAL_DECODE_ENABLED`)
+    pub partial_decode: bool,
 }
 
 /// Kafka configuration
@@ -74,6 +126,61 @@ pub struct KafkaTopics {
     pub blockchain_events: String,
     pub user_actions: String,
     pub recommendations: String,
+    /// Partition key strategy for the user actions topic. Per contract by
+    /// default; `KAFKA_USER_ACTIONS_KEY=user` keys by the acting user.
+    pub user_actions_key: KafkaKeyStrategy,
+    /// Dead-letter topic for messages the event processor keeps failing on.
+    /// Off unless `KAFKA_TOPIC_DLQ` is set (e.g. `events.dlq`); without it
+    /// failed messages are skipped.
+    pub dlq: Option<String>,
+    /// How long the dead-letter topic keeps messages
+    pub dlq_retention: Duration,
+    /// Indexer progress reports (with `INDEXER_PROGRESS_ENABLED`)
+    pub indexer_progress: String,
+}
+
+/// How events are keyed (and therefore partitioned) on a Kafka topic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KafkaKeyStrategy {
+    /// `contract_type.contract_address` - per-contract ordering
+    #[default]
+    Contract,
+    /// Acting user's address - per-user ordering
+    User,
+}
+
+impl std::str::FromStr for KafkaKeyStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "contract" => Ok(Self::Contract),
+            "user" => Ok(Self::User),
+            other => Err(format!("unknown key strategy '{}' (expected contract|user)", other)),
+        }
+    }
+}
+
+/// What to do with logs that have no block number or transaction hash yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PendingLogPolicy {
+    /// Drop them; they are picked up again once mined
+    #[default]
+    Reject,
+    /// Emit them with `pending` set
+    Flag,
+}
+
+impl std::str::FromStr for PendingLogPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "reject" => Ok(Self::Reject),
+            "flag" => Ok(Self::Flag),
+            other => Err(format!("unknown pending log policy '{}' (expected reject|flag)", other)),
+        }
+    }
 }
 
 /// Kafka producer configuration
@@ -128,6 +235,25 @@ pub struct DatabaseConfig {
     pub statement_cache_size: usize,
 }
 
+/// Attempts and backoff for the `with_retry` helpers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_delay: Duration,
+    /// Backoff never exceeds this, however many attempts have failed
+    pub max_delay: Duration,
+    /// Growth of the delay after each failed attempt
+    pub multiplier: f64,
+}
+
+impl RetryPolicy {
+    /// Delay before retrying after `attempt` (0-based) failed
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let grown = self.initial_delay.as_secs_f64() * self.multiplier.max(1.0).powi(attempt as i32);
+        Duration::from_secs_f64(grown.min(self.max_delay.as_secs_f64()))
+    }
+}
+
 /// API server configuration
 #[derive(Debug, Clone)]
 pub struct ApiConfig {
@@ -137,12 +263,38 @@ pub struct ApiConfig {
     pub host: String,
     /// Request timeout
     pub request_timeout: Duration,
+    /// Timeout for feed and recommendation routes (defaults to `request_timeout`)
+    pub feed_timeout: Duration,
+    /// Timeout for `/health`, kept tight so probes fail fast
+    pub health_timeout: Duration,
     /// Maximum request body size
     pub max_body_size: usize,
     /// Enable CORS
     pub cors_enabled: bool,
     /// Allowed origins for CORS
     pub cors_origins: Vec<String>,
+    /// Requests expected to hold a DB connection at once, used only to
+    /// sanity-check the pool size at startup
+    pub expected_concurrency: usize,
+    /// Bearer token for `/api/v1/admin` routes; unset disables them
+    pub admin_token: Option<String>,
+    /// Views a user may record per minute (0 disables the limit)
+    pub view_rate_limit_per_minute: u32,
+    /// Requests served at once outside `/health`; extra ones get 503
+    /// (0, the default, disables the limit)
+    pub max_concurrent_requests: usize,
+    /// Overall budget for `/feeds/home`
+    pub home_feed_timeout: Duration,
+    /// Serve `/debug/explain/:user`, which shows per-factor scores
+    pub debug_explain_enabled: bool,
+    /// Requests per second each client IP may sustain (0, the default,
+    /// disables the limit). Keyed on the socket peer, so behind a proxy every
+    /// client shares the proxy's bucket; leave it off there.
+    pub rate_limit_rps: f64,
+    /// Requests a client may make at once before the rate applies
+    pub rate_limit_burst: u32,
+    /// Also limit by the `:user_address` in the path, not just by IP
+    pub rate_limit_per_address: bool,
 }
 
 /// Contract addresses
@@ -150,6 +302,72 @@ pub struct ApiConfig {
 pub struct ContractAddresses {
     pub thera_friends: String,
     pub thera_social: String,
+    /// Redeployed contracts' old addresses, from `CONTRACT_ADDRESS_ALIASES`
+    pub aliases: ContractAliases,
+}
+
+/// Old contract addresses mapped to the canonical address that replaced
+/// them, so a redeployed contract's history and its new events are treated
+/// as one contract. Parsed from `old=new` pairs separated by commas.
+#[derive(Debug, Clone, Default)]
+pub struct ContractAliases {
+    canonical: HashMap<String, String>,
+}
+
+impl ContractAliases {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut canonical = HashMap::new();
+        for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some((old, new)) = pair.split_once('=') else {
+                return Err(Error::InvalidConfig {
+                    key: "CONTRACT_ADDRESS_ALIASES",
+                    message: format!("Expected old=new, got '{}'", pair).into(),
+                });
+            };
+            let (old, new) = (old.trim().to_lowercase(), new.trim().to_lowercase());
+            for addr in [&old, &new] {
+                if !addr.starts_with("0x") || addr.len() != 42 {
+                    return Err(Error::InvalidConfig {
+                        key: "CONTRACT_ADDRESS_ALIASES",
+                        message: format!("Invalid address '{}'", addr).into(),
+                    });
+                }
+            }
+            canonical.insert(old, new);
+        }
+
+        // An alias of an alias resolves to the final address; a chain that
+        // leads back to itself has no canonical address
+        let mut resolved = HashMap::with_capacity(canonical.len());
+        for old in canonical.keys() {
+            let mut seen = HashSet::from([old]);
+            let mut target = &canonical[old];
+            while let Some(next) = canonical.get(target) {
+                if !seen.insert(target) {
+                    return Err(Error::InvalidConfig {
+                        key: "CONTRACT_ADDRESS_ALIASES",
+                        message: format!("Aliases of '{}' form a cycle", old).into(),
+                    });
+                }
+                target = next;
+            }
+            resolved.insert(old.clone(), target.clone());
+        }
+        Ok(Self { canonical: resolved })
+    }
+
+    /// The canonical address for `address`: its alias target if it has one,
+    /// otherwise `address` unchanged
+    pub fn canonical<'a>(&'a self, address: &'a str) -> &'a str {
+        self.canonical
+            .get(&address.to_lowercase())
+            .map(String::as_str)
+            .unwrap_or(address)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.canonical.is_empty()
+    }
 }
 
 // ============================================================================
@@ -255,8 +473,12 @@ pub const START_BLOCK: u64 = 9903816;
 pub struct RecommendationConfig {
     /// Cache TTL for recommendations
     pub cache_ttl: Duration,
-    /// Maximum candidates to consider
-    pub max_candidates: usize,
+    /// Most `recommendation_cache` rows kept; the least recently computed
+    /// beyond it are evicted (0 keeps every live entry)
+    pub cache_max_rows: i64,
+    /// Candidates fetched per request, from `REC_MAX_CANDIDATES` and
+    /// `REC_CANDIDATE_MULTIPLIER_*`
+    pub candidates: CandidatePool,
     /// Minimum score threshold
     pub min_score: f32,
     /// Diversity factor (0.0-1.0)
@@ -267,6 +489,454 @@ pub struct RecommendationConfig {
     pub engagement_update_interval: Duration,
     /// Preference decay rate
     pub preference_decay_rate: f32,
+    /// Users whose recommendations are refreshed concurrently by the updater
+    pub updater_concurrency: usize,
+    /// Window for "hot now" trending; "rising" compares it with the window before
+    pub hot_window: Duration,
+    /// Trending decay and normalization, from `REC_TRENDING_*`
+    pub trending: TrendingConfig,
+    /// Scoring weights from `REC_WEIGHT_*`
+    pub weights: ScoringWeights,
+    /// Check at startup that the `nfts` table has the columns feeds read,
+    /// failing fast if not (`REC_NFTS_SCHEMA_CHECK`)
+    pub nfts_schema_check: bool,
+    /// Baseline quality for new creators, from `REC_NEW_CREATOR_*`
+    pub cold_start: CreatorColdStart,
+    /// Per-user rollouts for experimental scoring paths (`REC_FEATURE_FLAGS`)
+    pub feature_flags: FeatureFlags,
+    /// Discovery pick seeding, from `REC_SHUFFLE_*`
+    pub shuffle: DiscoveryShuffle,
+    /// Order among equal trending scores, from `REC_TRENDING_TIE_BREAKS`
+    pub trending_tie_breaks: Vec<TrendingTieBreak>,
+    /// Oldest cached feed served in degraded mode (`REC_MAX_STALE_SECS`)
+    pub max_stale: Duration,
+    /// Feed broadening for niche tastes, from `REC_NICHE_*`
+    pub niche: NicheBroadening,
+    /// Enhanced-feed candidate sources, from `REC_BLEND_*`
+    pub blend: SourceBlend,
+    /// Leave followed creators out of the enhanced feed
+    /// (`REC_EXCLUDE_FOLLOWED_CREATORS`)
+    pub exclude_followed_creators: bool,
+    /// Start with personalized scoring on; toggled at runtime through the
+    /// admin API (`REC_ENGINE_ENABLED`)
+    pub engine_enabled: bool,
+    /// Fill in creator usernames on feed items (`REC_INCLUDE_CREATOR_PROFILES`)
+    pub include_creator_profiles: bool,
+    /// Creator quality added per active badge; off unless set
+    /// (`REC_BADGE_QUALITY_BOOST`)
+    pub badge_quality_boost: f32,
+    /// Fraction of the score taken off NFTs the user unliked or unsaved
+    /// (`REC_REJECTED_PENALTY`)
+    pub rejected_penalty: f32,
+    /// Extra weight, as a fraction of the base weight, for a user's first
+    /// positive interaction with a creator or tag; 0 disables
+    /// (`REC_FIRST_CONTACT_BOOST`)
+    pub first_contact_boost: f32,
+    /// Tag vocabulary for taste vectors (`REC_TASTE_TAGS`)
+    pub taste_space: TasteSpace,
+    /// Daily preference decay per signal class (`REC_DECAY_PURCHASE` /
+    /// `REC_DECAY_VIEW`)
+    pub decay: DecayRates,
+    /// Score added to NFTs saved by users with similar taste, scaled by
+    /// their similarity (`REC_SAVED_BY_SIMILAR_BOOST`, 0 disables)
+    pub saved_by_similar_boost: f32,
+    /// Most of the total creator preference (above neutral) one creator may
+    /// hold when scoring; 1 disables (`REC_CREATOR_MAX_SHARE`)
+    pub creator_max_share: f32,
+}
+
+/// Recommendation weights (can be tuned)
+#[derive(Debug, Clone)]
+pub struct ScoringWeights {
+    pub tag_match: f32,
+    pub creator_affinity: f32,
+    pub content_type: f32,
+    pub trending: f32,
+    pub engagement: f32,
+    pub quality: f32,
+    pub recency: f32,
+    pub diversity_penalty: f32,
+    /// How far below 0 scores may go while ranking; exposed scores are still
+    /// clamped to 0-1 (not a weight, so not part of the sum)
+    pub negative_grace: f32,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        // ByteGraph-inspired weights: prioritize personalization heavily
+        Self {
+            tag_match: 0.35,         // 35% weight on tag matching (increased)
+            creator_affinity: 0.20,  // 20% weight on creator preference (increased)
+            content_type: 0.25,      // 25% weight on content type match (increased)
+            trending: 0.05,          // 5% weight on trending score (reduced)
+            engagement: 0.05,        // 5% weight on overall engagement (reduced)
+            quality: 0.05,           // 5% weight on quality score (reduced)
+            recency: 0.05,           // 5% weight on how new the NFT is
+            diversity_penalty: 0.02, // 2% penalty for too similar items
+            negative_grace: 0.0,     // Off: penalized items all floor at 0
+        }
+    }
+}
+
+impl ScoringWeights {
+    /// Load from `REC_WEIGHT_*` variables, falling back to the defaults, and
+    /// validate the result
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    /// Load from `lookup`, which maps variable names to values. Whatever mix
+    /// of set and default weights results must sum to 1.0.
+    pub fn from_vars(lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let defaults = Self::default();
+        let weight = |key: &'static str, default: f32| -> Result<f32> {
+            match lookup(key) {
+                None => Ok(default),
+                Some(v) => v
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|w| w.is_finite() && *w >= 0.0)
+                    .ok_or_else(|| Error::InvalidConfig {
+                        key,
+                        message: format!("expected a non-negative number, got '{}'", v).into(),
+                    }),
+            }
+        };
+
+        let weights = Self {
+            tag_match: weight("REC_WEIGHT_TAG_MATCH", defaults.tag_match)?,
+            creator_affinity: weight("REC_WEIGHT_CREATOR_AFFINITY", defaults.creator_affinity)?,
+            content_type: weight("REC_WEIGHT_CONTENT_TYPE", defaults.content_type)?,
+            trending: weight("REC_WEIGHT_TRENDING", defaults.trending)?,
+            engagement: weight("REC_WEIGHT_ENGAGEMENT", defaults.engagement)?,
+            quality: weight("REC_WEIGHT_QUALITY", defaults.quality)?,
+            recency: weight("REC_WEIGHT_RECENCY", defaults.recency)?,
+            diversity_penalty: weight("REC_WEIGHT_DIVERSITY_PENALTY", defaults.diversity_penalty)?,
+            negative_grace: weight("REC_NEGATIVE_SCORE_GRACE", defaults.negative_grace)?,
+        };
+        weights.validate()?;
+        Ok(weights)
+    }
+
+    /// The non-penalty weights must sum to 1.0 (within 0.01)
+    pub fn validate(&self) -> Result<()> {
+        let sum = self.tag_match
+            + self.creator_affinity
+            + self.content_type
+            + self.trending
+            + self.engagement
+            + self.quality
+            + self.recency;
+        if (sum - 1.0).abs() > 0.01 {
+            return Err(Error::InvalidConfig {
+                key: "REC_WEIGHT_*",
+                message: format!("scoring weights must sum to 1.0, got {:.3}", sum).into(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// How many candidates a feed request scores: `multiplier` times the page
+/// size, never more than `max_candidates`. Larger pools rank better but cost
+/// latency.
+#[derive(Debug, Clone)]
+pub struct CandidatePool {
+    pub max_candidates: usize,
+    /// Multiplier for personalized recommendations
+    pub personalized_multiplier: usize,
+    /// Multiplier for the enhanced feed
+    pub enhanced_multiplier: usize,
+    /// Multiplier for enhanced feed pages smaller than `SMALL_PAGE_LIMIT`,
+    /// which need a deeper pool to fill after diversity filtering
+    pub small_page_multiplier: usize,
+}
+
+/// Enhanced feed pages below this size use `small_page_multiplier`
+pub const SMALL_PAGE_LIMIT: usize = 20;
+
+impl Default for CandidatePool {
+    fn default() -> Self {
+        Self {
+            max_candidates: 1000,
+            personalized_multiplier: 4,
+            enhanced_multiplier: 3,
+            small_page_multiplier: 5,
+        }
+    }
+}
+
+/// Daily decay factor per signal class. Affinities with no recorded class
+/// (older rows) decay like engagement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecayRates {
+    pub purchase: f32,
+    pub engagement: f32,
+    pub view: f32,
+}
+
+impl Default for DecayRates {
+    fn default() -> Self {
+        Self {
+            purchase: 0.99,
+            engagement: 0.95,
+            view: 0.9,
+        }
+    }
+}
+
+/// Rollouts in effect for flags `REC_FEATURE_FLAGS` doesn't mention. Cold
+/// start predates the flags, so it stays on for everyone by default.
+pub const DEFAULT_FEATURE_FLAGS: &[(&str, u8)] = &[("cold_start", 100)];
+
+/// Percentage rollouts for experimental scoring paths, keyed by flag name.
+/// Evaluated per user in `recommendation::flags`.
+#[derive(Debug, Clone)]
+pub struct FeatureFlags {
+    pub(crate) rollouts: HashMap<String, u8>,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        let rollouts = DEFAULT_FEATURE_FLAGS
+            .iter()
+            .map(|(flag, percent)| (flag.to_string(), *percent))
+            .collect();
+        Self { rollouts }
+    }
+}
+
+/// Exploration baseline for creators with little interaction history.
+///
+/// Below `interaction_threshold` interactions, a creator's quality score is
+/// blended towards `baseline_quality`, shifting linearly to the real score as
+/// interactions accumulate.
+#[derive(Debug, Clone)]
+pub struct CreatorColdStart {
+    pub baseline_quality: f32,
+    pub interaction_threshold: u32,
+}
+
+impl Default for CreatorColdStart {
+    fn default() -> Self {
+        Self {
+            baseline_quality: 0.5,
+            interaction_threshold: 20,
+        }
+    }
+}
+
+/// Seeding for the discovery picks in the diversity shuffle.
+///
+/// The seed is derived from the user address and the current time window, so
+/// a user sees the same discovery picks within a window while different users
+/// and windows still vary. `fixed_seed` pins it for debugging and tests.
+#[derive(Debug, Clone)]
+pub struct DiscoveryShuffle {
+    pub window_secs: u64,
+    pub fixed_seed: Option<u64>,
+}
+
+impl Default for DiscoveryShuffle {
+    fn default() -> Self {
+        Self {
+            window_secs: 86_400,
+            fixed_seed: None,
+        }
+    }
+}
+
+/// Tie-breakers applied, in order, to NFTs with equal trending scores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrendingTieBreak {
+    /// Higher recent engagement first
+    Engagement,
+    /// Newer first
+    Recency,
+}
+
+/// Tie-breakers used when `REC_TRENDING_TIE_BREAKS` is unset
+pub const DEFAULT_TRENDING_TIE_BREAKS: &[TrendingTieBreak] =
+    &[TrendingTieBreak::Engagement, TrendingTieBreak::Recency];
+
+impl TrendingTieBreak {
+    /// Parse a comma-separated list; `none` or an empty list disables tie-breaking
+    pub fn parse_list(spec: &str) -> Vec<Self> {
+        spec.split(',')
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty() && s != "none")
+            .filter_map(|s| match s.as_str() {
+                "engagement" => Some(Self::Engagement),
+                "recency" => Some(Self::Recency),
+                other => {
+                    warn!("Ignoring unknown trending tie-break: {}", other);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Broadening for users whose tag preferences match few candidates.
+///
+/// When fewer than `min_tag_matches` candidates match a user's preferred
+/// tags, extra candidates are fetched from the content types they prefer and
+/// from tags that co-occur with theirs, and ranked after the tag matches as
+/// `Discovery`.
+#[derive(Debug, Clone)]
+pub struct NicheBroadening {
+    /// Tag matches below which the feed is broadened (0 disables)
+    pub min_tag_matches: usize,
+    /// Co-occurring tags to broaden with
+    pub co_tag_limit: usize,
+}
+
+impl Default for NicheBroadening {
+    fn default() -> Self {
+        Self {
+            min_tag_matches: 5,
+            co_tag_limit: 10,
+        }
+    }
+}
+
+/// Share of the enhanced feed's candidate pool each source contributes
+/// before scoring. Shares are normalized to sum to 1; a source that comes up
+/// short is backfilled from the others.
+#[derive(Debug, Clone)]
+pub struct SourceBlend {
+    pub personalized: f32,
+    pub trending: f32,
+    pub collaborative: f32,
+    pub following: f32,
+}
+
+impl Default for SourceBlend {
+    fn default() -> Self {
+        Self {
+            personalized: 0.5,
+            trending: 0.2,
+            collaborative: 0.2,
+            following: 0.1,
+        }
+    }
+}
+
+/// Tags in the taste space when `REC_TASTE_TAGS` is unset
+const DEFAULT_TASTE_TAGS: &[&str] = &[
+    "abstract", "portrait", "landscape", "nature", "photography", "animation",
+    "electronic", "hiphop", "ambient", "gaming", "fashion", "meme",
+];
+
+/// Canonical feature space for taste vectors: the four content-type
+/// affinities followed by a fixed tag vocabulary
+#[derive(Debug, Clone, PartialEq)]
+pub struct TasteSpace {
+    pub tags: Vec<String>,
+}
+
+impl Default for TasteSpace {
+    fn default() -> Self {
+        Self {
+            tags: DEFAULT_TASTE_TAGS.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+}
+
+/// How `trending_score` is computed
+#[derive(Debug, Clone)]
+pub struct TrendingConfig {
+    /// Age at which a like, comment or share counts half as much
+    pub half_life: Duration,
+    /// Measure trending against each content type's own baseline
+    pub normalize_by_type: bool,
+}
+
+impl Default for TrendingConfig {
+    fn default() -> Self {
+        Self {
+            half_life: Duration::from_secs(6 * 3600),
+            normalize_by_type: true,
+        }
+    }
+}
+
+/// Kafka event processor configuration
+#[derive(Debug, Clone)]
+pub struct ProcessorConfig {
+    /// Also consume events in the Elixir app's JSON shape
+    pub elixir_events_enabled: bool,
+    /// Topic carrying Elixir-shaped events (subscribed when enabled)
+    pub elixir_events_topic: String,
+    /// Store mints whose creator decodes to the zero or a malformed address
+    /// (they are counted and skipped by default)
+    pub store_invalid_creator_mints: bool,
+    /// Weight comment interactions by comment length and repetition
+    pub comment_analysis_enabled: bool,
+    /// Window in which a user repeating the same comment counts as spam
+    pub comment_repeat_window: Duration,
+    /// Interactions per user per day that feed preference learning, as a
+    /// multiple of the on-chain daily post limit (0 disables the cap)
+    pub interaction_cap_per_post: u64,
+    /// Longest a single message may take to process before it is abandoned
+    pub message_timeout: Duration,
+    /// Without a dead-letter topic, times a message may time out before it
+    /// is skipped (`PROCESSOR_MESSAGE_MAX_TIMEOUTS`)
+    pub message_max_timeouts: u32,
+    /// Look up whether share recipients are contracts (`eth_getCode`) and
+    /// weight shares to users above shares to contracts
+    pub classify_share_recipients: bool,
+    /// Attempts at a failing message before it is sent to the dead-letter topic
+    pub dlq_max_attempts: u32,
+    /// Publish each recorded interaction as a `UserActionEvent` on the
+    /// recommendations topic
+    pub echo_interactions: bool,
+    /// Sender followers at which a tip's creator boost reaches full weight;
+    /// fewer followers (and no verification) weigh less (0 disables)
+    pub tip_reputation_followers: u64,
+    /// How far an interaction's event time may trail the wall clock before
+    /// it counts as late (`PROCESSOR_LATE_EVENT_GRACE_SECS`)
+    pub late_event_grace: Duration,
+    /// Daily decay applied to a late interaction's weight for each day of
+    /// its age (`PROCESSOR_LATE_EVENT_DAILY_DECAY`, 1 disables)
+    pub late_event_daily_decay: f32,
+    /// Fetching of mint metadata documents for tags
+    pub metadata: MetadataFetchConfig,
+}
+
+/// Where and how much mint metadata the processor may fetch. Metadata URIs
+/// come from on-chain events, so anyone who can mint chooses them: only
+/// `ipfs://` URIs (through the gateway) and `https://` URIs on an allowed
+/// host are fetched.
+#[derive(Debug, Clone)]
+pub struct MetadataFetchConfig {
+    /// Gateway `ipfs://` URIs are fetched through, ending in `/`
+    /// (`IPFS_GATEWAY`)
+    pub ipfs_gateway: String,
+    /// Hosts `https://` metadata may be fetched from; empty allows IPFS only
+    /// (`METADATA_ALLOWED_HOSTS`, comma-separated)
+    pub allowed_hosts: Vec<String>,
+    /// Per-fetch timeout (`METADATA_FETCH_TIMEOUT_MS`)
+    pub timeout: Duration,
+    /// Largest metadata document read; bigger ones yield no tags
+    /// (`METADATA_MAX_BYTES`)
+    pub max_bytes: usize,
+    /// Fetches in flight at once; mints arriving while all are busy skip
+    /// the metadata lookup (`METADATA_FETCH_CONCURRENCY`)
+    pub max_concurrent: usize,
+}
+
+impl Default for MetadataFetchConfig {
+    fn default() -> Self {
+        Self {
+            ipfs_gateway: "https://ipfs.io/ipfs/".to_string(),
+            allowed_hosts: Vec::new(),
+            timeout: Duration::from_millis(5000),
+            max_bytes: 256 * 1024,
+            max_concurrent: 8,
+        }
+    }
 }
 
 impl Config {
@@ -279,18 +949,16 @@ impl Config {
             if p.is_dir() {
                 match std::fs::read_dir(p) {
                     Ok(entries) => {
-                        for entry in entries {
-                            if let Ok(e) = entry {
-                                if let Ok(fname) = e.file_name().into_string() {
-                                    let fpath = e.path();
-                                    if fpath.is_file() {
-                                        if let Ok(mut contents) = std::fs::read_to_string(&fpath) {
-                                            // Trim trailing newlines/spaces
-                                            contents = contents.trim().to_string();
-                                            // Only set env var if not already set in the environment
-                                            if std::env::var(&fname).is_err() {
-                                                std::env::set_var(&fname, contents);
-                                            }
+                        for e in entries.flatten() {
+                            if let Ok(fname) = e.file_name().into_string() {
+                                let fpath = e.path();
+                                if fpath.is_file() {
+                                    if let Ok(mut contents) = std::fs::read_to_string(&fpath) {
+                                        // Trim trailing newlines/spaces
+                                        contents = contents.trim().to_string();
+                                        // Only set env var if not already set in the environment
+                                        if std::env::var(&fname).is_err() {
+                                            std::env::set_var(&fname, contents);
                                         }
                                     }
                                 }
@@ -308,14 +976,17 @@ impl Config {
             dotenvy::dotenv().ok();
         }
 
+        let elixir_database = DatabaseConfig::from_env_elixir()?;
         let config = Self {
             blockchain: BlockchainConfig::from_env()?,
             kafka: KafkaConfig::from_env()?,
             database: DatabaseConfig::from_env()?,
-            elixir_database: DatabaseConfig::from_env_elixir()?,
+            read_database: DatabaseConfig::from_env_read_replica(&elixir_database),
+            elixir_database,
             api: ApiConfig::from_env()?,
             contracts: ContractAddresses::from_env()?,
             recommendation: RecommendationConfig::from_env()?,
+            processor: ProcessorConfig::from_env()?,
         };
 
         config.validate()?;
@@ -347,6 +1018,14 @@ impl Config {
             }
         }
 
+        // Replay republishes from `event_outbox`, which only the outbox writes
+        if self.blockchain.replay_range.is_some() && !self.blockchain.outbox_enabled {
+            return Err(Error::InvalidConfig {
+                key: "REPLAY_EVENTS_ENABLED",
+                message: "Replay reads event_outbox, which needs INDEXER_OUTBOX_ENABLED".into(),
+            });
+        }
+
         // Validate pool size
         if self.database.max_connections < self.database.min_connections {
             return Err(Error::InvalidConfig {
@@ -355,6 +1034,15 @@ impl Config {
             });
         }
 
+        if let Some(message) = pool_oversubscription(
+            self.database.max_connections,
+            PROCESSOR_CONCURRENCY,
+            self.recommendation.updater_concurrency,
+            self.api.expected_concurrency,
+        ) {
+            warn!("{}", message);
+        }
+
         Ok(())
     }
 
@@ -371,6 +1059,9 @@ impl Config {
             "    Pool Size: {}-{}",
             self.database.min_connections, self.database.max_connections
         );
+        if let Some(replica) = &self.read_database {
+            info!("    Read Replica: {}", mask_url(&replica.url));
+        }
         info!("  API:");
         info!("    Listening on: {}:{}", self.api.host, self.api.port);
         info!("  Kafka:");
@@ -381,7 +1072,65 @@ impl Config {
     }
 }
 
+/// `BACKFILL_FROM`..=`BACKFILL_TO`, when both are set
+fn backfill_range_from_env() -> Result<Option<(u64, u64)>> {
+    block_range_from_env("BACKFILL_FROM", "BACKFILL_TO")
+}
+
+/// `REPLAY_FROM`..=`REPLAY_TO` when `REPLAY_EVENTS_ENABLED` is set, which
+/// then requires both
+fn replay_range_from_env() -> Result<Option<(u64, u64)>> {
+    let enabled: bool = get_env_or("REPLAY_EVENTS_ENABLED", "false").parse().unwrap_or(false);
+    if !enabled {
+        return Ok(None);
+    }
+    match block_range_from_env("REPLAY_FROM", "REPLAY_TO")? {
+        Some(range) => Ok(Some(range)),
+        None => Err(Error::InvalidConfig {
+            key: "REPLAY_FROM",
+            message: "REPLAY_EVENTS_ENABLED needs REPLAY_FROM and REPLAY_TO".into(),
+        }),
+    }
+}
+
+/// Inclusive block range from `from_key` and `to_key`, when both are set
+fn block_range_from_env(from_key: &'static str, to_key: &'static str) -> Result<Option<(u64, u64)>> {
+    let from = std::env::var(from_key).ok().filter(|v| !v.is_empty());
+    let to = std::env::var(to_key).ok().filter(|v| !v.is_empty());
+    let (from, to) = match (from, to) {
+        (None, None) => return Ok(None),
+        (Some(from), Some(to)) => (from, to),
+        _ => {
+            return Err(Error::InvalidConfig {
+                key: from_key,
+                message: format!("{} and {} must be set together", from_key, to_key).into(),
+            })
+        }
+    };
+
+    let parse = |key: &'static str, value: String| {
+        value.parse::<u64>().map_err(|_| Error::InvalidConfig {
+            key,
+            message: format!("Invalid block number: {}", value).into(),
+        })
+    };
+    let (from, to) = (parse(from_key, from)?, parse(to_key, to)?);
+    if from > to {
+        return Err(Error::InvalidConfig {
+            key: from_key,
+            message: format!("{} ({}) is after {} ({})", from_key, from, to_key, to).into(),
+        });
+    }
+    Ok(Some((from, to)))
+}
+
 impl BlockchainConfig {
+    /// Backfill chunks to fetch at once: `backfill_parallelism` within
+    /// `backfill_max_parallelism`, and at least one
+    pub fn backfill_concurrency(&self) -> usize {
+        self.backfill_parallelism.min(self.backfill_max_parallelism).max(1)
+    }
+
     fn from_env() -> Result<Self> {
         Ok(Self {
             rpc_url: get_env("RPC_URL")?,
@@ -403,6 +1152,62 @@ impl BlockchainConfig {
                     .parse()
                     .unwrap_or(1000),
             ),
+            timestamp_tolerance_secs: get_env_or("INDEXER_TIMESTAMP_TOLERANCE_SECS", "0")
+                .parse()
+                .ok()
+                .filter(|&t: &u64| t > 0),
+            outbox_enabled: get_env_or("INDEXER_OUTBOX_ENABLED", "false")
+                .parse()
+                .unwrap_or(false),
+            outbox_retention: Some(
+                get_env_or("INDEXER_OUTBOX_RETENTION_DAYS", "30")
+                    .parse::<u64>()
+                    .unwrap_or(30),
+            )
+            .filter(|&days| days > 0)
+            .map(|days| Duration::from_secs(days * 86_400)),
+            lag_warn_blocks: get_env_or("INDEXER_LAG_WARN_BLOCKS", "1000")
+                .parse()
+                .unwrap_or(1000),
+            reorg_rewind_depth: get_env_or("REORG_REWIND_DEPTH", "12")
+                .parse()
+                .unwrap_or(12),
+            backfill_block_hashes: get_env_or("INDEXER_BACKFILL_BLOCK_HASHES", "true")
+                .parse()
+                .unwrap_or(true),
+            progress_enabled: get_env_or("INDEXER_PROGRESS_ENABLED", "false")
+                .parse()
+                .unwrap_or(false),
+            progress_interval: Duration::from_secs(
+                get_env_or("INDEXER_PROGRESS_INTERVAL_SECS", "30")
+                    .parse()
+                    .unwrap_or(30),
+            ),
+            backfill_range: backfill_range_from_env()?,
+            replay_range: replay_range_from_env()?,
+            backfill_parallelism: get_env_or("BACKFILL_PARALLELISM", "1")
+                .parse()
+                .unwrap_or(1),
+            backfill_max_parallelism: get_env_or("BACKFILL_MAX_PARALLELISM", "4")
+                .parse()
+                .unwrap_or(4),
+            rpc_breaker_threshold: get_env_or("RPC_BREAKER_THRESHOLD", "5")
+                .parse()
+                .unwrap_or(5),
+            rpc_breaker_cooldown: Duration::from_secs(
+                get_env_or("RPC_BREAKER_COOLDOWN_SECS", "30")
+                    .parse()
+                    .unwrap_or(30),
+            ),
+            pending_logs: get_env_or("INDEXER_PENDING_LOGS", "reject")
+                .parse()
+                .map_err(|e: String| Error::InvalidConfig {
+                    key: "INDEXER_PENDING_LOGS",
+                    message: e.into(),
+                })?,
+            partial_decode: get_env_or("EVENT_PARTIAL_DECODE_ENABLED", "true")
+                .parse()
+                .unwrap_or(true),
         })
     }
 }
@@ -419,6 +1224,20 @@ impl KafkaConfig {
                 blockchain_events: get_env_or("KAFKA_TOPIC_BLOCKCHAIN", "blockchain.events"),
                 user_actions: get_env_or("KAFKA_TOPIC_USER_ACTIONS", "user.actions"),
                 recommendations: get_env_or("KAFKA_TOPIC_RECOMMENDATIONS", "recommendations"),
+                user_actions_key: get_env_or("KAFKA_USER_ACTIONS_KEY", "contract")
+                    .parse()
+                    .map_err(|e: String| Error::InvalidConfig {
+                        key: "KAFKA_USER_ACTIONS_KEY",
+                        message: e.into(),
+                    })?,
+                dlq: get_env("KAFKA_TOPIC_DLQ").ok().filter(|t| !t.is_empty()),
+                dlq_retention: Duration::from_secs(
+                    get_env_or("KAFKA_DLQ_RETENTION_HOURS", "168")
+                        .parse::<u64>()
+                        .unwrap_or(168)
+                        * 3600,
+                ),
+                indexer_progress: get_env_or("KAFKA_TOPIC_INDEXER_PROGRESS", "indexer.progress"),
             },
             producer: KafkaProducerConfig {
                 message_timeout: Duration::from_millis(
@@ -440,8 +1259,12 @@ impl KafkaConfig {
                 linger: Duration::from_millis(
                     get_env_or("KAFKA_LINGER_MS", "5").parse().unwrap_or(5),
                 ),
-                compression: get_env_or("KAFKA_COMPRESSION", "lz4"),
-                acks: get_env_or("KAFKA_ACKS", "all"),
+                compression: one_of(
+                    "KAFKA_COMPRESSION",
+                    &get_env_or("KAFKA_COMPRESSION", "lz4"),
+                    KAFKA_COMPRESSION_CODECS,
+                )?,
+                acks: one_of("KAFKA_ACKS", &get_env_or("KAFKA_ACKS", "all"), KAFKA_ACKS_VALUES)?,
                 idempotent: get_env_or("KAFKA_IDEMPOTENT", "true")
                     .parse()
                     .unwrap_or(true),
@@ -533,15 +1356,37 @@ impl DatabaseConfig {
     }
 }
 
+impl DatabaseConfig {
+    /// `DATABASE_READ_URL`, pooled like `primary`, when set
+    fn from_env_read_replica(primary: &DatabaseConfig) -> Option<Self> {
+        let url = get_env("DATABASE_READ_URL").ok().filter(|url| !url.is_empty())?;
+        Some(Self {
+            url,
+            ..primary.clone()
+        })
+    }
+}
+
 impl ApiConfig {
-    fn from_env() -> Result<Self> {
+    pub(crate) fn from_env() -> Result<Self> {
+        let request_timeout = Duration::from_secs(
+            get_env_or("API_REQUEST_TIMEOUT_SECS", "30")
+                .parse()
+                .unwrap_or(30),
+        );
+
         Ok(Self {
             port: get_env_or("API_PORT", "8080").parse().unwrap_or(8080),
             host: get_env_or("API_HOST", "0.0.0.0"),
-            request_timeout: Duration::from_secs(
-                get_env_or("API_REQUEST_TIMEOUT_SECS", "30")
+            request_timeout,
+            feed_timeout: get_env_or("API_FEED_TIMEOUT_MS", "")
+                .parse()
+                .map(Duration::from_millis)
+                .unwrap_or(request_timeout),
+            health_timeout: Duration::from_millis(
+                get_env_or("API_HEALTH_TIMEOUT_MS", "1000")
                     .parse()
-                    .unwrap_or(30),
+                    .unwrap_or(1000),
             ),
             max_body_size: get_env_or("API_MAX_BODY_SIZE", "10485760")
                 .parse()
@@ -553,6 +1398,33 @@ impl ApiConfig {
                 .split(',')
                 .map(|s| s.trim().to_string())
                 .collect(),
+            expected_concurrency: get_env_or("API_EXPECTED_CONCURRENCY", "8")
+                .parse()
+                .unwrap_or(8),
+            admin_token: Some(get_env_or("API_ADMIN_TOKEN", "")).filter(|t| !t.is_empty()),
+            view_rate_limit_per_minute: get_env_or("API_VIEW_RATE_LIMIT_PER_MINUTE", "60")
+                .parse()
+                .unwrap_or(60),
+            max_concurrent_requests: get_env_or("API_MAX_CONCURRENT_REQUESTS", "0")
+                .parse()
+                .unwrap_or(0),
+            home_feed_timeout: Duration::from_millis(
+                get_env_or("API_HOME_FEED_TIMEOUT_MS", "2000")
+                    .parse()
+                    .unwrap_or(2000),
+            ),
+            debug_explain_enabled: get_env_or("API_DEBUG_EXPLAIN_ENABLED", "false")
+                .parse()
+                .unwrap_or(false),
+            rate_limit_rps: get_env_or("API_RATE_LIMIT_RPS", "0")
+                .parse()
+                .unwrap_or(0.0),
+            rate_limit_burst: get_env_or("API_RATE_LIMIT_BURST", "20")
+                .parse()
+                .unwrap_or(20),
+            rate_limit_per_address: get_env_or("API_RATE_LIMIT_PER_ADDRESS", "false")
+                .parse()
+                .unwrap_or(false),
         })
     }
 }
@@ -572,6 +1444,7 @@ impl ContractAddresses {
         Ok(Self {
             thera_friends: friends_addr,
             thera_social: social_addr,
+            aliases: ContractAliases::parse(&get_env_or("CONTRACT_ADDRESS_ALIASES", ""))?,
         })
     }
 }
@@ -584,9 +1457,10 @@ impl RecommendationConfig {
                     .parse()
                     .unwrap_or(300),
             ),
-            max_candidates: get_env_or("REC_MAX_CANDIDATES", "1000")
+            cache_max_rows: get_env_or("REC_CACHE_MAX_ROWS", "100000")
                 .parse()
-                .unwrap_or(1000),
+                .unwrap_or(100000),
+            candidates: CandidatePool::from_env(),
             min_score: get_env_or("REC_MIN_SCORE", "0.1").parse().unwrap_or(0.1),
             diversity_factor: get_env_or("REC_DIVERSITY_FACTOR", "0.2")
                 .parse()
@@ -604,19 +1478,387 @@ impl RecommendationConfig {
             preference_decay_rate: get_env_or("REC_PREFERENCE_DECAY", "0.95")
                 .parse()
                 .unwrap_or(0.95),
+            updater_concurrency: get_env_or("REC_UPDATER_CONCURRENCY", "10")
+                .parse()
+                .unwrap_or(10),
+            hot_window: Duration::from_secs(
+                get_env_or("REC_HOT_WINDOW_SECS", "21600")
+                    .parse()
+                    .unwrap_or(21600),
+            ),
+            trending: TrendingConfig::from_env(),
+            weights: ScoringWeights::from_env()?,
+            nfts_schema_check: get_env_or("REC_NFTS_SCHEMA_CHECK", "true")
+                .parse()
+                .unwrap_or(true),
+            cold_start: CreatorColdStart::from_env(),
+            feature_flags: FeatureFlags::parse(&get_env_or("REC_FEATURE_FLAGS", "")),
+            shuffle: DiscoveryShuffle::from_env(),
+            trending_tie_breaks: std::env::var("REC_TRENDING_TIE_BREAKS")
+                .map(|s| TrendingTieBreak::parse_list(&s))
+                .unwrap_or_else(|_| DEFAULT_TRENDING_TIE_BREAKS.to_vec()),
+            max_stale: Duration::from_secs(
+                get_env_or("REC_MAX_STALE_SECS", "3600")
+                    .parse()
+                    .unwrap_or(3600),
+            ),
+            niche: NicheBroadening::from_env(),
+            blend: SourceBlend::from_env(),
+            exclude_followed_creators: get_env_or("REC_EXCLUDE_FOLLOWED_CREATORS", "false")
+                .parse()
+                .unwrap_or(false),
+            engine_enabled: get_env_or("REC_ENGINE_ENABLED", "true")
+                .parse()
+                .unwrap_or(true),
+            include_creator_profiles: get_env_or("REC_INCLUDE_CREATOR_PROFILES", "true")
+                .parse()
+                .unwrap_or(true),
+            badge_quality_boost: get_env_or("REC_BADGE_QUALITY_BOOST", "0")
+                .parse::<f32>()
+                .ok()
+                .filter(|b| b.is_finite())
+                .unwrap_or(0.0)
+                .max(0.0),
+            rejected_penalty: get_env_or("REC_REJECTED_PENALTY", "0.9")
+                .parse::<f32>()
+                .ok()
+                .filter(|p| p.is_finite())
+                .unwrap_or(0.9)
+                .clamp(0.0, 1.0),
+            first_contact_boost: get_env_or("REC_FIRST_CONTACT_BOOST", "0.5")
+                .parse::<f32>()
+                .ok()
+                .filter(|b| b.is_finite())
+                .unwrap_or(0.5)
+                .max(0.0),
+            taste_space: TasteSpace::from_env(),
+            decay: DecayRates::from_env(),
+            saved_by_similar_boost: get_env_or("REC_SAVED_BY_SIMILAR_BOOST", "0.1")
+                .parse::<f32>()
+                .ok()
+                .filter(|b| b.is_finite())
+                .unwrap_or(0.1)
+                .clamp(0.0, 1.0),
+            creator_max_share: get_env_or("REC_CREATOR_MAX_SHARE", "0.4")
+                .parse::<f32>()
+                .ok()
+                .filter(|s| s.is_finite())
+                .unwrap_or(0.4),
+        })
+    }
+}
+
+impl DecayRates {
+    /// `REC_DECAY_PURCHASE` (default 0.99) and `REC_DECAY_VIEW` (default
+    /// 0.9); everything else decays at the default 0.95
+    fn from_env() -> Self {
+        let defaults = Self::default();
+        let rate = |key: &str, default: f32| {
+            get_env_or(key, "")
+                .parse::<f32>()
+                .ok()
+                .map(|r| r.clamp(0.0, 1.0))
+                .unwrap_or(default)
+        };
+        Self {
+            purchase: rate("REC_DECAY_PURCHASE", defaults.purchase),
+            engagement: defaults.engagement,
+            view: rate("REC_DECAY_VIEW", defaults.view),
+        }
+    }
+}
+
+impl CandidatePool {
+    /// Load from `REC_MAX_CANDIDATES` / `REC_CANDIDATE_MULTIPLIER_PERSONALIZED`
+    /// / `REC_CANDIDATE_MULTIPLIER_ENHANCED` / `REC_CANDIDATE_MULTIPLIER_SMALL_PAGE`
+    fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |key: &str, default: usize| {
+            get_env_or(key, "")
+                .parse()
+                .ok()
+                .filter(|&v: &usize| v > 0)
+                .unwrap_or(default)
+        };
+        Self {
+            max_candidates: var("REC_MAX_CANDIDATES", defaults.max_candidates),
+            personalized_multiplier: var(
+                "REC_CANDIDATE_MULTIPLIER_PERSONALIZED",
+                defaults.personalized_multiplier,
+            ),
+            enhanced_multiplier: var("REC_CANDIDATE_MULTIPLIER_ENHANCED", defaults.enhanced_multiplier),
+            small_page_multiplier: var(
+                "REC_CANDIDATE_MULTIPLIER_SMALL_PAGE",
+                defaults.small_page_multiplier,
+            ),
+        }
+    }
+}
+
+impl FeatureFlags {
+    /// Parse `name=percent` pairs separated by commas on top of the
+    /// defaults. Malformed entries are skipped and percentages are capped
+    /// at 100.
+    pub fn parse(spec: &str) -> Self {
+        let mut flags = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            match entry.split_once('=') {
+                Some((name, pct)) => match pct.trim().parse::<u8>() {
+                    Ok(pct) => flags = flags.with_rollout(name.trim(), pct),
+                    Err(_) => warn!("Ignoring feature flag with invalid percentage: {}", entry),
+                },
+                None => warn!("Ignoring malformed feature flag entry: {}", entry),
+            }
+        }
+        flags
+    }
+
+    /// Set a flag's rollout percentage
+    pub fn with_rollout(mut self, flag: &str, percent: u8) -> Self {
+        self.rollouts.insert(flag.to_lowercase(), percent.min(100));
+        self
+    }
+}
+
+impl CreatorColdStart {
+    /// Load from `REC_NEW_CREATOR_BASELINE_QUALITY` / `REC_NEW_CREATOR_INTERACTION_THRESHOLD`
+    fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            baseline_quality: get_env_or("REC_NEW_CREATOR_BASELINE_QUALITY", "")
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .map(|v| v.clamp(0.0, 1.0))
+                .unwrap_or(defaults.baseline_quality),
+            interaction_threshold: get_env_or("REC_NEW_CREATOR_INTERACTION_THRESHOLD", "")
+                .parse()
+                .unwrap_or(defaults.interaction_threshold),
+        }
+    }
+}
+
+impl DiscoveryShuffle {
+    /// Load from `REC_SHUFFLE_WINDOW_SECS` / `REC_SHUFFLE_SEED`
+    fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            window_secs: get_env_or("REC_SHUFFLE_WINDOW_SECS", "")
+                .parse()
+                .ok()
+                .filter(|&v: &u64| v > 0)
+                .unwrap_or(defaults.window_secs),
+            fixed_seed: get_env_or("REC_SHUFFLE_SEED", "").parse().ok(),
+        }
+    }
+}
+
+impl NicheBroadening {
+    /// Load from `REC_NICHE_MIN_TAG_MATCHES` / `REC_NICHE_CO_TAG_LIMIT`
+    fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            min_tag_matches: get_env_or("REC_NICHE_MIN_TAG_MATCHES", "")
+                .parse()
+                .unwrap_or(defaults.min_tag_matches),
+            co_tag_limit: get_env_or("REC_NICHE_CO_TAG_LIMIT", "")
+                .parse()
+                .unwrap_or(defaults.co_tag_limit),
+        }
+    }
+}
+
+impl SourceBlend {
+    /// Load from `REC_BLEND_PERSONALIZED` / `REC_BLEND_TRENDING` /
+    /// `REC_BLEND_COLLABORATIVE` / `REC_BLEND_FOLLOWING`
+    fn from_env() -> Self {
+        let defaults = Self::default();
+        let share = |key: &str, default: f32| {
+            get_env_or(key, "")
+                .parse::<f32>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .unwrap_or(default)
+        };
+        Self {
+            personalized: share("REC_BLEND_PERSONALIZED", defaults.personalized),
+            trending: share("REC_BLEND_TRENDING", defaults.trending),
+            collaborative: share("REC_BLEND_COLLABORATIVE", defaults.collaborative),
+            following: share("REC_BLEND_FOLLOWING", defaults.following),
+        }
+    }
+}
+
+impl TasteSpace {
+    /// Load from `REC_TASTE_TAGS` (comma-separated, default `DEFAULT_TASTE_TAGS`)
+    fn from_env() -> Self {
+        match std::env::var("REC_TASTE_TAGS") {
+            Ok(spec) => Self {
+                tags: spec
+                    .split(',')
+                    .map(|t| t.trim().to_lowercase())
+                    .filter(|t| !t.is_empty())
+                    .collect(),
+            },
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+impl TrendingConfig {
+    /// Load from `REC_TRENDING_HALFLIFE_HOURS` / `REC_TRENDING_NORMALIZE_BY_TYPE`
+    fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            half_life: get_env_or("REC_TRENDING_HALFLIFE_HOURS", "")
+                .parse::<f64>()
+                .ok()
+                .filter(|hours| hours.is_finite() && *hours > 0.0)
+                .map(|hours| Duration::from_secs_f64(hours * 3600.0))
+                .unwrap_or(defaults.half_life),
+            normalize_by_type: get_env_or("REC_TRENDING_NORMALIZE_BY_TYPE", "")
+                .parse()
+                .unwrap_or(defaults.normalize_by_type),
+        }
+    }
+}
+
+impl ProcessorConfig {
+    fn from_env() -> Result<Self> {
+        Ok(Self {
+            elixir_events_enabled: get_env_or("PROCESSOR_ELIXIR_EVENTS_ENABLED", "false")
+                .parse()
+                .unwrap_or(false),
+            elixir_events_topic: get_env_or("PROCESSOR_ELIXIR_EVENTS_TOPIC", "elixir.events"),
+            store_invalid_creator_mints: get_env_or("PROCESSOR_STORE_INVALID_CREATOR_MINTS", "false")
+                .parse()
+                .unwrap_or(false),
+            comment_analysis_enabled: get_env_or("PROCESSOR_COMMENT_ANALYSIS_ENABLED", "false")
+                .parse()
+                .unwrap_or(false),
+            comment_repeat_window: Duration::from_secs(
+                get_env_or("PROCESSOR_COMMENT_REPEAT_WINDOW_SECS", "3600")
+                    .parse()
+                    .unwrap_or(3600),
+            ),
+            interaction_cap_per_post: get_env_or("PROCESSOR_INTERACTION_CAP_PER_POST", "0")
+                .parse()
+                .unwrap_or(0),
+            message_timeout: Duration::from_millis(
+                get_env_or("PROCESSOR_MESSAGE_TIMEOUT_MS", "30000")
+                    .parse()
+                    .unwrap_or(30000),
+            ),
+            message_max_timeouts: get_env_or("PROCESSOR_MESSAGE_MAX_TIMEOUTS", "3")
+                .parse::<u32>()
+                .unwrap_or(3)
+                .max(1),
+            classify_share_recipients: get_env_or("PROCESSOR_CLASSIFY_SHARE_RECIPIENTS", "false")
+                .parse()
+                .unwrap_or(false),
+            dlq_max_attempts: get_env_or("PROCESSOR_DLQ_MAX_ATTEMPTS", "3")
+                .parse::<u32>()
+                .unwrap_or(3)
+                .max(1),
+            echo_interactions: get_env_or("PROCESSOR_ECHO_INTERACTIONS", "false")
+                .parse()
+                .unwrap_or(false),
+            tip_reputation_followers: get_env_or("PROCESSOR_TIP_REPUTATION_FOLLOWERS", "1000")
+                .parse()
+                .unwrap_or(1000),
+            late_event_grace: Duration::from_secs(
+                get_env_or("PROCESSOR_LATE_EVENT_GRACE_SECS", "300")
+                    .parse()
+                    .unwrap_or(300),
+            ),
+            late_event_daily_decay: get_env_or("PROCESSOR_LATE_EVENT_DAILY_DECAY", "0.95")
+                .parse::<f32>()
+                .ok()
+                .filter(|d| d.is_finite())
+                .unwrap_or(0.95)
+                .clamp(0.0, 1.0),
+            metadata: MetadataFetchConfig::from_env(),
         })
     }
 }
 
+impl MetadataFetchConfig {
+    fn from_env() -> Self {
+        let defaults = Self::default();
+        let gateway = get_env_or("IPFS_GATEWAY", &defaults.ipfs_gateway);
+        Self {
+            ipfs_gateway: format!("{}/", gateway.trim_end_matches('/')),
+            allowed_hosts: get_env_or("METADATA_ALLOWED_HOSTS", "")
+                .split(',')
+                .map(|h| h.trim().to_lowercase())
+                .filter(|h| !h.is_empty())
+                .collect(),
+            timeout: Duration::from_millis(
+                get_env_or("METADATA_FETCH_TIMEOUT_MS", "5000")
+                    .parse()
+                    .unwrap_or(5000),
+            ),
+            max_bytes: get_env_or("METADATA_MAX_BYTES", "")
+                .parse()
+                .unwrap_or(defaults.max_bytes),
+            max_concurrent: get_env_or("METADATA_FETCH_CONCURRENCY", "")
+                .parse::<usize>()
+                .unwrap_or(defaults.max_concurrent)
+                .max(1),
+        }
+    }
+}
+
 // ============================================================================
 // Helper functions
 // ============================================================================
 
+/// The event processor handles one message at a time
+const PROCESSOR_CONCURRENCY: usize = 1;
+
+/// Describe how the pool is over-subscribed if processor, updater and API
+/// concurrency together could need more connections than `max_connections`
+fn pool_oversubscription(
+    max_connections: u32,
+    processor: usize,
+    updater: usize,
+    api: usize,
+) -> Option<String> {
+    let demand = processor + updater + api;
+    (demand > max_connections as usize).then(|| {
+        format!(
+            "DB pool may be over-subscribed: processor ({}) + updater ({}) + API ({}) = {} concurrent \
+             connections, but DB_MAX_CONNECTIONS is {}. Raise DB_MAX_CONNECTIONS or lower \
+             REC_UPDATER_CONCURRENCY / API_EXPECTED_CONCURRENCY.",
+            processor, updater, api, demand, max_connections
+        )
+    })
+}
+
 /// Get required environment variable
 fn get_env(key: &'static str) -> Result<String> {
     std::env::var(key).map_err(|_| Error::MissingEnvVar { var: key })
 }
 
+/// Compression codecs librdkafka accepts for `KAFKA_COMPRESSION`
+const KAFKA_COMPRESSION_CODECS: &[&str] = &["none", "gzip", "snappy", "lz4", "zstd"];
+/// Settings accepted for `KAFKA_ACKS`
+const KAFKA_ACKS_VALUES: &[&str] = &["0", "1", "all"];
+
+/// `value` (trimmed, lowercased) if it is one of `allowed`, so a typo fails
+/// at startup instead of as an opaque producer error
+fn one_of(key: &'static str, value: &str, allowed: &[&str]) -> Result<String> {
+    let normalized = value.trim().to_lowercase();
+    if allowed.contains(&normalized.as_str()) {
+        Ok(normalized)
+    } else {
+        Err(Error::InvalidConfig {
+            key,
+            message: format!("'{}' is not one of {}", value, allowed.join(", ")).into(),
+        })
+    }
+}
+
 /// Get environment variable with default
 fn get_env_or(key: &str, default: &str) -> String {
     std::env::var(key).unwrap_or_else(|_| default.to_string())
@@ -683,3 +1925,125 @@ impl Config {
         self.blockchain.poll_interval.as_millis() as u64
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oversubscribed_pool_is_flagged() {
+        let message = pool_oversubscription(20, PROCESSOR_CONCURRENCY, 16, 8).unwrap();
+        assert!(message.contains("= 25"), "{}", message);
+        assert!(message.contains("DB_MAX_CONNECTIONS is 20"), "{}", message);
+
+        assert!(pool_oversubscription(20, PROCESSOR_CONCURRENCY, 10, 8).is_none());
+    }
+
+    #[test]
+    fn test_contract_aliases_resolve_to_canonical_address() {
+        let (old, mid, new) = (
+            "0x1111111111111111111111111111111111111111",
+            "0x2222222222222222222222222222222222222222",
+            "0xABCDEFabcdef0000000000000000000000000000",
+        );
+        let aliases = ContractAliases::parse(&format!("{}={}, {}={}", old, mid, mid, new)).unwrap();
+
+        let canonical = new.to_lowercase();
+        assert_eq!(aliases.canonical(old), canonical);
+        assert_eq!(aliases.canonical(&mid.to_uppercase().replace("0X", "0x")), canonical);
+        assert_eq!(aliases.canonical("0xother"), "0xother");
+
+        assert!(ContractAliases::parse("").unwrap().is_empty());
+        assert!(ContractAliases::parse("0xabc").is_err());
+        assert!(ContractAliases::parse(&format!("{}=0x12", old)).is_err());
+    }
+
+    #[test]
+    fn test_contract_alias_cycles_are_rejected() {
+        let (a, b, c) = (
+            "0x1111111111111111111111111111111111111111",
+            "0x2222222222222222222222222222222222222222",
+            "0x3333333333333333333333333333333333333333",
+        );
+
+        for spec in [
+            format!("{}={}", a, a),
+            format!("{}={},{}={}", a, b, b, a),
+            format!("{}={},{}={},{}={}", a, b, b, c, c, a),
+        ] {
+            assert!(
+                matches!(
+                    ContractAliases::parse(&spec),
+                    Err(Error::InvalidConfig { key: "CONTRACT_ADDRESS_ALIASES", .. })
+                ),
+                "{} should be rejected",
+                spec
+            );
+        }
+    }
+
+    #[test]
+    fn test_kafka_compression_and_acks_are_validated() {
+        assert_eq!(one_of("KAFKA_COMPRESSION", " ZSTD", KAFKA_COMPRESSION_CODECS).unwrap(), "zstd");
+        assert_eq!(one_of("KAFKA_ACKS", "all", KAFKA_ACKS_VALUES).unwrap(), "all");
+
+        let err = one_of("KAFKA_COMPRESSION", "lz44", KAFKA_COMPRESSION_CODECS).unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidConfig { key: "KAFKA_COMPRESSION", ref message } if message.contains("lz44")),
+            "{:?}",
+            err
+        );
+
+        let err = one_of("KAFKA_ACKS", "2", KAFKA_ACKS_VALUES).unwrap_err();
+        assert!(matches!(err, Error::InvalidConfig { key: "KAFKA_ACKS", .. }), "{:?}", err);
+    }
+
+    #[test]
+    fn test_kafka_config_rejects_unknown_compression_and_acks() {
+        for (key, value) in [("KAFKA_COMPRESSION", "lz44"), ("KAFKA_ACKS", "2")] {
+            std::env::set_var(key, value);
+            let result = KafkaConfig::from_env();
+            std::env::remove_var(key);
+            assert!(
+                matches!(result, Err(Error::InvalidConfig { key: k, .. }) if k == key),
+                "{}={} should be rejected",
+                key,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_scoring_weights_from_vars() {
+        let vars = |pairs: &[(&str, &str)]| {
+            let map: HashMap<String, String> =
+                pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            move |key: &str| map.get(key).cloned()
+        };
+
+        // Missing vars fall back to the defaults, which sum to 1.0
+        ScoringWeights::default().validate().unwrap();
+        let defaults = ScoringWeights::from_vars(vars(&[])).unwrap();
+        assert_eq!(defaults.tag_match, ScoringWeights::default().tag_match);
+        assert_eq!(defaults.diversity_penalty, ScoringWeights::default().diversity_penalty);
+
+        // A valid custom set that still sums to 1.0
+        let custom = ScoringWeights::from_vars(vars(&[
+            ("REC_WEIGHT_TAG_MATCH", "0.30"),
+            ("REC_WEIGHT_CREATOR_AFFINITY", "0.25"),
+            ("REC_WEIGHT_DIVERSITY_PENALTY", "0.5"),
+        ]))
+        .unwrap();
+        assert_eq!(custom.tag_match, 0.30);
+        assert_eq!(custom.creator_affinity, 0.25);
+        assert_eq!(custom.content_type, ScoringWeights::default().content_type);
+        assert_eq!(custom.diversity_penalty, 0.5);
+
+        // Weights that don't sum to 1.0 are rejected
+        let err = ScoringWeights::from_vars(vars(&[("REC_WEIGHT_TAG_MATCH", "0.6")])).unwrap_err();
+        assert!(matches!(err, Error::InvalidConfig { .. }));
+
+        // So is garbage
+        assert!(ScoringWeights::from_vars(vars(&[("REC_WEIGHT_TRENDING", "lots")])).is_err());
+    }
+}
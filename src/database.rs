@@ -6,7 +6,7 @@
 //! - Query instrumentation
 //! - Connection lifecycle management
 
-use crate::config::DatabaseConfig;
+use crate::config::{DatabaseConfig, RetryPolicy};
 use crate::error::{Error, Result};
 use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
 use sqlx::ConnectOptions;
@@ -28,6 +28,15 @@ impl Database {
         Ok(Self { pool })
     }
 
+    /// Create a pool that connects on first use instead of up front
+    ///
+    /// Lets startup proceed while the database is down; each acquire retries
+    /// the connection, so the pool recovers on its own once the server is back.
+    pub fn new_lazy(config: &DatabaseConfig) -> Result<Self> {
+        let pool = pool_options(config).connect_lazy_with(connect_options(config)?);
+        Ok(Self { pool })
+    }
+
     /// Get reference to the underlying pool
     pub fn pool(&self) -> &PgPool {
         &self.pool
@@ -45,12 +54,10 @@ impl Database {
         Ok(())
     }
 
-    /// Get pool statistics
+    /// Get pool statistics: connections open, idle and checked out right
+    /// now, against the cap
     pub fn stats(&self) -> PoolStats {
-        PoolStats {
-            size: self.pool.size(),
-            idle: self.pool.num_idle(),
-        }
+        PoolStats::of(&self.pool)
     }
 
     /// Close all connections gracefully
@@ -66,6 +73,24 @@ impl Database {
 pub struct PoolStats {
     pub size: u32,
     pub idle: usize,
+    /// Connections checked out of the pool
+    pub active: u32,
+    /// `DB_MAX_CONNECTIONS`, for spotting saturation
+    pub max: u32,
+}
+
+impl PoolStats {
+    /// Current statistics of `pool`
+    pub fn of(pool: &PgPool) -> Self {
+        let size = pool.size();
+        let idle = pool.num_idle();
+        Self {
+            size,
+            idle,
+            active: size.saturating_sub(idle as u32),
+            max: pool.options().get_max_connections(),
+        }
+    }
 }
 
 /// Create a connection pool with the given configuration
@@ -76,6 +101,34 @@ pub async fn create_pool(config: &DatabaseConfig) -> Result<PgPool> {
         config.max_connections, config.min_connections, config.connect_timeout
     );
 
+    let pool = pool_options(config)
+        .connect_with(connect_options(config)?)
+        .await
+        .map_err(|e| Error::Database {
+            message: format!("Failed to create connection pool: {}", e).into(),
+            source: Some(e),
+        })?;
+
+    // Verify we can connect
+    sqlx::query("SELECT 1")
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| Error::Database {
+            message: format!("Failed to verify database connection: {}", e).into(),
+            source: Some(e),
+        })?;
+
+    info!(
+        "Database connection pool created (size: {}, idle: {})",
+        pool.size(),
+        pool.num_idle()
+    );
+
+    Ok(pool)
+}
+
+/// Parse the connection URL and apply statement cache and logging settings
+fn connect_options(config: &DatabaseConfig) -> Result<PgConnectOptions> {
     // Parse connection options
     let mut connect_options =
         PgConnectOptions::from_str(&config.url).map_err(|e| Error::Config {
@@ -91,7 +144,12 @@ pub async fn create_pool(config: &DatabaseConfig) -> Result<PgPool> {
     connect_options =
         connect_options.log_slow_statements(log::LevelFilter::Warn, Duration::from_secs(1));
 
-    let pool = PgPoolOptions::new()
+    Ok(connect_options)
+}
+
+/// Pool sizing, timeouts and connection lifecycle hooks
+fn pool_options(config: &DatabaseConfig) -> PgPoolOptions {
+    PgPoolOptions::new()
         .max_connections(config.max_connections)
         .min_connections(config.min_connections)
         .acquire_timeout(config.connect_timeout)
@@ -115,29 +173,6 @@ pub async fn create_pool(config: &DatabaseConfig) -> Result<PgPool> {
                 Ok(true)
             })
         })
-        .connect_with(connect_options)
-        .await
-        .map_err(|e| Error::Database {
-            message: format!("Failed to create connection pool: {}", e).into(),
-            source: Some(e),
-        })?;
-
-    // Verify we can connect
-    sqlx::query("SELECT 1")
-        .fetch_one(&pool)
-        .await
-        .map_err(|e| Error::Database {
-            message: format!("Failed to verify database connection: {}", e).into(),
-            source: Some(e),
-        })?;
-
-    info!(
-        "Database connection pool created (size: {}, idle: {})",
-        pool.size(),
-        pool.num_idle()
-    );
-
-    Ok(pool)
 }
 
 /// Legacy function for backward compatibility
@@ -204,18 +239,69 @@ pub async fn run_migrations(pool: &PgPool) -> Result<()> {
     Ok(())
 }
 
+/// Columns of the Elixir-owned `nfts` table that feed queries read
+pub const NFTS_REQUIRED_COLUMNS: &[&str] = &[
+    "id",
+    "token_id",
+    "contract_address",
+    "contract_type",
+    "creator_address",
+    "creation_time",
+    "is_deleted",
+    "is_original",
+];
+
+/// Verify that `table` (in the current schema) has every column in
+/// `required`, so a renamed or dropped column fails at startup with its
+/// name rather than as an opaque SQL error on every request
+#[instrument(skip(executor, required))]
+pub async fn check_table_columns<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    table: &str,
+    required: &[&str],
+) -> Result<()> {
+    let present: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT column_name::text FROM information_schema.columns
+        WHERE table_schema = current_schema() AND table_name = $1
+        "#,
+    )
+    .bind(table)
+    .fetch_all(executor)
+    .await?;
+
+    if present.is_empty() {
+        return Err(Error::config(format!("Table {} not found", table)));
+    }
+    let missing: Vec<&str> = required
+        .iter()
+        .copied()
+        .filter(|column| !present.iter().any(|p| p == column))
+        .collect();
+    if !missing.is_empty() {
+        return Err(Error::config(format!(
+            "Table {} is missing expected column(s): {}",
+            table,
+            missing.join(", ")
+        )));
+    }
+
+    debug!(
+        "Table {} has all {} expected columns",
+        table,
+        required.len()
+    );
+    Ok(())
+}
+
 /// Retry helper for database operations
 #[allow(dead_code)]
-pub async fn with_retry<T, F, Fut>(
-    mut operation: F,
-    max_retries: u32,
-    initial_delay: Duration,
-) -> Result<T>
+pub async fn with_retry<T, F, Fut>(mut operation: F, policy: RetryPolicy) -> Result<T>
 where
     F: FnMut() -> Fut,
     Fut: std::future::Future<Output = Result<T>>,
 {
-    let mut delay = initial_delay;
+    let max_retries = policy.max_retries;
     let mut last_error = None;
 
     for attempt in 0..max_retries {
@@ -236,8 +322,7 @@ where
                 last_error = Some(e);
 
                 if attempt + 1 < max_retries {
-                    tokio::time::sleep(delay).await;
-                    delay = std::cmp::min(delay * 2, Duration::from_secs(30));
+                    tokio::time::sleep(policy.delay(attempt)).await;
                 }
             }
         }
@@ -246,20 +331,53 @@ where
     Err(last_error.unwrap_or_else(|| Error::database("Max retries exceeded")))
 }
 
+/// `DATABASE_URL` for the tests marked `#[ignore = "requires DATABASE_URL"]`
+#[cfg(test)]
+pub(crate) fn test_database_url() -> String {
+    std::env::var("DATABASE_URL").expect("database tests need DATABASE_URL")
+}
+
+/// A pool on `DATABASE_URL` with migrations applied
+#[cfg(test)]
+pub(crate) async fn test_pool() -> PgPool {
+    let pool = PgPool::connect(&test_database_url()).await.unwrap();
+    run_migrations(&pool).await.unwrap();
+    pool
+}
+
+/// `nfts` belongs to the app database; stand it up if this one lacks it
+#[cfg(test)]
+pub(crate) async fn ensure_nfts_table(pool: &PgPool) {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS nfts (
+            id UUID PRIMARY KEY,
+            token_id BIGINT NOT NULL,
+            contract_address TEXT NOT NULL,
+            contract_type TEXT,
+            creator_address TEXT NOT NULL,
+            creation_time TIMESTAMP NOT NULL DEFAULT NOW(),
+            likes_count BIGINT NOT NULL DEFAULT 0,
+            buys_count BIGINT NOT NULL DEFAULT 0,
+            is_deleted BOOLEAN NOT NULL DEFAULT false,
+            is_original BOOLEAN NOT NULL DEFAULT true
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
     async fn test_pool_stats() {
-        // This test requires a running database
-        // Skip in CI without database
-        if std::env::var("DATABASE_URL").is_err() {
-            return;
-        }
-
         let config = DatabaseConfig {
-            url: std::env::var("DATABASE_URL").unwrap(),
+            url: test_database_url(),
             max_connections: 5,
             min_connections: 1,
             connect_timeout: Duration::from_secs(5),
@@ -274,4 +392,131 @@ mod tests {
         assert!(stats.size > 0);
         db.close().await;
     }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_pool_stats_count_checked_out_connections() {
+        let url = test_database_url();
+
+        let config = DatabaseConfig {
+            url,
+            max_connections: 5,
+            min_connections: 0,
+            connect_timeout: Duration::from_secs(5),
+            idle_timeout: Duration::from_secs(60),
+            max_lifetime: Duration::from_secs(300),
+            statement_cache_size: 10,
+        };
+        let db = Database::new(&config).await.unwrap();
+
+        // Check out the whole pool: connections from setup may still be on
+        // their way back, so a single checkout can't be counted exactly
+        let mut held = Vec::new();
+        for _ in 0..5 {
+            held.push(db.pool().acquire().await.unwrap());
+        }
+        let stats = db.stats();
+        assert_eq!(stats.active, 5);
+        assert_eq!(stats.idle, 0);
+        assert_eq!(stats.max, 5);
+
+        drop(held);
+        db.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_lazy_pool_starts_without_database() {
+        let config = DatabaseConfig {
+            url: "postgres://postgres@127.0.0.1:1/unreachable".to_string(),
+            max_connections: 1,
+            min_connections: 0,
+            connect_timeout: Duration::from_millis(200),
+            idle_timeout: Duration::from_secs(60),
+            max_lifetime: Duration::from_secs(300),
+            statement_cache_size: 10,
+        };
+
+        assert!(Database::new(&config).await.is_err());
+        let db = Database::new_lazy(&config).unwrap();
+        assert!(db.health_check().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_follows_policy() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let attempts = AtomicU32::new(0);
+        let policy = RetryPolicy {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(2),
+            multiplier: 2.0,
+        };
+        let result: Result<()> = with_retry(
+            || async {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                Err(Error::database("db down"))
+            },
+            policy,
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_schema_check_names_missing_column() {
+        let pool = test_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+
+        // An nfts table whose `is_original` was renamed
+        let table = format!(
+            "nfts_schema_{}",
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        sqlx::query(&format!(
+            r#"
+            CREATE TABLE {} (
+                id UUID PRIMARY KEY,
+                token_id BIGINT NOT NULL,
+                contract_address TEXT NOT NULL,
+                contract_type TEXT,
+                creator_address TEXT NOT NULL,
+                creation_time TIMESTAMP NOT NULL DEFAULT NOW(),
+                is_deleted BOOLEAN NOT NULL DEFAULT false,
+                original BOOLEAN NOT NULL DEFAULT true
+            )
+            "#,
+            table
+        ))
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        let err = check_table_columns(&mut *tx, &table, NFTS_REQUIRED_COLUMNS)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Config { .. }));
+        assert!(err.to_string().contains("is_original"), "{err}");
+        assert!(!err.to_string().contains("is_deleted"), "{err}");
+
+        sqlx::query(&format!(
+            "ALTER TABLE {} RENAME COLUMN original TO is_original",
+            table
+        ))
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        check_table_columns(&mut *tx, &table, NFTS_REQUIRED_COLUMNS)
+            .await
+            .unwrap();
+
+        let err = check_table_columns(&mut *tx, "no_such_table", NFTS_REQUIRED_COLUMNS)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no_such_table"), "{err}");
+
+        tx.rollback().await.unwrap();
+    }
 }
@@ -7,17 +7,31 @@
 //!
 //! This ensures the recommendation engine has fresh data for personalization.
 
-use crate::config::Config;
+use crate::address::normalize_address;
+use crate::config::{Config, ContractAliases};
 use crate::error::{Error, Result};
 use crate::events::EventType;
-use crate::kafka::BlockchainEvent;
-use crate::recommendation::preferences::{record_interaction, InteractionEvent, InteractionType};
+use crate::kafka::{BlockchainEvent, EventPublisher, KafkaProducer};
+use crate::recommendation::preferences::{
+    normalize_interaction, record_tip_on, record_weighted_interaction_on, InteractionEcho,
+    InteractionEvent, TasteSpace,
+    InteractionType,
+};
+use crate::recommendation::profiles::{self, CreatorProfile};
+use crate::recommendation::features::MetadataFetcher;
+use crate::recommendation::{badges, blocks, follows, reputation, shares};
 use crate::AppState;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::Address;
 use rdkafka::config::ClientConfig;
-use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
 use rdkafka::message::Message;
-use sqlx::PgPool;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 use tracing::{debug, error, info, instrument, warn};
 use uuid::Uuid;
@@ -38,20 +52,790 @@ struct NftMetadataRow {
     tags: Vec<String>,
 }
 
-/// Event processor that consumes Kafka events and updates recommendations
-pub struct EventProcessor {
+/// Identity of a single purchase, shared by the PurchaseProcessed and
+/// ContentCopyMinted events a buy can emit
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PurchaseKey {
+    contract_address: String,
+    token_id: String,
+    buyer: String,
+    block_number: u64,
+}
+
+impl PurchaseKey {
+    /// ContentCopyMinted carries the purchased token as `originalId`,
+    /// PurchaseProcessed as `tokenId`
+    fn from_event(event: &BlockchainEvent) -> Option<Self> {
+        let data = event.data.as_ref()?;
+        let token_id = data
+            .get("originalId")
+            .or_else(|| data.get("tokenId"))
+            .and_then(|v| v.as_str())?;
+        let buyer = data.get("buyer").and_then(|v| v.as_str())?;
+
+        Some(Self {
+            contract_address: event.contract_address.to_lowercase(),
+            token_id: token_id.to_string(),
+            buyer: buyer.to_lowercase(),
+            block_number: event.block_number,
+        })
+    }
+}
+
+/// Event as produced by the Elixir app: snake_case event name and payload keys,
+/// and integer ids left as JSON numbers. Recognized by `"source": "elixir"` or
+/// by arriving on the configured Elixir topic.
+#[derive(Debug, Deserialize)]
+struct ElixirEvent {
+    event: String,
+    contract_address: String,
+    #[serde(default)]
+    contract_type: Option<String>,
+    block_number: u64,
+    #[serde(alias = "tx_hash")]
+    transaction_hash: String,
+    #[serde(default)]
+    log_index: u64,
+    #[serde(default)]
+    timestamp: Option<i64>,
+    #[serde(default)]
+    payload: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ElixirEvent {
+    fn is_elixir_shaped(value: &serde_json::Value) -> bool {
+        value.get("source").and_then(|s| s.as_str()) == Some("elixir")
+    }
+
+    /// Map into the indexer's event shape: PascalCase event type, camelCase
+    /// data keys, and `*Id` numbers as strings (handlers read ids with `as_str`)
+    fn into_blockchain_event(self) -> BlockchainEvent {
+        let data = self
+            .payload
+            .into_iter()
+            .map(|(key, value)| {
+                let key = snake_to_camel(&key);
+                let value = match value {
+                    serde_json::Value::Number(n) if key.ends_with("Id") => {
+                        serde_json::Value::String(n.to_string())
+                    }
+                    other => other,
+                };
+                (key, value)
+            })
+            .collect::<serde_json::Map<_, _>>();
+
+        BlockchainEvent {
+            event_type: snake_to_pascal(&self.event),
+            contract_address: self.contract_address,
+            contract_type: self.contract_type.unwrap_or_else(|| "friends".to_string()),
+            block_number: self.block_number,
+            transaction_hash: self.transaction_hash,
+            log_index: self.log_index,
+            timestamp: self
+                .timestamp
+                .unwrap_or_else(|| chrono::Utc::now().timestamp()),
+            data: Some(serde_json::Value::Object(data)),
+        }
+    }
+}
+
+/// `content_copy_minted` -> `ContentCopyMinted` (PascalCase input is kept)
+fn snake_to_pascal(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// `original_id` -> `originalId`
+fn snake_to_camel(name: &str) -> String {
+    let pascal = snake_to_pascal(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Decode a message payload into a `BlockchainEvent`. With `elixir_topic` set,
+/// messages on that topic or tagged `"source": "elixir"` are read as Elixir events.
+fn decode_event(payload: &[u8], topic: &str, elixir_topic: Option<&str>) -> Result<BlockchainEvent> {
+    let Some(elixir_topic) = elixir_topic else {
+        return serde_json::from_slice(payload).map_err(Error::Json);
+    };
+
+    let value: serde_json::Value = serde_json::from_slice(payload).map_err(Error::Json)?;
+    if topic == elixir_topic || ElixirEvent::is_elixir_shaped(&value) {
+        let event: ElixirEvent = serde_json::from_value(value).map_err(Error::Json)?;
+        Ok(event.into_blockchain_event())
+    } else {
+        serde_json::from_value(value).map_err(Error::Json)
+    }
+}
+
+/// Point an event from a redeployed contract's old address at the canonical
+/// address, so its NFT UUIDs and stats land on the same contract as new events
+fn apply_contract_alias(aliases: &ContractAliases, event: &mut BlockchainEvent) {
+    let canonical = aliases.canonical(&event.contract_address);
+    if canonical != event.contract_address {
+        event.contract_address = canonical.to_string();
+    }
+}
+
+/// Comments shorter than this (after trimming) are a weak signal
+const SHORT_COMMENT_CHARS: usize = 10;
+/// Comments at least this long are a slightly stronger signal
+const LONG_COMMENT_CHARS: usize = 80;
+/// Recent comments remembered per user for repetition checks
+const MAX_RECENT_COMMENTS: usize = 20;
+
+/// Scores comment text as a mild quality signal: very short comments count a
+/// bit less, longer ones a bit more, and a user repeating the same comment
+/// within `repeat_window` counts much less.
+struct CommentQuality {
+    repeat_window: Duration,
+    recent: HashMap<String, VecDeque<(Instant, String)>>,
+}
+
+impl CommentQuality {
+    fn new(repeat_window: Duration) -> Self {
+        Self {
+            repeat_window,
+            recent: HashMap::new(),
+        }
+    }
+
+    /// Quality multiplier for `comment` by `user` at `now` (0.2..=1.1)
+    fn score(&mut self, user: &str, comment: &str, now: Instant) -> f32 {
+        let normalized = comment
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+
+        let chars = normalized.chars().count();
+        let mut quality = if chars < SHORT_COMMENT_CHARS {
+            0.8
+        } else if chars >= LONG_COMMENT_CHARS {
+            1.1
+        } else {
+            1.0
+        };
+
+        let recent = self.recent.entry(user.to_lowercase()).or_default();
+        while recent
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) > self.repeat_window)
+        {
+            recent.pop_front();
+        }
+        if recent.iter().any(|(_, text)| *text == normalized) {
+            quality = 0.2;
+        }
+
+        if recent.len() >= MAX_RECENT_COMMENTS {
+            recent.pop_front();
+        }
+        recent.push_back((now, normalized));
+        quality
+    }
+}
+
+/// Caps how many of a user's interactions per day feed preference learning.
+/// The cap is a multiple of the on-chain daily post limit, so spam thresholds
+/// track `DailyLimitsUpdated`; until a limit is known nothing is capped.
+struct InteractionCap {
+    per_post: u64,
+    max_posts: Option<u64>,
+    day: u64,
+    counts: HashMap<String, u64>,
+}
+
+impl InteractionCap {
+    fn new(per_post: u64) -> Self {
+        Self {
+            per_post,
+            max_posts: None,
+            day: 0,
+            counts: HashMap::new(),
+        }
+    }
+
+    fn set_max_posts(&mut self, max_posts: u64) {
+        self.max_posts = Some(max_posts);
+    }
+
+    /// Interactions per user per day that count toward preferences
+    fn cap(&self) -> Option<u64> {
+        self.max_posts.map(|posts| posts.saturating_mul(self.per_post))
+    }
+
+    /// Preference weight multiplier for `user`'s next interaction on `day`
+    /// (days since the epoch): 1.0 within the cap, 0.0 beyond it
+    fn admit(&mut self, user: &str, day: u64) -> f32 {
+        if day != self.day {
+            self.day = day;
+            self.counts.clear();
+        }
+
+        let cap = self.cap();
+        let count = self.counts.entry(user.to_lowercase()).or_default();
+        *count += 1;
+        match cap {
+            Some(cap) if *count > cap => 0.0,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Event-time awareness for interactions: one delivered more than `grace`
+/// after it happened (a lagging consumer, a redelivery, a replay) is
+/// weighted by its age, the way preference decay would have worn it down
+/// had it arrived on time
+#[derive(Debug, Clone, Copy)]
+struct EventTimeWatermark {
+    grace: Duration,
+    daily_decay: f32,
+}
+
+impl EventTimeWatermark {
+    /// Preference weight multiplier for an event stamped `event_time`,
+    /// processed at `now` (both unix seconds): 1.0 up to the watermark,
+    /// `daily_decay` per day of age past it. Unknown (non-positive) and
+    /// future timestamps count as on time.
+    fn weight(&self, event_time: i64, now: i64) -> f32 {
+        let age_secs = now.saturating_sub(event_time);
+        if event_time <= 0 || age_secs <= self.grace.as_secs() as i64 {
+            return 1.0;
+        }
+        self.daily_decay.powf(age_secs as f32 / 86_400.0)
+    }
+
+    /// `quality` scaled by [`Self::weight`], left as is for on-time events
+    fn quality(&self, event_time: i64, now: i64, quality: Option<f32>) -> Option<f32> {
+        let weight = self.weight(event_time, now);
+        if weight < 1.0 {
+            Some(quality.unwrap_or(1.0) * weight)
+        } else {
+            quality
+        }
+    }
+}
+
+/// When `event` happened, for the watermark: the timestamp the contract
+/// embedded in its data, else when it was parsed
+fn event_time(event: &BlockchainEvent) -> i64 {
+    event
+        .data
+        .as_ref()
+        .and_then(crate::events::embedded_timestamp_json)
+        .and_then(|ts| i64::try_from(ts).ok())
+        .filter(|&ts| ts > 0)
+        .unwrap_or(event.timestamp)
+}
+
+/// Recipient addresses whose classification is cached before the cache resets
+const RECIPIENT_CACHE_CAPACITY: usize = 10_000;
+
+/// Whether a share went to a contract (group/collection) or a user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecipientKind {
+    Contract,
+    Eoa,
+}
+
+impl RecipientKind {
+    /// Sharer's preference weight multiplier: sharing with a person is a
+    /// stronger personal signal than sharing into a contract
+    fn share_quality(self) -> f32 {
+        match self {
+            RecipientKind::Eoa => 1.0,
+            RecipientKind::Contract => 0.5,
+        }
+    }
+}
+
+/// Classifies share recipients with `eth_getCode`, caching the result since
+/// an address rarely gains or loses code
+struct RecipientClassifier {
+    provider: Option<Provider<Http>>,
+    cache: Mutex<HashMap<String, RecipientKind>>,
+}
+
+impl RecipientClassifier {
+    fn new(provider: Option<Provider<Http>>) -> Self {
+        Self {
+            provider,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn cached(&self, address: &str) -> Option<RecipientKind> {
+        self.cache.lock().ok()?.get(&address.to_lowercase()).copied()
+    }
+
+    fn remember(&self, address: &str, kind: RecipientKind) {
+        if let Ok(mut cache) = self.cache.lock() {
+            if cache.len() >= RECIPIENT_CACHE_CAPACITY {
+                cache.clear();
+            }
+            cache.insert(address.to_lowercase(), kind);
+        }
+    }
+
+    /// Classify `address`, or `None` if it is malformed or the lookup fails
+    async fn classify(&self, address: &str) -> Option<RecipientKind> {
+        if let Some(kind) = self.cached(address) {
+            return Some(kind);
+        }
+
+        let provider = self.provider.as_ref()?;
+        let parsed: Address = address.parse().ok()?;
+        match provider.get_code(parsed, None).await {
+            Ok(code) => {
+                let kind = if code.as_ref().is_empty() {
+                    RecipientKind::Eoa
+                } else {
+                    RecipientKind::Contract
+                };
+                self.remember(address, kind);
+                Some(kind)
+            }
+            Err(e) => {
+                warn!("Failed to look up code for share recipient {}: {:?}", address, e);
+                None
+            }
+        }
+    }
+}
+
+/// Messages abandoned because processing exceeded the per-message timeout
+static MESSAGE_TIMEOUTS: AtomicU64 = AtomicU64::new(0);
+
+/// Messages skipped, without a dead-letter topic, after timing out on
+/// every redelivery
+static TIMED_OUT_SKIPS: AtomicU64 = AtomicU64::new(0);
+
+/// Processor counters since startup, as exported on `/metrics`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProcessorCounters {
+    pub message_timeouts: u64,
+    pub timed_out_skips: u64,
+    pub invalid_creator_mints: u64,
+}
+
+impl ProcessorCounters {
+    pub fn current() -> Self {
+        Self {
+            message_timeouts: MESSAGE_TIMEOUTS.load(Ordering::Relaxed),
+            timed_out_skips: TIMED_OUT_SKIPS.load(Ordering::Relaxed),
+            invalid_creator_mints: invalid_creator_mint_count(),
+        }
+    }
+}
+
+/// Consecutive timeouts of the message being redelivered. Messages are
+/// processed one at a time, so only the latest one is tracked.
+#[derive(Debug, Default)]
+struct TimeoutStreak {
+    message: Option<(String, i32, i64)>,
+    timeouts: u32,
+}
+
+impl TimeoutStreak {
+    /// Count a timeout of the message at `topic[partition]@offset`,
+    /// returning how many times in a row it has timed out
+    fn record(&mut self, topic: &str, partition: i32, offset: i64) -> u32 {
+        let message = (topic.to_string(), partition, offset);
+        if self.message.as_ref() != Some(&message) {
+            self.message = Some(message);
+            self.timeouts = 0;
+        }
+        self.timeouts += 1;
+        self.timeouts
+    }
+
+    fn clear(&mut self) {
+        self.message = None;
+        self.timeouts = 0;
+    }
+}
+
+/// Run `processing` for one message, giving up after `timeout` so a stuck
+/// handler can't block the consumer loop
+async fn process_with_timeout<F>(processing: F, timeout: Duration) -> Result<()>
+where
+    F: std::future::Future<Output = Result<()>>,
+{
+    match tokio::time::timeout(timeout, processing).await {
+        Ok(result) => result,
+        Err(_) => {
+            MESSAGE_TIMEOUTS.fetch_add(1, Ordering::Relaxed);
+            Err(Error::Timeout {
+                timeout_ms: timeout.as_millis() as u64,
+            })
+        }
+    }
+}
+
+/// A consumed message, as far as retrying and dead-lettering need it
+struct MessageRef<'a> {
+    topic: &'a str,
+    partition: i32,
+    offset: i64,
+    payload: &'a [u8],
+}
+
+/// What is published to the dead-letter topic for a message that kept failing
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct DeadLetter {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    /// Original payload (lossily decoded if not UTF-8)
+    pub payload: String,
+    pub error: String,
+    pub attempts: u32,
+    pub failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Retries failing messages and sends them to a dead-letter topic once they
+/// have used up their attempts, so a poison message can't stall the consumer
+/// but is never silently dropped
+struct DeadLetters<P: EventPublisher> {
+    publisher: P,
+    topic: String,
+    max_attempts: u32,
+    /// Failed attempts so far at each message that is being retried
+    attempts: HashMap<(String, i32, i64), u32>,
+}
+
+/// What to do with a message after a failed attempt
+#[derive(Debug, PartialEq)]
+enum AfterFailure {
+    /// Consume it again
+    Redeliver,
+    /// It was sent to the dead-letter topic and can be committed
+    DeadLettered,
+}
+
+impl<P: EventPublisher> DeadLetters<P> {
+    fn new(publisher: P, topic: String, max_attempts: u32) -> Self {
+        Self {
+            publisher,
+            topic,
+            max_attempts: max_attempts.max(1),
+            attempts: HashMap::new(),
+        }
+    }
+
+    /// Forget the attempts at `message` once it has been processed
+    fn succeeded(&mut self, message: &MessageRef<'_>) {
+        self.attempts
+            .remove(&(message.topic.to_string(), message.partition, message.offset));
+    }
+
+    /// Count a failed attempt at `message`. Until it has failed
+    /// `max_attempts` times it is redelivered; after that it is published to
+    /// the dead-letter topic. Errors only if that publish fails, in which
+    /// case the message must not be committed.
+    async fn failed(&mut self, message: &MessageRef<'_>, error: Error) -> Result<AfterFailure> {
+        let key = (message.topic.to_string(), message.partition, message.offset);
+        let attempts = self.attempts.entry(key.clone()).or_default();
+        *attempts = (*attempts + 1).min(self.max_attempts);
+        let attempts = *attempts;
+        if attempts < self.max_attempts {
+            warn!(
+                "Message at {}[{}]@{} failed (attempt {}/{}), leaving it for redelivery: {}",
+                message.topic, message.partition, message.offset, attempts, self.max_attempts, error
+            );
+            return Ok(AfterFailure::Redeliver);
+        }
+
+        let dead_letter = DeadLetter {
+            topic: message.topic.to_string(),
+            partition: message.partition,
+            offset: message.offset,
+            payload: String::from_utf8_lossy(message.payload).into_owned(),
+            error: error.to_string(),
+            attempts,
+            failed_at: chrono::Utc::now(),
+        };
+        self.publisher
+            .send_event(&self.topic, message.topic, &dead_letter)
+            .await
+            .map_err(|e| {
+                Error::kafka(format!(
+                    "Failed to dead-letter message at {}[{}]@{} ({}): {}",
+                    message.topic, message.partition, message.offset, error, e
+                ))
+            })?;
+        self.attempts.remove(&key);
+        warn!(
+            "☠️ Message at {}[{}]@{} failed {} times, sent to {}: {}",
+            message.topic, message.partition, message.offset, attempts, self.topic, error
+        );
+        Ok(AfterFailure::DeadLettered)
+    }
+}
+
+/// Profile fields from a `ProfileUpdated` (either layout) or
+/// `ProfileUpdatedExtended` payload.
+/// Fields the event doesn't carry stay `None` so stored values survive.
+fn creator_profile(data: &serde_json::Value) -> Option<CreatorProfile> {
+    let field = |key: &str| data.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    Some(CreatorProfile {
+        address: field("user").filter(|user| !user.is_empty())?,
+        username: field("username"),
+        bio: field("bio"),
+        website: field("website"),
+        profile_hash: field("profile_hash"),
+    })
+}
+
+/// `(user, badge)` from a `BadgeAwarded` or `BadgeRemoved` payload. Older
+/// producers sent the badge as `badgeType`.
+fn badge_event(event: &BlockchainEvent) -> Option<(&str, &str)> {
+    let data = event.data.as_ref()?;
+    let user = data.get("user").and_then(|v| v.as_str()).filter(|u| !u.is_empty())?;
+    let badge = data
+        .get("badge")
+        .or_else(|| data.get("badgeType"))
+        .and_then(|v| v.as_str())
+        .filter(|b| !b.is_empty())?;
+    Some((user, badge))
+}
+
+/// Tags carried on a mint event, if any
+fn mint_tags(data: &serde_json::Value) -> Vec<String> {
+    data.get("tags")
+        .and_then(|v| v.as_array())
+        .map(|tags| {
+            tags.iter()
+                .filter_map(|t| t.as_str())
+                .map(|t| t.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Metadata URI carried on a mint event, if any
+fn mint_metadata_uri(data: &serde_json::Value) -> Option<String> {
+    ["uri", "tokenURI", "metadataUri"]
+        .iter()
+        .find_map(|key| data.get(*key).and_then(|v| v.as_str()))
+        .map(str::trim)
+        .filter(|uri| !uri.is_empty())
+        .map(str::to_string)
+}
+
+/// A mint whose tags are to be read from its metadata once the mint commits
+#[derive(Debug, Clone, PartialEq)]
+struct TagFetch {
+    uri: String,
+    nft_id: Uuid,
+    contract_address: String,
+    token_id: i64,
+}
+
+/// Insert features for a minted NFT. A re-processed or colliding mint only
+/// replaces tags when it carries some, so tags extracted earlier survive.
+async fn upsert_mint_features<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    nft_id: Uuid,
+    contract_address: &str,
+    token_id: i64,
+    tags: &[String],
+) -> std::result::Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO nft_features (nft_id, contract_address, token_id, tags, inserted_at, updated_at)
+        VALUES ($1, $2, $3, $4, NOW(), NOW())
+        ON CONFLICT (nft_id) DO UPDATE SET
+            tags = CASE
+                WHEN cardinality(EXCLUDED.tags) > 0 THEN EXCLUDED.tags
+                ELSE nft_features.tags
+            END,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(nft_id)
+    .bind(contract_address)
+    .bind(token_id)
+    .bind(tags)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Mints seen with a zero or malformed creator address
+static INVALID_CREATOR_MINTS: AtomicU64 = AtomicU64::new(0);
+
+/// Number of mints with an invalid creator since startup
+pub fn invalid_creator_mint_count() -> u64 {
+    INVALID_CREATOR_MINTS.load(Ordering::Relaxed)
+}
+
+/// A creator must be a 0x-prefixed 20-byte hex address other than zero
+fn is_valid_creator(address: &str) -> bool {
+    normalize_address(address).is_ok_and(|address| address != format!("{:?}", Address::zero()))
+}
+
+/// Whether a mint should be stored. Mints with an invalid creator are counted
+/// and only stored when `store_invalid` is set.
+fn should_store_mint(event: &BlockchainEvent, store_invalid: bool) -> bool {
+    let creator = event
+        .data
+        .as_ref()
+        .and_then(|d| d.get("creator"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    if is_valid_creator(creator) {
+        return true;
+    }
+
+    INVALID_CREATOR_MINTS.fetch_add(1, Ordering::Relaxed);
+    warn!(
+        "⚠️ {} in tx {} has invalid creator '{}'{}",
+        event.event_type,
+        event.transaction_hash,
+        creator,
+        if store_invalid { "" } else { ", skipping" }
+    );
+    store_invalid
+}
+
+/// The recipient's side of a share. Receiving a share is a very light
+/// implicit-interest signal, recorded as a short view that nudges the
+/// recipient toward the NFT's creator and tags. Contracts have no interests,
+/// and sharing with yourself says nothing new.
+fn received_share(
+    shared: &InteractionEvent,
+    recipient: &str,
+    recipient_kind: Option<RecipientKind>,
+) -> Option<InteractionEvent> {
+    let to_user = recipient_kind != Some(RecipientKind::Contract);
+    let self_share = recipient.eq_ignore_ascii_case(&shared.user_address);
+    (!recipient.is_empty() && to_user && !self_share).then(|| InteractionEvent {
+        user_address: recipient.to_string(),
+        interaction_type: InteractionType::View,
+        source: Some("share".to_string()),
+        ..shared.clone()
+    })
+}
+
+/// Claim `event` in `processed_events`, returning false if it was already
+/// processed. Events without a transaction hash can't be keyed and are
+/// always processed.
+async fn claim_event(conn: &mut PgConnection, event: &BlockchainEvent) -> Result<bool> {
+    if event.transaction_hash.is_empty() {
+        return Ok(true);
+    }
+
+    let claimed = sqlx::query(
+        r#"
+        INSERT INTO processed_events (transaction_hash, log_index, event_type)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (transaction_hash, log_index) DO NOTHING
+        "#,
+    )
+    .bind(&event.transaction_hash)
+    .bind(event.log_index as i64)
+    .bind(&event.event_type)
+    .execute(conn)
+    .await?;
+
+    Ok(claimed.rows_affected() == 1)
+}
+
+/// Claim a purchase in `processed_purchases`, returning false if another
+/// event for the same buy already recorded it
+async fn claim_purchase(conn: &mut PgConnection, key: &PurchaseKey) -> Result<bool> {
+    let claimed = sqlx::query(
+        r#"
+        INSERT INTO processed_purchases (contract_address, token_id, buyer_address, block_number)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (contract_address, token_id, buyer_address, block_number) DO NOTHING
+        "#,
+    )
+    .bind(&key.contract_address)
+    .bind(&key.token_id)
+    .bind(&key.buyer)
+    .bind(key.block_number as i64)
+    .execute(conn)
+    .await?;
+
+    Ok(claimed.rows_affected() == 1)
+}
+
+/// Longest the processor waits on shutdown for queued dead letters and
+/// echoes to be delivered
+const SHUTDOWN_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest a rewind for redelivery may block the consumer loop
+const SEEK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Event processor that consumes Kafka events and updates recommendations.
+/// Dead letters and interaction echoes go out through `P`.
+pub struct EventProcessor<P: EventPublisher = KafkaProducer> {
     consumer: StreamConsumer,
+    /// Flushed on shutdown so dead letters and echoes aren't lost
+    publisher: P,
     pool: PgPool,
     _elixir_pool: PgPool,
     shutdown: broadcast::Receiver<()>,
+    /// Topic for Elixir-shaped events, when that format is enabled
+    elixir_topic: Option<String>,
+    store_invalid_creator_mints: bool,
+    /// Comment text analysis, when enabled
+    comment_quality: Option<Mutex<CommentQuality>>,
+    /// Daily per-user interaction cap, when enabled
+    interaction_cap: Option<Mutex<InteractionCap>>,
+    /// Longest a single message may take to process
+    message_timeout: Duration,
+    /// Without a dead-letter topic, timeouts before a message is skipped
+    max_timeouts: u32,
+    /// Contract vs user classification of share recipients, when enabled
+    recipient_classifier: Option<RecipientClassifier>,
+    /// Retry and dead-letter handling, when a dead-letter topic is configured
+    dead_letters: Option<DeadLetters<P>>,
+    /// Publishes recorded interactions downstream, when enabled
+    interaction_echo: Option<InteractionEcho<P>>,
+    /// Echoes of the interactions recorded for the event being processed,
+    /// held until its transaction commits
+    pending_echoes: Mutex<Vec<(InteractionEvent, Option<f32>)>>,
+    /// Metadata tag fetches for mints in the event being processed, started
+    /// once its transaction commits
+    pending_tag_fetches: Mutex<Vec<TagFetch>>,
+    /// Redeployed contracts' old addresses mapped to their canonical address
+    contract_aliases: ContractAliases,
+    /// Sender followers at which a tip counts fully (0 disables weighting)
+    tip_reputation_followers: u64,
+    /// Extra weight for a user's first positive interaction with a creator
+    /// or tag
+    first_contact_boost: f32,
+    /// Tag vocabulary for cached taste vectors
+    taste_space: TasteSpace,
+    /// Down-weighting of interactions that arrive late
+    watermark: EventTimeWatermark,
+    /// Bounded fetches of mint metadata for tags
+    metadata: MetadataFetcher,
 }
 
-impl EventProcessor {
+impl<P: EventPublisher + Clone> EventProcessor<P> {
     /// Create a new event processor
     pub fn new(
         config: &Config,
         pool: PgPool,
         elixir_pool: PgPool,
+        producer: P,
         shutdown: broadcast::Receiver<()>,
     ) -> Result<Self> {
         let consumer: StreamConsumer = ClientConfig::new()
@@ -63,21 +847,70 @@ impl EventProcessor {
             .set("heartbeat.interval.ms", "10000") // 10 seconds (1/3 of session timeout)
             .set("request.timeout.ms", "60000")  // 60 seconds for requests
             .set("socket.timeout.ms", "60000")   // 60 seconds socket timeout
-            .set("enable.auto.commit", "true")
+            // Offsets are committed once a message has been processed
+            .set("enable.auto.commit", "false")
             .set("auto.offset.reset", "latest")
             .create()
             .map_err(|e| Error::kafka(format!("Failed to create consumer: {}", e)))?;
 
         // Subscribe to relevant topics
+        let elixir_topic = config
+            .processor
+            .elixir_events_enabled
+            .then(|| config.processor.elixir_events_topic.clone());
+        let mut topics = vec![
+            config.kafka.topics.user_actions.as_str(),
+            config.kafka.topics.blockchain_events.as_str(),
+        ];
+        if let Some(topic) = &elixir_topic {
+            topics.push(topic.as_str());
+        }
         consumer
-            .subscribe(&["user.actions", "blockchain.events"])
+            .subscribe(&topics)
             .map_err(|e| Error::kafka(format!("Failed to subscribe: {}", e)))?;
 
+        let recipient_classifier = if config.processor.classify_share_recipients {
+            let provider = Provider::<Http>::try_from(config.blockchain.rpc_url.as_str())
+                .map_err(|e| Error::blockchain(format!("Failed to create provider: {}", e)))?;
+            Some(RecipientClassifier::new(Some(provider)))
+        } else {
+            None
+        };
+
         Ok(Self {
             consumer,
+            publisher: producer.clone(),
             pool,
             _elixir_pool: elixir_pool,
             shutdown,
+            elixir_topic,
+            store_invalid_creator_mints: config.processor.store_invalid_creator_mints,
+            comment_quality: config
+                .processor
+                .comment_analysis_enabled
+                .then(|| Mutex::new(CommentQuality::new(config.processor.comment_repeat_window))),
+            interaction_cap: (config.processor.interaction_cap_per_post > 0)
+                .then(|| Mutex::new(InteractionCap::new(config.processor.interaction_cap_per_post))),
+            message_timeout: config.processor.message_timeout,
+            max_timeouts: config.processor.message_max_timeouts,
+            recipient_classifier,
+            interaction_echo: config.processor.echo_interactions.then(|| {
+                InteractionEcho::new(producer.clone(), config.kafka.topics.recommendations.clone())
+            }),
+            pending_echoes: Mutex::new(Vec::new()),
+            pending_tag_fetches: Mutex::new(Vec::new()),
+            dead_letters: config.kafka.topics.dlq.clone().map(|topic| {
+                DeadLetters::new(producer.clone(), topic, config.processor.dlq_max_attempts)
+            }),
+            contract_aliases: config.contracts.aliases.clone(),
+            tip_reputation_followers: config.processor.tip_reputation_followers,
+            first_contact_boost: config.recommendation.first_contact_boost,
+            taste_space: config.recommendation.taste_space.clone(),
+            watermark: EventTimeWatermark {
+                grace: config.processor.late_event_grace,
+                daily_decay: config.processor.late_event_daily_decay,
+            },
+            metadata: MetadataFetcher::new(&config.processor.metadata),
         })
     }
 
@@ -125,14 +958,125 @@ impl EventProcessor {
         }))
     }
 
-    /// Generate a consistent UUID for an NFT based on contract address and token ID
-    /// DEPRECATED: Use lookup_nft_uuid instead to get the actual database ID
-    #[allow(dead_code)]
-    fn generate_nft_uuid(contract_address: &str, token_id: &str) -> Uuid {
-        // Create a deterministic UUID from contract_address + token_id
-        // This ensures the same NFT always gets the same UUID
-        let combined = format!("{}:{}", contract_address.to_lowercase(), token_id);
-        Uuid::new_v5(&Uuid::NAMESPACE_OID, combined.as_bytes())
+    /// Calibrate the interaction cap from the most recently persisted limits
+    async fn load_daily_limits(&self) -> Result<()> {
+        let max_posts = sqlx::query_scalar::<_, i64>(
+            "SELECT max_posts FROM daily_limits ORDER BY updated_at DESC LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(max_posts) = max_posts {
+            self.set_daily_post_limit(max_posts.max(0) as u64);
+        }
+        Ok(())
+    }
+
+    fn set_daily_post_limit(&self, max_posts: u64) {
+        if let Some(cap) = &self.interaction_cap {
+            if let Ok(mut cap) = cap.lock() {
+                cap.set_max_posts(max_posts);
+                info!("🚦 Interaction cap set to {:?} per user per day", cap.cap());
+            }
+        }
+    }
+
+    /// Scale `quality` to zero once `user` is over today's interaction cap
+    fn capped_quality(&self, user: &str, quality: Option<f32>) -> Option<f32> {
+        let Some(cap) = &self.interaction_cap else {
+            return quality;
+        };
+        let day = chrono::Utc::now().timestamp().max(0) as u64 / 86_400;
+        let admitted = cap.lock().map(|mut c| c.admit(user, day)).unwrap_or(1.0);
+        if admitted < 1.0 {
+            debug!("User {} is over the daily interaction cap", user);
+            Some(quality.unwrap_or(1.0) * admitted)
+        } else {
+            quality
+        }
+    }
+
+    /// Record an interaction on the event's transaction and, when enabled,
+    /// queue its echo for once the transaction commits. Users opted out of
+    /// tracking are never echoed.
+    /// Interactions whose `event_time` is past the watermark weigh less.
+    async fn record(
+        &self,
+        conn: &mut PgConnection,
+        mut interaction: InteractionEvent,
+        quality: Option<f32>,
+        event_time: i64,
+    ) -> Result<()> {
+        if let Err(e) = normalize_interaction(&mut interaction) {
+            warn!("Skipping {} interaction: {}", interaction.interaction_type, e);
+            return Ok(());
+        }
+        let quality = self
+            .watermark
+            .quality(event_time, chrono::Utc::now().timestamp(), quality);
+        if self.interaction_echo.is_none() {
+            record_weighted_interaction_on(
+                conn,
+                interaction,
+                quality,
+                self.first_contact_boost,
+                &self.taste_space,
+            )
+            .await?;
+        } else if record_weighted_interaction_on(
+            conn,
+            interaction.clone(),
+            quality,
+            self.first_contact_boost,
+            &self.taste_space,
+        )
+        .await?
+        {
+            if let Ok(mut pending) = self.pending_echoes.lock() {
+                pending.push((interaction, quality));
+            }
+        }
+        Ok(())
+    }
+
+    /// Take the echoes queued by `record`
+    fn take_pending_echoes(&self) -> Vec<(InteractionEvent, Option<f32>)> {
+        self.pending_echoes
+            .lock()
+            .map(|mut pending| std::mem::take(&mut *pending))
+            .unwrap_or_default()
+    }
+
+    /// Take the tag fetches queued by `handle_content_minted`
+    fn take_pending_tag_fetches(&self) -> Vec<TagFetch> {
+        self.pending_tag_fetches
+            .lock()
+            .map(|mut pending| std::mem::take(&mut *pending))
+            .unwrap_or_default()
+    }
+
+    /// Read a committed mint's tags from its metadata and store them. Runs
+    /// off the hot path, since a slow gateway shouldn't hold up the consumer;
+    /// with every fetch slot busy the lookup is skipped.
+    fn spawn_tag_fetch(&self, fetch: TagFetch) {
+        let Some(permit) = self.metadata.try_acquire() else {
+            warn!("Metadata fetches saturated; skipping tags for {}", fetch.nft_id);
+            return;
+        };
+        let metadata = self.metadata.clone();
+        let pool = self.pool.clone();
+        tokio::spawn(async move {
+            let _permit = permit;
+            let tags = metadata.extract_tags_from_metadata(&fetch.uri).await;
+            if tags.is_empty() {
+                return;
+            }
+            if let Err(e) =
+                upsert_mint_features(&pool, fetch.nft_id, &fetch.contract_address, fetch.token_id, &tags).await
+            {
+                warn!("Failed to store metadata tags for {}: {}", fetch.nft_id, e);
+            }
+        });
     }
 
     /// Run the event processor
@@ -140,13 +1084,91 @@ impl EventProcessor {
     pub async fn run(mut self) -> Result<()> {
         info!("🎯 Starting real-time event processor");
 
+        if self.interaction_cap.is_some() {
+            if let Err(e) = self.load_daily_limits().await {
+                warn!("Failed to load daily limits, interactions uncapped until next update: {:?}", e);
+            }
+        }
+
+        let mut timeout_streak = TimeoutStreak::default();
         loop {
             tokio::select! {
                 message = self.consumer.recv() => {
                     match message {
                         Ok(msg) => {
-                            if let Err(e) = self.process_message(&msg).await {
-                                error!("Failed to process message: {:?}", e);
+                            let result =
+                                process_with_timeout(self.process_message(&msg), self.message_timeout).await;
+                            match self.dead_letters.as_mut() {
+                                Some(dead_letters) => {
+                                    let message = MessageRef {
+                                        topic: msg.topic(),
+                                        partition: msg.partition(),
+                                        offset: msg.offset(),
+                                        payload: msg.payload().unwrap_or_default(),
+                                    };
+                                    // Processed or dead-lettered: either way it's done.
+                                    // Otherwise it is consumed again, without holding up
+                                    // the loop in between.
+                                    let done = match result {
+                                        Ok(()) => {
+                                            dead_letters.succeeded(&message);
+                                            true
+                                        }
+                                        Err(e) => match dead_letters.failed(&message, e).await {
+                                            Ok(after) => after == AfterFailure::DeadLettered,
+                                            Err(e) => {
+                                                error!("{}, leaving it for redelivery", e);
+                                                false
+                                            }
+                                        },
+                                    };
+                                    if done {
+                                        self.commit(&msg);
+                                    } else {
+                                        self.redeliver(&msg);
+                                    }
+                                }
+                                // Without a dead-letter topic a failed message is left
+                                // uncommitted; it is skipped once a later offset commits.
+                                // A timed-out one may just have been slow, so it is
+                                // consumed again instead, up to `max_timeouts` times.
+                                None => match result {
+                                    Err(Error::Timeout { timeout_ms }) => {
+                                        let timeouts =
+                                            timeout_streak.record(msg.topic(), msg.partition(), msg.offset());
+                                        if timeouts < self.max_timeouts {
+                                            warn!(
+                                                "⏱️ Message at {}[{}]@{} timed out after {}ms ({}/{}), leaving it for redelivery",
+                                                msg.topic(),
+                                                msg.partition(),
+                                                msg.offset(),
+                                                timeout_ms,
+                                                timeouts,
+                                                self.max_timeouts
+                                            );
+                                            self.redeliver(&msg);
+                                        } else {
+                                            error!(
+                                                "⏱️ Message at {}[{}]@{} timed out {} times, skipping it",
+                                                msg.topic(),
+                                                msg.partition(),
+                                                msg.offset(),
+                                                timeouts
+                                            );
+                                            TIMED_OUT_SKIPS.fetch_add(1, Ordering::Relaxed);
+                                            timeout_streak.clear();
+                                            self.commit(&msg);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        timeout_streak.clear();
+                                        error!("Failed to process message: {:?}", e);
+                                    }
+                                    Ok(()) => {
+                                        timeout_streak.clear();
+                                        self.commit(&msg);
+                                    }
+                                },
                             }
                         }
                         Err(e) => {
@@ -161,97 +1183,126 @@ impl EventProcessor {
             }
         }
 
+        self.publisher.flush(SHUTDOWN_FLUSH_TIMEOUT);
         Ok(())
     }
 
+    /// Commit `message`'s offset. Failures are logged: the message is
+    /// redelivered later and skipped by its processed-event claim.
+    fn commit(&self, message: &rdkafka::message::BorrowedMessage<'_>) {
+        if let Err(e) = self.consumer.commit_message(message, CommitMode::Async) {
+            warn!(
+                "Failed to commit offset {}[{}]@{}: {:?}",
+                message.topic(),
+                message.partition(),
+                message.offset(),
+                e
+            );
+        }
+    }
+
+    /// Rewind `message`'s partition to it, so it is consumed again rather
+    /// than skipped when a later offset commits
+    fn redeliver(&self, message: &rdkafka::message::BorrowedMessage<'_>) {
+        let offset = rdkafka::Offset::Offset(message.offset());
+        if let Err(e) = self.consumer.seek(message.topic(), message.partition(), offset, SEEK_TIMEOUT) {
+            warn!(
+                "Failed to rewind {}[{}] to {}: {:?}",
+                message.topic(),
+                message.partition(),
+                message.offset(),
+                e
+            );
+        }
+    }
+
     /// Process a single Kafka message
     async fn process_message(&self, message: &rdkafka::message::BorrowedMessage<'_>) -> Result<()> {
         let payload = message
             .payload()
             .ok_or_else(|| Error::kafka("Empty message payload"))?;
 
-        let event: BlockchainEvent = serde_json::from_slice(payload).map_err(Error::Json)?;
+        let mut event = decode_event(payload, message.topic(), self.elixir_topic.as_deref())?;
+        apply_contract_alias(&self.contract_aliases, &mut event);
 
         self.process_event(&event).await
     }
 
-    /// Process a blockchain event and update recommendation data
+    /// Process a blockchain event and update recommendation data. Each event
+    /// is applied at most once: its key is claimed in the same transaction as
+    /// its interaction writes, so a redelivered message is a no-op.
     async fn process_event(&self, event: &BlockchainEvent) -> Result<()> {
-        // Parse event type from string
-        let event_type = match event.event_type.as_str() {
-            "ContentMinted" => EventType::ContentMinted,
-            "ContentCopyMinted" => EventType::ContentCopyMinted,
-            "ContentLiked" => EventType::ContentLiked,
-            "ContentUnliked" => EventType::ContentUnliked,
-            "ContentCommented" => EventType::ContentCommented,
-            "ContentBookmarked" => EventType::ContentBookmarked,
-            "ContentShared" => EventType::ContentShared,
-            "UserFollowed" => EventType::UserFollowed,
-            "UserUnfollowed" => EventType::UserUnfollowed,
-            "UsernameRegistered" => EventType::UsernameRegistered,
-            "ProfileUpdated" => EventType::ProfileUpdated,
-            "ProfileUpdatedExtended" => EventType::ProfileUpdatedExtended,
-            "UserVerified" => EventType::UserVerified,
-            "UserBlocked" => EventType::UserBlocked,
-            "RoyaltyDistributed" => EventType::RoyaltyDistributed,
-            "EarningsWithdrawn" => EventType::EarningsWithdrawn,
-            "ContentRequirementsUpdated" => EventType::ContentRequirementsUpdated,
-            "ContentBurned" => EventType::ContentBurned,
-            "TreasuryUpdated" => EventType::TreasuryUpdated,
-            "DailyLimitsUpdated" => EventType::DailyLimitsUpdated,
-            "PricesUpdated" => EventType::PricesUpdated,
-            "TokensRecovered" => EventType::TokensRecovered,
-            "TipSent" => EventType::TipSent,
-            "BadgeAwarded" => EventType::BadgeAwarded,
-            "SnapMinted" => EventType::SnapMinted,
-            "ArtMinted" => EventType::ArtMinted,
-            "MusicMinted" => EventType::MusicMinted,
-            "FlixMinted" => EventType::FlixMinted,
-            "SnapLiked" => EventType::SnapLiked,
-            "ArtLiked" => EventType::ArtLiked,
-            "MusicLiked" => EventType::MusicLiked,
-            "FlixLiked" => EventType::FlixLiked,
-            "SnapCommented" => EventType::SnapCommented,
-            "ArtCommented" => EventType::ArtCommented,
-            "MusicCommented" => EventType::MusicCommented,
-            "FlixCommented" => EventType::FlixCommented,
-            "SnapBoughtAndMinted" => EventType::SnapBoughtAndMinted,
-            "ArtBoughtAndMinted" => EventType::ArtBoughtAndMinted,
-            "MusicBoughtAndMinted" => EventType::MusicBoughtAndMinted,
-            "FlixBoughtAndMinted" => EventType::FlixBoughtAndMinted,
-            _ => {
-                debug!("Ignoring unknown event type: {}", event.event_type);
-                return Ok(());
-            }
+        let Ok(event_type) = event.event_type.parse::<EventType>() else {
+            debug!("Ignoring unknown event type: {}", event.event_type);
+            return Ok(());
         };
 
+        let mut tx = self.pool.begin().await?;
+        if !claim_event(&mut tx, event).await? {
+            debug!(
+                "Skipping already processed {} ({}:{})",
+                event.event_type, event.transaction_hash, event.log_index
+            );
+            return Ok(());
+        }
+
+        // Echoes and tag fetches left by an event that failed to commit must
+        // not go out
+        self.take_pending_echoes();
+        self.take_pending_tag_fetches();
+        self.dispatch_event(event_type, event, &mut tx).await?;
+        tx.commit().await?;
+
+        for fetch in self.take_pending_tag_fetches() {
+            self.spawn_tag_fetch(fetch);
+        }
+
+        if let Some(echo) = &self.interaction_echo {
+            for (interaction, quality) in self.take_pending_echoes() {
+                echo.publish(&interaction, quality).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn dispatch_event(
+        &self,
+        event_type: EventType,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
         match event_type {
             // Content creation events
-            EventType::ContentMinted => self.handle_content_minted(event).await,
-            EventType::ContentCopyMinted => self.handle_content_purchase(event).await,
+            EventType::ContentMinted => self.handle_content_minted(event, conn).await,
+            EventType::ContentCopyMinted | EventType::PurchaseProcessed => {
+                self.handle_content_purchase(event, conn).await
+            }
 
             // Social interaction events
-            EventType::ContentLiked => self.handle_like(event, InteractionType::Like).await,
-            EventType::ContentUnliked => self.handle_like(event, InteractionType::Unlike).await,
-            EventType::ContentCommented => self.handle_comment(event).await,
-            EventType::ContentBookmarked => self.handle_bookmark(event).await,
-            EventType::ContentShared => self.handle_share(event).await,
+            EventType::ContentLiked => self.handle_like(event, InteractionType::Like, conn).await,
+            EventType::ContentUnliked => self.handle_like(event, InteractionType::Unlike, conn).await,
+            EventType::ContentCommented => self.handle_comment(event, conn).await,
+            EventType::ContentBookmarked => self.handle_bookmark(event, conn).await,
+            EventType::ContentShared => self.handle_share(event, conn).await,
 
             // Social relationship events
-            EventType::UserFollowed => self.handle_follow(event).await,
-            EventType::UserUnfollowed => self.handle_unfollow(event).await,
+            EventType::UserFollowed => self.handle_follow(event, conn).await,
+            EventType::UserUnfollowed => self.handle_unfollow(event, conn).await,
 
             // User profile events
             EventType::UsernameRegistered => self.handle_username_registration(event).await,
-            EventType::ProfileUpdated => self.handle_profile_update(event).await,
-            EventType::ProfileUpdatedExtended => self.handle_profile_update_extended(event).await,
-            EventType::UserVerified => self.handle_user_verified(event).await,
-            EventType::UserBlocked => self.handle_user_blocked(event).await,
+            EventType::ProfileUpdated => self.handle_profile_update(event, conn).await,
+            EventType::ProfileUpdatedExtended => {
+                self.handle_profile_update_extended(event, conn).await
+            }
+            EventType::UserVerified => self.handle_user_verified(event, conn).await,
+            EventType::UserBlocked => self.handle_user_blocked(event, conn).await,
+            EventType::UserUnblocked => self.handle_user_unblocked(event, conn).await,
 
             // Financial events
-            EventType::RoyaltyDistributed => self.handle_royalty_distributed(event).await,
+            EventType::RoyaltyDistributed => self.handle_royalty_distributed(event, conn).await,
             EventType::EarningsWithdrawn => self.handle_earnings_withdrawn(event).await,
-            EventType::TipSent => self.handle_tip(event).await,
+            EventType::TipSent => self.handle_tip(event, conn).await,
 
             // Administrative events
             EventType::ContentRequirementsUpdated => {
@@ -260,37 +1311,47 @@ impl EventProcessor {
             EventType::ContentBurned => self.handle_content_burned(event).await,
             EventType::BurnedContentRevenue => self.handle_burned_content_revenue(event).await,
             EventType::TreasuryUpdated => self.handle_treasury_updated(event).await,
-            EventType::DailyLimitsUpdated => self.handle_daily_limits_updated(event).await,
+            EventType::DailyLimitsUpdated => self.handle_daily_limits_updated(event, conn).await,
             EventType::PricesUpdated => self.handle_prices_updated(event).await,
             EventType::TokensRecovered => self.handle_tokens_recovered(event).await,
 
             // Other social events
-            EventType::BadgeAwarded => self.handle_badge(event).await,
+            EventType::BadgeAwarded => self.handle_badge(event, conn).await,
+            EventType::BadgeRemoved => self.handle_badge_removed(event, conn).await,
 
             // Legacy events (for backward compatibility)
             EventType::SnapMinted
             | EventType::ArtMinted
             | EventType::MusicMinted
-            | EventType::FlixMinted => self.handle_legacy_mint(event).await,
+            | EventType::FlixMinted => self.handle_legacy_mint(event, conn).await,
             EventType::SnapLiked
             | EventType::ArtLiked
             | EventType::MusicLiked
-            | EventType::FlixLiked => self.handle_legacy_like(event).await,
+            | EventType::FlixLiked => self.handle_legacy_like(event, conn).await,
             EventType::SnapCommented
             | EventType::ArtCommented
             | EventType::MusicCommented
-            | EventType::FlixCommented => self.handle_legacy_comment(event).await,
+            | EventType::FlixCommented => self.handle_legacy_comment(event, conn).await,
             EventType::SnapBoughtAndMinted
             | EventType::ArtBoughtAndMinted
             | EventType::MusicBoughtAndMinted
-            | EventType::FlixBoughtAndMinted => self.handle_legacy_purchase(event).await,
+            | EventType::FlixBoughtAndMinted => self.handle_legacy_purchase(event, conn).await,
 
             // Events we don't process for recommendations
             _ => Ok(()),
         }
     }
 
-    async fn handle_content_minted(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_content_minted(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
+        // A zero creator would poison creator affinities for everyone
+        if !should_store_mint(event, self.store_invalid_creator_mints) {
+            return Ok(());
+        }
+
         // Extract NFT metadata and create/update NFT features
         if let Some(data) = &event.data {
             let token_id_str = data.get("tokenId").and_then(|v| v.as_str()).unwrap_or("");
@@ -314,39 +1375,58 @@ impl EventProcessor {
                 .unwrap_or(0);
 
             // Generate consistent UUID for this NFT
-            let nft_uuid = Self::generate_nft_uuid(&event.contract_address, token_id_str);
+            let nft_uuid = generate_nft_uuid(&event.contract_address, token_id_str);
 
             // Insert or update NFT features
-            sqlx::query(
-                r#"
-                INSERT INTO nft_features (nft_id, contract_address, token_id, tags, inserted_at, updated_at)
-                VALUES ($1, $2, $3, $4, NOW(), NOW())
-                ON CONFLICT (nft_id) DO UPDATE SET
-                    tags = EXCLUDED.tags,
-                    updated_at = NOW()
-                "#
-            )
-            .bind(nft_uuid)
-            .bind(&event.contract_address)
-            .bind(token_id)
-            .bind(Vec::<String>::new())
-            .execute(&self.pool)
-            .await
-            .map_err(|e| Error::Database {
-                message: "Failed to update NFT features".into(),
-                source: Some(e),
-            })?;
+            let tags = mint_tags(data);
+            upsert_mint_features(conn, nft_uuid, &event.contract_address, token_id, &tags)
+                .await
+                .map_err(|e| Error::Database {
+                    message: "Failed to update NFT features".into(),
+                    source: Some(e),
+                })?;
+
+            // Without tags on the event, read them from the metadata once
+            // the mint has committed
+            if let Some(uri) = mint_metadata_uri(data).filter(|_| tags.is_empty()) {
+                if let Ok(mut pending) = self.pending_tag_fetches.lock() {
+                    pending.push(TagFetch {
+                        uri,
+                        nft_id: nft_uuid,
+                        contract_address: event.contract_address.clone(),
+                        token_id,
+                    });
+                }
+            }
 
             info!("📝 Processed content mint: {} by {}", nft_uuid, creator);
         }
         Ok(())
     }
 
-    async fn handle_content_purchase(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_content_purchase(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
+        // A single buy can emit both PurchaseProcessed and ContentCopyMinted;
+        // only the first one for a token/buyer/block counts. The claim is part
+        // of the event's transaction, so a rolled-back attempt releases it.
+        if let Some(key) = PurchaseKey::from_event(event) {
+            if !claim_purchase(conn, &key).await? {
+                debug!(
+                    "Skipping duplicate purchase from {} at block {}",
+                    event.event_type, event.block_number
+                );
+                return Ok(());
+            }
+        }
+
         if let Some(data) = &event.data {
             let buyer = data.get("buyer").and_then(|v| v.as_str()).unwrap_or("");
             let original_id = data
                 .get("originalId")
+                .or_else(|| data.get("tokenId"))
                 .and_then(|v| v.as_str())
                 .unwrap_or("");
             let new_token_id = data
@@ -389,7 +1469,7 @@ impl EventProcessor {
                 nft_tags: tags,
             };
 
-            record_interaction(&self.pool, interaction).await?;
+            self.record(conn, interaction, None, event_time(event)).await?;
 
             info!(
                 "💰 Processed content purchase: {} bought copy of {} (uuid={})",
@@ -403,6 +1483,7 @@ impl EventProcessor {
         &self,
         event: &BlockchainEvent,
         interaction_type: InteractionType,
+        conn: &mut PgConnection,
     ) -> Result<()> {
         if let Some(data) = &event.data {
             let liker = data.get("liker").and_then(|v| v.as_str()).unwrap_or("");
@@ -436,7 +1517,12 @@ impl EventProcessor {
                 nft_tags: tags,
             };
 
-            record_interaction(&self.pool, interaction).await?;
+            let is_like = interaction.interaction_type == InteractionType::Like;
+            let quality = self.capped_quality(liker, None);
+            self.record(conn, interaction, quality, event_time(event)).await?;
+            if is_like {
+                self.credit_shared_engagement(conn, liker, &nft_uuid).await;
+            }
 
             info!(
                 "👍 Processed {}: {} on {} (uuid={})",
@@ -446,11 +1532,21 @@ impl EventProcessor {
         Ok(())
     }
 
-    async fn handle_comment(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_comment(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
         if let Some(data) = &event.data {
             let commenter = data.get("commenter").and_then(|v| v.as_str()).unwrap_or("");
             let token_id = data.get("tokenId").and_then(|v| v.as_str()).unwrap_or("");
-            let _comment = data.get("comment").and_then(|v| v.as_str()).unwrap_or("");
+            let comment = data.get("comment").and_then(|v| v.as_str()).unwrap_or("");
+            let quality = self.comment_quality.as_ref().and_then(|analyzer| {
+                analyzer
+                    .lock()
+                    .ok()
+                    .map(|mut a| a.score(commenter, comment, Instant::now()))
+            });
 
             // Look up actual NFT UUID from database
             let nft_uuid = match self.lookup_nft_uuid(&event.contract_address, token_id).await? {
@@ -479,14 +1575,20 @@ impl EventProcessor {
                 nft_tags: tags,
             };
 
-            record_interaction(&self.pool, interaction).await?;
+            let quality = self.capped_quality(commenter, quality);
+            self.record(conn, interaction, quality, event_time(event)).await?;
+            self.credit_shared_engagement(conn, commenter, &nft_uuid).await;
 
             info!("💬 Processed comment: {} on {} (uuid={})", commenter, token_id, nft_uuid);
         }
         Ok(())
     }
 
-    async fn handle_bookmark(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_bookmark(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
         if let Some(data) = &event.data {
             let user = data.get("user").and_then(|v| v.as_str()).unwrap_or("");
             let token_id = data.get("tokenId").and_then(|v| v.as_str()).unwrap_or("");
@@ -528,7 +1630,11 @@ impl EventProcessor {
                 nft_tags: tags,
             };
 
-            record_interaction(&self.pool, interaction).await?;
+            let quality = self.capped_quality(user, None);
+            self.record(conn, interaction, quality, event_time(event)).await?;
+            if bookmarked {
+                self.credit_shared_engagement(conn, user, &nft_uuid).await;
+            }
 
             info!(
                 "🔖 Processed bookmark: {} {} {} (uuid={})",
@@ -541,9 +1647,14 @@ impl EventProcessor {
         Ok(())
     }
 
-    async fn handle_share(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_share(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
         if let Some(data) = &event.data {
             let sharer = data.get("sharer").and_then(|v| v.as_str()).unwrap_or("");
+            let recipient = data.get("recipient").and_then(|v| v.as_str()).unwrap_or("");
             let token_id = data.get("tokenId").and_then(|v| v.as_str()).unwrap_or("");
 
             // Look up actual NFT UUID from database
@@ -573,29 +1684,80 @@ impl EventProcessor {
                 nft_tags: tags,
             };
 
-            record_interaction(&self.pool, interaction).await?;
+            let recipient_kind = match &self.recipient_classifier {
+                Some(classifier) if !recipient.is_empty() => classifier.classify(recipient).await,
+                _ => None,
+            };
+
+            let received = received_share(&interaction, recipient, recipient_kind);
+
+            let quality = self.capped_quality(sharer, recipient_kind.map(RecipientKind::share_quality));
+            self.record(conn, interaction, quality, event_time(event)).await?;
+
+            if let Some(received) = received {
+                self.record(conn, received, None, event_time(event)).await?;
+                shares::record_share(conn, sharer, recipient, &nft_uuid.to_string()).await?;
+            }
 
-            info!("📤 Processed share: {} shared {} (uuid={})", sharer, token_id, nft_uuid);
+            info!(
+                "📤 Processed share: {} shared {} with {} (uuid={})",
+                sharer, token_id, recipient, nft_uuid
+            );
         }
         Ok(())
     }
 
-    async fn handle_follow(&self, event: &BlockchainEvent) -> Result<()> {
+    /// Strengthen sharer -> recipient affinity if `user` engaged with an NFT
+    /// that was shared with them. Failures only cost the signal: the credit
+    /// runs under a savepoint, so it never aborts the event's transaction.
+    async fn credit_shared_engagement(&self, conn: &mut PgConnection, user: &str, nft_uuid: &Uuid) {
+        let credited = async {
+            let mut savepoint = sqlx::Connection::begin(&mut *conn).await?;
+            shares::credit_engagement(&mut savepoint, user, &nft_uuid.to_string()).await?;
+            savepoint.commit().await?;
+            anyhow::Ok(())
+        };
+        if let Err(e) = credited.await {
+            warn!("Failed to credit shared engagement for {}: {:?}", user, e);
+        }
+    }
+
+    async fn handle_follow(&self, event: &BlockchainEvent, conn: &mut PgConnection) -> Result<()> {
         if let Some(data) = &event.data {
             let follower = data.get("follower").and_then(|v| v.as_str()).unwrap_or("");
             let target = data.get("target").and_then(|v| v.as_str()).unwrap_or("");
+            if follower.is_empty() || target.is_empty() {
+                return Ok(());
+            }
+            let (Ok(follower), Ok(target)) = (normalize_address(follower), normalize_address(target)) else {
+                warn!("Skipping follow with an invalid address: {} -> {}", follower, target);
+                return Ok(());
+            };
+
+            follows::follow_user(conn, &follower, &target).await?;
 
-            // Following relationships help with creator affinity scoring
-            // We could store this in a separate table or use it for preference updates
             info!("👥 Processed follow: {} follows {}", follower, target);
         }
         Ok(())
     }
 
-    async fn handle_unfollow(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_unfollow(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
         if let Some(data) = &event.data {
             let follower = data.get("follower").and_then(|v| v.as_str()).unwrap_or("");
             let target = data.get("target").and_then(|v| v.as_str()).unwrap_or("");
+            if follower.is_empty() || target.is_empty() {
+                return Ok(());
+            }
+            let (Ok(follower), Ok(target)) = (normalize_address(follower), normalize_address(target)) else {
+                warn!("Skipping unfollow with an invalid address: {} -> {}", follower, target);
+                return Ok(());
+            };
+
+            follows::unfollow_user(conn, &follower, &target).await?;
 
             info!("👋 Processed unfollow: {} unfollows {}", follower, target);
         }
@@ -615,76 +1777,154 @@ impl EventProcessor {
         Ok(())
     }
 
-    async fn handle_profile_update(&self, event: &BlockchainEvent) -> Result<()> {
-        if let Some(data) = &event.data {
-            let user = data.get("user").and_then(|v| v.as_str()).unwrap_or("");
+    async fn handle_profile_update(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
+        if let Some(profile) = event.data.as_ref().and_then(creator_profile) {
+            profiles::upsert_creator_profile(conn, &profile).await?;
+
+            info!("📝 Processed profile update: {}", profile.address);
+        }
+        Ok(())
+    }
+
+    async fn handle_profile_update_extended(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
+        if let Some(profile) = event.data.as_ref().and_then(creator_profile) {
+            profiles::upsert_creator_profile(conn, &profile).await?;
+
+            info!(
+                "📝 Processed profile updated extended: {} (username={:?}, profile_hash={:?}, bio={:?}, website={:?})",
+                profile.address, profile.username, profile.profile_hash, profile.bio, profile.website
+            );
+        }
+        Ok(())
+    }
+
+    /// A tip boosts the sender's affinity for the recipient as a creator,
+    /// weighted by the sender's reputation so throwaway accounts can't tip
+    /// their way into someone's recommendations
+    async fn handle_tip(&self, event: &BlockchainEvent, conn: &mut PgConnection) -> Result<()> {
+        if let Some(data) = &event.data {
+            let field = |name: &str, legacy: &str| {
+                data.get(name)
+                    .or_else(|| data.get(legacy))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+            };
+            let (from, to, amount) = (field("sender", "from"), field("recipient", "to"), field("amount", "amount"));
+            let (Ok(from), Ok(to)) = (normalize_address(from), normalize_address(to)) else {
+                warn!("Skipping tip with an invalid address: {} -> {}", from, to);
+                return Ok(());
+            };
+
+            let sender = reputation::get_reputation(&mut *conn, &from).await?;
+            record_tip_on(conn, &from, &to, sender.weight(self.tip_reputation_followers)).await?;
 
-            info!("📝 Processed profile update: {}", user);
+            info!("💸 Processed tip: {} sent {} to {}", from, amount, to);
         }
         Ok(())
     }
 
-    async fn handle_profile_update_extended(&self, event: &BlockchainEvent) -> Result<()> {
-        if let Some(data) = &event.data {
-            let user = data.get("user").and_then(|v| v.as_str()).unwrap_or("");
-            let username = data.get("username").and_then(|v| v.as_str()).unwrap_or("");
-            let profile_hash = data.get("profile_hash").and_then(|v| v.as_str()).unwrap_or("");
-            let bio = data.get("bio").and_then(|v| v.as_str()).unwrap_or("");
-            let website = data.get("website").and_then(|v| v.as_str()).unwrap_or("");
+    async fn handle_badge(&self, event: &BlockchainEvent, conn: &mut PgConnection) -> Result<()> {
+        if let Some((user, badge)) = badge_event(event) {
+            badges::award_badge(conn, user, badge).await?;
 
-            info!("📝 Processed profile updated extended: {} (username={}, profile_hash={}, bio={}, website={})", user, username, profile_hash, bio, website);
+            info!("🏆 Processed badge: {} earned {}", user, badge);
         }
         Ok(())
     }
 
-    async fn handle_tip(&self, event: &BlockchainEvent) -> Result<()> {
-        if let Some(data) = &event.data {
-            let from = data.get("from").and_then(|v| v.as_str()).unwrap_or("");
-            let to = data.get("to").and_then(|v| v.as_str()).unwrap_or("");
-            let amount = data.get("amount").and_then(|v| v.as_u64()).unwrap_or(0);
+    async fn handle_badge_removed(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
+        if let Some((user, badge)) = badge_event(event) {
+            badges::remove_badge(conn, user, badge).await?;
 
-            info!("💸 Processed tip: {} sent {} to {}", from, amount, to);
+            info!("🏆 Processed badge removal: {} lost {}", user, badge);
         }
         Ok(())
     }
 
-    async fn handle_badge(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_user_verified(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
         if let Some(data) = &event.data {
             let user = data.get("user").and_then(|v| v.as_str()).unwrap_or("");
-            let badge_type = data.get("badgeType").and_then(|v| v.as_str()).unwrap_or("");
+            if user.is_empty() {
+                return Ok(());
+            }
 
-            info!("🏆 Processed badge: {} earned {}", user, badge_type);
+            reputation::mark_verified(conn, user).await?;
+
+            info!("✅ Processed user verification: {}", user);
         }
         Ok(())
     }
 
-    async fn handle_user_verified(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_user_blocked(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
         if let Some(data) = &event.data {
             let user = data.get("user").and_then(|v| v.as_str()).unwrap_or("");
+            let blocked_by = data.get("blockedBy").and_then(|v| v.as_str()).unwrap_or("");
+            if user.is_empty() || blocked_by.is_empty() {
+                return Ok(());
+            }
 
-            info!("✅ Processed user verification: {}", user);
+            blocks::block_user(conn, blocked_by, user).await?;
+
+            info!("🚫 Processed user block: {} blocked {}", blocked_by, user);
         }
         Ok(())
     }
 
-    async fn handle_user_blocked(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_user_unblocked(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
         if let Some(data) = &event.data {
             let user = data.get("user").and_then(|v| v.as_str()).unwrap_or("");
-            let blocked_by = data.get("blockedBy").and_then(|v| v.as_str()).unwrap_or("");
+            let unblocked_by = data
+                .get("unblockedBy")
+                .or_else(|| data.get("blockedBy"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            if user.is_empty() || unblocked_by.is_empty() {
+                return Ok(());
+            }
 
-            info!("🚫 Processed user block: {} blocked {}", blocked_by, user);
+            blocks::unblock_user(conn, unblocked_by, user).await?;
+
+            info!("✅ Processed user unblock: {} unblocked {}", unblocked_by, user);
         }
         Ok(())
     }
 
-    async fn handle_royalty_distributed(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_royalty_distributed(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
         if let Some(data) = &event.data {
             let token_id = data.get("tokenId").and_then(|v| v.as_str()).unwrap_or("");
             let recipient = data.get("recipient").and_then(|v| v.as_str()).unwrap_or("");
             let amount = data.get("amount").and_then(|v| v.as_str()).unwrap_or("");
 
             // Generate UUID for the NFT
-            let nft_uuid = Self::generate_nft_uuid(&event.contract_address, token_id);
+            let nft_uuid = generate_nft_uuid(&event.contract_address, token_id);
 
             // For recommendations, a royalty distribution indicates a purchase/sale
             // This is valuable signal for content popularity and creator success
@@ -699,7 +1939,7 @@ impl EventProcessor {
                 nft_tags: vec![],
             };
 
-            record_interaction(&self.pool, interaction).await?;
+            self.record(conn, interaction, None, event_time(event)).await?;
 
             // self.update_nft_buys_count(&event.contract_address, token_id, true).await?;
 
@@ -772,11 +2012,44 @@ impl EventProcessor {
         Ok(())
     }
 
-    async fn handle_daily_limits_updated(&self, event: &BlockchainEvent) -> Result<()> {
+    async fn handle_daily_limits_updated(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
         if let Some(data) = &event.data {
             let updater = data.get("updater").and_then(|v| v.as_str()).unwrap_or("");
+            let limit = |key: &str| {
+                data.get(key)
+                    .and_then(|v| v.as_str().and_then(|s| s.parse::<u64>().ok()).or_else(|| v.as_u64()))
+            };
+            let (Some(max_posts), Some(max_follows)) = (limit("maxPosts"), limit("maxFollows")) else {
+                warn!("DailyLimitsUpdated without limits: {:?}", data);
+                return Ok(());
+            };
 
-            info!("📊 Processed daily limits update by: {}", updater);
+            sqlx::query(
+                r#"
+                INSERT INTO daily_limits (contract_address, max_posts, max_follows, updated_at)
+                VALUES ($1, $2, $3, NOW())
+                ON CONFLICT (contract_address) DO UPDATE
+                SET max_posts = EXCLUDED.max_posts,
+                    max_follows = EXCLUDED.max_follows,
+                    updated_at = NOW()
+                "#,
+            )
+            .bind(event.contract_address.to_lowercase())
+            .bind(max_posts as i64)
+            .bind(max_follows as i64)
+            .execute(conn)
+            .await?;
+
+            self.set_daily_post_limit(max_posts);
+
+            info!(
+                "📊 Processed daily limits update by {}: {} posts, {} follows",
+                updater, max_posts, max_follows
+            );
         }
         Ok(())
     }
@@ -871,32 +2144,71 @@ impl EventProcessor {
     }
 
     // Legacy event handlers for backward compatibility
-    async fn handle_legacy_mint(&self, event: &BlockchainEvent) -> Result<()> {
-        self.handle_content_minted(event).await
+    async fn handle_legacy_mint(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
+        self.handle_content_minted(event, conn).await
     }
 
-    async fn handle_legacy_like(&self, event: &BlockchainEvent) -> Result<()> {
-        self.handle_like(event, InteractionType::Like).await
+    async fn handle_legacy_like(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
+        self.handle_like(event, InteractionType::Like, conn).await
     }
 
-    async fn handle_legacy_comment(&self, event: &BlockchainEvent) -> Result<()> {
-        self.handle_comment(event).await
+    async fn handle_legacy_comment(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
+        self.handle_comment(event, conn).await
     }
 
-    async fn handle_legacy_purchase(&self, event: &BlockchainEvent) -> Result<()> {
-        self.handle_content_purchase(event).await
+    async fn handle_legacy_purchase(
+        &self,
+        event: &BlockchainEvent,
+        conn: &mut PgConnection,
+    ) -> Result<()> {
+        self.handle_content_purchase(event, conn).await
     }
 }
 
+/// Generate a consistent UUID for an NFT based on contract address and token ID
+/// DEPRECATED: Use lookup_nft_uuid instead to get the actual database ID
+fn generate_nft_uuid(contract_address: &str, token_id: &str) -> Uuid {
+    // Create a deterministic UUID from contract_address + token_id
+    // This ensures the same NFT always gets the same UUID
+    let combined = format!("{}:{}", contract_address.to_lowercase(), token_id);
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, combined.as_bytes())
+}
+
 /// Spawn the event processor
 pub fn spawn_event_processor(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
     let shutdown_rx = state.shutdown.subscribe();
+    let mut ready_shutdown_rx = state.shutdown.subscribe();
 
     tokio::spawn(async move {
-        let processor = match EventProcessor::new(
+        // Deferred while the Elixir database is down, until it reconnects
+        if !state.elixir_ready.load(Ordering::Relaxed) {
+            info!("⏸️ Event processor waiting for the Elixir database");
+            while !state.elixir_ready.load(Ordering::Relaxed) {
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs(1)) => {}
+                    _ = ready_shutdown_rx.recv() => return,
+                }
+            }
+            info!("▶️ Elixir database ready, starting event processor");
+        }
+
+        let processor = match EventProcessor::<KafkaProducer>::new(
             &state.config,
             state.db.pool().clone(),
             state.elixir_db.pool().clone(),
+            state.kafka.clone(),
             shutdown_rx,
         ) {
             Ok(p) => p,
@@ -911,3 +2223,988 @@ pub fn spawn_event_processor(state: Arc<AppState>) -> tokio::task::JoinHandle<()
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::random_address;
+    use crate::kafka::{FlakyPublisher, InMemoryPublisher};
+    use std::collections::HashSet;
+    use std::sync::atomic::Ordering;
+
+    fn purchase_event(event_type: &str, data: serde_json::Value) -> BlockchainEvent {
+        BlockchainEvent::new(event_type, "0xABC", "friends", 42, "0xtx").with_data(data)
+    }
+
+    /// A processor on `pool` publishing in memory, with every optional stage off
+    fn test_processor(pool: PgPool) -> EventProcessor<InMemoryPublisher> {
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("group.id", "event-processor-tests")
+            .set("bootstrap.servers", "localhost:9")
+            .create()
+            .unwrap();
+        EventProcessor {
+            consumer,
+            publisher: InMemoryPublisher::new(),
+            pool: pool.clone(),
+            _elixir_pool: pool,
+            shutdown: broadcast::channel(1).1,
+            elixir_topic: None,
+            store_invalid_creator_mints: false,
+            comment_quality: None,
+            interaction_cap: None,
+            message_timeout: Duration::from_secs(30),
+            max_timeouts: 3,
+            recipient_classifier: None,
+            dead_letters: None,
+            interaction_echo: None,
+            pending_echoes: Mutex::new(Vec::new()),
+            pending_tag_fetches: Mutex::new(Vec::new()),
+            contract_aliases: ContractAliases::default(),
+            tip_reputation_followers: 0,
+            first_contact_boost: 0.5,
+            taste_space: TasteSpace::default(),
+            watermark: EventTimeWatermark {
+                grace: Duration::from_secs(3600),
+                daily_decay: 0.9,
+            },
+            metadata: MetadataFetcher::new(&crate::config::MetadataFetchConfig::default()),
+        }
+    }
+
+    #[test]
+    fn test_purchase_and_copy_mint_share_a_purchase_key() {
+        let events = [
+            purchase_event(
+                "PurchaseProcessed",
+                serde_json::json!({"tokenId": "7", "buyer": "0xBuyer", "amount": "100"}),
+            ),
+            purchase_event(
+                "ContentCopyMinted",
+                serde_json::json!({"originalId": "7", "buyer": "0xbuyer", "newTokenId": "8"}),
+            ),
+        ];
+
+        let keys: HashSet<_> = events.iter().filter_map(PurchaseKey::from_event).collect();
+        assert_eq!(keys.len(), 1);
+
+        // Same buyer purchasing again in a later block is a new purchase
+        let mut later = events[0].clone();
+        later.block_number = 43;
+        assert!(!keys.contains(&PurchaseKey::from_event(&later).unwrap()));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_purchase_and_copy_mint_record_one_purchase() {
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        let contract = random_address();
+        let buyer = random_address();
+        let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+        let nft_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address) \
+             VALUES ($1, 7, $2, 'art', '0x00000000000000000000000000000000000c0de5')",
+        )
+        .bind(nft_id)
+        .bind(&contract)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // One buy emits both events in the same transaction
+        let events = [
+            ("PurchaseProcessed", serde_json::json!({"tokenId": "7", "buyer": buyer, "amount": "100"}), 1),
+            ("ContentCopyMinted", serde_json::json!({"originalId": "7", "buyer": buyer, "newTokenId": "8"}), 2),
+        ]
+        .map(|(event_type, data, log_index)| {
+            BlockchainEvent::new(event_type, &contract, "art", 42, &tx_hash)
+                .with_data(data)
+                .with_log_index(log_index)
+        });
+
+        // A claim whose transaction rolls back doesn't hold the purchase
+        let key = PurchaseKey::from_event(&events[0]).unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        assert!(claim_purchase(&mut tx, &key).await.unwrap());
+        tx.rollback().await.unwrap();
+
+        let processor = test_processor(pool.clone());
+        for event in &events {
+            processor.process_event(event).await.unwrap();
+        }
+        // Nor does a restart forget it, even for a differently keyed copy
+        let restarted = test_processor(pool.clone());
+        restarted
+            .process_event(&events[1].clone().with_log_index(3))
+            .await
+            .unwrap();
+
+        let purchases = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM user_interactions WHERE user_address = $1 AND interaction_type = 'purchase'",
+        )
+        .bind(&buyer)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(purchases, 1);
+
+        for query in [
+            "DELETE FROM user_interactions WHERE user_address = $1",
+            "DELETE FROM user_preferences WHERE user_address = $1",
+            "DELETE FROM processed_purchases WHERE buyer_address = $1",
+        ] {
+            sqlx::query(query).bind(&buyer).execute(&pool).await.unwrap();
+        }
+        sqlx::query("DELETE FROM nft_engagement_counts WHERE nft_id = $1")
+            .bind(nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM processed_events WHERE transaction_hash = $1")
+            .bind(&tx_hash)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM nfts WHERE id = $1")
+            .bind(nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_elixir_event_is_mapped_to_blockchain_event() {
+        let payload = serde_json::json!({
+            "source": "elixir",
+            "event": "content_copy_minted",
+            "contract_address": "0xABC",
+            "block_number": 42,
+            "tx_hash": "0xtx",
+            "timestamp": 1_700_000_000,
+            "payload": {"original_id": 7, "buyer": "0xBuyer", "new_token_id": 8}
+        })
+        .to_string();
+
+        let event = decode_event(payload.as_bytes(), "user.actions", Some("elixir.events")).unwrap();
+        assert_eq!(event.event_type, "ContentCopyMinted");
+        assert_eq!(event.contract_type, "friends");
+        assert_eq!(event.transaction_hash, "0xtx");
+        assert_eq!(event.timestamp, 1_700_000_000);
+
+        let data = event.data.as_ref().unwrap();
+        assert_eq!(data["originalId"], "7");
+        assert_eq!(data["newTokenId"], "8");
+
+        // Handlers see it exactly like the indexer's ContentCopyMinted
+        let key = PurchaseKey::from_event(&event).unwrap();
+        assert_eq!(key.token_id, "7");
+        assert_eq!(key.buyer, "0xbuyer");
+
+        // Untagged messages on the Elixir topic are also read as Elixir events
+        let untagged = serde_json::json!({
+            "event": "ContentLiked",
+            "contract_address": "0xABC",
+            "block_number": 1,
+            "transaction_hash": "0xtx",
+            "payload": {"token_id": "3", "liker": "0xL"}
+        })
+        .to_string();
+        let event = decode_event(untagged.as_bytes(), "elixir.events", Some("elixir.events")).unwrap();
+        assert_eq!(event.event_type, "ContentLiked");
+        assert_eq!(event.data.unwrap()["tokenId"], "3");
+
+        // Disabled: the Elixir shape isn't accepted
+        assert!(decode_event(payload.as_bytes(), "user.actions", None).is_err());
+    }
+
+    #[test]
+    fn test_zero_address_creator_mint_is_not_stored() {
+        let mint = |creator: &str| {
+            purchase_event(
+                "ContentMinted",
+                serde_json::json!({"tokenId": "1", "creator": creator, "contentType": 0}),
+            )
+        };
+        let before = invalid_creator_mint_count();
+
+        assert!(!should_store_mint(&mint("0x0000000000000000000000000000000000000000"), false));
+        assert!(!should_store_mint(&mint("not-an-address"), false));
+        assert!(should_store_mint(&mint("0x00000000000000000000000000000000000000aB"), false));
+        assert!(invalid_creator_mint_count() >= before + 2);
+
+        // Opting in keeps the legacy behaviour
+        assert!(should_store_mint(&mint("0x0000000000000000000000000000000000000000"), true));
+    }
+
+    #[test]
+    fn test_repeated_comment_records_less_weight() {
+        use crate::recommendation::preferences::interaction_weight;
+
+        let comment = InteractionEvent {
+            user_address: "0xuser".to_string(),
+            nft_id: "nft".to_string(),
+            interaction_type: InteractionType::Comment,
+            view_duration_ms: None,
+            source: None,
+            nft_contract_type: None,
+            nft_creator_address: None,
+            nft_tags: vec![],
+        };
+        let base = interaction_weight(&comment);
+
+        let start = Instant::now();
+        let mut analyzer = CommentQuality::new(Duration::from_secs(3600));
+        let novel = analyzer.score("0xUser", "Love the colours in this one", start);
+        let repeated = analyzer.score("0xuser", "love the  colours in this ONE", start + Duration::from_secs(60));
+        assert!(base * repeated < base * novel);
+
+        // Another user saying the same thing isn't spam
+        assert_eq!(analyzer.score("0xother", "Love the colours in this one", start), novel);
+
+        // Outside the window the comment is novel again
+        let later = analyzer.score("0xuser", "Love the colours in this one", start + Duration::from_secs(7200));
+        assert_eq!(later, novel);
+
+        // Very short comments count a little less
+        assert!(analyzer.score("0xuser", "nice", start) < novel);
+    }
+
+    #[test]
+    fn test_lowered_daily_limit_tightens_interaction_cap() {
+        let mut cap = InteractionCap::new(2);
+        let day = 20_000;
+
+        // No on-chain limit yet: nothing is capped
+        assert!((0..50).all(|_| cap.admit("0xuser", day) == 1.0));
+
+        cap.set_max_posts(10);
+        let admitted = |cap: &mut InteractionCap, day| {
+            (0..30).filter(|_| cap.admit("0xUser", day) == 1.0).count()
+        };
+        assert_eq!(admitted(&mut cap, day + 1), 20);
+
+        // A lowered limit admits fewer interactions into preference learning
+        cap.set_max_posts(5);
+        assert_eq!(admitted(&mut cap, day + 2), 10);
+
+        // Other users have their own budget
+        assert_eq!(cap.admit("0xother", day + 2), 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_slow_message_times_out_and_loop_continues() {
+        let before = ProcessorCounters::current().message_timeouts;
+        let timeout = Duration::from_millis(50);
+
+        let mut processed = Vec::new();
+        for (i, delay_ms) in [0u64, 500, 0].into_iter().enumerate() {
+            let handler = async move {
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                Ok(())
+            };
+            match process_with_timeout(handler, timeout).await {
+                Ok(()) => processed.push(i),
+                Err(Error::Timeout { timeout_ms }) => assert_eq!(timeout_ms, 50),
+                Err(e) => panic!("unexpected error: {:?}", e),
+            }
+        }
+
+        // The slow message was abandoned and the next one still ran
+        assert_eq!(processed, vec![0, 2]);
+        assert!(ProcessorCounters::current().message_timeouts > before);
+    }
+
+    #[test]
+    fn test_timeout_streak_counts_consecutive_timeouts_per_message() {
+        let mut streak = TimeoutStreak::default();
+        assert_eq!(streak.record("blockchain.events", 0, 7), 1);
+        assert_eq!(streak.record("blockchain.events", 0, 7), 2);
+
+        // Another message, even at the same offset elsewhere, starts over
+        assert_eq!(streak.record("user.actions", 0, 7), 1);
+        assert_eq!(streak.record("user.actions", 0, 7), 2);
+
+        streak.clear();
+        assert_eq!(streak.record("user.actions", 0, 7), 1);
+    }
+
+    #[tokio::test]
+    async fn test_share_recipient_classification_weights_users_above_contracts() {
+        // No provider: only cached code-presence results are available
+        let classifier = RecipientClassifier::new(None);
+        classifier.remember("0xGroupContract", RecipientKind::Contract);
+        classifier.remember("0xfriend", RecipientKind::Eoa);
+
+        let contract = classifier.classify("0xgroupcontract").await.unwrap();
+        let user = classifier.classify("0xFriend").await.unwrap();
+        assert_eq!(contract, RecipientKind::Contract);
+        assert_eq!(user, RecipientKind::Eoa);
+        assert!(user.share_quality() > contract.share_quality());
+
+        // Unknown and uncached: unclassified, so the share is unweighted
+        assert_eq!(classifier.classify("0xunknown").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_event_is_sent_to_dead_letter_topic() {
+        let publisher = InMemoryPublisher::new();
+        let mut dead_letters = DeadLetters::new(publisher.clone(), "events.dlq".to_string(), 3);
+        let payload = br#"{"event_type": "ContentLiked", "block_number": "#;
+        let message = MessageRef {
+            topic: "blockchain.events",
+            partition: 2,
+            offset: 41,
+            payload,
+        };
+
+        // Each failed delivery is counted against the message until its
+        // attempts run out
+        let mut outcomes = Vec::new();
+        for _ in 0..3 {
+            let error = decode_event(payload, "blockchain.events", None).unwrap_err();
+            outcomes.push(dead_letters.failed(&message, error).await.unwrap());
+        }
+        assert_eq!(
+            outcomes,
+            [AfterFailure::Redeliver, AfterFailure::Redeliver, AfterFailure::DeadLettered]
+        );
+        let sent = publisher.messages();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].topic, "events.dlq");
+        assert_eq!(sent[0].payload["partition"], 2);
+        assert_eq!(sent[0].payload["offset"], 41);
+        assert_eq!(sent[0].payload["attempts"], 3);
+        assert_eq!(sent[0].payload["payload"], String::from_utf8_lossy(payload).as_ref());
+        assert!(sent[0].payload["error"].as_str().unwrap().contains("EOF"));
+        assert!(dead_letters.attempts.is_empty());
+
+        // A message that succeeds on redelivery is not dead-lettered, and
+        // attempts are counted per message
+        let retried = MessageRef { offset: 42, ..message };
+        let other = MessageRef { partition: 3, ..retried };
+        for _ in 0..2 {
+            let outcome = dead_letters.failed(&retried, Error::kafka("transient")).await.unwrap();
+            assert_eq!(outcome, AfterFailure::Redeliver);
+        }
+        let outcome = dead_letters.failed(&other, Error::kafka("transient")).await.unwrap();
+        assert_eq!(outcome, AfterFailure::Redeliver);
+        dead_letters.succeeded(&retried);
+        dead_letters.succeeded(&other);
+        assert!(dead_letters.attempts.is_empty());
+        assert_eq!(publisher.messages().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_dead_letter_publish_is_an_error() {
+        let mut dead_letters =
+            DeadLetters::new(FlakyPublisher::default(), "events.dlq".to_string(), 2);
+        let message = MessageRef {
+            topic: "blockchain.events",
+            partition: 0,
+            offset: 7,
+            payload: b"{}",
+        };
+        let poison = || Error::kafka("poison");
+
+        // The message can't be committed until it reaches the dead-letter topic
+        assert_eq!(
+            dead_letters.failed(&message, poison()).await.unwrap(),
+            AfterFailure::Redeliver
+        );
+        dead_letters.publisher.down.store(true, Ordering::SeqCst);
+        assert!(dead_letters.failed(&message, poison()).await.is_err());
+        assert!(dead_letters.publisher.inner.messages().is_empty());
+
+        // Its attempts are spent, so the next failure dead-letters it
+        dead_letters.publisher.down.store(false, Ordering::SeqCst);
+        assert_eq!(
+            dead_letters.failed(&message, poison()).await.unwrap(),
+            AfterFailure::DeadLettered
+        );
+        let sent = dead_letters.publisher.inner.messages();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].payload["attempts"], 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_reprocessed_mint_without_tags_keeps_existing_tags() {
+        let pool = crate::database::test_pool().await;
+
+        let nft_id = Uuid::new_v4();
+        let tags = |t: &[&str]| t.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let stored = || async {
+            sqlx::query_scalar::<_, Vec<String>>("SELECT tags FROM nft_features WHERE nft_id = $1")
+                .bind(nft_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        upsert_mint_features(&pool, nft_id, "0xabc", 7, &tags(&["sunset", "beach"])).await.unwrap();
+        upsert_mint_features(&pool, nft_id, "0xabc", 7, &mint_tags(&serde_json::json!({"tokenId": "7"})))
+            .await
+            .unwrap();
+        assert_eq!(stored().await, tags(&["sunset", "beach"]));
+
+        // New tags still replace the old ones
+        upsert_mint_features(&pool, nft_id, "0xabc", 7, &tags(&["night"])).await.unwrap();
+        assert_eq!(stored().await, tags(&["night"]));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_mint_metadata_fetch_waits_for_commit() {
+        let pool = crate::database::test_pool().await;
+
+        let event = BlockchainEvent::new("ContentMinted", random_address(), "art", 42, "0xtx").with_data(
+            serde_json::json!({"tokenId": "7", "creator": random_address(), "uri": "ipfs://QmTags"}),
+        );
+        let processor = test_processor(pool.clone());
+
+        // A mint that rolls back only ever queued its fetch
+        let mut tx = pool.begin().await.unwrap();
+        processor
+            .dispatch_event(EventType::ContentMinted, &event, &mut tx)
+            .await
+            .unwrap();
+        tx.rollback().await.unwrap();
+
+        let queued = processor.take_pending_tag_fetches();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].uri, "ipfs://QmTags");
+        let stored = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM nft_features WHERE nft_id = $1")
+            .bind(queued[0].nft_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, 0);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_profile_update_extended_persists_and_enriches_feed() {
+        use crate::recommendation::engine::{RecommendationEngine, ScoringWeights, TrendingMode};
+        use ethers::abi::Token;
+        use ethers::types::{Bytes, Log, H256};
+
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        let creator = random_address();
+        let sig = H256::from(ethers::utils::keccak256(
+            "ProfileUpdatedExtended(address,string,string,string,string,uint256)",
+        ));
+        let mut user_topic = [0u8; 32];
+        user_topic[12..].copy_from_slice(&hex::decode(&creator[2..]).unwrap());
+        let log = Log {
+            topics: vec![sig, H256::from(user_topic)],
+            data: Bytes::from(ethers::abi::encode(&[
+                Token::String("alice".to_string()),
+                Token::String("Qmprofile".to_string()),
+                Token::String("hello bio".to_string()),
+                Token::String("https://example.com".to_string()),
+                Token::Uint(1_700_000_500u64.into()),
+            ])),
+            ..Default::default()
+        };
+
+        // Round-trip through JSON the way the event arrives from Kafka
+        let parsed = crate::events::parse_log(&log, "friends").unwrap();
+        let event: BlockchainEvent =
+            serde_json::from_value(serde_json::to_value(&parsed).unwrap()).unwrap();
+        let profile = creator_profile(event.data.as_ref().unwrap()).unwrap();
+        assert_eq!(profile.address, creator);
+        profiles::upsert_creator_profile(&pool, &profile).await.unwrap();
+
+        // A legacy ProfileUpdated only touches the username, and an empty one
+        // leaves it alone
+        let legacy = serde_json::json!({"user": creator, "username": "alice2"});
+        profiles::upsert_creator_profile(&pool, &creator_profile(&legacy).unwrap()).await.unwrap();
+        let cleared = CreatorProfile {
+            address: creator.clone(),
+            username: Some(String::new()),
+            ..Default::default()
+        };
+        profiles::upsert_creator_profile(&pool, &cleared).await.unwrap();
+        let (bio, website): (Option<String>, Option<String>) =
+            sqlx::query_as("SELECT bio, website FROM creator_profiles WHERE address = $1")
+                .bind(&creator)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(bio.as_deref(), Some("hello bio"));
+        assert_eq!(website.as_deref(), Some("https://example.com"));
+
+        // Feed items carry the username, whatever the case of the creator
+        let contract_type = format!("ks{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let profiled = Uuid::new_v4();
+        let creators = [
+            (profiled, creator.to_uppercase().replacen("0X", "0x", 1)),
+            (Uuid::new_v4(), "0xnoprofile".to_string()),
+        ];
+        for (id, creator_address) in &creators {
+            sqlx::query(
+                "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address) VALUES ($1, 1, '0xabc', $2, $3)",
+            )
+            .bind(id)
+            .bind(&contract_type)
+            .bind(creator_address)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let engine = RecommendationEngine::new(pool.clone(), ScoringWeights::default());
+        let feed = engine
+            .get_trending(10, 0, Some(&contract_type), TrendingMode::Trending)
+            .await
+            .unwrap();
+        assert_eq!(feed.len(), 2);
+        for item in &feed {
+            let expected = (item.nft_id == profiled.to_string()).then_some("alice2");
+            assert_eq!(item.creator_username.as_deref(), expected);
+        }
+    }
+
+    #[test]
+    fn test_interaction_addresses_are_normalized() {
+        let mut interaction = InteractionEvent {
+            user_address: " 0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".to_string(),
+            nft_id: Uuid::new_v4().to_string(),
+            interaction_type: InteractionType::Like,
+            view_duration_ms: None,
+            source: None,
+            nft_contract_type: Some("art".to_string()),
+            nft_creator_address: Some("0xFB6916095CA1DF60BB79CE92CE3EA74C37C5D359".to_string()),
+            nft_tags: vec![],
+        };
+        normalize_interaction(&mut interaction).unwrap();
+        assert_eq!(interaction.user_address, "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+        assert_eq!(
+            interaction.nft_creator_address.as_deref(),
+            Some("0xfb6916095ca1df60bb79ce92ce3ea74c37c5d359")
+        );
+
+        interaction.nft_creator_address = Some("0xnotanaddress".to_string());
+        normalize_interaction(&mut interaction).unwrap();
+        assert!(interaction.nft_creator_address.is_none());
+
+        interaction.user_address = "liker".to_string();
+        assert!(matches!(normalize_interaction(&mut interaction), Err(Error::InvalidAddress { .. })));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_replayed_message_records_one_interaction() {
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        let (contract, user) = (random_address(), random_address());
+        let nft_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address) VALUES ($1, 7, $2, 'art', $3)",
+        )
+        .bind(nft_id)
+        .bind(&contract)
+        .bind(random_address())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+        let payload = serde_json::json!({
+            "event_type": "ContentLiked",
+            "contract_address": contract,
+            "contract_type": "art",
+            "block_number": 42,
+            "transaction_hash": tx_hash,
+            "log_index": 3,
+            "timestamp": 1_700_000_000,
+            "data": {"liker": user, "tokenId": "7"},
+        })
+        .to_string();
+
+        let mut processor = test_processor(pool.clone());
+        let echo = InteractionEcho::new(processor.publisher.clone(), "recommendations");
+        processor.interaction_echo = Some(echo);
+
+        // The same Kafka message delivered twice
+        for _ in 0..2 {
+            let event = decode_event(payload.as_bytes(), "blockchain.events", None).unwrap();
+            processor.process_event(&event).await.unwrap();
+        }
+
+        let recorded = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM user_interactions WHERE user_address = $1 AND nft_id = $2",
+        )
+        .bind(&user)
+        .bind(nft_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(recorded, 1);
+        // Echoed once, after the first delivery committed
+        let echoed = processor.publisher.messages();
+        assert_eq!(echoed.len(), 1);
+        assert_eq!(echoed[0].key, user);
+
+        for table in ["user_interactions", "user_preferences"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE user_address = $1", table))
+                .bind(&user)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("DELETE FROM nft_engagement_counts WHERE nft_id = $1")
+            .bind(nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM processed_events WHERE transaction_hash = $1")
+            .bind(&tx_hash)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM nfts WHERE id = $1")
+            .bind(nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_replayed_share_counts_once_toward_affinity() {
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        let (contract, sharer, recipient) = (random_address(), random_address(), random_address());
+        let nft_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address) VALUES ($1, 7, $2, 'art', $3)",
+        )
+        .bind(nft_id)
+        .bind(&contract)
+        .bind(random_address())
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+        let payload = serde_json::json!({
+            "event_type": "ContentShared",
+            "contract_address": contract,
+            "contract_type": "art",
+            "block_number": 42,
+            "transaction_hash": tx_hash,
+            "log_index": 1,
+            "timestamp": 1_700_000_000,
+            "data": {"sharer": sharer, "recipient": recipient, "tokenId": "7"},
+        })
+        .to_string();
+
+        // The same Kafka message delivered twice
+        let processor = test_processor(pool.clone());
+        for _ in 0..2 {
+            let event = decode_event(payload.as_bytes(), "blockchain.events", None).unwrap();
+            processor.process_event(&event).await.unwrap();
+        }
+
+        let shares = sqlx::query_scalar::<_, i32>(
+            "SELECT shares FROM share_affinity WHERE sharer_address = $1 AND recipient_address = $2",
+        )
+        .bind(&sharer)
+        .bind(&recipient)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(shares, 1);
+
+        for table in ["content_shares", "share_affinity"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE sharer_address = $1", table))
+                .bind(&sharer)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        for user in [&sharer, &recipient] {
+            for table in ["user_interactions", "user_preferences"] {
+                sqlx::query(&format!("DELETE FROM {} WHERE user_address = $1", table))
+                    .bind(user)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+        }
+        sqlx::query("DELETE FROM nft_engagement_counts WHERE nft_id = $1")
+            .bind(nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM processed_events WHERE transaction_hash = $1")
+            .bind(&tx_hash)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM nfts WHERE id = $1")
+            .bind(nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_share_raises_recipient_creator_affinity() {
+        use crate::recommendation::preferences::get_or_create_preferences;
+
+        let pool = crate::database::test_pool().await;
+
+        let (sharer, recipient, creator) = (random_address(), random_address(), random_address());
+        let nft_id = Uuid::new_v4().to_string();
+        let shared = InteractionEvent {
+            user_address: sharer.clone(),
+            nft_id: nft_id.clone(),
+            interaction_type: InteractionType::Share,
+            view_duration_ms: None,
+            source: Some("feed".to_string()),
+            nft_contract_type: Some("art".to_string()),
+            nft_creator_address: Some(creator.clone()),
+            nft_tags: vec![],
+        };
+
+        // Sharing with yourself doesn't count twice
+        assert!(received_share(&shared, &sharer.to_uppercase().replacen("0X", "0x", 1), None).is_none());
+        assert!(received_share(&shared, &recipient, Some(RecipientKind::Contract)).is_none());
+
+        let received = received_share(&shared, &recipient, Some(RecipientKind::Eoa)).unwrap();
+        assert_eq!(received.user_address, recipient);
+        let mut conn = pool.acquire().await.unwrap();
+        record_weighted_interaction_on(&mut conn, received, None, 0.5, &TasteSpace::default()).await.unwrap();
+
+        let prefs = get_or_create_preferences(&pool, &recipient).await.unwrap();
+        assert!(prefs.creator_preferences[&creator] > 0.5);
+
+        sqlx::query("DELETE FROM user_interactions WHERE user_address = $1")
+            .bind(&recipient)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM user_preferences WHERE user_address = $1")
+            .bind(&recipient)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM nft_engagement_counts WHERE nft_id = $1::uuid")
+            .bind(&nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_aliased_contract_events_land_on_canonical_contract() {
+        let old = "0x1111111111111111111111111111111111111111";
+        let canonical = random_address();
+        let aliases = ContractAliases::parse(&format!("{}={}", old, canonical)).unwrap();
+
+        let payload = serde_json::json!({
+            "event_type": "ContentMinted",
+            "contract_address": old,
+            "contract_type": "art",
+            "block_number": 42,
+            "transaction_hash": format!("0x{}", Uuid::new_v4().simple()),
+            "log_index": 0,
+            "timestamp": 1_700_000_000,
+            "data": {"tokenId": "7"},
+        })
+        .to_string();
+        let mut event = decode_event(payload.as_bytes(), "blockchain.events", None).unwrap();
+        apply_contract_alias(&aliases, &mut event);
+
+        assert_eq!(event.contract_address, canonical);
+        let nft_id = generate_nft_uuid(&event.contract_address, "7");
+        assert_eq!(nft_id, generate_nft_uuid(&canonical, "7"));
+        assert_ne!(nft_id, generate_nft_uuid(old, "7"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_aliased_mint_features_stored_under_canonical_contract() {
+        let pool = crate::database::test_pool().await;
+
+        let old = "0x1111111111111111111111111111111111111111";
+        let canonical = random_address();
+        let aliases = ContractAliases::parse(&format!("{}={}", old, canonical)).unwrap();
+        let mut event = BlockchainEvent::new("ContentMinted", old, "art", 42, "0xtx");
+        apply_contract_alias(&aliases, &mut event);
+        let nft_id = generate_nft_uuid(&event.contract_address, "7");
+
+        upsert_mint_features(&pool, nft_id, &event.contract_address, 7, &[]).await.unwrap();
+        let per_contract: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM nft_features WHERE contract_address = $1")
+            .bind(&canonical)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(per_contract, 1);
+
+        sqlx::query("DELETE FROM nft_features WHERE nft_id = $1")
+            .bind(nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_reputable_tipper_boosts_creator_more() {
+        let pool = crate::database::test_pool().await;
+
+        let (reputable, newcomer, creator) = (random_address(), random_address(), random_address());
+        let fans: Vec<String> = (0..5).map(|_| random_address()).collect();
+        for fan in &fans {
+            follows::follow_user(&pool, fan, &reputable).await.unwrap();
+        }
+        reputation::mark_verified(&pool, &reputable).await.unwrap();
+
+        let mut boosts = Vec::new();
+        for sender in [&reputable, &newcomer] {
+            let mut conn = pool.acquire().await.unwrap();
+            let weight = reputation::get_reputation(&mut *conn, sender).await.unwrap().weight(5);
+            assert!(record_tip_on(&mut conn, sender, &creator, weight).await.unwrap());
+            let prefs = crate::recommendation::preferences::get_or_create_preferences(&pool, sender).await.unwrap();
+            boosts.push(prefs.creator_preferences[&creator]);
+        }
+        assert!(boosts[0] > boosts[1], "{:?}", boosts);
+        assert!(boosts[1] > 0.5);
+
+        sqlx::query("DELETE FROM user_follows WHERE target_address = $1")
+            .bind(&reputable)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM creator_profiles WHERE address = $1")
+            .bind(&reputable)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM user_preferences WHERE user_address = ANY($1)")
+            .bind(vec![reputable, newcomer])
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_late_interaction_applies_age_decayed_weight() {
+        let watermark = EventTimeWatermark {
+            grace: Duration::from_secs(300),
+            daily_decay: 0.95,
+        };
+        let now = 1_700_000_000;
+        let day = 86_400;
+
+        // On time, within the grace period, undated or from the future: full weight
+        assert_eq!(watermark.weight(now, now), 1.0);
+        assert_eq!(watermark.weight(now - 299, now), 1.0);
+        assert_eq!(watermark.weight(0, now), 1.0);
+        assert_eq!(watermark.weight(now + day, now), 1.0);
+        assert_eq!(watermark.quality(now, now, None), None);
+
+        // Thirty days late: weighted as thirty days of decay would leave it
+        let late = watermark.quality(now - 30 * day, now, None).unwrap();
+        assert!((late - 0.95f32.powi(30)).abs() < 1e-4, "{late}");
+        let late_comment = watermark.quality(now - 30 * day, now, Some(0.5)).unwrap();
+        assert!((late_comment - 0.5 * 0.95f32.powi(30)).abs() < 1e-4);
+
+        // Event time comes from the timestamp embedded in the data, falling
+        // back to when the event was parsed
+        let event = BlockchainEvent::new("ContentLiked", "0xabc", "art", 1, "0x01");
+        let parsed_at = event.timestamp;
+        assert_eq!(event_time(&event), parsed_at);
+        let dated = event.clone().with_data(serde_json::json!({"timestamp": "1600000000"}));
+        assert_eq!(event_time(&dated), 1_600_000_000);
+        let undated = event.with_data(serde_json::json!({"timestamp": "0"}));
+        assert_eq!(event_time(&undated), parsed_at);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_late_like_event_weighs_less_than_on_time_like() {
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        let (contract, creator) = (random_address(), random_address());
+        let nft_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address) VALUES ($1, 7, $2, 'art', $3)",
+        )
+        .bind(nft_id)
+        .bind(&contract)
+        .bind(&creator)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // The same like for the same creator, once on time and once stamped
+        // thirty days ago; the fixture decays late events 0.9 a day
+        let processor = test_processor(pool.clone());
+        let now = chrono::Utc::now().timestamp();
+        let (on_time, late) = (random_address(), random_address());
+        let tx_hash = format!("0x{}", Uuid::new_v4().simple());
+        for (log_index, (liker, timestamp)) in [(&on_time, now), (&late, now - 30 * 86_400)]
+            .into_iter()
+            .enumerate()
+        {
+            let event = BlockchainEvent::new("ContentLiked", &contract, "art", 42, &tx_hash)
+                .with_log_index(log_index as u64)
+                .with_data(serde_json::json!({
+                    "tokenId": "7",
+                    "liker": liker,
+                    "creator": creator,
+                    "timestamp": timestamp.to_string(),
+                }));
+            processor.process_event(&event).await.unwrap();
+        }
+
+        let mut gains = Vec::new();
+        for user in [&on_time, &late] {
+            let prefs: serde_json::Value = sqlx::query_scalar(
+                "SELECT creator_preferences FROM user_preferences WHERE user_address = $1",
+            )
+            .bind(user)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            gains.push(prefs[creator.as_str()].as_f64().unwrap() - 0.5);
+        }
+        let ratio = gains[1] / gains[0];
+        assert!((ratio - 0.9f64.powi(30)).abs() < 1e-3, "{gains:?}");
+
+        let users = [on_time.as_str(), late.as_str()];
+        for table in ["user_interactions", "user_preferences"] {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE user_address = ANY($1)",
+                table
+            ))
+            .bind(&users[..])
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        sqlx::query("DELETE FROM processed_events WHERE transaction_hash = $1")
+            .bind(&tx_hash)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM nfts WHERE id = $1")
+            .bind(nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -14,10 +14,13 @@
 //! - `blockchain.events` - Raw blockchain events with full log data
 //! - `user.actions` - Processed user actions for recommendations
 
+use crate::config::{KafkaKeyStrategy, KafkaTopics, PendingLogPolicy};
 use crate::error::Result;
 use ethers::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::debug;
 use once_cell::sync::Lazy;
 
 // ============================================================================
@@ -547,16 +550,58 @@ impl EventType {
         )
     }
 
-    /// Get Kafka topic for this event type
-    pub fn kafka_topic(&self) -> &'static str {
+    /// Which configured Kafka topic this event type belongs on
+    pub fn kafka_topic(&self) -> TopicKind {
         if self.is_social() {
-            "user.actions"
+            TopicKind::UserActions
         } else {
-            "blockchain.events"
+            TopicKind::BlockchainEvents
         }
     }
 }
 
+/// The configured topics an indexed event can be routed to. Resolve the
+/// actual name with [`TopicKind::name`], so deployments that rename topics
+/// only have to change `KafkaTopics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TopicKind {
+    UserActions,
+    BlockchainEvents,
+}
+
+impl TopicKind {
+    /// This topic's name in `topics`
+    pub fn name(self, topics: &KafkaTopics) -> &str {
+        match self {
+            TopicKind::UserActions => &topics.user_actions,
+            TopicKind::BlockchainEvents => &topics.blockchain_events,
+        }
+    }
+}
+
+impl std::str::FromStr for EventType {
+    type Err = crate::error::Error;
+
+    /// Parse an event name using the same mapping as serde, so the accepted
+    /// names can't drift from the serialized ones
+    fn from_str(s: &str) -> Result<Self> {
+        use serde::de::IntoDeserializer;
+
+        let de: serde::de::value::StrDeserializer<'_, serde::de::value::Error> = s.into_deserializer();
+        EventType::deserialize(de).map_err(|_| crate::error::Error::InvalidFormat {
+            message: format!("unknown event type: {}", s).into(),
+        })
+    }
+}
+
+impl TryFrom<&str> for EventType {
+    type Error = crate::error::Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        s.parse()
+    }
+}
+
 impl std::fmt::Display for EventType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Serialize to the same format as serde
@@ -595,6 +640,42 @@ pub struct ParsedEvent {
     /// Raw log data (hex encoded)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub raw_data: Option<String>,
+    /// Emitted from a pending log: no block number or transaction hash yet,
+    /// so the position fields are placeholders and the event may be reorged
+    /// away
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub pending: bool,
+}
+
+/// Whether `log` has not been mined yet
+pub fn is_pending_log(log: &Log) -> bool {
+    log.block_number.is_none() || log.transaction_hash.is_none()
+}
+
+/// [`parse_log`] for the indexers: pending logs are rejected with
+/// `Error::EventDecode` or flagged, per `policy`, rather than emitted with
+/// zeroed positions. Without `partial_decode`, undecodable data is emitted as
+/// plain `Raw`.
+pub fn parse_mined_log(
+    log: &Log,
+    fallback_contract_type: &str,
+    policy: PendingLogPolicy,
+    partial_decode: bool,
+) -> Result<ParsedEvent> {
+    let pending = is_pending_log(log);
+    if pending && policy == PendingLogPolicy::Reject {
+        return Err(crate::error::Error::EventDecode {
+            event: "log",
+            message: "pending log has no block number or transaction hash".into(),
+        });
+    }
+
+    let mut parsed = parse_log(log, fallback_contract_type)?;
+    parsed.pending = pending;
+    if !partial_decode {
+        parsed.data = parsed.data.map(ParsedEventData::without_partial_decode);
+    }
+    Ok(parsed)
 }
 
 /// Decoded event data for different event types
@@ -668,8 +749,9 @@ pub enum ParsedEventData {
         followed_username: String,
         timestamp: String,
     },
-    /// ProfileUpdatedExtended event data (username, profileHash, bio, website, timestamp)
+    /// ProfileUpdatedExtended event data (user, username, profileHash, bio, website, timestamp)
     ProfileUpdatedExtended {
+        user: String,
         username: String,
         profile_hash: String,
         bio: String,
@@ -751,6 +833,15 @@ pub enum ParsedEventData {
         timestamp: String,
     },
 
+    /// ProfileUpdated with the legacy four-string layout (user, username, profileHash, bio, website)
+    ProfileUpdatedLegacy {
+        user: String,
+        username: String,
+        profile_hash: String,
+        bio: String,
+        website: String,
+    },
+
     /// UserVerified event
     UserVerifiedEvent {
         user: String,
@@ -817,9 +908,129 @@ pub enum ParsedEventData {
         timestamp: String,
     },
 
+    /// NotificationEvent
+    NotificationEvent {
+        sender: String,
+        recipient: String,
+        notification_type: String,
+        token_id: String,
+        title: String,
+        body: String,
+        ref_hash: String,
+        metadata: String,
+    },
+
     /// Generic/raw data
     Raw { hex: String },
 
+    /// A known event whose topics decoded but whose data didn't match the
+    /// expected layout
+    PartiallyDecoded { indexed: Vec<String>, raw_hex: String },
+}
+
+impl ParsedEventData {
+    /// `PartiallyDecoded` data back as plain `Raw`, for consumers that don't
+    /// handle partial decodes
+    pub fn without_partial_decode(self) -> Self {
+        match self {
+            ParsedEventData::PartiallyDecoded { raw_hex, .. } => ParsedEventData::Raw { hex: raw_hex },
+            other => other,
+        }
+    }
+
+    /// Timestamp the contract embedded in the event, if the event carries one
+    pub fn embedded_timestamp(&self) -> Option<u64> {
+        let ts = match self {
+            ParsedEventData::Minted { timestamp, .. }
+            | ParsedEventData::CopyMinted { timestamp, .. }
+            | ParsedEventData::Liked { timestamp, .. }
+            | ParsedEventData::Commented { timestamp, .. }
+            | ParsedEventData::Bookmarked { timestamp, .. }
+            | ParsedEventData::Shared { timestamp, .. }
+            | ParsedEventData::Followed { timestamp, .. }
+            | ParsedEventData::ProfileUpdatedExtended { timestamp, .. }
+            | ParsedEventData::RoyaltyDistributed { timestamp, .. }
+            | ParsedEventData::EarningsWithdrawn { timestamp, .. }
+            | ParsedEventData::PricesUpdated { timestamp, .. }
+            | ParsedEventData::TreasuryUpdated { timestamp, .. }
+            | ParsedEventData::DailyLimitsUpdated { timestamp, .. }
+            | ParsedEventData::ContentRequirementsUpdated { timestamp, .. }
+            | ParsedEventData::BurnedContentRevenue { timestamp, .. }
+            | ParsedEventData::UsernameRegistered { timestamp, .. }
+            | ParsedEventData::ProfileUpdatedSimple { timestamp, .. }
+            | ParsedEventData::UserVerifiedEvent { timestamp, .. }
+            | ParsedEventData::UserBlockedEvent { timestamp, .. }
+            | ParsedEventData::ContentBurned { timestamp, .. }
+            | ParsedEventData::TokensRecovered { timestamp, .. }
+            | ParsedEventData::TipSent { timestamp, .. }
+            | ParsedEventData::BadgeAwardedData { timestamp, .. }
+            | ParsedEventData::BadgeRemovedData { timestamp, .. }
+            | ParsedEventData::CollabProposedData { timestamp, .. }
+            | ParsedEventData::UsernameTransferredData { timestamp, .. } => timestamp,
+            _ => return None,
+        };
+        ts.parse().ok()
+    }
+}
+
+/// [`ParsedEventData::embedded_timestamp`] for event data that arrives in
+/// its serialized (JSON) form
+pub fn embedded_timestamp_json(data: &serde_json::Value) -> Option<u64> {
+    let ts = data.get("timestamp")?;
+    ts.as_str()
+        .and_then(|s| s.parse().ok())
+        .or_else(|| ts.as_u64())
+}
+
+// ============================================================================
+// Timestamp Consistency
+// ============================================================================
+
+/// Number of events whose embedded timestamp diverged from their block time
+static TIMESTAMP_DIVERGENCES: AtomicU64 = AtomicU64::new(0);
+
+/// Total timestamp divergences seen since startup
+pub fn timestamp_divergence_count() -> u64 {
+    TIMESTAMP_DIVERGENCES.load(Ordering::Relaxed)
+}
+
+/// Outcome of comparing an event's embedded timestamp with its block time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampCheck {
+    /// Within tolerance
+    Consistent,
+    /// Event has no (parseable) embedded timestamp
+    Missing,
+    /// Embedded timestamp is further than the tolerance from block time
+    Divergent {
+        event_timestamp: u64,
+        block_timestamp: u64,
+        diff_secs: u64,
+    },
+}
+
+/// Compare an event's embedded timestamp against the block timestamp.
+/// Divergences are counted in [`timestamp_divergence_count`].
+pub fn check_timestamp_consistency(
+    event: &ParsedEvent,
+    block_timestamp: u64,
+    tolerance_secs: u64,
+) -> TimestampCheck {
+    let Some(event_timestamp) = event.data.as_ref().and_then(|d| d.embedded_timestamp()) else {
+        return TimestampCheck::Missing;
+    };
+
+    let diff_secs = event_timestamp.abs_diff(block_timestamp);
+    if diff_secs <= tolerance_secs {
+        return TimestampCheck::Consistent;
+    }
+
+    TIMESTAMP_DIVERGENCES.fetch_add(1, Ordering::Relaxed);
+    TimestampCheck::Divergent {
+        event_timestamp,
+        block_timestamp,
+        diff_secs,
+    }
 }
 
 // ============================================================================
@@ -903,6 +1114,7 @@ pub fn parse_log(log: &Log, fallback_contract_type: &str) -> Result<ParsedEvent>
         } else {
             Some(format!("0x{}", hex::encode(&log.data)))
         },
+        pending: false,
     })
 }
 
@@ -915,6 +1127,16 @@ pub fn parse_log(log: &Log, fallback_contract_type: &str) -> Result<ParsedEvent>
 ///
 /// This provides Elixir with properly formatted values for immediate use.
 fn extract_indexed_params(event_type: &EventType, topics: &[H256]) -> Vec<String> {
+    let expected = indexed_param_layout(event_type).len();
+    if *event_type != EventType::Unknown && topics.len() > expected + 1 {
+        debug!(
+            "{} has {} indexed params, expected {}; preserving extras as bytes32",
+            event_type,
+            topics.len() - 1,
+            expected
+        );
+    }
+
     // Skip first topic (event signature) and process remaining
     topics
         .iter()
@@ -954,195 +1176,134 @@ enum IndexedParamType {
     Bytes32,
 }
 
-/// Determine the type of an indexed parameter based on event type and position
-fn get_indexed_param_type(event_type: &EventType, param_index: usize) -> IndexedParamType {
+/// Indexed parameter layout (in topic order, after the signature) for each event.
+///
+/// Topics beyond the known layout - e.g. a parameter added by a contract upgrade -
+/// are typed as bytes32 and preserved as-is, so known positions keep decoding.
+fn indexed_param_layout(event_type: &EventType) -> &'static [IndexedParamType] {
+    use IndexedParamType::{Address, Uint256};
+
     match event_type {
         // Minted events: (uint256 indexed tokenId, ...)
         EventType::SnapMinted
         | EventType::ArtMinted
         | EventType::MusicMinted
-        | EventType::FlixMinted => match param_index {
-            0 => IndexedParamType::Uint256, // tokenId
-            _ => IndexedParamType::Bytes32,
-        },
+        | EventType::FlixMinted => &[Uint256], // tokenId
 
         // Unified TheraFriends events - map indexed params per event
-        EventType::ContentMinted => match param_index {
-            0 => IndexedParamType::Uint256, // tokenId
-            1 => IndexedParamType::Address, // creator
-            2 => IndexedParamType::Uint256, // contentType (uint8 encoded as uint256)
-            _ => IndexedParamType::Bytes32,
-        },
-        EventType::ContentCopyMinted => match param_index {
-            0 => IndexedParamType::Uint256, // originalId
-            1 => IndexedParamType::Address, // buyer
-            2 => IndexedParamType::Uint256, // newTokenId
-            _ => IndexedParamType::Bytes32,
-        },
-        EventType::ContentLiked | EventType::ContentUnliked => match param_index {
-            0 => IndexedParamType::Uint256, // tokenId
-            1 => IndexedParamType::Address, // liker / unliker
-            2 => IndexedParamType::Address, // creator
-            _ => IndexedParamType::Bytes32,
-        },
+        // tokenId, creator, contentType (uint8 encoded as uint256)
+        EventType::ContentMinted => &[Uint256, Address, Uint256],
+        // originalId, buyer, newTokenId
+        EventType::ContentCopyMinted => &[Uint256, Address, Uint256],
+        // tokenId, liker / unliker, creator
+        EventType::ContentLiked | EventType::ContentUnliked => &[Uint256, Address, Address],
+        // tokenId, commenter / moderator / user
         EventType::ContentCommented | EventType::ContentBlocked | EventType::ContentBookmarked => {
-            match param_index {
-                0 => IndexedParamType::Uint256, // tokenId
-                1 => IndexedParamType::Address, // commenter / moderator / user
-                _ => IndexedParamType::Bytes32,
-            }
+            &[Uint256, Address]
         }
-        EventType::ContentShared => match param_index {
-            0 => IndexedParamType::Uint256, // tokenId
-            1 => IndexedParamType::Address, // sharer
-            2 => IndexedParamType::Address, // recipient
-            _ => IndexedParamType::Bytes32,
-        },
+        // tokenId, sharer, recipient
+        EventType::ContentShared => &[Uint256, Address, Address],
 
-        // Liked events: (uint256 indexed tokenId, address liker, ...)
+        // Legacy per-contract events: (uint256 indexed tokenId, ...)
         EventType::SnapLiked
         | EventType::ArtLiked
         | EventType::MusicLiked
-        | EventType::FlixLiked => match param_index {
-            0 => IndexedParamType::Uint256, // tokenId
-            _ => IndexedParamType::Bytes32,
-        },
-
-        // Commented events: (uint256 indexed tokenId, ...)
-        EventType::SnapCommented
+        | EventType::FlixLiked
+        | EventType::SnapCommented
         | EventType::ArtCommented
         | EventType::MusicCommented
-        | EventType::FlixCommented => match param_index {
-            0 => IndexedParamType::Uint256, // tokenId
-            _ => IndexedParamType::Bytes32,
-        },
-
-        // BoughtAndMinted events: (uint256 indexed tokenId, ...)
-        EventType::SnapBoughtAndMinted
+        | EventType::FlixCommented
+        | EventType::SnapBoughtAndMinted
         | EventType::ArtBoughtAndMinted
         | EventType::MusicBoughtAndMinted
-        | EventType::FlixBoughtAndMinted => match param_index {
-            0 => IndexedParamType::Uint256, // tokenId
-            _ => IndexedParamType::Bytes32,
-        },
-
-        // Deleted events: (uint256 indexed tokenId, ...)
-        EventType::SnapDeleted
+        | EventType::FlixBoughtAndMinted
+        | EventType::SnapDeleted
         | EventType::ArtDeleted
         | EventType::MusicDeleted
-        | EventType::FlixDeleted => match param_index {
-            0 => IndexedParamType::Uint256, // tokenId
-            _ => IndexedParamType::Bytes32,
-        },
+        | EventType::FlixDeleted => &[Uint256], // tokenId
 
         // Social events with addresses (legacy `Followed/Unfollowed` and new `UserFollowed/UserUnfollowed`)
+        // follower, followed/target
         EventType::Followed
         | EventType::Unfollowed
         | EventType::UserFollowed
-        | EventType::UserUnfollowed => match param_index {
-            0 => IndexedParamType::Address, // follower
-            1 => IndexedParamType::Address, // followed/target
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::UserBlocked | EventType::UserUnblocked => match param_index {
-            0 => IndexedParamType::Address, // user
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::UsernameRegistered | EventType::UserVerified | EventType::UserUnverified => {
-            match param_index {
-                0 => IndexedParamType::Address, // user
-                _ => IndexedParamType::Bytes32,
-            }
-        }
-
-        EventType::UsernameTransferred => match param_index {
-            0 => IndexedParamType::Address, // from
-            1 => IndexedParamType::Address, // to
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::ProfileUpdated => match param_index {
-            0 => IndexedParamType::Address, // user
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::ContentBurned => match param_index {
-            0 => IndexedParamType::Uint256, // tokenId
-            1 => IndexedParamType::Address, // owner
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::TokensRecovered => match param_index {
-            0 => IndexedParamType::Address,
-            1 => IndexedParamType::Address,
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::TipSent => match param_index {
-            0 => IndexedParamType::Address,
-            1 => IndexedParamType::Address,
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::BadgeAwarded | EventType::BadgeRemoved => match param_index {
-            0 => IndexedParamType::Address,
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::CollabProposed => match param_index {
-            0 => IndexedParamType::Uint256,
-            1 => IndexedParamType::Address,
-            2 => IndexedParamType::Address,
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::ProfileUpdatedExtended => match param_index {
-            0 => IndexedParamType::Address, // user
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::EarningsWithdrawn => match param_index {
-            0 => IndexedParamType::Address, // user
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::NotificationEvent => match param_index {
-            0 => IndexedParamType::Address, // sender
-            1 => IndexedParamType::Address, // recipient
-            _ => IndexedParamType::Bytes32,
-        },
-
+        | EventType::UserUnfollowed => &[Address, Address],
+
+        // user
+        EventType::UserBlocked
+        | EventType::UserUnblocked
+        | EventType::UsernameRegistered
+        | EventType::UserVerified
+        | EventType::UserUnverified
+        | EventType::ProfileUpdated
+        | EventType::ProfileUpdatedExtended
+        | EventType::EarningsWithdrawn
+        | EventType::BadgeAwarded
+        | EventType::BadgeRemoved => &[Address],
+
+        // from, to
+        EventType::UsernameTransferred => &[Address, Address],
+        // tokenId, owner
+        EventType::ContentBurned => &[Uint256, Address],
+        // token, to
+        EventType::TokensRecovered => &[Address, Address],
+        // sender, recipient
+        EventType::TipSent => &[Address, Address],
+        // tokenId, proposer, recipient
+        EventType::CollabProposed => &[Uint256, Address, Address],
+        // sender, recipient
+        EventType::NotificationEvent => &[Address, Address],
         // Transfer: (address indexed from, address indexed to, uint256 indexed tokenId)
-        EventType::Transfer => match param_index {
-            0 => IndexedParamType::Address, // from
-            1 => IndexedParamType::Address, // to
-            2 => IndexedParamType::Uint256, // tokenId
-            _ => IndexedParamType::Bytes32,
-        },
-
-        EventType::PurchaseProcessed | EventType::RoyaltyDistributed => match param_index {
-            0 => IndexedParamType::Uint256, // tokenId or similar
-            1 => IndexedParamType::Address,
-            _ => IndexedParamType::Bytes32,
-        },
-
-        // Default to bytes32 for unknown types (only types not handled above)
+        EventType::Transfer => &[Address, Address, Uint256],
+        // tokenId or similar, recipient / buyer
+        EventType::PurchaseProcessed | EventType::RoyaltyDistributed => &[Uint256, Address],
+        // oldTreasury, newTreasury
+        EventType::TreasuryUpdated => &[Address, Address],
+        // tokenId
+        EventType::BurnedContentRevenue => &[Uint256],
+
+        // Admin events with no indexed params
         EventType::PricesUpdated
         | EventType::ContentRequirementsUpdated
-        | EventType::BurnedContentRevenue
-        | EventType::TreasuryUpdated
-        | EventType::DailyLimitsUpdated => IndexedParamType::Bytes32,
-        EventType::Unknown => IndexedParamType::Bytes32,
+        | EventType::DailyLimitsUpdated => &[],
+        EventType::Unknown => &[],
     }
 }
 
-/// Parse event-specific data based on event type
+/// Determine the type of an indexed parameter based on event type and position
+fn get_indexed_param_type(event_type: &EventType, param_index: usize) -> IndexedParamType {
+    indexed_param_layout(event_type)
+        .get(param_index)
+        .copied()
+        .unwrap_or(IndexedParamType::Bytes32)
+}
+
+/// Parse event-specific data based on event type. A known event whose data
+/// doesn't decode keeps its decoded topics as `PartiallyDecoded` rather than
+/// bare `Raw`.
 fn parse_event_data(
     event_type: &EventType,
     indexed_params: &[String],
     data: &Bytes,
+) -> Option<ParsedEventData> {
+    let decoded = decode_event_data(event_type, indexed_params, data);
+    match decoded {
+        Some(ParsedEventData::Raw { hex })
+            if *event_type != EventType::Unknown && !indexed_params.is_empty() =>
+        {
+            Some(ParsedEventData::PartiallyDecoded {
+                indexed: indexed_params.to_vec(),
+                raw_hex: hex,
+            })
+        }
+        other => other,
+    }
+}
+
+fn decode_event_data(
+    event_type: &EventType,
+    indexed_params: &[String],
+    data: &Bytes,
 ) -> Option<ParsedEventData> {
     match event_type {
         EventType::ContentMinted => {
@@ -1345,22 +1506,36 @@ fn parse_event_data(
             // uri and creator are in data
             let token_id = indexed_params.first().cloned().unwrap_or_default();
 
-            if data.len() >= 64 {
-                // Decode creator address (last 20 bytes of first 32-byte word)
-                let creator = format!("0x{}", hex::encode(&data[12..32]));
-                // Legacy minted events did not include price/timestamp; keep fields empty
-                Some(ParsedEventData::Minted {
-                    token_id,
-                    uri: String::new(),
-                    creator,
-                    content_type: String::new(),
-                    price: String::new(),
-                    timestamp: String::new(),
-                })
-            } else {
-                Some(ParsedEventData::Raw {
+            match ethers::abi::decode(
+                &[ethers::abi::ParamType::String, ethers::abi::ParamType::Address],
+                &data.0,
+            ) {
+                Ok(tokens) => {
+                    use ethers::abi::Token;
+                    let uri = tokens
+                        .get(0)
+                        .and_then(|t| match t { Token::String(s) => Some(s.clone()), _ => None })
+                        .unwrap_or_default();
+                    let creator = tokens
+                        .get(1)
+                        .and_then(|t| match t {
+                            Token::Address(a) => Some(format!("0x{}", hex::encode(a.as_bytes()))),
+                            _ => None,
+                        })
+                        .unwrap_or_default();
+                    // Legacy minted events did not include price/timestamp; keep fields empty
+                    Some(ParsedEventData::Minted {
+                        token_id,
+                        uri,
+                        creator,
+                        content_type: String::new(),
+                        price: String::new(),
+                        timestamp: String::new(),
+                    })
+                }
+                Err(_) => Some(ParsedEventData::Raw {
                     hex: format!("0x{}", hex::encode(data)),
-                })
+                }),
             }
         }
 
@@ -1583,6 +1758,7 @@ fn parse_event_data(
 
         EventType::ProfileUpdatedExtended => {
             // ProfileUpdatedExtended(address indexed user, string username, string profileHash, string bio, string website, uint256 timestamp)
+            let user = indexed_params.first().cloned().unwrap_or_default();
             if data.is_empty() {
                 None
             } else {
@@ -1620,7 +1796,7 @@ fn parse_event_data(
                             .and_then(|t| match t { Token::Uint(u) => Some(u.to_string()), _ => None })
                             .unwrap_or_default();
 
-                        Some(ParsedEventData::ProfileUpdatedExtended { username, profile_hash, bio, website, timestamp })
+                        Some(ParsedEventData::ProfileUpdatedExtended { user, username, profile_hash, bio, website, timestamp })
                     }
                     Err(_) => Some(ParsedEventData::Raw { hex: format!("0x{}", hex::encode(data)) }),
                 }
@@ -1645,16 +1821,31 @@ fn parse_event_data(
             }
         }
 
+        // Two layouts share this type:
+        // ProfileUpdated(address indexed user, string username, string profileHash, string bio, string website) (legacy)
         // ProfileUpdated(address indexed user, string username, uint256 timestamp)
+        // The four-string decode is tried first: a two-field payload can't pass
+        // it, while the reverse would misread legacy payloads.
         EventType::ProfileUpdated => {
+            use ethers::abi::{ParamType, Token};
             let user = indexed_params.first().cloned().unwrap_or_default();
+            let string_at = |tokens: &[Token], i: usize| {
+                tokens.get(i).and_then(|t| match t { Token::String(s) => Some(s.clone()), _ => None }).unwrap_or_default()
+            };
             if data.is_empty() {
                 Some(ParsedEventData::ProfileUpdatedSimple { user, username: String::new(), timestamp: String::new() })
+            } else if let Ok(tokens) = ethers::abi::decode(&[ParamType::String, ParamType::String, ParamType::String, ParamType::String], &data.0) {
+                Some(ParsedEventData::ProfileUpdatedLegacy {
+                    user,
+                    username: string_at(&tokens, 0),
+                    profile_hash: string_at(&tokens, 1),
+                    bio: string_at(&tokens, 2),
+                    website: string_at(&tokens, 3),
+                })
             } else {
-                match ethers::abi::decode(&[ethers::abi::ParamType::String, ethers::abi::ParamType::Uint(256)], &data.0) {
+                match ethers::abi::decode(&[ParamType::String, ParamType::Uint(256)], &data.0) {
                     Ok(tokens) => {
-                        use ethers::abi::Token;
-                        let username = tokens.get(0).and_then(|t| match t { Token::String(s) => Some(s.clone()), _ => None }).unwrap_or_default();
+                        let username = string_at(&tokens, 0);
                         let timestamp = tokens.get(1).and_then(|t| match t { Token::Uint(u) => Some(u.to_string()), _ => None }).unwrap_or_default();
                         Some(ParsedEventData::ProfileUpdatedSimple { user, username, timestamp })
                     }
@@ -1807,6 +1998,50 @@ fn parse_event_data(
             }
         }
 
+        // NotificationEvent(address sender, address recipient, uint8 notificationType,
+        //                   uint256 tokenId, string title, string body, bytes32 refHash, string metadata)
+        EventType::NotificationEvent => {
+            let sender = indexed_params.first().cloned().unwrap_or_default();
+            let recipient = indexed_params.get(1).cloned().unwrap_or_default();
+            use ethers::abi::ParamType;
+            match ethers::abi::decode(
+                &[
+                    ParamType::Uint(8),
+                    ParamType::Uint(256),
+                    ParamType::String,
+                    ParamType::String,
+                    ParamType::FixedBytes(32),
+                    ParamType::String,
+                ],
+                &data.0,
+            ) {
+                Ok(tokens) => {
+                    use ethers::abi::Token;
+                    let uint = |i: usize| tokens.get(i).and_then(|t| match t { Token::Uint(u) => Some(u.to_string()), _ => None }).unwrap_or_default();
+                    let string = |i: usize| tokens.get(i).and_then(|t| match t { Token::String(s) => Some(s.clone()), _ => None }).unwrap_or_default();
+                    let ref_hash = tokens
+                        .get(4)
+                        .and_then(|t| match t {
+                            Token::FixedBytes(b) => Some(format!("0x{}", hex::encode(b))),
+                            _ => None,
+                        })
+                        .unwrap_or_default();
+                    Some(ParsedEventData::NotificationEvent {
+                        sender,
+                        recipient,
+                        notification_type: uint(0),
+                        token_id: uint(1),
+                        title: string(2),
+                        body: string(3),
+                        ref_hash,
+                        metadata: string(5),
+                    })
+                }
+                Err(_) if data.is_empty() => None,
+                Err(_) => Some(ParsedEventData::Raw { hex: format!("0x{}", hex::encode(data)) }),
+            }
+        }
+
         _ => {
             // For unknown events, just return raw data
             if data.is_empty() {
@@ -1826,6 +2061,53 @@ pub fn event_kafka_key(event: &ParsedEvent) -> String {
     format!("{}.{}", event.contract_type, event.contract_address)
 }
 
+/// Get Kafka key for an event under the given strategy.
+/// `User` keys by the acting user's address, falling back to the contract key
+/// when the event has no identifiable actor (admin/config events).
+pub fn event_kafka_key_with(event: &ParsedEvent, strategy: KafkaKeyStrategy) -> String {
+    match strategy {
+        KafkaKeyStrategy::Contract => event_kafka_key(event),
+        KafkaKeyStrategy::User => event_actor_address(event)
+            .map(|a| a.to_lowercase())
+            .unwrap_or_else(|| event_kafka_key(event)),
+    }
+}
+
+/// Address of the user who performed the action, if any
+pub fn event_actor_address(event: &ParsedEvent) -> Option<&str> {
+    let actor = match event.data.as_ref()? {
+        ParsedEventData::Minted { creator, .. } => creator,
+        ParsedEventData::CopyMinted { buyer, .. } => buyer,
+        ParsedEventData::Liked { liker, .. } => liker,
+        ParsedEventData::Commented { commenter, .. } => commenter,
+        ParsedEventData::Bookmarked { user, .. } => user,
+        ParsedEventData::Shared { sharer, .. } => sharer,
+        ParsedEventData::BoughtAndMinted { buyer, .. } => buyer,
+        ParsedEventData::Deleted { deleter, .. } => deleter,
+        ParsedEventData::Followed { follower, .. } => follower,
+        ParsedEventData::Purchase { buyer, .. } => buyer,
+        ParsedEventData::EarningsWithdrawn { user, .. } => user,
+        ParsedEventData::UsernameRegistered { user, .. } => user,
+        ParsedEventData::ProfileUpdatedSimple { user, .. } => user,
+        ParsedEventData::ProfileUpdatedLegacy { user, .. } => user,
+        ParsedEventData::UserVerifiedEvent { user, .. } => user,
+        ParsedEventData::UserBlockedEvent { user, .. } => user,
+        ParsedEventData::ContentBurned { owner, .. } => owner,
+        ParsedEventData::TipSent { sender, .. } => sender,
+        ParsedEventData::BadgeAwardedData { user, .. } => user,
+        ParsedEventData::BadgeRemovedData { user, .. } => user,
+        ParsedEventData::UsernameTransferredData { from, .. } => from,
+        ParsedEventData::ProfileUpdatedExtended { user, .. } => user,
+        _ => return None,
+    };
+
+    if actor.is_empty() {
+        None
+    } else {
+        Some(actor.as_str())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1913,7 +2195,8 @@ mod tests {
 
         let parsed = parse_log(&log, "friends").expect("parse failed");
         assert_eq!(parsed.event_type, "ProfileUpdatedExtended");
-        if let Some(ParsedEventData::ProfileUpdatedExtended { username, profile_hash, bio, website, timestamp }) = parsed.data {
+        if let Some(ParsedEventData::ProfileUpdatedExtended { user, username, profile_hash, bio, website, timestamp }) = parsed.data {
+            assert_eq!(user, "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
             assert_eq!(username, "alice");
             assert_eq!(profile_hash, "Qmabcdef123");
             assert_eq!(bio, "hello bio");
@@ -1956,6 +2239,39 @@ mod tests {
         } else { panic!("Expected Minted data"); }
     }
 
+    #[test]
+    fn test_parse_legacy_minted_uri_and_creator() {
+        use ethers::abi::Token;
+        use ethers::types::{Bytes, H160};
+
+        let sig = keccak256_signature("ArtMinted(uint256,string,address)");
+        let token_topic = h256_from_hex("0x0000000000000000000000000000000000000000000000000000000000000007");
+        let uri = "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi/metadata.json";
+        let creator = H160::from_slice(&[0xaa; 20]);
+        let data = ethers::abi::encode(&[Token::String(uri.to_string()), Token::Address(creator)]);
+
+        let mut log = ethers::types::Log::default();
+        log.topics = vec![sig, token_topic];
+        log.data = Bytes::from(data);
+
+        let parsed = parse_log(&log, "art").expect("parse failed");
+        assert_eq!(parsed.event_type, "ArtMinted");
+        if let Some(ParsedEventData::Minted { token_id, uri: parsed_uri, creator, .. }) = parsed.data {
+            assert_eq!(token_id, "7");
+            assert_eq!(parsed_uri, uri);
+            assert_eq!(creator, "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        } else { panic!("Expected Minted data"); }
+
+        // Data that is not a (string, address) tuple is kept raw, alongside
+        // the token id decoded from the topics
+        log.data = Bytes::from(vec![0u8; 16]);
+        let parsed = parse_log(&log, "art").expect("parse failed");
+        assert!(matches!(
+            parsed.data,
+            Some(ParsedEventData::PartiallyDecoded { ref indexed, .. }) if indexed == &["7"]
+        ));
+    }
+
     #[test]
     fn test_parse_content_liked_event() {
         use ethers::types::Bytes;
@@ -2020,6 +2336,46 @@ mod tests {
         } else { panic!("Expected Commented data"); }
     }
 
+    #[test]
+    fn test_undecodable_data_keeps_decoded_topics() {
+        let sig = h256_from_hex("0x505d1203546d4a3699987fc90279e0a1dfe65117be15cac29d00ca3ed7a673b6");
+        let token_topic = h256_from_hex("0x000000000000000000000000000000000000000000000000000000000000002a");
+        let commenter_topic = h256_from_hex("0x000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+
+        // Too short for (uint256, string, uint8, uint256)
+        let mut log = ethers::types::Log::default();
+        log.topics = vec![sig, token_topic, commenter_topic];
+        log.data = Bytes::from(vec![0xab; 20]);
+
+        let parsed = parse_log(&log, "friends").expect("parse failed");
+        assert_eq!(parsed.event_type, "ContentCommented");
+        let data = parsed.data.expect("data");
+        assert!(matches!(
+            &data,
+            ParsedEventData::PartiallyDecoded { indexed, raw_hex }
+                if indexed == &["42", "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"]
+                    && raw_hex == &format!("0x{}", "ab".repeat(20))
+        ));
+        let json = serde_json::to_value(&data).unwrap();
+        assert_eq!(json["indexed"][0], "42");
+        assert!(json.get("hex").is_none());
+
+        // Unknown events have nothing decoded to surface
+        log.topics = vec![H256::repeat_byte(0x01), token_topic];
+        let parsed = parse_log(&log, "friends").expect("parse failed");
+        assert!(matches!(parsed.data, Some(ParsedEventData::Raw { .. })));
+
+        // Indexers with partial decoding off emit the data as plain `Raw`
+        log.topics = vec![sig, token_topic, commenter_topic];
+        log.block_number = Some(1.into());
+        log.transaction_hash = Some(H256::repeat_byte(0x02));
+        let parsed = parse_mined_log(&log, "friends", PendingLogPolicy::Reject, false).expect("parse failed");
+        assert!(matches!(
+            parsed.data,
+            Some(ParsedEventData::Raw { ref hex }) if hex == &format!("0x{}", "ab".repeat(20))
+        ));
+    }
+
     #[test]
     fn test_parse_prices_updated_event() {
         use ethers::types::Bytes;
@@ -2283,6 +2639,62 @@ mod tests {
         } else { panic!("Expected ProfileUpdated data"); }
     }
 
+    #[test]
+    fn test_pending_log_is_rejected_or_flagged_not_zeroed() {
+        let sig = H256::from(ethers::utils::keccak256("UserFollowed(address,address,uint256)"));
+        let pending = Log {
+            topics: vec![sig, H256::from_low_u64_be(0xaaaa), H256::from_low_u64_be(0xbbbb)],
+            data: Bytes::from(vec![0u8; 32]),
+            ..Default::default()
+        };
+
+        let err = parse_mined_log(&pending, "friends", PendingLogPolicy::Reject, true).unwrap_err();
+        assert!(matches!(err, crate::error::Error::EventDecode { event: "log", .. }));
+
+        let flagged = parse_mined_log(&pending, "friends", PendingLogPolicy::Flag, true).unwrap();
+        assert!(flagged.pending);
+        assert_eq!(serde_json::to_value(&flagged).unwrap()["pending"], true);
+
+        // A mined log parses as before, without the flag on the wire
+        let mined = Log {
+            block_number: Some(U64::from(100)),
+            transaction_hash: Some(H256::repeat_byte(0x11)),
+            ..pending
+        };
+        let parsed = parse_mined_log(&mined, "friends", PendingLogPolicy::Reject, true).unwrap();
+        assert!(!parsed.pending);
+        assert_eq!(parsed.block_number, 100);
+        assert!(serde_json::to_value(&parsed).unwrap().get("pending").is_none());
+    }
+
+    #[test]
+    fn test_parse_profile_updated_legacy_layout() {
+        use ethers::abi::Token;
+        use ethers::types::Bytes;
+        let sig = keccak256_signature("ProfileUpdated(address,string,string,string,string)");
+        let user_topic = h256_from_hex("0x000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        let tokens = vec![
+            Token::String("bob".to_string()),
+            Token::String("QmProfile".to_string()),
+            Token::String("making things".to_string()),
+            Token::String("https://bob.example".to_string()),
+        ];
+
+        let mut log = ethers::types::Log::default();
+        log.topics = vec![sig, user_topic];
+        log.data = Bytes::from(ethers::abi::encode(&tokens));
+
+        let parsed = parse_log(&log, "friends").expect("parse failed");
+        assert_eq!(parsed.event_type, "ProfileUpdated");
+        if let Some(ParsedEventData::ProfileUpdatedLegacy { user, username, profile_hash, bio, website }) = parsed.data {
+            assert_eq!(user, "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+            assert_eq!(username, "bob");
+            assert_eq!(profile_hash, "QmProfile");
+            assert_eq!(bio, "making things");
+            assert_eq!(website, "https://bob.example");
+        } else { panic!("Expected ProfileUpdatedLegacy data"); }
+    }
+
     #[test]
     fn test_parse_user_verified_and_blocked_events() {
         use ethers::abi::Token;
@@ -2452,5 +2864,167 @@ mod tests {
             assert_eq!(timestamp, "1700000500");
         } else { panic!("Expected UsernameTransferred data"); }
     }
-}
 
+    #[test]
+    fn test_parse_notification_event() {
+        use ethers::abi::Token;
+        use ethers::types::{Bytes, U256};
+
+        let sig = keccak256_signature("NotificationEvent(address,address,uint8,uint256,string,string,bytes32,string)");
+        let sender = h256_from_hex("0x0000000000000000000000006666666666666666666666666666666666666666");
+        let recipient = h256_from_hex("0x0000000000000000000000007777777777777777777777777777777777777777");
+        let ref_hash = [0xabu8; 32];
+        let tokens = vec![
+            Token::Uint(U256::from(3u8)),
+            Token::Uint(U256::from(42u64)),
+            Token::String("New follower".to_string()),
+            Token::String("alice followed you".to_string()),
+            Token::FixedBytes(ref_hash.to_vec()),
+            Token::String("{\"source\":\"app\"}".to_string()),
+        ];
+        let mut log = ethers::types::Log::default();
+        log.topics = vec![sig, sender, recipient];
+        log.data = Bytes::from(ethers::abi::encode(&tokens));
+
+        let parsed = parse_log(&log, "thera_social").expect("parse failed");
+        assert_eq!(parsed.event_type, "NotificationEvent");
+        let Some(ParsedEventData::NotificationEvent {
+            sender,
+            recipient,
+            notification_type,
+            token_id,
+            title,
+            body,
+            ref_hash: hash,
+            metadata,
+        }) = parsed.data
+        else {
+            panic!("Expected NotificationEvent data, got {:?}", parsed.data);
+        };
+        assert_eq!(sender, "0x6666666666666666666666666666666666666666");
+        assert_eq!(recipient, "0x7777777777777777777777777777777777777777");
+        assert_eq!(notification_type, "3");
+        assert_eq!(token_id, "42");
+        assert_eq!(title, "New follower");
+        assert_eq!(body, "alice followed you");
+        assert_eq!(hash, format!("0x{}", hex::encode(ref_hash)));
+        assert_eq!(metadata, "{\"source\":\"app\"}");
+    }
+
+    #[test]
+    fn test_user_actions_key_is_user_address() {
+        let sig = keccak256_signature("UserFollowed(address,address,uint256)");
+        let follower_topic = h256_from_hex("0x000000000000000000000000AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA");
+        let target_topic = h256_from_hex("0x000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+
+        let mut log = ethers::types::Log::default();
+        log.address = ethers::types::H160::from_low_u64_be(0xabc);
+        log.topics = vec![sig, follower_topic, target_topic];
+        log.data = ethers::types::Bytes::from(vec![0u8; 32]);
+
+        let parsed = parse_log(&log, "friends").expect("parse failed");
+        assert_eq!(
+            event_kafka_key_with(&parsed, KafkaKeyStrategy::User),
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+        );
+        assert_eq!(
+            event_kafka_key_with(&parsed, KafkaKeyStrategy::Contract),
+            event_kafka_key(&parsed)
+        );
+
+        // Admin events have no actor and keep contract keying
+        let sig_treasury = keccak256_signature("TreasuryUpdated(address,address,uint256)");
+        log.topics = vec![sig_treasury, follower_topic, target_topic];
+        let parsed_admin = parse_log(&log, "friends").expect("parse failed");
+        assert_eq!(
+            event_kafka_key_with(&parsed_admin, KafkaKeyStrategy::User),
+            event_kafka_key(&parsed_admin)
+        );
+    }
+
+    #[test]
+    fn test_timestamp_far_from_block_time_is_flagged() {
+        let sig = keccak256_signature("UserFollowed(address,address,uint256)");
+        let follower_topic = h256_from_hex("0x000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        let target_topic = h256_from_hex("0x000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+        let mut data_vec = vec![0u8; 32];
+        ethers::types::U256::from(1_700_000_000u64).to_big_endian(&mut data_vec);
+
+        let mut log = ethers::types::Log::default();
+        log.topics = vec![sig, follower_topic, target_topic];
+        log.data = ethers::types::Bytes::from(data_vec);
+        let parsed = parse_log(&log, "friends").expect("parse failed");
+
+        assert_eq!(
+            check_timestamp_consistency(&parsed, 1_700_000_030, 60),
+            TimestampCheck::Consistent
+        );
+
+        let before = timestamp_divergence_count();
+        assert_eq!(
+            check_timestamp_consistency(&parsed, 1_700_086_400, 60),
+            TimestampCheck::Divergent {
+                event_timestamp: 1_700_000_000,
+                block_timestamp: 1_700_086_400,
+                diff_secs: 86_400,
+            }
+        );
+        assert!(timestamp_divergence_count() > before);
+
+        let mut no_ts = parsed.clone();
+        no_ts.data = None;
+        assert_eq!(check_timestamp_consistency(&no_ts, 1_700_086_400, 60), TimestampCheck::Missing);
+    }
+
+    #[test]
+    fn test_extra_trailing_indexed_param_is_preserved() {
+        let sig = keccak256_signature("UserFollowed(address,address,uint256)");
+        let follower_topic = h256_from_hex("0x000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        let target_topic = h256_from_hex("0x000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+        let extra_topic = h256_from_hex("0x00000000000000000000000000000000000000000000000000000000000000ff");
+        let ts = ethers::abi::Token::Uint(ethers::types::U256::from(1_700_000_000u64));
+
+        let mut log = ethers::types::Log::default();
+        log.topics = vec![sig, follower_topic, target_topic, extra_topic];
+        log.data = ethers::types::Bytes::from(ethers::abi::encode(&[ts]));
+
+        let parsed = parse_log(&log, "friends").expect("parse failed");
+        assert_eq!(parsed.event_type, "UserFollowed");
+        assert_eq!(parsed.indexed_params.len(), 3);
+        assert_eq!(parsed.indexed_params[2], format!("{:?}", extra_topic));
+        if let Some(ParsedEventData::Followed { follower, followed, timestamp, .. }) = parsed.data {
+            assert_eq!(follower, "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+            assert_eq!(followed, "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb");
+            assert_eq!(timestamp, "1700000000");
+        } else { panic!("Expected Followed data"); }
+    }
+
+    #[test]
+    fn test_every_event_type_round_trips_through_from_str() {
+        use EventType::*;
+        let all = [
+            SnapMinted, SnapLiked, SnapCommented, SnapBoughtAndMinted, SnapDeleted,
+            ArtMinted, ArtLiked, ArtCommented, ArtBoughtAndMinted, ArtDeleted,
+            MusicMinted, MusicLiked, MusicCommented, MusicBoughtAndMinted, MusicDeleted,
+            FlixMinted, FlixLiked, FlixCommented, FlixBoughtAndMinted, FlixDeleted,
+            Followed, Unfollowed, UsernameRegistered, UsernameTransferred, ProfileUpdated,
+            ProfileUpdatedExtended, NotificationEvent, EarningsWithdrawn, UserVerified,
+            UserUnverified, UserBlocked, UserUnblocked, ContentMinted, ContentCopyMinted,
+            ContentLiked, ContentUnliked, ContentCommented, ContentBlocked, ContentBookmarked,
+            ContentShared, ContentRequirementsUpdated, ContentBurned, BurnedContentRevenue,
+            UserFollowed, UserUnfollowed, TreasuryUpdated, DailyLimitsUpdated, TokensRecovered,
+            BadgeAwarded, BadgeRemoved, TipSent, PricesUpdated, Transfer, PurchaseProcessed,
+            RoyaltyDistributed, CollabProposed, Unknown,
+        ];
+
+        for event_type in all {
+            let serialized = serde_json::to_value(event_type).unwrap();
+            let name = serialized.as_str().unwrap();
+            assert_eq!(name.parse::<EventType>().unwrap(), event_type);
+            assert_eq!(EventType::try_from(name).unwrap(), event_type);
+            assert_eq!(event_type.to_string(), name);
+        }
+
+        assert!("NotAnEvent".parse::<EventType>().is_err());
+    }
+}
@@ -8,16 +8,28 @@
 //! - `friend` - TheraFriends contract (social features)
 //! - `thera_friends` - TheraFriends unified contract (all content types)
 //! - `thera_social` - TheraFriends unified contract (social features)
+//!
+//! With `INDEXER_OUTBOX_ENABLED`, events are written to the `event_outbox` table
+//! in the same transaction that advances `last_block`, and `outbox` drains them
+//! to Kafka separately.
 
 pub mod friend;
+pub mod outbox;
 pub mod thera_friends;
 pub mod thera_social;
 
+use crate::config::{KafkaTopics, PendingLogPolicy};
 use crate::error::{Error, Result};
+use crate::events::{check_timestamp_consistency, ParsedEvent, TimestampCheck, TopicKind};
+use crate::kafka::EventPublisher;
 use ethers::prelude::*;
-use sqlx::PgPool;
-use std::time::Duration;
-use tracing::{instrument, warn};
+use once_cell::sync::Lazy;
+use sqlx::{PgConnection, PgPool};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, instrument, warn};
 
 /// Common configuration for indexers
 #[allow(dead_code)]
@@ -31,6 +43,178 @@ pub struct IndexerConfig {
     pub retry_delay: Duration,
 }
 
+/// Minimum time between two lag warnings from the same indexer
+const LAG_WARN_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Latest `indexer_blocks_behind` gauge value per indexer
+static BLOCKS_BEHIND: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(Default::default);
+
+/// Snapshot of the `indexer_blocks_behind` gauge, keyed by indexer name
+pub fn indexer_blocks_behind() -> HashMap<String, u64> {
+    BLOCKS_BEHIND.lock().map(|g| g.clone()).unwrap_or_default()
+}
+
+/// Indexer gauges since startup, as exported on `/metrics`
+#[derive(Debug, Clone, Default)]
+pub struct IndexerCounters {
+    /// Blocks behind the chain head per indexer, by name
+    pub blocks_behind: Vec<(String, u64)>,
+    /// Events whose embedded timestamp diverged from their block time
+    pub timestamp_divergences: u64,
+}
+
+impl IndexerCounters {
+    pub fn current() -> Self {
+        let mut blocks_behind: Vec<_> = indexer_blocks_behind().into_iter().collect();
+        blocks_behind.sort();
+        Self {
+            blocks_behind,
+            timestamp_divergences: crate::events::timestamp_divergence_count(),
+        }
+    }
+}
+
+/// How far `last_block` trails the chain head
+pub fn blocks_behind(last_block: u64, head: u64) -> u64 {
+    head.saturating_sub(last_block)
+}
+
+/// Tracks how far an indexer trails the chain head and warns, at most once
+/// per `LAG_WARN_INTERVAL`, while the gap exceeds the threshold
+#[derive(Debug)]
+pub struct LagMonitor {
+    name: &'static str,
+    warn_blocks: u64,
+    last_warned: Option<Instant>,
+}
+
+impl LagMonitor {
+    pub fn new(name: &'static str, warn_blocks: u64) -> Self {
+        Self {
+            name,
+            warn_blocks,
+            last_warned: None,
+        }
+    }
+
+    /// Record the gap for this poll. Returns true when a warning was emitted.
+    pub fn observe(&mut self, last_block: u64, head: u64, now: Instant) -> bool {
+        let behind = blocks_behind(last_block, head);
+        if let Ok(mut gauge) = BLOCKS_BEHIND.lock() {
+            gauge.insert(self.name.to_string(), behind);
+        }
+
+        if self.warn_blocks == 0 || behind <= self.warn_blocks {
+            return false;
+        }
+        if self
+            .last_warned
+            .is_some_and(|at| now.duration_since(at) < LAG_WARN_INTERVAL)
+        {
+            return false;
+        }
+
+        self.last_warned = Some(now);
+        warn!(
+            "🐢 {} indexer is {} blocks behind head (last_block={}, head={}, threshold={})",
+            self.name, behind, last_block, head, self.warn_blocks
+        );
+        true
+    }
+}
+
+/// Progress report published to the indexer progress topic
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct IndexerProgress {
+    pub indexer: String,
+    /// Last block fully indexed
+    pub block: u64,
+    /// Chain head at the time of the report
+    pub head: u64,
+    pub blocks_behind: u64,
+    /// Logs indexed per second since the previous report
+    pub events_per_sec: f64,
+    pub timestamp: i64,
+}
+
+/// Publishes an indexer's progress once per `interval`. Disabled (no topic)
+/// it only counts.
+#[derive(Debug)]
+pub struct ProgressEmitter {
+    name: &'static str,
+    topic: Option<String>,
+    interval: Duration,
+    window_start: Option<Instant>,
+    events: u64,
+}
+
+impl ProgressEmitter {
+    pub fn new(name: &'static str, topic: Option<String>, interval: Duration) -> Self {
+        Self {
+            name,
+            topic,
+            interval,
+            window_start: None,
+            events: 0,
+        }
+    }
+
+    /// From the `INDEXER_PROGRESS_*` settings
+    pub fn from_config(name: &'static str, config: &crate::config::Config) -> Self {
+        let topic = config
+            .blockchain
+            .progress_enabled
+            .then(|| config.kafka.topics.indexer_progress.clone());
+        Self::new(name, topic, config.blockchain.progress_interval)
+    }
+
+    /// Count logs indexed since the last report
+    pub fn record_events(&mut self, count: u64) {
+        self.events += count;
+    }
+
+    /// Publish a report if `interval` has passed since the last one (or since
+    /// the first call). Returns true when one was sent.
+    pub async fn maybe_emit<P: EventPublisher>(
+        &mut self,
+        publisher: &P,
+        block: u64,
+        head: u64,
+        now: Instant,
+    ) -> bool {
+        let Some(topic) = &self.topic else {
+            return false;
+        };
+        let Some(start) = self.window_start else {
+            self.window_start = Some(now);
+            return false;
+        };
+        let elapsed = now.duration_since(start);
+        if elapsed < self.interval {
+            return false;
+        }
+
+        let progress = IndexerProgress {
+            indexer: self.name.to_string(),
+            block,
+            head,
+            blocks_behind: blocks_behind(block, head),
+            events_per_sec: self.events as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+        self.window_start = Some(now);
+        self.events = 0;
+
+        match publisher.send_event(topic, self.name, &progress).await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("Failed to publish {} indexer progress: {:?}", self.name, e);
+                false
+            }
+        }
+    }
+}
+
 /// Indexer state stored in database
 #[allow(dead_code)]
 #[derive(Debug, Clone)]
@@ -40,6 +224,25 @@ pub struct IndexerState {
     pub last_block: u64,
 }
 
+/// First block to index on startup: the one after the last indexed block,
+/// which was already emitted, or `start_block` when nothing has been indexed
+pub fn resume_block(last_indexed: Option<u64>, start_block: u64) -> u64 {
+    match last_indexed {
+        Some(last) => last + 1,
+        None => start_block,
+    }
+}
+
+/// Inclusive range for the next batch: up to `batch_size` blocks from
+/// `next_block`, capped at `latest_block`. `None` once caught up.
+pub fn next_batch_range(next_block: u64, latest_block: u64, batch_size: u64) -> Option<(u64, u64)> {
+    if latest_block < next_block {
+        return None;
+    }
+    let to_block = next_block.saturating_add(batch_size.max(1) - 1).min(latest_block);
+    Some((next_block, to_block))
+}
+
 pub async fn get_last_indexed_block(
     pool: &PgPool,
     contract_address: &str,
@@ -92,18 +295,191 @@ pub async fn save_last_indexed_block(
     Ok(())
 }
 
+/// Hash stored for `last_block`, if one has been recorded
+pub async fn get_last_block_hash(pool: &PgPool, contract_address: &str) -> Result<Option<String>> {
+    let hash = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT last_block_hash FROM indexer_state WHERE LOWER(contract_address) = $1",
+    )
+    .bind(contract_address.to_lowercase())
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(hash.flatten())
+}
+
+/// Record the canonical hash of `block` alongside `last_block` so the next
+/// poll can detect a reorg. Lookup failures are logged and otherwise ignored.
+pub async fn save_last_block_hash<M: Middleware>(
+    pool: &PgPool,
+    provider: &M,
+    breaker: &CircuitBreaker,
+    contract_address: &str,
+    block: u64,
+) {
+    let hash = match get_block(provider, breaker, block).await {
+        Ok(Some(b)) => b.hash,
+        Ok(None) => None,
+        Err(e) => {
+            warn!("Failed to fetch block {} hash: {}", block, e);
+            return;
+        }
+    };
+    let Some(hash) = hash else {
+        return;
+    };
+
+    if let Err(e) = sqlx::query(
+        "UPDATE indexer_state SET last_block_hash = $2 WHERE LOWER(contract_address) = $1 AND last_block = $3",
+    )
+    .bind(contract_address.to_lowercase())
+    .bind(format!("{:?}", hash))
+    .bind(block as i64)
+    .execute(pool)
+    .await
+    {
+        warn!("Failed to save hash of block {}: {:?}", block, e);
+    }
+}
+
+/// Fill in `last_block_hash` for rows saved before hashes were recorded, so
+/// reorg detection covers them from the first poll instead of the first
+/// save. Rows whose block can't be fetched are left for the indexer to fill.
+/// Returns how many rows were filled.
+pub async fn backfill_last_block_hashes<M: Middleware>(
+    conn: &mut PgConnection,
+    provider: &M,
+    breaker: &CircuitBreaker,
+) -> Result<u64> {
+    let missing = sqlx::query_as::<_, (String, i64)>(
+        "SELECT contract_address, last_block FROM indexer_state WHERE last_block_hash IS NULL",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut filled = 0;
+    for (contract_address, last_block) in missing {
+        let hash = match get_block(provider, breaker, last_block as u64).await {
+            Ok(Some(block)) => block.hash,
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Failed to fetch block {} hash for {}: {}", last_block, contract_address, e);
+                None
+            }
+        };
+        let Some(hash) = hash else {
+            continue;
+        };
+
+        // The indexer may have moved on (and saved its own hash) meanwhile
+        let updated = sqlx::query(
+            r#"
+            UPDATE indexer_state SET last_block_hash = $2
+            WHERE contract_address = $1 AND last_block = $3 AND last_block_hash IS NULL
+            "#,
+        )
+        .bind(&contract_address)
+        .bind(format!("{:?}", hash))
+        .bind(last_block)
+        .execute(&mut *conn)
+        .await?;
+        filled += updated.rows_affected();
+    }
+
+    Ok(filled)
+}
+
+/// Block to resume from if the canonical hash at `last_block` no longer
+/// matches `stored_hash`, i.e. the chain reorganized under us
+pub async fn check_block_hash<M: Middleware>(
+    provider: &M,
+    breaker: &CircuitBreaker,
+    last_block: u64,
+    stored_hash: &str,
+    rewind_depth: u64,
+) -> Result<Option<u64>> {
+    let canonical = get_block(provider, breaker, last_block).await?.and_then(|b| b.hash);
+
+    // A node that doesn't have the block yet can't tell us anything
+    let Some(canonical) = canonical else {
+        return Ok(None);
+    };
+
+    let canonical = format!("{:?}", canonical);
+    if canonical.eq_ignore_ascii_case(stored_hash) {
+        return Ok(None);
+    }
+
+    let rewound = last_block.saturating_sub(rewind_depth);
+    warn!(
+        "🔀 Reorg detected at block {} (stored {}, canonical {}), rewinding to {}",
+        last_block, stored_hash, canonical, rewound
+    );
+    Ok(Some(rewound))
+}
+
+/// Compare the stored hash of `last_block` with the chain; on mismatch,
+/// returns the block to rewind to (`rewind_depth` blocks back)
+pub async fn detect_reorg<M: Middleware>(
+    pool: &PgPool,
+    provider: &M,
+    breaker: &CircuitBreaker,
+    contract_address: &str,
+    last_block: u64,
+    rewind_depth: u64,
+) -> Result<Option<u64>> {
+    match get_last_block_hash(pool, contract_address).await? {
+        Some(stored) => check_block_hash(provider, breaker, last_block, &stored, rewind_depth).await,
+        None => Ok(None),
+    }
+}
+
+/// Fetch `block` through `breaker`, so block lookups count towards (and are
+/// cut off by) the same breaker as the indexer's other RPC calls
+async fn get_block<M: Middleware>(
+    provider: &M,
+    breaker: &CircuitBreaker,
+    block: u64,
+) -> Result<Option<Block<H256>>> {
+    breaker
+        .call(|| async {
+            provider
+                .get_block(block)
+                .await
+                .map_err(|e| Error::blockchain(format!("Failed to get block {}: {}", block, e)))
+        })
+        .await
+}
+
+pub use crate::config::RetryPolicy;
+
+/// Polling the chain head: retried quickly so a stuck RPC can't hold up
+/// shutdown for long
+pub const BLOCK_NUMBER_RETRY: RetryPolicy = RetryPolicy {
+    max_retries: 3,
+    initial_delay: Duration::from_millis(500),
+    max_delay: Duration::from_secs(2),
+    multiplier: 2.0,
+};
+
+/// Log range queries, which providers throttle harder, get more room
+pub const GET_LOGS_RETRY: RetryPolicy = RetryPolicy {
+    max_retries: 3,
+    initial_delay: Duration::from_millis(500),
+    max_delay: Duration::from_secs(10),
+    multiplier: 2.0,
+};
+
 /// Retry helper for RPC calls with exponential backoff
 pub async fn with_retry<T, F, Fut>(
     operation: F,
-    max_retries: u32,
-    initial_delay: Duration,
+    policy: RetryPolicy,
     operation_name: &str,
 ) -> Result<T>
 where
     F: Fn() -> Fut,
     Fut: std::future::Future<Output = Result<T>>,
 {
-    let mut delay = initial_delay;
+    let max_retries = policy.max_retries;
     let mut last_error = None;
 
     for attempt in 0..max_retries {
@@ -125,8 +501,7 @@ where
                 last_error = Some(e);
 
                 if attempt + 1 < max_retries {
-                    tokio::time::sleep(delay).await;
-                    delay = std::cmp::min(delay * 2, Duration::from_secs(30));
+                    tokio::time::sleep(policy.delay(attempt)).await;
                 }
             }
         }
@@ -135,6 +510,389 @@ where
     Err(last_error.unwrap_or_else(|| Error::blockchain("Max retries exceeded")))
 }
 
+/// Consecutive-failure circuit breaker for RPC calls, so an endpoint that
+/// stays down isn't retried every poll cycle. After `threshold` retryable
+/// failures in a row it opens, and calls fail fast with
+/// `Error::ServiceUnavailable` for `cooldown`. The first call after that goes
+/// out as a probe: success closes the breaker, failure opens it again. A
+/// `threshold` of 0 disables it.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    service: &'static str,
+    threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(service: &'static str, threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            service,
+            threshold,
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Whether calls are currently being short-circuited
+    pub fn is_open(&self, now: Instant) -> bool {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.open_until.is_some_and(|until| now < until)
+    }
+
+    /// Run `operation` unless the breaker is open, and record its outcome
+    pub async fn call<T, F, Fut>(&self, operation: F) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        if self.is_open(Instant::now()) {
+            return Err(Error::ServiceUnavailable { service: self.service });
+        }
+
+        let result = operation().await;
+        match &result {
+            Ok(_) => self.record_success(),
+            Err(e) if e.is_retryable() => self.record_failure(Instant::now()),
+            Err(_) => {}
+        }
+        result
+    }
+
+    fn record_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.open_until.take().is_some() {
+            info!("🔌 {} circuit closed after a successful probe", self.service);
+        }
+        state.consecutive_failures = 0;
+    }
+
+    fn record_failure(&self, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        if self.threshold > 0 && state.consecutive_failures >= self.threshold {
+            warn!(
+                "🔌 {} circuit open for {:?} after {} consecutive failures",
+                self.service, self.cooldown, state.consecutive_failures
+            );
+            state.open_until = Some(now + self.cooldown);
+        }
+    }
+}
+
+/// Publish a parsed event with the topic and key the event processor expects.
+///
+/// Unknown events go to the blockchain events topic keyed by contract;
+/// everything else goes to the user actions topic keyed per
+/// `topics.user_actions_key`.
+pub async fn publish_parsed<P: EventPublisher>(
+    publisher: &P,
+    parsed: &ParsedEvent,
+    topics: &KafkaTopics,
+) -> Result<()> {
+    let (topic, kafka_key) = event_route(parsed, topics);
+    publisher.send_event(topic, &kafka_key, parsed).await
+}
+
+/// Batch publishing of parsed events, routed like [`publish_parsed`]
+pub trait ParsedEventPublisher {
+    /// Publish `events` with one batch per topic, each event routed and keyed
+    /// by [`event_route`]. Every batch is attempted; failures are reported
+    /// together per topic.
+    fn send_events(
+        &self,
+        events: &[ParsedEvent],
+        topics: &KafkaTopics,
+    ) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl<P: EventPublisher> ParsedEventPublisher for P {
+    async fn send_events(&self, events: &[ParsedEvent], topics: &KafkaTopics) -> Result<()> {
+        if !self.is_enabled() || events.is_empty() {
+            return Ok(());
+        }
+
+        // BTreeMap keeps the per-topic send (and error) order stable
+        let mut batches: BTreeMap<&str, Vec<(String, &ParsedEvent)>> = BTreeMap::new();
+        for event in events {
+            let (topic, key) = event_route(event, topics);
+            batches.entry(topic).or_default().push((key, event));
+        }
+
+        let mut failures = Vec::new();
+        for (topic, batch) in &batches {
+            if let Err(e) = self.send_batch(topic, batch).await {
+                failures.push(format!("{} ({} events): {}", topic, batch.len(), e));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::kafka(format!(
+                "{} of {} topic batches failed: {}",
+                failures.len(),
+                batches.len(),
+                failures.join("; ")
+            )))
+        }
+    }
+}
+
+/// Re-emit `contract`'s events from `from_block` through `to_block` in
+/// `batch_size` chunks, for re-indexing a historical range (e.g. after a
+/// redeploy). The stored `last_block` is left alone, so the live indexer
+/// carries on where it was. Events already claimed in `processed_events`
+/// are skipped, so repeating or overlapping a backfill emits nothing twice.
+///
+/// Up to `parallelism` chunks are fetched at once, but they are published
+/// in block order and checkpointed in `backfill_checkpoints` as each one
+/// completes. A backfill of the same range that was interrupted resumes
+/// after its last completed chunk; the checkpoint is cleared once the range
+/// is done.
+///
+/// Returns the number of events emitted.
+#[allow(clippy::too_many_arguments)]
+pub async fn backfill<M, P>(
+    pool: &PgPool,
+    provider: Arc<M>,
+    publisher: &P,
+    contract: Address,
+    from_block: u64,
+    to_block: u64,
+    batch_size: u64,
+    parallelism: usize,
+    topics: &KafkaTopics,
+    partial_decode: bool,
+) -> Result<u64>
+where
+    M: Middleware + 'static,
+    P: EventPublisher,
+{
+    let contract_key = format!("{:?}", contract);
+    let mut emitted = 0;
+    let mut next_block = match get_backfill_checkpoint(pool, &contract_key, from_block, to_block).await? {
+        Some(completed) if completed >= to_block => to_block.saturating_add(1),
+        Some(completed) => {
+            info!("⏪ Resuming backfill after block {}", completed);
+            completed + 1
+        }
+        None => from_block,
+    };
+
+    while next_block <= to_block {
+        // The next wave of chunks, fetched concurrently
+        let mut chunks = Vec::new();
+        while chunks.len() < parallelism.max(1) {
+            let Some((batch_from, batch_to)) = next_batch_range(next_block, to_block, batch_size) else {
+                break;
+            };
+            chunks.push((batch_from, batch_to));
+            if batch_to == u64::MAX {
+                break;
+            }
+            next_block = batch_to + 1;
+        }
+        if chunks.is_empty() {
+            break;
+        }
+
+        let mut fetches = tokio::task::JoinSet::new();
+        for (index, &(batch_from, batch_to)) in chunks.iter().enumerate() {
+            let provider = provider.clone();
+            let filter = Filter::new()
+                .address(contract)
+                .from_block(batch_from)
+                .to_block(batch_to);
+            fetches.spawn(async move {
+                let logs = with_retry(
+                    || async {
+                        provider
+                            .get_logs(&filter)
+                            .await
+                            .map_err(|e| Error::blockchain(format!("Failed to get logs: {}", e)))
+                    },
+                    GET_LOGS_RETRY,
+                    "get_logs",
+                )
+                .await;
+                (index, logs)
+            });
+        }
+
+        let mut fetched: Vec<Option<Result<Vec<Log>>>> = chunks.iter().map(|_| None).collect();
+        while let Some(joined) = fetches.join_next().await {
+            let (index, logs) = joined.map_err(Error::internal)?;
+            fetched[index] = Some(logs);
+        }
+
+        // Publish in block order, stopping at the first chunk that failed so
+        // the checkpoint never skips past a gap
+        for (&(batch_from, batch_to), logs) in chunks.iter().zip(fetched) {
+            let logs = logs.expect("every spawned chunk reports back")?;
+
+            let mut events = Vec::with_capacity(logs.len());
+            for log in &logs {
+                match crate::events::parse_mined_log(log, "friends", PendingLogPolicy::Reject, partial_decode) {
+                    Ok(parsed) => events.push(parsed),
+                    Err(e) => warn!("Failed to parse log during backfill: {:?}", e),
+                }
+            }
+
+            let processed = processed_event_keys(pool, &events).await?;
+            events.retain(|parsed| {
+                !processed.contains(&(parsed.transaction_hash.clone(), parsed.log_index as i64))
+            });
+            publisher.send_events(&events, topics).await?;
+            emitted += events.len() as u64;
+
+            save_backfill_checkpoint(pool, &contract_key, from_block, to_block, batch_to).await?;
+            info!("⏪ Backfilled blocks {}-{} ({} events so far)", batch_from, batch_to, emitted);
+        }
+
+        if chunks.last().is_some_and(|&(_, batch_to)| batch_to == u64::MAX) {
+            break;
+        }
+    }
+
+    clear_backfill_checkpoint(pool, &contract_key, from_block, to_block).await?;
+    Ok(emitted)
+}
+
+/// Last block of the `from_block..=to_block` backfill of `contract_address`
+/// known to be fully emitted
+pub async fn get_backfill_checkpoint(
+    pool: &PgPool,
+    contract_address: &str,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Option<u64>> {
+    let completed = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT completed_through FROM backfill_checkpoints
+        WHERE contract_address = $1 AND from_block = $2 AND to_block = $3
+        "#,
+    )
+    .bind(contract_address)
+    .bind(from_block as i64)
+    .bind(to_block as i64)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(completed.map(|b| b as u64))
+}
+
+async fn save_backfill_checkpoint(
+    pool: &PgPool,
+    contract_address: &str,
+    from_block: u64,
+    to_block: u64,
+    completed_through: u64,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO backfill_checkpoints (contract_address, from_block, to_block, completed_through)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (contract_address, from_block, to_block)
+        DO UPDATE SET completed_through = EXCLUDED.completed_through, updated_at = NOW()
+        "#,
+    )
+    .bind(contract_address)
+    .bind(from_block as i64)
+    .bind(to_block as i64)
+    .bind(completed_through.min(i64::MAX as u64) as i64)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn clear_backfill_checkpoint(pool: &PgPool, contract_address: &str, from_block: u64, to_block: u64) -> Result<()> {
+    sqlx::query("DELETE FROM backfill_checkpoints WHERE contract_address = $1 AND from_block = $2 AND to_block = $3")
+        .bind(contract_address)
+        .bind(from_block as i64)
+        .bind(to_block as i64)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// `(transaction_hash, log_index)` of the `events` already processed
+async fn processed_event_keys(pool: &PgPool, events: &[ParsedEvent]) -> Result<HashSet<(String, i64)>> {
+    let hashes: Vec<&str> = events
+        .iter()
+        .map(|e| e.transaction_hash.as_str())
+        .filter(|h| !h.is_empty())
+        .collect();
+    if hashes.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        "SELECT transaction_hash, log_index FROM processed_events WHERE transaction_hash = ANY($1)",
+    )
+    .bind(&hashes)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Topic and partition key for a parsed event
+pub fn event_route<'a>(parsed: &ParsedEvent, topics: &'a KafkaTopics) -> (&'a str, String) {
+    if parsed.event_type == "Unknown" {
+        (
+            TopicKind::BlockchainEvents.name(topics),
+            crate::events::event_kafka_key(parsed),
+        )
+    } else {
+        (
+            TopicKind::UserActions.name(topics),
+            crate::events::event_kafka_key_with(parsed, topics.user_actions_key),
+        )
+    }
+}
+
+/// Compare an event's embedded timestamp with its block's timestamp and warn
+/// on divergence. Lookup failures are logged and otherwise ignored.
+pub async fn verify_event_timestamp<M: Middleware>(
+    provider: &M,
+    breaker: &CircuitBreaker,
+    parsed: &ParsedEvent,
+    tolerance_secs: u64,
+) {
+    let block = match get_block(provider, breaker, parsed.block_number).await {
+        Ok(Some(block)) => block,
+        Ok(None) => return,
+        Err(e) => {
+            warn!("Failed to fetch block {} for timestamp check: {}", parsed.block_number, e);
+            return;
+        }
+    };
+
+    if let TimestampCheck::Divergent {
+        event_timestamp,
+        block_timestamp,
+        diff_secs,
+    } = check_timestamp_consistency(parsed, block.timestamp.as_u64(), tolerance_secs)
+    {
+        warn!(
+            "⏱️ {} in tx {} has timestamp {} but block {} is at {} ({}s apart)",
+            parsed.event_type,
+            parsed.transaction_hash,
+            event_timestamp,
+            parsed.block_number,
+            block_timestamp,
+            diff_secs
+        );
+    }
+}
+
 /// Parse Ethereum address from string
 pub fn parse_address(addr: &str) -> Result<Address> {
     addr.parse().map_err(|_| Error::InvalidAddress {
@@ -219,6 +977,7 @@ pub fn decode_string(data: &[u8], offset: usize) -> Result<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::address::random_address;
 
     #[test]
     fn test_format_address() {
@@ -236,4 +995,516 @@ mod tests {
         let value = decode_uint256(&data, 0).unwrap();
         assert_eq!(value, U256::from(42));
     }
+
+    fn test_topics() -> KafkaTopics {
+        KafkaTopics {
+            blockchain_events: "blockchain.events".to_string(),
+            user_actions: "user.actions".to_string(),
+            recommendations: "recommendations".to_string(),
+            user_actions_key: crate::config::KafkaKeyStrategy::User,
+            dlq: None,
+            dlq_retention: Duration::from_secs(7 * 24 * 3600),
+            indexer_progress: "indexer.progress".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_route_to_configured_topic_names() {
+        use crate::kafka::InMemoryPublisher;
+
+        let topics = KafkaTopics {
+            blockchain_events: "prod.chain".to_string(),
+            user_actions: "prod.actions".to_string(),
+            ..test_topics()
+        };
+        let event = |event_type: &str| ParsedEvent {
+            event_type: event_type.to_string(),
+            contract_address: "0xsocial".to_string(),
+            contract_type: "friends".to_string(),
+            block_number: 1,
+            transaction_hash: "0xtx".to_string(),
+            log_index: 0,
+            timestamp: 0,
+            indexed_params: Vec::new(),
+            data: None,
+            raw_data: None,
+            pending: false,
+        };
+
+        let publisher = InMemoryPublisher::new();
+        publish_parsed(&publisher, &event("UserFollowed"), &topics).await.unwrap();
+        publish_parsed(&publisher, &event("Unknown"), &topics).await.unwrap();
+        publish_parsed(&publisher, &event("Minted"), &topics).await.unwrap();
+        let entry = crate::indexer::outbox::OutboxEntry::from_parsed(&event("UserFollowed"), &topics).unwrap();
+
+        let routed: Vec<String> = publisher.messages().into_iter().map(|m| m.topic).collect();
+        assert_eq!(routed, vec!["prod.actions", "prod.chain", "prod.actions"]);
+        assert_eq!(entry.topic, "prod.actions");
+        assert!(!routed.iter().any(|t| t == "user.actions" || t == "blockchain.events"));
+    }
+
+    #[tokio::test]
+    async fn test_send_events_batches_per_topic_like_publish_parsed() {
+        use crate::kafka::{FlakyPublisher, InMemoryPublisher};
+        use std::sync::atomic::Ordering;
+
+        let event = |event_type: &str, contract_type: &str, contract: &str| ParsedEvent {
+            event_type: event_type.to_string(),
+            contract_address: contract.to_string(),
+            contract_type: contract_type.to_string(),
+            block_number: 1,
+            transaction_hash: "0xtx".to_string(),
+            log_index: 0,
+            timestamp: 0,
+            indexed_params: Vec::new(),
+            data: None,
+            raw_data: None,
+            pending: false,
+        };
+        let events = vec![
+            event("UserFollowed", "friends", "0xsocial"),
+            event("Minted", "snap", "0xsnap"),
+            event("Unknown", "snap", "0xsnap"),
+            event("TipSent", "friends", "0xsocial"),
+        ];
+        let topics = test_topics();
+
+        let publisher = InMemoryPublisher::new();
+        publisher.send_events(&events, &topics).await.unwrap();
+        let batched: Vec<(String, String)> =
+            publisher.messages().into_iter().map(|m| (m.topic, m.key)).collect();
+
+        // Same topics and keys as publishing one at a time, grouped per topic
+        let single = InMemoryPublisher::new();
+        for parsed in &events {
+            publish_parsed(&single, parsed, &topics).await.unwrap();
+        }
+        let mut expected: Vec<(String, String)> =
+            single.messages().into_iter().map(|m| (m.topic, m.key)).collect();
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(batched, expected);
+        assert_eq!(
+            batched.iter().map(|(topic, _)| topic.as_str()).collect::<Vec<_>>(),
+            vec!["blockchain.events", "user.actions", "user.actions", "user.actions"]
+        );
+
+        // Failures are reported per topic
+        let flaky = FlakyPublisher::default();
+        flaky.down.store(true, Ordering::SeqCst);
+        let error = flaky.send_events(&events, &topics).await.unwrap_err().to_string();
+        assert!(error.contains("2 of 2 topic batches failed"));
+        assert!(error.contains("user.actions (3 events)"));
+    }
+
+    #[tokio::test]
+    async fn test_publish_parsed_records_key_and_payload() {
+        use crate::kafka::InMemoryPublisher;
+
+        let sig = H256::from(ethers::utils::keccak256("UserFollowed(address,address,uint256)"));
+        let follower: H256 = "0x000000000000000000000000aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+            .parse()
+            .unwrap();
+        let target: H256 = "0x000000000000000000000000bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"
+            .parse()
+            .unwrap();
+
+        let mut data = vec![0u8; 32];
+        U256::from(1_700_000_500u64).to_big_endian(&mut data);
+
+        let log = Log {
+            topics: vec![sig, follower, target],
+            data: Bytes::from(data),
+            block_number: Some(U64::from(100)),
+            ..Default::default()
+        };
+
+        let publisher = InMemoryPublisher::new();
+        let parsed = crate::events::parse_log(&log, "friends").unwrap();
+        publish_parsed(&publisher, &parsed, &test_topics())
+            .await
+            .unwrap();
+
+        let messages = publisher.messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].topic, "user.actions");
+        assert_eq!(messages[0].key, "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        assert_eq!(messages[0].payload["event_type"], "UserFollowed");
+        assert_eq!(messages[0].payload["block_number"], 100);
+        assert_eq!(
+            messages[0].payload["data"]["followed"],
+            "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"
+        );
+    }
+
+    #[test]
+    fn test_lag_warning_threshold_and_rate_limit() {
+        assert_eq!(blocks_behind(100, 1_600), 1_500);
+        assert_eq!(blocks_behind(1_600, 1_500), 0);
+
+        let start = Instant::now();
+        let mut lag = LagMonitor::new("lag_test", 1_000);
+
+        // At or under the threshold: no warning, gauge still updated
+        assert!(!lag.observe(600, 1_600, start));
+        assert_eq!(indexer_blocks_behind()["lag_test"], 1_000);
+
+        // Over the threshold: warns once, then stays quiet until the interval passes
+        assert!(lag.observe(100, 1_600, start));
+        assert!(!lag.observe(100, 1_700, start + Duration::from_secs(10)));
+        assert!(lag.observe(100, 1_700, start + LAG_WARN_INTERVAL));
+        assert_eq!(indexer_blocks_behind()["lag_test"], 1_600);
+
+        // Threshold 0 disables warnings
+        let mut disabled = LagMonitor::new("lag_test_disabled", 0);
+        assert!(!disabled.observe(0, 1_000_000, start));
+    }
+
+    #[tokio::test]
+    async fn test_progress_emitted_once_per_interval() {
+        let publisher = crate::kafka::InMemoryPublisher::new();
+        let start = Instant::now();
+        let interval = Duration::from_secs(10);
+        let mut progress = ProgressEmitter::new("progress_test", Some("indexer.progress".to_string()), interval);
+
+        // The first poll opens the window; nothing is due until it has elapsed
+        assert!(!progress.maybe_emit(&publisher, 90, 100, start).await);
+        progress.record_events(50);
+        assert!(!progress.maybe_emit(&publisher, 95, 100, start + Duration::from_secs(5)).await);
+        assert!(progress.maybe_emit(&publisher, 98, 110, start + interval).await);
+        assert!(!progress.maybe_emit(&publisher, 99, 110, start + interval + Duration::from_secs(1)).await);
+
+        let messages = publisher.messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].topic, "indexer.progress");
+        assert_eq!(messages[0].key, "progress_test");
+        let payload = &messages[0].payload;
+        assert_eq!(payload["indexer"], "progress_test");
+        assert_eq!(payload["block"], 98);
+        assert_eq!(payload["head"], 110);
+        assert_eq!(payload["blocks_behind"], 12);
+        assert_eq!(payload["events_per_sec"], 5.0);
+
+        // Without a topic nothing is ever published
+        let mut disabled = ProgressEmitter::new("progress_test", None, interval);
+        assert!(!disabled.maybe_emit(&publisher, 0, 1, start).await);
+        assert!(!disabled.maybe_emit(&publisher, 0, 1, start + interval).await);
+        assert_eq!(publisher.messages().len(), 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_backfill_emits_range_without_moving_last_block() {
+        let pool = crate::database::test_pool().await;
+
+        let contract = random_address();
+        save_last_indexed_block(&pool, &contract, "friends", 50).await.unwrap();
+
+        let sig = H256::from(ethers::utils::keccak256("UserFollowed(address,address,uint256)"));
+        let follower = H256::from_low_u64_be(0xaaaa);
+        let target = H256::from_low_u64_be(0xbbbb);
+        let log = |block: u64, tx: H256, log_index: u64| Log {
+            topics: vec![sig, follower, target],
+            data: Bytes::from(vec![0u8; 32]),
+            block_number: Some(U64::from(block)),
+            transaction_hash: Some(tx),
+            log_index: Some(U256::from(log_index)),
+            ..Default::default()
+        };
+        let [tx_100, tx_101, tx_102, tx_103, tx_104] = std::array::from_fn(|_| H256::random());
+
+        // Block 102's first log was emitted and processed before
+        sqlx::query("INSERT INTO processed_events (transaction_hash, log_index, event_type) VALUES ($1, 0, 'UserFollowed')")
+            .bind(format!("{:?}", tx_102))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Batches of 2: 100-101, 102-103, 104; the mock answers last-pushed first
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Vec<Log>, _>(vec![log(104, tx_104, 0)]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(102, tx_102, 0), log(102, tx_102, 1), log(103, tx_103, 0)]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(100, tx_100, 0), log(101, tx_101, 0)]).unwrap();
+
+        let publisher = crate::kafka::InMemoryPublisher::new();
+        let address: Address = contract.parse().unwrap();
+        let emitted = backfill(&pool, Arc::new(provider), &publisher, address, 100, 104, 2, 1, &test_topics(), true)
+            .await
+            .unwrap();
+        assert_eq!(emitted, 5);
+
+        let blocks: Vec<u64> = publisher
+            .messages()
+            .iter()
+            .map(|m| m.payload["block_number"].as_u64().unwrap())
+            .collect();
+        assert_eq!(blocks, vec![100, 101, 102, 103, 104]);
+        assert_eq!(
+            get_last_indexed_block(&pool, &contract, "friends").await.unwrap(),
+            Some(50)
+        );
+
+        sqlx::query("DELETE FROM processed_events WHERE transaction_hash = $1")
+            .bind(format!("{:?}", tx_102))
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM indexer_state WHERE contract_address = $1")
+            .bind(&contract)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_interrupted_backfill_resumes_after_last_completed_chunk() {
+        let pool = crate::database::test_pool().await;
+
+        let contract = random_address();
+        let address: Address = contract.parse().unwrap();
+        let sig = H256::from(ethers::utils::keccak256("UserFollowed(address,address,uint256)"));
+        let log = |block: u64| Log {
+            topics: vec![sig, H256::from_low_u64_be(0xaaaa), H256::from_low_u64_be(0xbbbb)],
+            data: Bytes::from(vec![0u8; 32]),
+            block_number: Some(U64::from(block)),
+            transaction_hash: Some(H256::random()),
+            log_index: Some(U256::zero()),
+            ..Default::default()
+        };
+        let blocks = |publisher: &crate::kafka::InMemoryPublisher| -> Vec<u64> {
+            publisher
+                .messages()
+                .iter()
+                .map(|m| m.payload["block_number"].as_u64().unwrap())
+                .collect()
+        };
+
+        // Chunks of 2 over 100-107; the RPC gives out after the first two
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Vec<Log>, _>(vec![log(102), log(103)]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(100), log(101)]).unwrap();
+        let publisher = crate::kafka::InMemoryPublisher::new();
+        let interrupted = backfill(&pool, Arc::new(provider), &publisher, address, 100, 107, 2, 1, &test_topics(), true).await;
+        assert!(interrupted.is_err());
+        assert_eq!(blocks(&publisher), vec![100, 101, 102, 103]);
+        assert_eq!(
+            get_backfill_checkpoint(&pool, &contract, 100, 107).await.unwrap(),
+            Some(103)
+        );
+
+        // The rerun starts at the third chunk, so two responses are enough
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Vec<Log>, _>(vec![log(106), log(107)]).unwrap();
+        mock.push::<Vec<Log>, _>(vec![log(104), log(105)]).unwrap();
+        let publisher = crate::kafka::InMemoryPublisher::new();
+        let emitted = backfill(&pool, Arc::new(provider), &publisher, address, 100, 107, 2, 1, &test_topics(), true)
+            .await
+            .unwrap();
+        assert_eq!(emitted, 4);
+        assert_eq!(blocks(&publisher), vec![104, 105, 106, 107]);
+        assert_eq!(get_backfill_checkpoint(&pool, &contract, 100, 107).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_divergent_block_hash_rewinds() {
+        let block_with_hash = |hash: H256| Block::<H256> {
+            hash: Some(hash),
+            number: Some(100u64.into()),
+            ..Default::default()
+        };
+        let stored = format!("{:?}", H256::repeat_byte(0xaa));
+        let breaker = CircuitBreaker::new("rpc", 1, Duration::from_secs(60));
+
+        // Canonical hash matches: no reorg
+        let (provider, mock) = Provider::mocked();
+        mock.push(block_with_hash(H256::repeat_byte(0xaa))).unwrap();
+        assert_eq!(check_block_hash(&provider, &breaker, 100, &stored, 12).await.unwrap(), None);
+
+        // Canonical hash diverges: rewind by the configured depth
+        let (provider, mock) = Provider::mocked();
+        mock.push(block_with_hash(H256::repeat_byte(0xbb))).unwrap();
+        assert_eq!(check_block_hash(&provider, &breaker, 100, &stored, 12).await.unwrap(), Some(88));
+
+        // Never rewinds past genesis
+        let (provider, mock) = Provider::mocked();
+        mock.push(block_with_hash(H256::repeat_byte(0xbb))).unwrap();
+        assert_eq!(check_block_hash(&provider, &breaker, 5, &stored, 12).await.unwrap(), Some(0));
+
+        // A failed lookup opens the breaker, which then fails fast without
+        // asking the node
+        let (provider, mock) = Provider::mocked();
+        assert!(matches!(
+            check_block_hash(&provider, &breaker, 100, &stored, 12).await,
+            Err(Error::Blockchain { .. })
+        ));
+        mock.push(block_with_hash(H256::repeat_byte(0xaa))).unwrap();
+        assert!(matches!(
+            check_block_hash(&provider, &breaker, 100, &stored, 12).await,
+            Err(Error::ServiceUnavailable { service: "rpc" })
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_backfill_fills_missing_block_hash() {
+        let pool = crate::database::test_pool().await;
+
+        // Rolled back, and scoped to this row so the mock answers for it alone
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query("DELETE FROM indexer_state WHERE last_block_hash IS NULL")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let contract = random_address();
+        sqlx::query(
+            "INSERT INTO indexer_state (id, contract_address, contract_type, last_block) VALUES (gen_random_uuid(), $1, 'friend', 100)",
+        )
+        .bind(&contract)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        let breaker = CircuitBreaker::new("rpc", 5, Duration::from_secs(60));
+        let (provider, mock) = Provider::mocked();
+        mock.push(Block::<H256> {
+            hash: Some(H256::repeat_byte(0xaa)),
+            number: Some(100u64.into()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(backfill_last_block_hashes(&mut tx, &provider, &breaker).await.unwrap(), 1);
+
+        let hash: Option<String> = sqlx::query_scalar("SELECT last_block_hash FROM indexer_state WHERE contract_address = $1")
+            .bind(&contract)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(hash, Some(format!("{:?}", H256::repeat_byte(0xaa))));
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[test]
+    fn test_retry_delays_respect_custom_ceiling() {
+        let policy = RetryPolicy {
+            max_retries: 6,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            multiplier: 3.0,
+        };
+        let delays: Vec<_> = (0..5).map(|attempt| policy.delay(attempt).as_millis()).collect();
+        assert_eq!(delays, vec![100, 300, 500, 500, 500]);
+
+        // The shipped policies stay under their ceilings however long a call fails
+        for policy in [BLOCK_NUMBER_RETRY, GET_LOGS_RETRY] {
+            assert_eq!(policy.delay(0), policy.initial_delay);
+            assert_eq!(policy.delay(1_000), policy.max_delay);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_retry_stops_after_max_retries() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let attempts = AtomicU32::new(0);
+        let policy = RetryPolicy {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(2),
+            multiplier: 2.0,
+        };
+        let result: Result<()> = with_retry(
+            || async {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                Err(Error::blockchain("rpc down"))
+            },
+            policy,
+            "test",
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_resume_starts_after_last_indexed_block() {
+        // Cold start: nothing indexed yet
+        assert_eq!(resume_block(None, 500), 500);
+        // Resume after progress: the last indexed block was already emitted
+        assert_eq!(resume_block(Some(1_000), 500), 1_001);
+        assert_eq!(resume_block(Some(0), 0), 1);
+    }
+
+    #[test]
+    fn test_batch_range_never_repeats_last_block() {
+        // Head at the last indexed block: nothing to do
+        let next = resume_block(Some(1_000), 0);
+        assert_eq!(next_batch_range(next, 1_000, 100), None);
+        // One new block: exactly that block
+        assert_eq!(next_batch_range(next, 1_001, 100), Some((1_001, 1_001)));
+        // Batches hold `batch_size` blocks and chain without overlap
+        let (from, to) = next_batch_range(next, 5_000, 100).unwrap();
+        assert_eq!((from, to), (1_001, 1_100));
+        assert_eq!(next_batch_range(to + 1, 5_000, 100), Some((1_101, 1_200)));
+        // A zero batch size still makes progress
+        assert_eq!(next_batch_range(next, 5_000, 0), Some((1_001, 1_001)));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_resume_block_from_saved_progress() {
+        let pool = crate::database::test_pool().await;
+
+        let contract = "0x000000000000000000000000000000000000e5e7";
+        sqlx::query("DELETE FROM indexer_state WHERE contract_address = $1")
+            .bind(contract)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let last = get_last_indexed_block(&pool, contract, "resume_test").await.unwrap();
+        assert_eq!(resume_block(last, 42), 42);
+
+        save_last_indexed_block(&pool, contract, "resume_test", 1_234).await.unwrap();
+        let last = get_last_indexed_block(&pool, contract, "resume_test").await.unwrap();
+        assert_eq!(resume_block(last, 42), 1_235);
+
+        sqlx::query("DELETE FROM indexer_state WHERE contract_address = $1")
+            .bind(contract)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_opens_then_closes_after_cooldown() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let breaker = CircuitBreaker::new("rpc", 3, Duration::from_millis(50));
+        let calls = AtomicUsize::new(0);
+        let failing = || async {
+            calls.fetch_add(1, Ordering::Relaxed);
+            Err::<u64, _>(Error::blockchain("rpc down"))
+        };
+
+        for _ in 0..3 {
+            assert!(matches!(breaker.call(failing).await, Err(Error::Blockchain { .. })));
+        }
+        assert!(breaker.is_open(Instant::now()));
+
+        // Open: short-circuits without touching the endpoint
+        let result = breaker.call(failing).await;
+        assert!(matches!(result, Err(Error::ServiceUnavailable { service: "rpc" })));
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+
+        // After the cooldown a successful probe closes it
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(breaker.call(|| async { Ok(42u64) }).await.unwrap(), 42);
+        assert!(!breaker.is_open(Instant::now()));
+
+        // Closed again, so a single failure doesn't reopen it
+        assert!(breaker.call(failing).await.is_err());
+        assert!(!breaker.is_open(Instant::now()));
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
+    }
 }
@@ -0,0 +1,480 @@
+//! Transactional Outbox
+//!
+//! Indexers write parsed events to `event_outbox` in the same transaction that
+//! advances `indexer_state.last_block`, so a crash can neither lose events nor
+//! re-index a block whose events were already recorded. A separate drain loop
+//! publishes pending rows to Kafka and marks them sent.
+//!
+//! Delivery is at-least-once: a crash after publishing but before marking a row
+//! sent republishes it on recovery.
+//!
+//! Rows are kept after they are sent, so [`replay_events`] can republish a
+//! block range that consumers missed (e.g. during a Kafka outage), until
+//! they are older than `INDEXER_OUTBOX_RETENTION_DAYS`.
+
+use crate::config::KafkaTopics;
+use crate::error::Result;
+use crate::events::ParsedEvent;
+use crate::indexer::event_route;
+use crate::kafka::EventPublisher;
+use crate::AppState;
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, instrument, warn};
+
+/// Maximum rows published per drain pass
+const DRAIN_BATCH_SIZE: i64 = 500;
+
+/// Blocks of stored events read per replay pass
+const REPLAY_BLOCK_WINDOW: u64 = 1000;
+
+/// How often sent rows past their retention are deleted
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// An event waiting to be written to the outbox
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub topic: String,
+    pub key: String,
+    pub payload: serde_json::Value,
+}
+
+impl OutboxEntry {
+    /// Build an entry routed the same way `publish_parsed` would send it
+    pub fn from_parsed(parsed: &ParsedEvent, topics: &KafkaTopics) -> Result<Self> {
+        let (topic, key) = event_route(parsed, topics);
+        Ok(Self {
+            topic: topic.to_string(),
+            key,
+            payload: serde_json::to_value(parsed)?,
+        })
+    }
+}
+
+/// A pending outbox row
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutboxRow {
+    pub id: i64,
+    pub topic: String,
+    pub kafka_key: String,
+    pub payload: serde_json::Value,
+}
+
+/// Insert `entries` and advance `last_block` atomically
+#[instrument(skip(pool, entries), fields(events = entries.len()))]
+pub async fn commit_batch(
+    pool: &PgPool,
+    contract_address: &str,
+    contract_type: &str,
+    block: u64,
+    entries: &[OutboxEntry],
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for entry in entries {
+        sqlx::query("INSERT INTO event_outbox (topic, kafka_key, payload) VALUES ($1, $2, $3)")
+            .bind(&entry.topic)
+            .bind(&entry.key)
+            .bind(&entry.payload)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO indexer_state (id, contract_address, contract_type, last_block, inserted_at, updated_at)
+        VALUES (gen_random_uuid(), $1, $2, $3, NOW(), NOW())
+        ON CONFLICT (contract_address) DO UPDATE
+        SET last_block = EXCLUDED.last_block,
+            contract_type = EXCLUDED.contract_type,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(contract_address.to_lowercase())
+    .bind(contract_type)
+    .bind(block as i64)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Publish rows in order, stopping at the first failure so later events for
+/// the same key are not delivered ahead of an earlier one. Returns the ids
+/// that were published.
+pub async fn publish_pending<P: EventPublisher>(publisher: &P, rows: &[OutboxRow]) -> Vec<i64> {
+    let mut sent = Vec::with_capacity(rows.len());
+    for row in rows {
+        if let Err(e) = publisher
+            .send_event(&row.topic, &row.kafka_key, &row.payload)
+            .await
+        {
+            warn!("Outbox publish failed for row {}: {:?}", row.id, e);
+            break;
+        }
+        sent.push(row.id);
+    }
+    sent
+}
+
+/// Publish up to `limit` pending rows and mark them sent.
+///
+/// Rows are locked with `SKIP LOCKED`, so concurrent drainers never publish
+/// the same row twice.
+pub async fn drain<P: EventPublisher>(pool: &PgPool, publisher: &P, limit: i64) -> Result<usize> {
+    let mut tx = pool.begin().await?;
+
+    let rows = sqlx::query_as::<_, OutboxRow>(
+        r#"
+        SELECT id, topic, kafka_key, payload
+        FROM event_outbox
+        WHERE sent_at IS NULL
+        ORDER BY id
+        LIMIT $1
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let sent = publish_pending(publisher, &rows).await;
+    if !sent.is_empty() {
+        sqlx::query("UPDATE event_outbox SET sent_at = NOW() WHERE id = ANY($1)")
+            .bind(&sent)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(sent.len())
+}
+
+/// Republish the stored events of blocks `from_block` through `to_block`,
+/// in block and log order, with the topic and partition key they were
+/// routed to when indexed. Events consumers already recorded in
+/// `processed_events` are left out, as are repeated copies of one event, so
+/// a replay doesn't make anyone process an event twice. Sent and unsent rows
+/// alike are replayed; neither is marked.
+///
+/// Returns the number of events published.
+pub async fn replay_events<P: EventPublisher>(
+    pool: &PgPool,
+    publisher: &P,
+    from_block: u64,
+    to_block: u64,
+) -> Result<u64> {
+    let mut replayed = 0;
+    let mut next_block = from_block;
+
+    while let Some((window_from, window_to)) = crate::indexer::next_batch_range(next_block, to_block, REPLAY_BLOCK_WINDOW) {
+        let rows = sqlx::query_as::<_, OutboxRow>(
+            r#"
+            SELECT id, topic, kafka_key, payload FROM (
+                SELECT DISTINCT ON (payload->>'transaction_hash', payload->>'log_index')
+                    id, topic, kafka_key, payload
+                FROM event_outbox
+                WHERE (payload->>'block_number')::bigint BETWEEN $1 AND $2
+                ORDER BY payload->>'transaction_hash', payload->>'log_index', id DESC
+            ) stored
+            WHERE NOT EXISTS (
+                SELECT 1 FROM processed_events p
+                WHERE p.transaction_hash = stored.payload->>'transaction_hash'
+                  AND p.log_index = (stored.payload->>'log_index')::bigint
+            )
+            ORDER BY (payload->>'block_number')::bigint, (payload->>'log_index')::bigint, id
+            "#,
+        )
+        .bind(window_from.min(i64::MAX as u64) as i64)
+        .bind(window_to.min(i64::MAX as u64) as i64)
+        .fetch_all(pool)
+        .await?;
+
+        // Consecutive rows for one topic go out as a batch, keeping the order
+        let mut start = 0;
+        while start < rows.len() {
+            let topic = &rows[start].topic;
+            let len = rows[start..].iter().take_while(|row| &row.topic == topic).count();
+            let batch: Vec<(String, &serde_json::Value)> = rows[start..start + len]
+                .iter()
+                .map(|row| (row.kafka_key.clone(), &row.payload))
+                .collect();
+            publisher.send_batch(topic, &batch).await?;
+            replayed += len as u64;
+            start += len;
+        }
+
+        info!("🔁 Replayed blocks {}-{} ({} events so far)", window_from, window_to, replayed);
+        if window_to == u64::MAX {
+            break;
+        }
+        next_block = window_to + 1;
+    }
+
+    Ok(replayed)
+}
+
+/// Delete rows sent more than `retention` ago. Returns how many were removed.
+pub async fn prune_sent(pool: &PgPool, retention: Duration) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM event_outbox WHERE sent_at < NOW() - make_interval(secs => $1)")
+        .bind(retention.as_secs_f64())
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Drain the outbox every poll interval until shutdown, pruning sent rows
+/// past their retention every hour
+pub async fn run_with_state(state: Arc<AppState>) -> Result<()> {
+    let mut shutdown_rx = state.shutdown.subscribe();
+    let mut interval = tokio::time::interval(state.config.blockchain.poll_interval);
+    let mut prune_interval = tokio::time::interval(PRUNE_INTERVAL);
+    let retention = state.config.blockchain.outbox_retention;
+
+    info!("📤 Outbox publisher started");
+
+    loop {
+        tokio::select! {
+            _ = shutdown_rx.recv() => {
+                info!("Outbox publisher shutting down");
+                break;
+            }
+            _ = interval.tick() => {
+                // Keep draining while full batches come back
+                loop {
+                    match drain(state.db.pool(), &state.kafka, DRAIN_BATCH_SIZE).await {
+                        Ok(n) if n as i64 == DRAIN_BATCH_SIZE => continue,
+                        Ok(n) => {
+                            if n > 0 {
+                                debug!("📤 Published {} outbox events", n);
+                            }
+                            break;
+                        }
+                        Err(e) => {
+                            error!("❌ Outbox drain failed: {:?}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+            _ = prune_interval.tick(), if retention.is_some() => {
+                if let Some(retention) = retention {
+                    match prune_sent(state.db.pool(), retention).await {
+                        Ok(0) => {}
+                        Ok(n) => info!("🧹 Pruned {} sent outbox events", n),
+                        Err(e) => error!("❌ Outbox prune failed: {:?}", e),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::random_address;
+    use crate::kafka::{FlakyPublisher, InMemoryPublisher};
+    use std::sync::atomic::Ordering;
+
+    fn row(id: i64) -> OutboxRow {
+        OutboxRow {
+            id,
+            topic: "user.actions".to_string(),
+            kafka_key: format!("0x{:040x}", id),
+            payload: serde_json::json!({ "event_type": "UserFollowed", "id": id }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unpublished_rows_are_sent_on_recovery() {
+        let rows = vec![row(1), row(2)];
+        let publisher = FlakyPublisher::default();
+
+        // Crash between outbox write and publish: nothing is marked sent
+        publisher.down.store(true, Ordering::SeqCst);
+        assert!(publish_pending(&publisher, &rows).await.is_empty());
+
+        // Recovery drains the same rows, in order
+        publisher.down.store(false, Ordering::SeqCst);
+        assert_eq!(publish_pending(&publisher, &rows).await, vec![1, 2]);
+
+        let messages = publisher.inner.messages();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].payload["id"], 1);
+        assert_eq!(messages[1].payload["id"], 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_outbox_survives_crash_before_drain() {
+        let pool = crate::database::test_pool().await;
+        // Hold the rows already pending so the drains below skip them
+        // rather than publishing someone else's events
+        let mut others = pool.begin().await.unwrap();
+        sqlx::query("SELECT id FROM event_outbox WHERE sent_at IS NULL FOR UPDATE")
+            .execute(&mut *others)
+            .await
+            .unwrap();
+
+        let key = random_address();
+        let entry = OutboxEntry {
+            topic: "user.actions".to_string(),
+            key: key.clone(),
+            payload: serde_json::json!({ "event_type": "UserFollowed" }),
+        };
+        let contract = "0x00000000000000000000000000000000000007e5";
+        commit_batch(&pool, contract, "outbox_test", 42, &[entry])
+            .await
+            .unwrap();
+
+        // Simulated crash: the process restarts with a fresh publisher
+        let publisher = InMemoryPublisher::new();
+        assert_eq!(drain(&pool, &publisher, 10).await.unwrap(), 1);
+        assert_eq!(publisher.messages()[0].key, key);
+
+        // Already marked sent
+        assert_eq!(drain(&pool, &publisher, 10).await.unwrap(), 0);
+
+        let last_block = crate::indexer::get_last_indexed_block(&pool, contract, "outbox_test")
+            .await
+            .unwrap();
+        assert_eq!(last_block, Some(42));
+
+        others.rollback().await.unwrap();
+        sqlx::query("DELETE FROM event_outbox WHERE kafka_key = $1")
+            .bind(&key)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM indexer_state WHERE contract_address = $1")
+            .bind(contract)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_replay_republishes_stored_events_in_order() {
+        let pool = crate::database::test_pool().await;
+
+        // Far above any block other tests store
+        let base = 9_000_000_000 + (uuid::Uuid::new_v4().as_u128() % 1_000_000) as u64 * 10;
+        let tx = |n: u64| format!("0x{:064x}", base + n);
+        let store = |topic: &'static str, key: &str, block: u64, log_index: u64, hash: String| {
+            let payload = serde_json::json!({
+                "event_type": "Replayed",
+                "block_number": block,
+                "transaction_hash": hash,
+                "log_index": log_index,
+            });
+            let key = key.to_string();
+            let pool = pool.clone();
+            async move {
+                sqlx::query("INSERT INTO event_outbox (topic, kafka_key, payload, sent_at) VALUES ($1, $2, $3, NOW())")
+                    .bind(topic)
+                    .bind(key)
+                    .bind(payload)
+                    .execute(&pool)
+                    .await
+                    .unwrap();
+            }
+        };
+
+        // Stored out of order, one event twice, one already processed and
+        // one outside the range
+        store("nft.likes", "0xliker", base + 2, 0, tx(2)).await;
+        store("user.actions", "0xfollower", base + 1, 1, tx(1)).await;
+        store("user.actions", "0xfollower", base + 1, 0, tx(1)).await;
+        store("user.actions", "0xfollower", base + 1, 0, tx(1)).await;
+        store("nft.mints", "0xcreator", base + 3, 0, tx(3)).await;
+        store("nft.mints", "0xcreator", base + 4, 0, tx(4)).await;
+        store("nft.mints", "0xcreator", base + 9, 0, tx(9)).await;
+        sqlx::query("INSERT INTO processed_events (transaction_hash, log_index, event_type) VALUES ($1, 0, 'Replayed')")
+            .bind(tx(4))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let publisher = InMemoryPublisher::new();
+        let replayed = replay_events(&pool, &publisher, base + 1, base + 5).await.unwrap();
+        assert_eq!(replayed, 4);
+
+        let sent: Vec<(String, String, u64, u64)> = publisher
+            .messages()
+            .iter()
+            .map(|m| {
+                (
+                    m.topic.clone(),
+                    m.key.clone(),
+                    m.payload["block_number"].as_u64().unwrap() - base,
+                    m.payload["log_index"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        let expected = [
+            ("user.actions", "0xfollower", 1, 0),
+            ("user.actions", "0xfollower", 1, 1),
+            ("nft.likes", "0xliker", 2, 0),
+            ("nft.mints", "0xcreator", 3, 0),
+        ];
+        assert_eq!(
+            sent,
+            expected.map(|(t, k, b, l)| (t.to_string(), k.to_string(), b, l))
+        );
+
+        sqlx::query("DELETE FROM event_outbox WHERE payload->>'transaction_hash' = ANY($1)")
+            .bind([1, 2, 3, 4, 9].map(tx).to_vec())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM processed_events WHERE transaction_hash = $1")
+            .bind(tx(4))
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_prune_removes_only_sent_rows_past_retention() {
+        let pool = crate::database::test_pool().await;
+
+        let topic = format!("prune.{}", uuid::Uuid::new_v4().simple());
+        for (key, sent_days_ago) in [("old", Some(40)), ("recent", Some(1)), ("pending", None)] {
+            sqlx::query(
+                r#"
+                INSERT INTO event_outbox (topic, kafka_key, payload, sent_at)
+                VALUES ($1, $2, '{}', NOW() - make_interval(days => $3))
+                "#,
+            )
+            .bind(&topic)
+            .bind(key)
+            .bind(sent_days_ago)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        prune_sent(&pool, Duration::from_secs(30 * 86_400)).await.unwrap();
+
+        let mut kept: Vec<String> = sqlx::query_scalar("SELECT kafka_key FROM event_outbox WHERE topic = $1")
+            .bind(&topic)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        kept.sort();
+        assert_eq!(kept, vec!["pending", "recent"]);
+
+        sqlx::query("DELETE FROM event_outbox WHERE topic = $1")
+            .bind(&topic)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+}
@@ -2,25 +2,42 @@
 //!
 //! Indexes unified events (ContentMinted, ContentLiked, ContentCopyMinted, ContentCommented, ContentBlocked)
 
+use crate::config::{KafkaTopics, PendingLogPolicy};
 use crate::error::{Error, Result};
-use crate::indexer::{get_last_indexed_block, parse_address, save_last_indexed_block, with_retry};
-use crate::kafka::KafkaProducer;
+use crate::events::ParsedEvent;
+use crate::indexer::outbox::{commit_batch, OutboxEntry};
+use crate::indexer::{
+    detect_reorg, get_last_indexed_block, next_batch_range, parse_address, publish_parsed,
+    resume_block, save_last_block_hash, save_last_indexed_block, verify_event_timestamp, with_retry,
+    CircuitBreaker, LagMonitor, ProgressEmitter, BLOCK_NUMBER_RETRY, GET_LOGS_RETRY,
+};
+use crate::kafka::EventPublisher;
 use crate::AppState;
 use ethers::prelude::*;
 use sqlx::PgPool;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 use tracing::{error, info, instrument, warn};
 
-struct TheraSocialIndexer {
+struct TheraSocialIndexer<P: EventPublisher> {
     provider: Arc<Provider<Http>>,
     contract_address: Address,
-    kafka: KafkaProducer,
+    kafka: P,
     pool: PgPool,
     poll_interval: Duration,
     batch_size: u64,
-    current_block: u64,
+    /// Next block to fetch; everything before it has been emitted
+    next_block: u64,
+    topics: KafkaTopics,
+    timestamp_tolerance_secs: Option<u64>,
+    outbox_enabled: bool,
+    lag: LagMonitor,
+    progress: ProgressEmitter,
+    reorg_rewind_depth: u64,
+    pending_logs: PendingLogPolicy,
+    partial_decode: bool,
+    rpc_breaker: CircuitBreaker,
 }
 
 pub async fn run_with_state(state: Arc<AppState>) -> Result<()> {
@@ -28,13 +45,13 @@ pub async fn run_with_state(state: Arc<AppState>) -> Result<()> {
     let provider = Provider::<Http>::try_from(state.config.blockchain.rpc_url.as_str())
         .map_err(|e| Error::blockchain(format!("Failed to create provider: {}", e)))?;
 
-    let start_block = get_last_indexed_block(
+    let last_indexed = get_last_indexed_block(
         state.db.pool(),
         &format!("{:?}", contract_address),
         "friends",
     )
-    .await?
-    .unwrap_or(state.config.blockchain.start_block);
+    .await?;
+    let next_block = resume_block(last_indexed, state.config.blockchain.start_block);
 
     let mut indexer = TheraSocialIndexer {
         provider: Arc::new(provider),
@@ -43,21 +60,34 @@ pub async fn run_with_state(state: Arc<AppState>) -> Result<()> {
         pool: state.db.pool().clone(),
         poll_interval: state.config.blockchain.poll_interval,
         batch_size: state.config.blockchain.batch_size,
-        current_block: start_block,
+        next_block,
+        topics: state.config.kafka.topics.clone(),
+        timestamp_tolerance_secs: state.config.blockchain.timestamp_tolerance_secs,
+        outbox_enabled: state.config.blockchain.outbox_enabled,
+        reorg_rewind_depth: state.config.blockchain.reorg_rewind_depth,
+        lag: LagMonitor::new("thera_social", state.config.blockchain.lag_warn_blocks),
+        progress: ProgressEmitter::from_config("thera_social", &state.config),
+        pending_logs: state.config.blockchain.pending_logs,
+        partial_decode: state.config.blockchain.partial_decode,
+        rpc_breaker: CircuitBreaker::new(
+            "rpc",
+            state.config.blockchain.rpc_breaker_threshold,
+            state.config.blockchain.rpc_breaker_cooldown,
+        ),
     };
 
     let mut shutdown_rx = state.shutdown.subscribe();
     indexer.run(&mut shutdown_rx).await
 }
 
-impl TheraSocialIndexer {
+impl<P: EventPublisher> TheraSocialIndexer<P> {
     #[instrument(skip(self, shutdown_rx), fields(contract = %self.contract_address))]
     async fn run(&mut self, shutdown_rx: &mut broadcast::Receiver<()>) -> Result<()> {
         info!(
             "🧩 TheraSocialIndexer started for contract: {:?}",
             self.contract_address
         );
-        info!("📍 Starting from block: {}", self.current_block);
+        info!("📍 Starting from block: {}", self.next_block);
 
         loop {
             tokio::select! {
@@ -81,80 +111,155 @@ impl TheraSocialIndexer {
     }
 
     async fn process_batch(&mut self) -> Result<()> {
-        let latest_block = with_retry(
-            || async {
-                self.provider
-                    .get_block_number()
-                    .await
-                    .map(|b| b.as_u64())
-                    .map_err(|e| Error::blockchain(format!("Failed to get block number: {}", e)))
-            },
-            3,
-            Duration::from_millis(500),
-            "get_block_number",
-        )
-        .await?;
+        let latest_block = self
+            .rpc_breaker
+            .call(|| {
+                with_retry(
+                    || async {
+                        self.provider
+                            .get_block_number()
+                            .await
+                            .map(|b| b.as_u64())
+                            .map_err(|e| Error::blockchain(format!("Failed to get block number: {}", e)))
+                    },
+                    BLOCK_NUMBER_RETRY,
+                    "get_block_number",
+                )
+            })
+            .await?;
 
-        if latest_block <= self.current_block {
-            return Ok(());
+        let contract = format!("{:?}", self.contract_address);
+        if let Some(rewound) = detect_reorg(
+            &self.pool,
+            self.provider.as_ref(),
+            &self.rpc_breaker,
+            &contract,
+            self.next_block.saturating_sub(1),
+            self.reorg_rewind_depth,
+        )
+        .await?
+        {
+            // Re-emit everything after the rewound height
+            save_last_indexed_block(&self.pool, &contract, "friends", rewound).await?;
+            save_last_block_hash(
+                &self.pool,
+                self.provider.as_ref(),
+                &self.rpc_breaker,
+                &contract,
+                rewound,
+            )
+            .await;
+            self.next_block = rewound + 1;
         }
 
-        let to_block = std::cmp::min(self.current_block + self.batch_size, latest_block);
+        self.lag
+            .observe(self.next_block.saturating_sub(1), latest_block, Instant::now());
+        self.progress
+            .maybe_emit(&self.kafka, self.next_block.saturating_sub(1), latest_block, Instant::now())
+            .await;
+
+        let Some((from_block, to_block)) =
+            next_batch_range(self.next_block, latest_block, self.batch_size)
+        else {
+            return Ok(());
+        };
 
         let filter = Filter::new()
             .address(self.contract_address)
-            .from_block(self.current_block)
+            .from_block(from_block)
             .to_block(to_block);
 
-        let logs = with_retry(
-            || async {
-                self.provider
-                    .get_logs(&filter)
-                    .await
-                    .map_err(|e| Error::blockchain(format!("Failed to get logs: {}", e)))
-            },
-            3,
-            Duration::from_millis(500),
-            "get_logs",
-        )
-        .await?;
+        let logs = self
+            .rpc_breaker
+            .call(|| {
+                with_retry(
+                    || async {
+                        self.provider
+                            .get_logs(&filter)
+                            .await
+                            .map_err(|e| Error::blockchain(format!("Failed to get logs: {}", e)))
+                    },
+                    GET_LOGS_RETRY,
+                    "get_logs",
+                )
+            })
+            .await?;
+        self.progress.record_events(logs.len() as u64);
 
         if !logs.is_empty() {
             info!(
                 "🔍 Found {} logs in blocks {}-{}",
                 logs.len(),
-                self.current_block,
+                from_block,
                 to_block
             );
         }
 
+        if self.outbox_enabled {
+            let mut entries = Vec::with_capacity(logs.len());
+            for log in &logs {
+                match self.prepare_log(log).await {
+                    Ok(entry) => entries.push(entry),
+                    Err(e) => warn!("Failed to process log: {:?}", e),
+                }
+            }
+
+            commit_batch(
+                &self.pool,
+                &contract,
+                "friends",
+                to_block,
+                &entries,
+            )
+            .await?;
+            save_last_block_hash(
+                &self.pool,
+                self.provider.as_ref(),
+                &self.rpc_breaker,
+                &contract,
+                to_block,
+            )
+            .await;
+            self.next_block = to_block + 1;
+            return Ok(());
+        }
+
         for log in logs {
             if let Err(e) = self.process_log(&log).await {
                 warn!("Failed to process log: {:?}", e);
             }
         }
 
-        self.current_block = to_block;
-        save_last_indexed_block(
+        self.next_block = to_block + 1;
+        save_last_indexed_block(&self.pool, &contract, "friends", to_block).await?;
+        save_last_block_hash(
             &self.pool,
-            &format!("{:?}", self.contract_address),
-            "friends",
+            self.provider.as_ref(),
+            &self.rpc_breaker,
+            &contract,
             to_block,
         )
-        .await?;
+        .await;
 
         Ok(())
     }
 
+    async fn parse_and_verify(&self, log: &Log) -> Result<ParsedEvent> {
+        let parsed = crate::events::parse_mined_log(log, "friends", self.pending_logs, self.partial_decode)?;
+        if let Some(tolerance) = self.timestamp_tolerance_secs {
+            verify_event_timestamp(self.provider.as_ref(), &self.rpc_breaker, &parsed, tolerance)
+                .await;
+        }
+        Ok(parsed)
+    }
+
     async fn process_log(&self, log: &Log) -> Result<()> {
-        let parsed = crate::events::parse_log(log, "friends")?;
-        let kafka_key = crate::events::event_kafka_key(&parsed);
-        let topic = if parsed.event_type == "Unknown" {
-            "blockchain.events"
-        } else {
-            "user.actions"
-        };
+        let parsed = self.parse_and_verify(log).await?;
+        publish_parsed(&self.kafka, &parsed, &self.topics).await
+    }
 
-        self.kafka.send_event(topic, &kafka_key, &parsed).await
+    async fn prepare_log(&self, log: &Log) -> Result<OutboxEntry> {
+        let parsed = self.parse_and_verify(log).await?;
+        OutboxEntry::from_parsed(&parsed, &self.topics)
     }
 }
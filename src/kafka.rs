@@ -9,14 +9,85 @@
 
 use crate::config::KafkaConfig;
 use crate::error::{Error, Result};
+use rdkafka::admin::{AdminClient, AdminOptions, AlterConfig, ResourceSpecifier};
+use rdkafka::client::DefaultClientContext;
 use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{BaseConsumer, Consumer};
+use rdkafka::error::KafkaError;
+use rdkafka::message::Message;
 use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
 use rdkafka::util::Timeout;
+use rdkafka::{Offset, TopicPartitionList};
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
-use tracing::{debug, error, info, instrument};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, instrument, warn};
+
+/// Run `send` until it succeeds or `max_attempts` attempts have failed,
+/// sleeping `base_backoff_ms * 2^(attempt - 1)` plus up to 100ms of jitter
+/// between attempts. Returns the last error on exhaustion.
+async fn send_with_backoff<T, F, Fut>(
+    max_attempts: u32,
+    base_backoff_ms: u64,
+    mut send: F,
+) -> std::result::Result<T, KafkaError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::result::Result<T, KafkaError>>,
+{
+    let max_attempts = max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match send().await {
+            Ok(delivered) => return Ok(delivered),
+            Err(err) => {
+                error!("Attempt {}/{} - Failed to deliver message: {:?}", attempt, max_attempts, err);
+                if attempt >= max_attempts {
+                    return Err(err);
+                }
+
+                // Exponential backoff with jitter
+                let exp = 2u64.saturating_pow(attempt - 1);
+                let jitter = rand::random::<u64>() % 100;
+                let backoff = base_backoff_ms.saturating_mul(exp).saturating_add(jitter);
+                debug!("Backing off for {}ms before retrying (attempt {}/{})", backoff, attempt, max_attempts);
+                tokio::time::sleep(Duration::from_millis(backoff)).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Anything that can publish serialized events to a topic.
+///
+/// Implemented by [`KafkaProducer`] for production and by `InMemoryPublisher`
+/// in tests so components can assert on what they emit without a broker.
+pub trait EventPublisher: Send + Sync {
+    /// Publish a single event with the given partition key
+    fn send_event<T: Serialize + std::fmt::Debug + Sync>(
+        &self,
+        topic: &str,
+        key: &str,
+        event: &T,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Publish multiple keyed events to one topic
+    fn send_batch<T: Serialize + std::fmt::Debug + Sync>(
+        &self,
+        topic: &str,
+        events: &[(String, T)],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Flush pending messages
+    fn flush(&self, timeout: Duration);
+
+    /// Whether sends actually go anywhere (a disabled producer drops them)
+    fn is_enabled(&self) -> bool {
+        true
+    }
+}
 
 /// Kafka producer with batching and reliability features
 #[derive(Clone)]
@@ -29,8 +100,24 @@ pub struct KafkaProducer {
     /// send retry behavior
     send_max_attempts: u32,
     send_backoff_base_ms: u64,
+    /// Last broker probe result, refreshed in the background
+    health: Arc<Mutex<BrokerHealth>>,
+}
+
+/// Broker reachability as of the last probe
+#[derive(Debug, Default)]
+struct BrokerHealth {
+    /// When the last probe finished and whether it reached the brokers
+    last: Option<(Instant, bool)>,
+    /// A probe is running, so another shouldn't be started
+    probing: bool,
 }
 
+/// Longest a broker reachability probe may take
+const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long a probe result is reused before probing again
+const HEALTH_CACHE_TTL: Duration = Duration::from_secs(5);
+
 /// Producer metrics
 struct KafkaProducerMetrics {
     messages_sent: AtomicU64,
@@ -76,15 +163,15 @@ impl KafkaProducer {
             .set("acks", &config.producer.acks)
             .set("enable.idempotence", config.producer.idempotent.to_string())
             .set("max.in.flight.requests.per.connection", "5")
-            .set("retries", &config.producer.retries.to_string())
+            .set("retries", config.producer.retries.to_string())
             .set("retry.backoff.ms", "100")
             .set(
                 "reconnect.backoff.ms",
-                &config.producer.reconnect_backoff_ms.to_string(),
+                config.producer.reconnect_backoff_ms.to_string(),
             )
             .set(
                 "reconnect.backoff.max.ms",
-                &config.producer.reconnect_backoff_max_ms.to_string(),
+                config.producer.reconnect_backoff_max_ms.to_string(),
             )
             // Batching
             .set("batch.size", config.producer.batch_size.to_string())
@@ -130,6 +217,7 @@ impl KafkaProducer {
             delivery_timeout: config.producer.delivery_timeout,
             send_max_attempts: config.producer.send_max_attempts,
             send_backoff_base_ms: config.producer.send_backoff_base_ms,
+            health: Arc::new(Mutex::new(BrokerHealth::default())),
         })
     }
 
@@ -147,6 +235,7 @@ impl KafkaProducer {
             delivery_timeout: Duration::from_secs(5),
             send_max_attempts: 1,
             send_backoff_base_ms: 200,
+            health: Arc::new(Mutex::new(BrokerHealth::default())),
         }
     }
 
@@ -160,6 +249,10 @@ impl KafkaProducer {
                 blockchain_events: "blockchain.events".to_string(),
                 user_actions: "user.actions".to_string(),
                 recommendations: "recommendations".to_string(),
+                user_actions_key: crate::config::KafkaKeyStrategy::Contract,
+                dlq: None,
+                dlq_retention: Duration::from_secs(7 * 24 * 3600),
+                indexer_progress: "indexer.progress".to_string(),
             },
             producer: crate::config::KafkaProducerConfig {
                 message_timeout: Duration::from_secs(5),
@@ -200,67 +293,48 @@ impl KafkaProducer {
         debug!("Sending event to topic '{}' with key '{}'", topic, key);
 
         // Local retry loop with exponential backoff + jitter to handle transient broker/connectivity issues
-        let max_attempts = self.config_send_max_attempts();
+        let max_attempts = self.config_send_max_attempts().max(1);
         let base_backoff = self.config_send_backoff_base_ms();
 
-        for attempt in 1..=max_attempts {
+        let result = send_with_backoff(max_attempts, base_backoff, || async {
             let record = FutureRecord::to(topic).key(key).payload(payload.as_str());
-            match self
-                .producer
+            self.producer
                 .send(record, Timeout::After(self.delivery_timeout))
                 .await
-            {
-                Ok((partition, offset)) => {
-                    debug!(
-                        "Message delivered to partition {} at offset {} (attempt {}/{})",
-                        partition,
-                        offset,
-                        attempt,
-                        max_attempts
-                    );
-                    self.config.messages_sent.fetch_add(1, Ordering::Relaxed);
-                    self.config
-                        .bytes_sent
-                        .fetch_add(payload_len as u64, Ordering::Relaxed);
-                    return Ok(());
-                }
-                Err((err, _)) => {
-                    self.config.messages_failed.fetch_add(1, Ordering::Relaxed);
-                    error!("Attempt {}/{} - Failed to deliver message: {:?}", attempt, max_attempts, err);
-
-                    // On terminal failure, return the error
-                    if attempt == max_attempts {
-                        // On final failure, fetch broker metadata for diagnostics and log it
-                        match self.producer.client().fetch_metadata(None, Timeout::After(Duration::from_secs(5))) {
-                            Ok(md) => {
-                                let brokers: Vec<String> = md.brokers().iter().map(|b| format!("{}:{}", b.host(), b.port())).collect();
-                                error!("Broker metadata on failure: brokers={:?}, topics_count={}", brokers, md.topics().len());
-                            }
-                            Err(merr) => {
-                                error!("Failed to fetch broker metadata: {:?}", merr);
-                            }
-                        }
-
-                        return Err(Error::Kafka {
-                            message: format!("Failed to send message after {} attempts: {}", max_attempts, err).into(),
-                            source: Some(err),
-                        });
+                .map_err(|(err, _)| err)
+        })
+        .await;
+
+        match result {
+            Ok((partition, offset)) => {
+                debug!("Message delivered to partition {} at offset {}", partition, offset);
+                self.config.messages_sent.fetch_add(1, Ordering::Relaxed);
+                self.config
+                    .bytes_sent
+                    .fetch_add(payload_len as u64, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(err) => {
+                // Only counted once every attempt has been exhausted
+                self.config.messages_failed.fetch_add(1, Ordering::Relaxed);
+
+                // On final failure, fetch broker metadata for diagnostics and log it
+                match self.producer.client().fetch_metadata(None, Timeout::After(Duration::from_secs(5))) {
+                    Ok(md) => {
+                        let brokers: Vec<String> = md.brokers().iter().map(|b| format!("{}:{}", b.host(), b.port())).collect();
+                        error!("Broker metadata on failure: brokers={:?}, topics_count={}", brokers, md.topics().len());
+                    }
+                    Err(merr) => {
+                        error!("Failed to fetch broker metadata: {:?}", merr);
                     }
-
-                    // Exponential backoff with jitter
-                    let exp = 2u64.pow((attempt - 1) as u32);
-                    let mut backoff = base_backoff.saturating_mul(exp);
-                    // add up to 100ms of jitter
-                    let jitter = rand::random::<u64>() % 100;
-                    backoff = backoff.saturating_add(jitter);
-                    debug!("Backing off for {}ms before retrying (attempt {}/{})", backoff, attempt, max_attempts);
-                    tokio::time::sleep(Duration::from_millis(backoff)).await;
-                    // retry
                 }
+
+                Err(Error::Kafka {
+                    message: format!("Failed to send message after {} attempts: {}", max_attempts, err).into(),
+                    source: Some(err),
+                })
             }
         }
-
-        unreachable!("send_event retry loop should return above")
     }
 
     /// Send multiple events in a batch
@@ -339,16 +413,360 @@ impl KafkaProducer {
         }
     }
 
-    /// Check if producer is healthy
+    /// Whether the brokers were reachable at the last probe.
+    ///
+    /// Never waits on the brokers, so health routes answer within their
+    /// timeout during an outage. When the last result is older than
+    /// `HEALTH_CACHE_TTL` (5s) a background task probes by fetching cluster
+    /// metadata, waiting at most `HEALTH_PROBE_TIMEOUT` (2s), and the
+    /// previous result is returned meanwhile. Until the first probe finishes
+    /// the brokers count as unreachable. A disabled (no-op) producer is
+    /// always healthy.
     pub fn is_healthy(&self) -> bool {
         if !self.enabled {
             return true;
         }
-        // Check if we can reach the broker
-        self.producer.in_flight_count() < 10000 // Arbitrary threshold
+
+        let mut health = self.health.lock().unwrap();
+        let fresh = health
+            .last
+            .is_some_and(|(checked_at, _)| checked_at.elapsed() < HEALTH_CACHE_TTL);
+        if !fresh && !health.probing {
+            health.probing = true;
+            self.spawn_health_probe();
+        }
+        health.last.is_some_and(|(_, healthy)| healthy)
+    }
+
+    /// Probe the brokers off the async runtime and store the result
+    fn spawn_health_probe(&self) {
+        let producer = self.producer.clone();
+        let health = self.health.clone();
+        tokio::spawn(async move {
+            let probe = tokio::task::spawn_blocking(move || {
+                producer.client().fetch_metadata(None, HEALTH_PROBE_TIMEOUT)
+            })
+            .await;
+            let healthy = match probe {
+                Ok(Ok(_)) => true,
+                Ok(Err(e)) => {
+                    warn!("Kafka brokers unreachable: {}", e);
+                    false
+                }
+                Err(e) => {
+                    warn!("Kafka health probe failed: {}", e);
+                    false
+                }
+            };
+            let mut health = health.lock().unwrap();
+            health.last = Some((Instant::now(), healthy));
+            health.probing = false;
+        });
+    }
+}
+
+#[cfg(test)]
+impl KafkaProducer {
+    /// An enabled producer pointed at a port no broker listens on
+    pub fn unreachable() -> Self {
+        let mut producer = Self::noop();
+        producer.producer = Arc::new(
+            ClientConfig::new()
+                .set("bootstrap.servers", "127.0.0.1:1")
+                .create()
+                .expect("Failed to create unreachable producer"),
+        );
+        producer.enabled = true;
+        producer
     }
 }
 
+impl EventPublisher for KafkaProducer {
+    fn send_event<T: Serialize + std::fmt::Debug + Sync>(
+        &self,
+        topic: &str,
+        key: &str,
+        event: &T,
+    ) -> impl Future<Output = Result<()>> + Send {
+        KafkaProducer::send_event(self, topic, key, event)
+    }
+
+    fn send_batch<T: Serialize + std::fmt::Debug + Sync>(
+        &self,
+        topic: &str,
+        events: &[(String, T)],
+    ) -> impl Future<Output = Result<()>> + Send {
+        KafkaProducer::send_batch(self, topic, events)
+    }
+
+    fn flush(&self, timeout: Duration) {
+        KafkaProducer::flush(self, timeout)
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// A message captured by [`InMemoryPublisher`]
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct PublishedMessage {
+    pub topic: String,
+    pub key: String,
+    pub payload: serde_json::Value,
+}
+
+/// In-memory publisher that records every send, for deterministic tests
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct InMemoryPublisher {
+    messages: Arc<std::sync::Mutex<Vec<PublishedMessage>>>,
+}
+
+#[cfg(test)]
+impl InMemoryPublisher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of everything published so far, in send order
+    pub fn messages(&self) -> Vec<PublishedMessage> {
+        self.messages.lock().unwrap().clone()
+    }
+
+    fn record<T: Serialize>(&self, topic: &str, key: &str, event: &T) -> Result<()> {
+        let payload = serde_json::to_value(event)?;
+        self.messages.lock().unwrap().push(PublishedMessage {
+            topic: topic.to_string(),
+            key: key.to_string(),
+            payload,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+impl EventPublisher for InMemoryPublisher {
+    fn send_event<T: Serialize + std::fmt::Debug + Sync>(
+        &self,
+        topic: &str,
+        key: &str,
+        event: &T,
+    ) -> impl Future<Output = Result<()>> + Send {
+        let result = self.record(topic, key, event);
+        async move { result }
+    }
+
+    fn send_batch<T: Serialize + std::fmt::Debug + Sync>(
+        &self,
+        topic: &str,
+        events: &[(String, T)],
+    ) -> impl Future<Output = Result<()>> + Send {
+        let result = events
+            .iter()
+            .try_for_each(|(key, event)| self.record(topic, key, event));
+        async move { result }
+    }
+
+    fn flush(&self, _timeout: Duration) {}
+}
+
+/// Publisher that fails while `down` is set, simulating a broker outage
+/// or a crash before delivery
+#[cfg(test)]
+#[derive(Default)]
+pub struct FlakyPublisher {
+    pub down: std::sync::atomic::AtomicBool,
+    pub inner: InMemoryPublisher,
+}
+
+#[cfg(test)]
+impl EventPublisher for FlakyPublisher {
+    fn send_event<T: Serialize + std::fmt::Debug + Sync>(
+        &self,
+        topic: &str,
+        key: &str,
+        event: &T,
+    ) -> impl Future<Output = Result<()>> + Send {
+        let result = if self.down.load(Ordering::SeqCst) {
+            Err(Error::kafka("broker unavailable"))
+        } else {
+            Ok(self.inner.send_event(topic, key, event))
+        };
+        async move { result?.await }
+    }
+
+    fn send_batch<T: Serialize + std::fmt::Debug + Sync>(
+        &self,
+        topic: &str,
+        events: &[(String, T)],
+    ) -> impl Future<Output = Result<()>> + Send {
+        let result = if self.down.load(Ordering::SeqCst) {
+            Err(Error::kafka("broker unavailable"))
+        } else {
+            Ok(self.inner.send_batch(topic, events))
+        };
+        async move { result?.await }
+    }
+
+    fn flush(&self, _timeout: Duration) {}
+}
+
+/// A message read back from a topic
+#[derive(Debug, Clone)]
+pub struct PeekedMessage {
+    pub partition: i32,
+    pub offset: i64,
+    pub payload: Vec<u8>,
+}
+
+/// Reads the most recent messages on a topic without consuming them
+pub trait TopicPeek: Send + Sync {
+    /// Up to `limit` of the newest messages on the topic, newest first.
+    /// May block; call from a blocking task.
+    fn peek(&self, limit: usize) -> Result<Vec<PeekedMessage>>;
+}
+
+/// [`TopicPeek`] over Kafka. Partitions are assigned directly at
+/// `high watermark - limit`, so nothing joins a group or commits offsets.
+pub struct KafkaTopicPeek {
+    consumer: Mutex<BaseConsumer>,
+    topic: String,
+    timeout: Duration,
+}
+
+impl KafkaTopicPeek {
+    pub fn new(config: &KafkaConfig, topic: impl Into<String>) -> Result<Self> {
+        let consumer: BaseConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("group.id", format!("{}-peek", config.group_id))
+            .set("enable.auto.commit", "false")
+            .set("enable.auto.offset.store", "false")
+            .create()
+            .map_err(|e| Error::kafka(format!("Failed to create peek consumer: {}", e)))?;
+
+        Ok(Self {
+            consumer: Mutex::new(consumer),
+            topic: topic.into(),
+            timeout: Duration::from_secs(5),
+        })
+    }
+}
+
+impl TopicPeek for KafkaTopicPeek {
+    fn peek(&self, limit: usize) -> Result<Vec<PeekedMessage>> {
+        let consumer = self.consumer.lock().unwrap_or_else(|e| e.into_inner());
+        let metadata = consumer.fetch_metadata(Some(&self.topic), self.timeout)?;
+        let partitions: Vec<i32> = metadata
+            .topics()
+            .iter()
+            .flat_map(|t| t.partitions().iter().map(|p| p.id()))
+            .collect();
+
+        // Start each partition `limit` back from its end; the newest `limit`
+        // overall are among them
+        let mut assignment = TopicPartitionList::new();
+        let mut remaining = 0;
+        for partition in partitions {
+            let (low, high) = consumer.fetch_watermarks(&self.topic, partition, self.timeout)?;
+            let start = (high - limit as i64).max(low);
+            if start < high {
+                assignment.add_partition_offset(&self.topic, partition, Offset::Offset(start))?;
+                remaining += high - start;
+            }
+        }
+        if remaining == 0 {
+            return Ok(Vec::new());
+        }
+
+        consumer.assign(&assignment)?;
+        let mut messages = Vec::new();
+        let deadline = Instant::now() + self.timeout;
+        while remaining > 0 && Instant::now() < deadline {
+            match consumer.poll(Duration::from_millis(100)) {
+                Some(Ok(message)) => {
+                    messages.push((
+                        message.timestamp().to_millis().unwrap_or(0),
+                        PeekedMessage {
+                            partition: message.partition(),
+                            offset: message.offset(),
+                            payload: message.payload().unwrap_or_default().to_vec(),
+                        },
+                    ));
+                    remaining -= 1;
+                }
+                Some(Err(e)) => {
+                    consumer.unassign()?;
+                    return Err(e.into());
+                }
+                None => {}
+            }
+        }
+        consumer.unassign()?;
+
+        messages.sort_by_key(|(timestamp, _)| std::cmp::Reverse(*timestamp));
+        Ok(messages.into_iter().take(limit).map(|(_, m)| m).collect())
+    }
+}
+
+/// [`TopicPeek`] over what an [`InMemoryPublisher`] sent to one topic
+#[cfg(test)]
+pub struct InMemoryTopicPeek {
+    publisher: InMemoryPublisher,
+    topic: String,
+}
+
+#[cfg(test)]
+impl InMemoryTopicPeek {
+    pub fn new(publisher: InMemoryPublisher, topic: impl Into<String>) -> Self {
+        Self {
+            publisher,
+            topic: topic.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl TopicPeek for InMemoryTopicPeek {
+    fn peek(&self, limit: usize) -> Result<Vec<PeekedMessage>> {
+        let mut messages: Vec<_> = self
+            .publisher
+            .messages()
+            .into_iter()
+            .filter(|m| m.topic == self.topic)
+            .enumerate()
+            .map(|(offset, m)| PeekedMessage {
+                partition: 0,
+                offset: offset as i64,
+                payload: serde_json::to_vec(&m.payload).unwrap_or_default(),
+            })
+            .collect();
+        messages.reverse();
+        messages.truncate(limit);
+        Ok(messages)
+    }
+}
+
+/// Set `retention.ms` on `topic` so it can't grow unbounded. This is a full
+/// (non-incremental) config update, so only use it on topics we own.
+pub async fn set_topic_retention(config: &KafkaConfig, topic: &str, retention: Duration) -> Result<()> {
+    let admin: AdminClient<DefaultClientContext> = ClientConfig::new()
+        .set("bootstrap.servers", &config.brokers)
+        .create()
+        .map_err(|e| Error::kafka(format!("Failed to create admin client: {}", e)))?;
+
+    let retention_ms = retention.as_millis().to_string();
+    let alter = AlterConfig::new(ResourceSpecifier::Topic(topic)).set("retention.ms", &retention_ms);
+    let options = AdminOptions::new().request_timeout(Some(Duration::from_secs(10)));
+    for result in admin.alter_configs([&alter], &options).await? {
+        result.map_err(|(_, code)| {
+            Error::kafka(format!("Failed to set retention on {}: {}", topic, code))
+        })?;
+    }
+    Ok(())
+}
+
 /// Producer statistics
 #[derive(Debug, Clone)]
 pub struct ProducerStats {
@@ -386,7 +804,6 @@ pub struct BlockchainEvent {
 }
 
 /// User action event message
-#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize)]
 pub struct UserActionEvent {
     pub action_type: String,
@@ -460,4 +877,77 @@ mod tests {
         assert_eq!(metrics.messages_sent.load(Ordering::Relaxed), 10);
         assert_eq!(metrics.messages_failed.load(Ordering::Relaxed), 1);
     }
+
+    #[tokio::test]
+    async fn test_noop_producer_is_healthy_without_probing() {
+        let producer = KafkaProducer::noop();
+        assert!(producer.is_healthy());
+        // Nothing was probed, so nothing is cached
+        let health = producer.health.lock().unwrap();
+        assert!(health.last.is_none() && !health.probing);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_publisher_records_sends() {
+        let publisher = InMemoryPublisher::new();
+        let event = BlockchainEvent::new("SnapMinted", "0xabc", "snap", 1, "0xdef");
+
+        EventPublisher::send_event(&publisher, "blockchain.events", "snap.0xabc", &event)
+            .await
+            .unwrap();
+        EventPublisher::send_batch(
+            &publisher,
+            "user.actions",
+            &[("a".to_string(), event.clone()), ("b".to_string(), event)],
+        )
+        .await
+        .unwrap();
+
+        let messages = publisher.messages();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].topic, "blockchain.events");
+        assert_eq!(messages[0].key, "snap.0xabc");
+        assert_eq!(messages[0].payload["event_type"], "SnapMinted");
+        assert_eq!(messages[2].key, "b");
+    }
+
+    #[tokio::test]
+    async fn test_send_retries_up_to_configured_attempts() {
+        use rdkafka::types::RDKafkaErrorCode;
+        use std::sync::atomic::AtomicU32;
+
+        let failing = || KafkaError::MessageProduction(RDKafkaErrorCode::BrokerTransportFailure);
+
+        // Always failing: every configured attempt runs before giving up
+        let attempts = AtomicU32::new(0);
+        let result: std::result::Result<(), _> = send_with_backoff(3, 1, || async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(failing())
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+
+        // Transient failure: stops retrying as soon as a send succeeds
+        let attempts = AtomicU32::new(0);
+        let result = send_with_backoff(5, 1, || async {
+            if attempts.fetch_add(1, Ordering::Relaxed) < 1 {
+                Err(failing())
+            } else {
+                Ok((0, 42))
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), (0, 42));
+        assert_eq!(attempts.load(Ordering::Relaxed), 2);
+
+        // Zero attempts is treated as a single attempt
+        let attempts = AtomicU32::new(0);
+        let _ = send_with_backoff::<(), _, _>(0, 1, || async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(failing())
+        })
+        .await;
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
 }
@@ -2,11 +2,13 @@
 //!
 //! Re-exports core modules for integration tests and external use.
 
+pub mod address;
 pub mod recommendation;
 pub mod kafka;
 pub mod config;
 pub mod database;
 pub mod error;
+pub mod serde_helpers;
 
 // Re-export commonly used types
 pub use recommendation::*;
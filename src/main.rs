@@ -15,13 +15,15 @@
 //! - Kafka messages are flushed
 //! - Database connections are closed cleanly
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::signal;
 use tokio::sync::broadcast;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+mod address;
 mod api;
 mod config;
 mod database;
@@ -31,23 +33,36 @@ mod events;
 mod indexer;
 mod kafka;
 mod recommendation;
+mod serde_helpers;
 
-use config::Config;
+use config::{Config, RetryPolicy};
 use database::Database;
 use error::Result;
-use kafka::KafkaProducer;
+use ethers::providers::{Http, Provider};
+use kafka::{KafkaProducer, KafkaTopicPeek, TopicPeek};
+use recommendation::engine::RecommendationEngine;
 
 /// Application state shared across components
 pub struct AppState {
     pub config: Arc<Config>,
     pub db: Database,
     pub elixir_db: Database,
+    /// Read replica of the Elixir database for feed reads, when configured
+    pub read_db: Option<Database>,
+    /// False while the Elixir database is unreachable
+    pub elixir_ready: Arc<AtomicBool>,
     pub kafka: KafkaProducer,
     pub shutdown: broadcast::Sender<()>,
+    /// When the process started, for uptime reporting
+    pub started_at: Instant,
+    /// Shared by the API and the updater, so runtime toggles reach both
+    pub engine: RecommendationEngine,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let started_at = Instant::now();
+
     // Initialize tracing with structured logging
     init_tracing();
 
@@ -72,6 +87,15 @@ async fn main() -> Result<()> {
     let kafka_producer = KafkaProducer::new(&config.kafka)?;
     info!("✅ Kafka producer initialized");
 
+    // Bound the dead-letter topic; it's only read when someone inspects it
+    if let (true, Some(dlq)) = (config.kafka.enabled, &config.kafka.topics.dlq) {
+        let retention = config.kafka.topics.dlq_retention;
+        match kafka::set_topic_retention(&config.kafka, dlq, retention).await {
+            Ok(()) => info!("✅ Dead-letter topic {} keeps messages for {:?}", dlq, retention),
+            Err(e) => warn!("Failed to set retention on dead-letter topic {}: {:?}", dlq, e),
+        }
+    }
+
     // Initialize database connection pool
     let db = Database::new(&config.database).await?;
     info!("✅ Database connection pool established");
@@ -81,20 +105,95 @@ async fn main() -> Result<()> {
     database::run_migrations(db.pool()).await?;
     info!("✅ Database migrations applied");
 
+    // One-shot maintenance: `theragraph-engine backfill-creators [batch_size]`
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("backfill-creators") {
+        let batch_size = args.get(1).and_then(|n| n.parse().ok()).unwrap_or(1000);
+        recommendation::preferences::backfill_interaction_creators(db.pool(), batch_size).await?;
+        return Ok(());
+    }
+
+    // One-shot re-index of a historical range: `BACKFILL_FROM`/`BACKFILL_TO`
+    if let Some((from_block, to_block)) = config.blockchain.backfill_range {
+        backfill_blocks(&config, db.pool(), &kafka_producer, from_block, to_block).await?;
+        kafka_producer.flush(Duration::from_secs(5));
+        return Ok(());
+    }
+
+    // One-shot republish of stored events after a Kafka outage:
+    // `REPLAY_EVENTS_ENABLED` with `REPLAY_FROM`/`REPLAY_TO`
+    if let Some((from_block, to_block)) = config.blockchain.replay_range {
+        info!("🔁 Replaying stored events for blocks {}-{}", from_block, to_block);
+        let replayed = indexer::outbox::replay_events(db.pool(), &kafka_producer, from_block, to_block).await?;
+        kafka_producer.flush(Duration::from_secs(5));
+        info!("✅ Replayed {} events", replayed);
+        return Ok(());
+    }
+
     // Initialize Elixir database connection
+    // Elixir being down must not take the indexers with it: fall back to a
+    // lazy pool and let the API report 503 until it reconnects.
     info!("🔗 Connecting to Elixir database...");
-    let elixir_db = Database::new(&config.elixir_database).await?;
-    info!("✅ Connected to Elixir database");
+    let (elixir_db, elixir_ready) = match Database::new(&config.elixir_database).await {
+        Ok(db) => {
+            info!("✅ Connected to Elixir database");
+            (db, true)
+        }
+        Err(e) => {
+            warn!("⚠️ Elixir database unavailable, starting in degraded mode: {}", e);
+            (Database::new_lazy(&config.elixir_database)?, false)
+        }
+    };
+
+    // Feed reads go to the replica when one is configured and reachable
+    let read_db = match &config.read_database {
+        Some(replica) => match Database::new(replica).await {
+            Ok(db) => {
+                info!("✅ Connected to read replica");
+                Some(db)
+            }
+            Err(e) => {
+                warn!("⚠️ Read replica unavailable, reading from the primary: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Feeds query the Elixir-owned `nfts` table; a renamed column should
+    // stop startup by name rather than fail every feed request
+    if config.recommendation.nfts_schema_check {
+        if read_db.is_none() && !elixir_ready {
+            warn!("⚠️ Elixir database unavailable, skipping the nfts schema check");
+        } else {
+            let feed_pool = read_db.as_ref().unwrap_or(&elixir_db).pool();
+            database::check_table_columns(feed_pool, "nfts", database::NFTS_REQUIRED_COLUMNS).await?;
+            info!("✅ nfts table has the expected columns");
+        }
+    }
+
+    let mut engine = RecommendationEngine::from_config(elixir_db.pool().clone(), &config.recommendation);
+    if let Some(read_db) = &read_db {
+        engine = engine.with_read_pool(read_db.pool().clone());
+    }
 
     // Create shared state
     let state = Arc::new(AppState {
         config: config.clone(),
         db: db.clone(),
         elixir_db: elixir_db.clone(),
+        read_db,
+        elixir_ready: Arc::new(AtomicBool::new(elixir_ready)),
         kafka: kafka_producer.clone(),
         shutdown: shutdown_tx.clone(),
+        started_at,
+        engine,
     });
 
+    if !elixir_ready {
+        spawn_elixir_reconnect(state.clone());
+    }
+
     // Spawn all services
     let mut handles = Vec::new();
 
@@ -187,9 +286,69 @@ fn init_tracing() {
         .init();
 }
 
+/// Store the hash of each indexer's `last_block` where it is missing
+async fn backfill_block_hashes(state: &AppState) -> Result<()> {
+    let provider = Provider::<Http>::try_from(state.config.blockchain.rpc_url.as_str())
+        .map_err(|e| error::Error::blockchain(format!("Failed to create provider: {}", e)))?;
+    let breaker = indexer::CircuitBreaker::new(
+        "rpc",
+        state.config.blockchain.rpc_breaker_threshold,
+        state.config.blockchain.rpc_breaker_cooldown,
+    );
+    let mut conn = state.db.pool().acquire().await?;
+    let filled = indexer::backfill_last_block_hashes(&mut conn, &provider, &breaker).await?;
+    if filled > 0 {
+        info!("✅ Backfilled block hashes for {} indexer rows", filled);
+    }
+    Ok(())
+}
+
+/// Re-emit the social contract's events in `from_block..=to_block` without
+/// moving the indexers' stored progress
+async fn backfill_blocks(
+    config: &Config,
+    pool: &sqlx::PgPool,
+    kafka: &KafkaProducer,
+    from_block: u64,
+    to_block: u64,
+) -> Result<()> {
+    let provider = Provider::<Http>::try_from(config.blockchain.rpc_url.as_str())
+        .map_err(|e| error::Error::blockchain(format!("Failed to create provider: {}", e)))?;
+    let contract = indexer::parse_address(&config.contracts.thera_friends)?;
+
+    info!("⏪ Backfilling blocks {}-{} for {:?}", from_block, to_block, contract);
+    let emitted = indexer::backfill(
+        pool,
+        Arc::new(provider),
+        kafka,
+        contract,
+        from_block,
+        to_block,
+        config.blockchain.batch_size,
+        config.blockchain.backfill_concurrency(),
+        &config.kafka.topics,
+        config.blockchain.partial_decode,
+    )
+    .await?;
+    info!("✅ Backfill emitted {} events", emitted);
+    Ok(())
+}
+
 /// Spawn all blockchain indexers
 fn spawn_indexers(state: Arc<AppState>) -> Vec<tokio::task::JoinHandle<()>> {
     let mut handles = Vec::new();
+
+    // One-shot: hashes for last_block rows saved before reorg detection.
+    // Detached, since a finished handle would read as a failed service.
+    if state.config.blockchain.backfill_block_hashes {
+        let backfill_state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = backfill_block_hashes(&backfill_state).await {
+                warn!("Block hash backfill failed: {:?}", e);
+            }
+        });
+    }
+
     // Spawn only active indexers: friends and thera_social (unified contract)
     let friend_state = state.clone();
     handles.push(tokio::spawn(async move {
@@ -206,6 +365,16 @@ fn spawn_indexers(state: Arc<AppState>) -> Vec<tokio::task::JoinHandle<()>> {
         }
     }));
 
+    // Outbox publisher (indexers only write to the outbox when enabled)
+    if state.config.blockchain.outbox_enabled {
+        let outbox_state = state.clone();
+        handles.push(tokio::spawn(async move {
+            if let Err(e) = indexer::outbox::run_with_state(outbox_state).await {
+                error!("Outbox publisher failed: {:?}", e);
+            }
+        }));
+    }
+
     handles
 }
 
@@ -217,6 +386,11 @@ fn spawn_score_updater(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
         let update_interval = state.config.recommendation.engagement_update_interval;
         let mut interval = tokio::time::interval(update_interval);
 
+        let refresh_notifier = recommendation::updater::RefreshNotifier::new(
+            state.kafka.clone(),
+            state.config.kafka.topics.recommendations.clone(),
+        );
+
         // Skip first tick (runs immediately otherwise)
         interval.tick().await;
 
@@ -231,16 +405,31 @@ fn spawn_score_updater(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
                         error!("Failed to update engagement scores: {:?}", e);
                     }
 
-                    if let Err(e) = recommendation::features::update_trending_scores(pool).await {
+                    let trending = &state.config.recommendation.trending;
+                    if let Err(e) = recommendation::features::update_trending_scores(pool, trending).await {
                         error!("Failed to update trending scores: {:?}", e);
                     }
 
-                    if let Err(e) = recommendation::preferences::apply_preference_decay(pool).await {
+                    let hot_window = state.config.recommendation.hot_window;
+                    if let Err(e) = recommendation::features::update_trending_windows(pool, hot_window).await {
+                        error!("Failed to update hot/rising scores: {:?}", e);
+                    }
+
+                    let decay = &state.config.recommendation.decay;
+                    if let Err(e) = recommendation::preferences::apply_preference_decay(pool, decay).await {
                         error!("Failed to apply preference decay: {:?}", e);
                     }
 
+                    let cache_max_rows = Some(state.config.recommendation.cache_max_rows).filter(|n| *n > 0);
+                    match recommendation::engine::prune_recommendation_cache(pool, cache_max_rows).await {
+                        Ok(removed) if removed > 0 => info!("Evicted {} recommendation cache entries", removed),
+                        Ok(_) => {}
+                        Err(e) => error!("Failed to prune recommendation cache: {:?}", e),
+                    }
+
                     // Generate personalized recommendations for active users
-                    if let Err(e) = recommendation::updater::update_all_recommendations(pool).await {
+                    let concurrency = state.config.recommendation.updater_concurrency;
+                    if let Err(e) = recommendation::updater::update_all_recommendations(pool, &state.engine, concurrency, &refresh_notifier).await {
                         error!("Failed to update user recommendations: {:?}", e);
                     }
 
@@ -255,15 +444,57 @@ fn spawn_score_updater(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
     })
 }
 
+/// Backoff between Elixir reconnect attempts in degraded mode, which are
+/// retried until they succeed or the process shuts down
+const ELIXIR_RECONNECT: RetryPolicy = RetryPolicy {
+    max_retries: u32::MAX,
+    initial_delay: Duration::from_secs(1),
+    max_delay: Duration::from_secs(30),
+    multiplier: 2.0,
+};
+
+/// Reconnect to the Elixir database in the background and mark it ready,
+/// which takes the API out of degraded mode and starts the event processor
+fn spawn_elixir_reconnect(state: Arc<AppState>) {
+    let mut shutdown_rx = state.shutdown.subscribe();
+
+    tokio::spawn(async move {
+        let mut attempt = 0;
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(ELIXIR_RECONNECT.delay(attempt)) => {}
+                _ = shutdown_rx.recv() => return,
+            }
+
+            match state.elixir_db.health_check().await {
+                Ok(()) => {
+                    state.elixir_ready.store(true, Ordering::Relaxed);
+                    info!("✅ Reconnected to Elixir database, leaving degraded mode");
+                    return;
+                }
+                Err(e) => debug!("Elixir database still unavailable: {}", e),
+            }
+            attempt = attempt.saturating_add(1);
+        }
+    });
+}
+
 /// Spawn the API server
 fn spawn_api_server(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
-    let port = state.config.api.port;
+    let api_config = state.config.api.clone();
+    let chain_id = state.config.blockchain.chain_id;
+    let started_at = state.started_at;
     let pool = state.elixir_db.pool().clone();  // Use Elixir DB for NFT queries
+    let engine = state.engine.clone();
+    let indexer_pool = state.db.pool().clone();
+    let producer = state.kafka.clone();
+    let database_ready = state.elixir_ready.clone();
+    let dead_letters = dead_letter_peek(&state.config);
     let mut shutdown_rx = state.shutdown.subscribe();
 
     tokio::spawn(async move {
         tokio::select! {
-            result = api::start_server(pool, port) => {
+            result = api::start_server(pool, engine, api_config, chain_id, started_at, producer, indexer_pool, database_ready, dead_letters) => {
                 if let Err(e) = result {
                     error!("API server error: {:?}", e);
                 }
@@ -275,6 +506,18 @@ fn spawn_api_server(state: Arc<AppState>) -> tokio::task::JoinHandle<()> {
     })
 }
 
+/// Reader for `/api/v1/admin/deadletter`, when Kafka and dead-lettering are on
+fn dead_letter_peek(config: &Config) -> Option<Arc<dyn TopicPeek>> {
+    let topic = config.kafka.topics.dlq.as_ref().filter(|_| config.kafka.enabled)?;
+    match KafkaTopicPeek::new(&config.kafka, topic.as_str()) {
+        Ok(peek) => Some(Arc::new(peek)),
+        Err(e) => {
+            warn!("Dead-letter inspection unavailable: {:?}", e);
+            None
+        }
+    }
+}
+
 /// Wait for any task to fail
 async fn wait_for_any_failure(handles: &mut [tokio::task::JoinHandle<()>]) {
     loop {
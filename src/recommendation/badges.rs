@@ -0,0 +1,113 @@
+//! User Badges
+//!
+//! `BadgeAwarded` and `BadgeRemoved` toggle `user_badges.is_active`. Each
+//! active badge a creator holds lifts the quality score of their NFTs, so a
+//! removal takes back exactly what the award gave.
+
+use anyhow::Result;
+use sqlx::{PgExecutor, PgPool};
+use std::collections::HashMap;
+
+use crate::address::normalize_address;
+
+/// Record that `user` holds `badge`. Replays are harmless.
+pub async fn award_badge<'e>(executor: impl PgExecutor<'e>, user: &str, badge: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_badges (user_address, badge, is_active)
+        VALUES ($1, $2, true)
+        ON CONFLICT (user_address, badge) DO UPDATE SET
+            is_active = true,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(normalize_address(user)?)
+    .bind(badge)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Revoke `badge` from `user`. A no-op when it was never awarded.
+pub async fn remove_badge<'e>(
+    executor: impl PgExecutor<'e>,
+    user: &str,
+    badge: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE user_badges SET is_active = false, updated_at = NOW()
+        WHERE user_address = $1 AND badge = $2
+        "#,
+    )
+    .bind(normalize_address(user)?)
+    .bind(badge)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Active badge counts per user (normalized address); users without any,
+/// or with a malformed address, are left out
+pub async fn get_badge_counts(pool: &PgPool, users: &[String]) -> Result<HashMap<String, u64>> {
+    let users: Vec<String> = users.iter().filter_map(|u| normalize_address(u).ok()).collect();
+    if users.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        SELECT user_address, COUNT(*)
+        FROM user_badges
+        WHERE user_address = ANY($1) AND is_active = true
+        GROUP BY user_address
+        "#,
+    )
+    .bind(&users)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|(user, count)| (user, count as u64)).collect())
+}
+
+/// Quality after `boost` per active badge, capped at 1.0
+pub fn badge_quality(quality: f32, badges: u64, boost: f32) -> f32 {
+    if badges == 0 {
+        return quality;
+    }
+    (quality + boost * badges as f32).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_award_then_remove_leaves_quality_unchanged() {
+        let pool = crate::database::test_pool().await;
+
+        let creator = "0x00000000000000000000000000000000000BAD6E";
+        let creators = vec![creator.to_string()];
+        let quality = || async {
+            let counts = get_badge_counts(&pool, &creators).await.unwrap();
+            let badges = counts.get(&creator.to_lowercase()).copied().unwrap_or(0);
+            badge_quality(0.5, badges, 0.05)
+        };
+        sqlx::query("DELETE FROM user_badges WHERE user_address = $1")
+            .bind(creator.to_lowercase())
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let before = quality().await;
+        award_badge(&pool, creator, "top_creator").await.unwrap();
+        award_badge(&pool, creator, "top_creator").await.unwrap();
+        assert!(quality().await > before);
+
+        remove_badge(&pool, creator, "top_creator").await.unwrap();
+        assert_eq!(quality().await, before);
+    }
+}
@@ -0,0 +1,55 @@
+//! User Blocks
+//!
+//! `UserBlocked` keeps a creator's content out of the blocker's enhanced feed;
+//! `UserUnblocked` lifts that. Both invalidate the blocker's cached feeds so
+//! the change shows up on the next request.
+
+use anyhow::Result;
+use sqlx::{PgConnection, PgPool};
+
+use super::engine::invalidate_cached_recommendations;
+use crate::address::normalize_address;
+
+/// Record that `blocker` blocked `blocked`
+pub async fn block_user(conn: &mut PgConnection, blocker: &str, blocked: &str) -> Result<()> {
+    let blocker = normalize_address(blocker)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_blocks (blocker_address, blocked_address)
+        VALUES ($1, $2)
+        ON CONFLICT (blocker_address, blocked_address) DO NOTHING
+        "#,
+    )
+    .bind(&blocker)
+    .bind(normalize_address(blocked)?)
+    .execute(&mut *conn)
+    .await?;
+
+    invalidate_cached_recommendations(conn, &blocker).await
+}
+
+/// Lift a block so the blocked user's content can reappear
+pub async fn unblock_user(conn: &mut PgConnection, blocker: &str, blocked: &str) -> Result<()> {
+    let blocker = normalize_address(blocker)?;
+
+    sqlx::query("DELETE FROM user_blocks WHERE blocker_address = $1 AND blocked_address = $2")
+        .bind(&blocker)
+        .bind(normalize_address(blocked)?)
+        .execute(&mut *conn)
+        .await?;
+
+    invalidate_cached_recommendations(conn, &blocker).await
+}
+
+/// Addresses `user` has blocked
+pub async fn get_blocked_users(pool: &PgPool, user: &str) -> Result<Vec<String>> {
+    let blocked = sqlx::query_scalar::<_, String>(
+        "SELECT blocked_address FROM user_blocks WHERE blocker_address = $1",
+    )
+    .bind(normalize_address(user)?)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(blocked)
+}
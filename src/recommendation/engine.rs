@@ -6,23 +6,42 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use std::collections::HashMap;
-use tracing::debug;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+use tracing::{debug, info, warn};
+
+use crate::config::RecommendationConfig;
+pub use crate::config::{
+    CandidatePool, CreatorColdStart, DiscoveryShuffle, NicheBroadening, ScoringWeights,
+    SourceBlend, TrendingTieBreak, DEFAULT_TRENDING_TIE_BREAKS, SMALL_PAGE_LIMIT,
+};
 
 use super::features::NftFeatures;
-use super::preferences::UserPreferences;
+use super::flags::FeatureFlags;
+use super::preferences::{TasteSpace, UserPreferences};
+use super::single_flight::SingleFlight;
 
 /// A scored recommendation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScoredNft {
     pub nft_id: String,
+    #[serde(with = "crate::serde_helpers::string_int")]
     pub token_id: i64,
     pub contract_address: String,
     pub score: f32,
     pub reason: RecommendationReason,
+    /// Top contributing reasons, `reason` first (verbose responses only;
+    /// empty and omitted otherwise)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<RecommendationReason>,
     pub contract_type: String,
     pub creator_address: String,
     pub tags: Vec<String>,
+    /// Creator's username from `creator_profiles`, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator_username: Option<String>,
 }
 
 /// Why this NFT was recommended
@@ -43,37 +62,488 @@ pub enum RecommendationReason {
     HighEngagement { engagement_score: f32 },
     /// Serendipity - introducing variety
     Discovery,
+    /// Saved by users with similar taste
+    SavedBySimilar { similar_users: usize },
 }
 
-/// Recommendation weights (can be tuned)
-#[derive(Debug, Clone)]
-pub struct ScoringWeights {
+impl RecommendationReason {
+    /// Variant name as serialized, for analytics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RecommendationReason::TagMatch { .. } => "tag_match",
+            RecommendationReason::CreatorAffinity { .. } => "creator_affinity",
+            RecommendationReason::ContentTypeMatch { .. } => "content_type_match",
+            RecommendationReason::Trending { .. } => "trending",
+            RecommendationReason::Following { .. } => "following",
+            RecommendationReason::HighEngagement { .. } => "high_engagement",
+            RecommendationReason::Discovery => "discovery",
+            RecommendationReason::SavedBySimilar { .. } => "saved_by_similar",
+        }
+    }
+}
+
+/// Most reasons kept per item for verbose responses
+pub const MAX_VERBOSE_REASONS: usize = 3;
+
+/// `primary` followed by the other positive contributions, strongest first,
+/// `max` in all
+fn rank_reasons(
+    primary: &RecommendationReason,
+    mut contributions: Vec<(f32, RecommendationReason)>,
+    max: usize,
+) -> Vec<RecommendationReason> {
+    contributions.retain(|(score, reason)| *score > 0.0 && reason.kind() != primary.kind());
+    contributions.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    std::iter::once(primary.clone())
+        .chain(contributions.into_iter().map(|(_, reason)| reason))
+        .take(max)
+        .collect()
+}
+
+/// Weighted contribution of each scoring factor to one candidate's score
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScoreBreakdown {
     pub tag_match: f32,
-    pub creator_affinity: f32,
+    pub creator: f32,
     pub content_type: f32,
     pub trending: f32,
     pub engagement: f32,
     pub quality: f32,
     pub recency: f32,
+    /// Creator and tag repetition penalties, subtracted from the rest
     pub diversity_penalty: f32,
 }
 
-impl Default for ScoringWeights {
-    fn default() -> Self {
-        // ByteGraph-inspired weights: prioritize personalization heavily
-        Self {
-            tag_match: 0.35,         // 35% weight on tag matching (increased)
-            creator_affinity: 0.20,  // 20% weight on creator preference (increased)
-            content_type: 0.25,      // 25% weight on content type match (increased)
-            trending: 0.05,          // 5% weight on trending score (reduced)
-            engagement: 0.05,        // 5% weight on overall engagement (reduced)
-            quality: 0.05,           // 5% weight on quality score (reduced)
-            recency: 0.03,           // 3% weight on how new the NFT is (reduced)
-            diversity_penalty: 0.02, // 2% penalty for too similar items
+impl ScoreBreakdown {
+    /// The final score: the components minus the penalty, clamped to 0-1
+    pub fn total(&self) -> f32 {
+        self.ranking_score(0.0)
+    }
+
+    /// Score used for ranking, allowed down to `-negative_grace` so heavily
+    /// penalized items keep their relative order
+    pub fn ranking_score(&self, negative_grace: f32) -> f32 {
+        let sum = self.tag_match
+            + self.creator
+            + self.content_type
+            + self.trending
+            + self.engagement
+            + self.quality
+            + self.recency;
+        (sum - self.diversity_penalty).clamp(-negative_grace, 1.0)
+    }
+}
+
+/// A candidate's score with its per-factor breakdown and the adjustments
+/// the personalized feed makes on top of it
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainedNft {
+    pub nft_id: String,
+    pub score: f32,
+    pub reason: RecommendationReason,
+    pub breakdown: ScoreBreakdown,
+    /// Added because users with similar taste saved it
+    pub saved_by_similar: f32,
+    /// Taken off because the user unliked or unsaved it
+    pub rejected_penalty: f32,
+}
+
+impl CreatorColdStart {
+    /// Quality to score with, given the stored quality and how many
+    /// interactions the creator's content has received
+    pub fn effective_quality(&self, quality_score: f32, creator_interactions: u64) -> f32 {
+        if self.interaction_threshold == 0 || creator_interactions >= self.interaction_threshold as u64 {
+            return quality_score;
+        }
+        let confidence = creator_interactions as f32 / self.interaction_threshold as f32;
+        let blended = self.baseline_quality * (1.0 - confidence) + quality_score * confidence;
+        // Never penalize a new creator who already has a better real score
+        blended.max(quality_score)
+    }
+}
+
+impl CandidatePool {
+    /// Candidates to fetch for a personalized page of `limit`
+    pub fn personalized(&self, limit: usize) -> usize {
+        self.max_candidates.min(limit.saturating_mul(self.personalized_multiplier))
+    }
+
+    /// Candidates to fetch for an enhanced feed page of `limit`
+    pub fn enhanced(&self, limit: usize) -> usize {
+        let multiplier = if limit < SMALL_PAGE_LIMIT {
+            self.small_page_multiplier
+        } else {
+            self.enhanced_multiplier
+        };
+        self.max_candidates.min(limit.saturating_mul(multiplier))
+    }
+}
+
+impl DiscoveryShuffle {
+    /// Seed for `user_address` at unix time `now_secs`
+    pub fn seed(&self, user_address: &str, now_secs: u64) -> u64 {
+        if let Some(seed) = self.fixed_seed {
+            return seed;
+        }
+        let window = now_secs / self.window_secs.max(1);
+        super::flags::stable_hash(&format!("{}:{}", user_address.to_lowercase(), window))
+    }
+
+    /// Seed for `user_address` right now
+    pub fn seed_now(&self, user_address: &str) -> u64 {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.seed(user_address, now)
+    }
+}
+
+/// Which trending variant to rank by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendingMode {
+    /// Time-decayed engagement over the last week
+    #[default]
+    Trending,
+    /// Engagement in the latest short window
+    Hot,
+    /// Growth of engagement over the window before
+    Rising,
+}
+
+impl TrendingMode {
+    /// Column in `nft_trending_windows` holding this mode's score, if any
+    fn window_column(self) -> Option<&'static str> {
+        match self {
+            Self::Trending => None,
+            Self::Hot => Some("hot_score"),
+            Self::Rising => Some("rising_score"),
+        }
+    }
+}
+
+impl NicheBroadening {
+    /// Whether a user with `prefs` needs broadening given the scored feed
+    pub fn needs_broadening(&self, prefs: &UserPreferences, scored: &[ScoredNft], limit: usize) -> bool {
+        let has_tag_interests = prefs.tag_preferences.values().any(|w| *w > 0.6);
+        let tag_matches = scored
+            .iter()
+            .filter(|s| matches!(s.reason, RecommendationReason::TagMatch { .. }))
+            .count();
+        has_tag_interests && tag_matches < self.min_tag_matches.min(limit)
+    }
+
+    /// Rank tag matches first, then broadened picks (as `Discovery`), then the
+    /// rest of the feed, dropping duplicates
+    pub fn merge(scored: Vec<ScoredNft>, broadened: Vec<ScoredNft>) -> Vec<ScoredNft> {
+        let (matches, rest): (Vec<_>, Vec<_>) = scored
+            .into_iter()
+            .partition(|s| matches!(s.reason, RecommendationReason::TagMatch { .. }));
+
+        let mut seen = std::collections::HashSet::new();
+        let broadened = broadened.into_iter().map(|mut s| {
+            s.reason = RecommendationReason::Discovery;
+            s
+        });
+        matches
+            .into_iter()
+            .chain(broadened)
+            .chain(rest)
+            .filter(|s| seen.insert(s.nft_id.clone()))
+            .collect()
+    }
+}
+
+/// Where an enhanced-feed candidate came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandidateSource {
+    /// Recent and engaging content, ranked by content-based scoring
+    Personalized,
+    /// Highest trending scores
+    Trending,
+    /// Liked by users who like what this user likes
+    Collaborative,
+    /// From creators the user follows
+    Following,
+}
+
+impl SourceBlend {
+    fn shares(&self) -> [(CandidateSource, f32); 4] {
+        [
+            (CandidateSource::Personalized, self.personalized),
+            (CandidateSource::Trending, self.trending),
+            (CandidateSource::Collaborative, self.collaborative),
+            (CandidateSource::Following, self.following),
+        ]
+    }
+
+    /// Candidates each source contributes to a pool of `total`, summing to
+    /// `total` (largest remainder). All-zero shares put everything on
+    /// `Personalized`.
+    pub fn quotas(&self, total: usize) -> Vec<(CandidateSource, usize)> {
+        let shares = self.shares();
+        let sum: f32 = shares.iter().map(|(_, s)| s).sum();
+        if sum <= 0.0 {
+            return vec![(CandidateSource::Personalized, total)];
+        }
+
+        let exact: Vec<f32> = shares.iter().map(|(_, s)| s / sum * total as f32).collect();
+        let mut quotas: Vec<usize> = exact.iter().map(|e| e.floor() as usize).collect();
+        let mut by_remainder: Vec<usize> = (0..shares.len()).collect();
+        by_remainder.sort_by(|&a, &b| {
+            (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor()))
+        });
+        let missing = total.saturating_sub(quotas.iter().sum());
+        for &i in by_remainder.iter().take(missing) {
+            quotas[i] += 1;
+        }
+
+        shares
+            .iter()
+            .map(|(source, _)| *source)
+            .zip(quotas)
+            .collect()
+    }
+
+    /// Pool of up to `total` candidates: each source's quota first, then any
+    /// shortfall backfilled from the sources' leftovers in order. Duplicates
+    /// across sources are dropped.
+    fn blend(
+        &self,
+        total: usize,
+        mut sources: HashMap<CandidateSource, Vec<(CandidateNft, Option<NftFeatures>)>>,
+    ) -> Vec<(CandidateNft, Option<NftFeatures>)> {
+        let mut seen = std::collections::HashSet::new();
+        let mut pool = Vec::with_capacity(total);
+        let mut leftovers = Vec::new();
+
+        for (source, quota) in self.quotas(total) {
+            let mut taken = 0;
+            for candidate in sources.remove(&source).unwrap_or_default() {
+                if taken < quota {
+                    if candidate
+                        .0
+                        .id
+                        .as_ref()
+                        .is_some_and(|id| seen.insert(id.clone()))
+                    {
+                        pool.push(candidate);
+                        taken += 1;
+                    }
+                } else {
+                    leftovers.push(candidate);
+                }
+            }
+        }
+
+        for candidate in leftovers {
+            if pool.len() >= total {
+                break;
+            }
+            if candidate
+                .0
+                .id
+                .as_ref()
+                .is_some_and(|id| seen.insert(id.clone()))
+            {
+                pool.push(candidate);
+            }
+        }
+        pool
+    }
+}
+
+/// Content types the user leans towards, strongest first
+fn preferred_content_types(prefs: &UserPreferences) -> Vec<String> {
+    let mut types = [
+        ("snap", prefs.snap_affinity),
+        ("art", prefs.art_affinity),
+        ("music", prefs.music_affinity),
+        ("flix", prefs.flix_affinity),
+    ];
+    types.sort_by(|a, b| b.1.total_cmp(&a.1));
+    types
+        .iter()
+        .filter(|(_, affinity)| *affinity > 0.5)
+        .map(|(t, _)| t.to_string())
+        .collect()
+}
+
+/// Savers whose taste vector is at least this similar to the user's count
+/// as similar users
+const SIMILAR_SAVER_MIN_SIMILARITY: f32 = 0.7;
+
+/// Users with similar taste who saved an NFT
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimilarSaves {
+    pub similar_users: usize,
+    /// Similarity of the closest of them
+    pub max_similarity: f32,
+}
+
+/// Group `saver_tastes` (NFT id and the taste vector of a user who saved
+/// it) into the savers at least `min_similarity` similar to `taste`
+pub fn similar_saves(
+    taste: &[f32],
+    saver_tastes: Vec<(String, Vec<f32>)>,
+    min_similarity: f32,
+) -> HashMap<String, SimilarSaves> {
+    let mut saves: HashMap<String, SimilarSaves> = HashMap::new();
+    for (nft_id, saver_taste) in saver_tastes {
+        let similarity = super::preferences::cosine_similarity(taste, &saver_taste);
+        if similarity < min_similarity {
+            continue;
+        }
+        let entry = saves.entry(nft_id).or_insert(SimilarSaves {
+            similar_users: 0,
+            max_similarity: 0.0,
+        });
+        entry.similar_users += 1;
+        entry.max_similarity = entry.max_similarity.max(similarity);
+    }
+    saves
+}
+
+/// Raise the score of each item saved by similar users by `boost`, scaled by
+/// the closest saver's similarity, and lead its reasons with
+/// [`RecommendationReason::SavedBySimilar`]
+fn apply_saved_by_similar(scored: &mut [ScoredNft], saves: &HashMap<String, SimilarSaves>, boost: f32) {
+    if boost <= 0.0 {
+        return;
+    }
+    for item in scored.iter_mut() {
+        let Some(saved) = saves.get(&item.nft_id) else {
+            continue;
+        };
+        item.score += boost * saved.max_similarity;
+        let reason = RecommendationReason::SavedBySimilar {
+            similar_users: saved.similar_users,
+        };
+        let previous = std::mem::replace(&mut item.reason, reason.clone());
+        if item.reasons.is_empty() {
+            item.reasons.push(previous);
+        }
+        item.reasons.insert(0, reason);
+        item.reasons.truncate(MAX_VERBOSE_REASONS);
+    }
+}
+
+/// [`apply_saved_by_similar`] and [`apply_rejection_penalty`] for explained
+/// scores, recording how much each moved the score
+fn adjust_explained(
+    explained: &mut [ExplainedNft],
+    saves: &HashMap<String, SimilarSaves>,
+    boost: f32,
+    rejected: &HashSet<String>,
+    penalty: f32,
+) {
+    for item in explained.iter_mut() {
+        if let Some(saved) = saves.get(&item.nft_id).filter(|_| boost > 0.0) {
+            item.saved_by_similar = boost * saved.max_similarity;
+            item.score += item.saved_by_similar;
+            item.reason = RecommendationReason::SavedBySimilar {
+                similar_users: saved.similar_users,
+            };
+        }
+        if rejected.contains(&item.nft_id) {
+            item.rejected_penalty = item.score.abs() * penalty;
+            item.score -= item.rejected_penalty;
+        }
+    }
+}
+
+/// Cut the score of each item in `rejected` by `penalty` (a fraction of its
+/// magnitude, so negative ranking scores drop further too), then restore
+/// score order
+fn apply_rejection_penalty(scored: &mut [ScoredNft], rejected: &HashSet<String>, penalty: f32) {
+    for item in scored.iter_mut().filter(|item| rejected.contains(&item.nft_id)) {
+        item.score -= item.score.abs() * penalty;
+    }
+    scored.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+}
+
+/// Clamp ranking scores, which may dip below 0 (`REC_NEGATIVE_SCORE_GRACE`),
+/// to the 0-1 range callers see
+fn clamp_exposed_scores(mut items: Vec<ScoredNft>) -> Vec<ScoredNft> {
+    for item in &mut items {
+        item.score = item.score.clamp(0.0, 1.0);
+    }
+    items
+}
+
+/// Drop candidates whose creator is in `creators` (case-insensitive)
+fn exclude_creators(
+    candidates: Vec<(CandidateNft, Option<NftFeatures>)>,
+    creators: &[String],
+) -> Vec<(CandidateNft, Option<NftFeatures>)> {
+    if creators.is_empty() {
+        return candidates;
+    }
+    let creators: std::collections::HashSet<String> = creators.iter().map(|c| c.to_lowercase()).collect();
+    candidates
+        .into_iter()
+        .filter(|(nft, _)| !creators.contains(&nft.creator_address.to_lowercase()))
+        .collect()
+}
+
+/// Default bound on cache age served in degraded mode (`REC_MAX_STALE_SECS`)
+const DEFAULT_MAX_STALE_SECS: u64 = 3600;
+
+/// What to serve when the feed can't be computed
+#[derive(Debug)]
+pub enum DegradedFeed {
+    /// A cached feed, possibly expired but within the staleness bound
+    Cache(Vec<ScoredNft>),
+    /// No usable cache; fall back to trending
+    Trending,
+}
+
+impl DegradedFeed {
+    /// Serve `cached` only if it was computed within `max_stale` of `now`
+    pub fn choose(
+        cached: Option<(chrono::DateTime<chrono::Utc>, Vec<ScoredNft>)>,
+        now: chrono::DateTime<chrono::Utc>,
+        max_stale: std::time::Duration,
+    ) -> Self {
+        match cached {
+            Some((computed_at, items))
+                if (now - computed_at).to_std().unwrap_or_default() <= max_stale =>
+            {
+                Self::Cache(items)
+            }
+            _ => Self::Trending,
         }
     }
 }
 
+/// Keyset position in the enhanced feed: the `(creation_time, id)` of the
+/// oldest candidate a page covered. Opaque to clients as `<micros>_<uuid>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedCursor {
+    pub created_at: chrono::NaiveDateTime,
+    pub id: Uuid,
+}
+
+impl FeedCursor {
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.created_at.and_utc().timestamp_micros(), self.id.simple())
+    }
+
+    /// `None` for anything `encode` couldn't have produced
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let (micros, id) = cursor.split_once('_')?;
+        Some(Self {
+            created_at: chrono::DateTime::from_timestamp_micros(micros.parse().ok()?)?.naive_utc(),
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
+
 /// Context used for scoring a single NFT
     pub struct ScoringContext<'a> {
         pub prefs: &'a UserPreferences,
@@ -89,20 +559,236 @@ impl Default for ScoringWeights {
 #[derive(Clone)]
 pub struct RecommendationEngine {
     pool: PgPool,
+    /// Candidate, feature and seen lookups; a read replica when configured,
+    /// otherwise `pool`
+    read_pool: PgPool,
     weights: ScoringWeights,
+    flags: FeatureFlags,
+    cold_start: CreatorColdStart,
+    shuffle: DiscoveryShuffle,
+    trending_tie_breaks: Vec<TrendingTieBreak>,
+    max_stale: std::time::Duration,
+    niche: NicheBroadening,
+    /// Leave creators the user follows out of the enhanced feed, since they
+    /// already appear in the following feed
+    exclude_followed_creators: bool,
+    blend: SourceBlend,
+    /// Kill switch: when off, feeds fall back to plain trending. Shared by
+    /// clones so an admin toggle reaches every handle on this engine.
+    enabled: Arc<AtomicBool>,
+    /// Fill in `ScoredNft::creator_username` from `creator_profiles`
+    include_creator_profiles: bool,
+    /// Personalized recommendations scoring below this are dropped, unless
+    /// that would leave less than half a page (`REC_MIN_SCORE`)
+    min_score: f32,
+    /// Creator quality added per active badge (`REC_BADGE_QUALITY_BOOST`,
+    /// 0 leaves badges out of scoring)
+    badge_quality_boost: f32,
+    /// Fraction of the score taken off NFTs the user unliked or unsaved
+    /// (`REC_REJECTED_PENALTY`)
+    rejected_penalty: f32,
+    /// Extra weight for a user's first positive interaction with a creator
+    /// or tag (`REC_FIRST_CONTACT_BOOST`)
+    first_contact_boost: f32,
+    /// Tag vocabulary taste vectors are computed over (`REC_TASTE_TAGS`)
+    taste_space: TasteSpace,
+    /// Score added to NFTs saved by users with similar taste
+    /// (`REC_SAVED_BY_SIMILAR_BOOST`)
+    saved_by_similar_boost: f32,
+    /// Most of the creator preference one creator may hold when scoring
+    /// (`REC_CREATOR_MAX_SHARE`)
+    creator_max_share: f32,
+    candidates: CandidatePool,
+    /// Feed recomputations in progress, shared by clones so concurrent
+    /// requests for the same feed wait for one computation
+    in_flight: Arc<SingleFlight<FlightResult>>,
+}
+
+/// Relevance versus novelty in the MMR reranker: 1.0 keeps score order,
+/// lower values trade score for variety
+const MMR_LAMBDA: f32 = 0.7;
+
+/// Maximal marginal relevance: pick the first `limit` items one at a time,
+/// each maximizing its score against its similarity to those already
+/// picked. The rest follow in their original order.
+fn mmr_rerank(mut scored: Vec<ScoredNft>, limit: usize, lambda: f32) -> Vec<ScoredNft> {
+    let mut picked: Vec<ScoredNft> = Vec::with_capacity(limit.min(scored.len()));
+    while picked.len() < limit && !scored.is_empty() {
+        let best = scored
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let redundancy = picked
+                    .iter()
+                    .map(|p| item_similarity(item, p))
+                    .fold(0.0, f32::max);
+                (i, lambda * item.score - (1.0 - lambda) * redundancy)
+            })
+            // Ties go to the higher-ranked item
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        picked.push(scored.remove(best));
+    }
+    picked.extend(scored);
+    picked
+}
+
+/// 1.0 for the same creator, otherwise the Jaccard overlap of the tags
+fn item_similarity(a: &ScoredNft, b: &ScoredNft) -> f32 {
+    if a.creator_address.eq_ignore_ascii_case(&b.creator_address) {
+        return 1.0;
+    }
+    let shared = a.tags.iter().filter(|t| b.tags.contains(t)).count();
+    let union = a.tags.len() + b.tags.len() - shared;
+    if union == 0 {
+        0.0
+    } else {
+        shared as f32 / union as f32
+    }
 }
 
+/// Outcome of a single-flight feed computation; the error is shared
+/// between every caller that waited on it
+type FlightResult = std::result::Result<Vec<ScoredNft>, Arc<anyhow::Error>>;
+
 impl RecommendationEngine {
-    pub fn new(pool: PgPool) -> Self {
+    pub fn new(pool: PgPool, weights: ScoringWeights) -> Self {
         Self {
+            read_pool: pool.clone(),
             pool,
-            weights: ScoringWeights::default(),
+            weights,
+            flags: FeatureFlags::default(),
+            cold_start: CreatorColdStart::default(),
+            shuffle: DiscoveryShuffle::default(),
+            trending_tie_breaks: DEFAULT_TRENDING_TIE_BREAKS.to_vec(),
+            max_stale: std::time::Duration::from_secs(DEFAULT_MAX_STALE_SECS),
+            niche: NicheBroadening::default(),
+            exclude_followed_creators: false,
+            blend: SourceBlend::default(),
+            enabled: Arc::new(AtomicBool::new(true)),
+            include_creator_profiles: true,
+            min_score: 0.0,
+            badge_quality_boost: 0.0,
+            rejected_penalty: 0.9,
+            first_contact_boost: 0.5,
+            taste_space: TasteSpace::default(),
+            saved_by_similar_boost: 0.1,
+            creator_max_share: 0.4,
+            candidates: CandidatePool::default(),
+            in_flight: Arc::new(SingleFlight::new()),
+        }
+    }
+
+    /// An engine with the operator's settings from `config`
+    pub fn from_config(pool: PgPool, config: &RecommendationConfig) -> Self {
+        let mut engine = Self::new(pool, config.weights.clone())
+            .with_min_score(config.min_score)
+            .with_candidates(config.candidates.clone())
+            .with_flags(config.feature_flags.clone());
+        engine.cold_start = config.cold_start.clone();
+        engine.shuffle = config.shuffle.clone();
+        engine.trending_tie_breaks = config.trending_tie_breaks.clone();
+        engine.max_stale = config.max_stale;
+        engine.niche = config.niche.clone();
+        engine.blend = config.blend.clone();
+        engine.exclude_followed_creators = config.exclude_followed_creators;
+        engine.enabled = Arc::new(AtomicBool::new(config.engine_enabled));
+        engine.include_creator_profiles = config.include_creator_profiles;
+        engine.badge_quality_boost = config.badge_quality_boost;
+        engine.rejected_penalty = config.rejected_penalty;
+        engine.first_contact_boost = config.first_contact_boost;
+        engine.taste_space = config.taste_space.clone();
+        engine.saved_by_similar_boost = config.saved_by_similar_boost;
+        engine.creator_max_share = config.creator_max_share;
+        engine
+    }
+
+    /// Size candidate pools per `candidates`
+    pub fn with_candidates(mut self, candidates: CandidatePool) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    /// Serve candidate, feature and seen reads from `read_pool` (a read
+    /// replica); everything else stays on the primary
+    pub fn with_read_pool(mut self, read_pool: PgPool) -> Self {
+        self.read_pool = read_pool;
+        self
+    }
+
+    /// Set the score below which personalized recommendations are dropped
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    /// Creator usernames come joined onto the candidates; drop them from
+    /// feed items when profiles are switched off (`REC_INCLUDE_CREATOR_PROFILES`)
+    pub fn apply_creator_profiles(&self, items: &mut [ScoredNft]) {
+        if !self.include_creator_profiles {
+            items.iter_mut().for_each(|item| item.creator_username = None);
         }
     }
 
-    #[allow(dead_code)]
-    pub fn with_weights(pool: PgPool, weights: ScoringWeights) -> Self {
-        Self { pool, weights }
+    /// Whether personalized scoring is on (runtime-toggleable)
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// `prefs` as scoring reads them: no creator holds more than
+    /// `creator_max_share` of the creator preference above neutral. Stored
+    /// preferences stay uncapped.
+    fn scoring_preferences(&self, mut prefs: UserPreferences) -> UserPreferences {
+        super::preferences::cap_creator_share(&mut prefs.creator_preferences, self.creator_max_share);
+        prefs
+    }
+
+    /// First-contact boost for interactions recorded alongside this engine
+    pub fn first_contact_boost(&self) -> f32 {
+        self.first_contact_boost
+    }
+
+    /// Tag vocabulary for taste vectors recorded alongside this engine
+    pub fn taste_space(&self) -> &TasteSpace {
+        &self.taste_space
+    }
+
+    /// Switch personalized scoring on or off without a restart
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        info!("Recommendation engine {}", if enabled { "enabled" } else { "disabled, serving trending" });
+    }
+
+    /// Feed served while the engine is switched off
+    async fn get_fallback_feed(
+        &self,
+        limit: usize,
+        offset: usize,
+        contract_type_filter: Option<&str>,
+    ) -> Result<Vec<ScoredNft>> {
+        self.get_trending(limit, offset, contract_type_filter, TrendingMode::Trending)
+            .await
+    }
+
+    /// Replace the feature flags
+    pub fn with_flags(mut self, flags: FeatureFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Whether an experimental path is enabled for this user
+    pub fn flag_enabled(&self, flag: &str, user_address: &str) -> bool {
+        self.flags.is_enabled(flag, user_address)
+    }
+
+    /// Whether new-creator quality blending applies. Reads not made for a
+    /// user (trending) get it only once it is rolled out to everyone.
+    fn cold_start_enabled(&self, user_address: Option<&str>) -> bool {
+        match user_address {
+            Some(user) => self.flag_enabled(super::flags::COLD_START, user),
+            None => self.flags.rollout(super::flags::COLD_START) == 100,
+        }
     }
 
     /// Get personalized enhanced feed for a user
@@ -122,83 +808,63 @@ impl RecommendationEngine {
     ) -> Result<Vec<ScoredNft>> {
         use super::metrics::PerformanceTimer;
         let _timer = PerformanceTimer::new("get_enhanced_feed");
+
+        if !self.is_enabled() {
+            return self.get_fallback_feed(limit, offset, contract_type_filter).await;
+        }
         
-        let prefs = super::preferences::get_or_create_preferences(&self.pool, user_address).await?;
+        let prefs = self.scoring_preferences(
+            super::preferences::get_or_create_preferences(&self.pool, user_address).await?,
+        );
 
         // Andrew Gallant: Fetch more candidates for better diversity filtering
-        // Use multiplicative factor based on request size
-        let fetch_multiplier = if limit < 20 { 5 } else { 3 };
-        let candidates = self
-            .get_candidates(contract_type_filter, limit * fetch_multiplier, offset)
+        let fetch_count = self.candidates.enhanced(limit);
+        let mut candidates = self
+            .get_blended_candidates(
+                user_address,
+                contract_type_filter,
+                fetch_count,
+                offset,
+            )
             .await?;
 
+        let mut excluded = if self.exclude_followed_creators {
+            self.get_following_addresses(user_address).await?
+        } else {
+            Vec::new()
+        };
+        excluded.extend(super::blocks::get_blocked_users(&self.pool, user_address).await?);
+        candidates = exclude_creators(candidates, &excluded);
+
         // Niko Matsakis: Move to Rayon for CPU-bound parallel scoring
         // This doesn't block the tokio runtime
         let weights = self.weights.clone();
+        let niche = self.niche.clone();
         
-        let scored = tokio::task::spawn_blocking(move || {
-            use rayon::prelude::*;
-            let _scoring_timer = PerformanceTimer::new("parallel_scoring");
-            
-            // Ralf Jung: Parallel iteration with proper ownership semantics
-            // Process in chunks to balance parallelism overhead
-            let chunk_size = (candidates.len() / rayon::current_num_threads()).max(50);
-            
-            let mut scored: Vec<ScoredNft> = candidates
-                .into_par_iter()
-                .chunks(chunk_size)
-                .flat_map(|chunk| {
-                    let mut local_scored = Vec::with_capacity(chunk.len());
-                    
-                    for (nft, features) in chunk {
-                        let nft_id = match &nft.id {
-                            Some(id) => id.clone(),
-                            None => continue,
-                        };
-                        let contract_type = nft.contract_type.clone().unwrap_or_default();
-                        let created_at = nft.created_at.clone().unwrap_or_default();
-
-                        let ctx = ScoringContext {
-                            prefs: &prefs,
-                            contract_type: &contract_type,
-                            creator_address: &nft.creator_address,
-                            created_at: &created_at,
-                            features: &features,
-                            seen_creators: &HashMap::new(), // Per-chunk tracking
-                            seen_tags: &HashMap::new(),
-                        };
-
-                        let (score, reason) = Self::calculate_score_static(&ctx, &weights);
-
-                        local_scored.push(ScoredNft {
-                            nft_id,
-                            token_id: nft.token_id,
-                            contract_address: nft.contract_address.clone(),
-                            score,
-                            reason,
-                            contract_type,
-                            creator_address: nft.creator_address.clone(),
-                            tags: features.map(|f| f.tags).unwrap_or_default(),
-                        });
-                    }
-                    
-                    local_scored
-                })
-                .collect();
-
-            // Alex Crichton: Unstable sort for speed (we don't need stable order)
-            scored.sort_unstable_by(|a, b| {
-                b.score
-                    .partial_cmp(&a.score)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            });
-            
-            scored
+        let (mut scored, prefs) = tokio::task::spawn_blocking(move || {
+            let scored = Self::score_candidates_parallel(candidates, &prefs, &weights);
+            (scored, prefs)
         })
         .await?;
 
+        // Few tag matches for a niche user: broaden before the feed starves
+        if niche.needs_broadening(&prefs, &scored, limit) {
+            let broadened = self
+                .get_broadened_candidates(&prefs, contract_type_filter, fetch_count)
+                .await?;
+            let broadened = exclude_creators(broadened, &excluded);
+            let weights = self.weights.clone();
+            let broadened = tokio::task::spawn_blocking(move || {
+                Self::score_candidates_parallel(broadened, &prefs, &weights)
+            })
+            .await?;
+            debug!("Broadened niche feed for {} with {} candidates", user_address, broadened.len());
+            scored = NicheBroadening::merge(scored, broadened);
+        }
+        self.penalize_rejected(user_address, &mut scored).await?;
+
         // Apply diversity shuffle on already-sorted results
-        let result = Self::apply_diversity_shuffle_static(scored, limit);
+        let result = clamp_exposed_scores(self.apply_diversity_shuffle(scored, limit, user_address));
 
         debug!(
             "Generated {} recommendations for user {} (parallel scoring)",
@@ -209,96 +875,441 @@ impl RecommendationEngine {
         Ok(result)
     }
 
-    /// Get personalized recommendations for a user
-    /// This is the main method called by the Elixir GraphQL API
-    pub async fn get_recommendations(
+    /// Enhanced feed paged by keyset over `(creation_time, id)` instead of an
+    /// offset, so NFTs minted between requests can't repeat or skip items.
+    /// Each page scores the next `limit` NFTs older than `cursor`; the
+    /// returned cursor is `None` once they run out. Niche broadening is
+    /// skipped since it would pull in NFTs from outside the page's window.
+    pub async fn get_enhanced_feed_cursor(
         &self,
         user_address: &str,
         limit: usize,
         contract_type_filter: Option<&str>,
-        exclude_seen: bool,
-    ) -> Result<Vec<ScoredNft>> {
-        // Check cache first
-        if let Some(cached) =
-            get_cached_recommendations(&self.pool, user_address, "personalized").await?
-        {
-            let total = cached.len();
-            if total >= limit {
-                return Ok(cached.into_iter().take(limit).collect());
-            }
-        }
-
-        let prefs = super::preferences::get_or_create_preferences(&self.pool, user_address).await?;
+        cursor: Option<&FeedCursor>,
+    ) -> Result<(Vec<ScoredNft>, Option<FeedCursor>)> {
+        let page = self
+            .get_candidates_before(contract_type_filter, limit, cursor)
+            .await?;
+        let next_cursor = if page.len() == limit {
+            page.last().map(|c| FeedCursor {
+                created_at: c.cursor_time,
+                id: c.cursor_id,
+            })
+        } else {
+            None
+        };
 
-        // Get candidate NFTs (more than needed for diversity)
         let candidates = self
-            .get_candidates(contract_type_filter, limit * 4, 0)
+            .attach_features(page.into_iter().map(|c| c.nft).collect(), Some(user_address))
             .await?;
 
-        // Score each candidate
-        let mut scored: Vec<ScoredNft> = Vec::with_capacity(candidates.len());
-        let mut seen_creators: HashMap<String, usize> = HashMap::new();
-        let mut seen_tags: HashMap<String, usize> = HashMap::new();
+        // Switched off: the page as-is, newest first
+        if !self.is_enabled() {
+            let items = candidates
+                .into_iter()
+                .filter_map(|(nft, features)| Self::chronological_item(nft, features))
+                .collect();
+            return Ok((items, next_cursor));
+        }
 
-        for (nft, features) in candidates {
-            let nft_id = match &nft.id {
-                Some(id) => id.clone(),
-                None => continue,
-            };
+        let prefs = self.scoring_preferences(
+            super::preferences::get_or_create_preferences(&self.pool, user_address).await?,
+        );
+        let mut excluded = if self.exclude_followed_creators {
+            self.get_following_addresses(user_address).await?
+        } else {
+            Vec::new()
+        };
+        excluded.extend(super::blocks::get_blocked_users(&self.pool, user_address).await?);
+        let candidates = exclude_creators(candidates, &excluded);
 
-            // Skip if user has already seen this NFT and exclude_seen is true
-            if exclude_seen && self.has_user_seen_nft(user_address, &nft_id).await? {
-                continue;
-            }
+        let weights = self.weights.clone();
+        let mut scored = tokio::task::spawn_blocking(move || {
+            Self::score_candidates_parallel(candidates, &prefs, &weights)
+        })
+        .await?;
+        self.penalize_rejected(user_address, &mut scored).await?;
 
-            let contract_type = nft.contract_type.clone().unwrap_or_default();
-            let creator_address = nft.creator_address.clone();
-            let created_at = nft.created_at.clone().unwrap_or_default();
+        let page = clamp_exposed_scores(self.apply_diversity_shuffle(scored, limit, user_address));
+        Ok((page, next_cursor))
+    }
 
-            let ctx = ScoringContext {
-                prefs: &prefs,
-                contract_type: &contract_type,
-                creator_address: &creator_address,
-                created_at: &created_at,
-                features: &features,
-                seen_creators: &seen_creators,
-                seen_tags: &seen_tags,
-            };
+    /// Unscored feed item, ranked only by position
+    fn chronological_item(nft: CandidateNft, features: Option<NftFeatures>) -> Option<ScoredNft> {
+        Some(ScoredNft {
+            nft_id: nft.id?,
+            token_id: nft.token_id,
+            contract_address: nft.contract_address,
+            score: 0.0,
+            reason: RecommendationReason::Trending {
+                trending_score: features.as_ref().map(|f| f.trending_score).unwrap_or(0.0),
+            },
+            reasons: Vec::new(),
+            contract_type: nft.contract_type.unwrap_or_default(),
+            creator_address: nft.creator_address,
+            tags: features.map(|f| f.tags).unwrap_or_default(),
+            creator_username: nft.creator_username,
+        })
+    }
 
-            let (score, reason) = self.calculate_score(&ctx);
+    /// Degraded-mode feed for when the personalized feed can't be computed:
+    /// the user's cached feed if it is fresher than `REC_MAX_STALE_SECS`,
+    /// otherwise trending
+    pub async fn get_degraded_feed(
+        &self,
+        user_address: &str,
+        feed_type: &str,
+        limit: usize,
+        offset: usize,
+        contract_type_filter: Option<&str>,
+    ) -> Result<Vec<ScoredNft>> {
+        let cached = get_cache_entry(&self.pool, user_address, &cache_feed_type(feed_type, contract_type_filter))
+            .await
+            .unwrap_or_else(|e| {
+                warn!("Failed to read cache in degraded mode: {:?}", e);
+                None
+            });
 
-            // Track seen creators/tags for diversity
-            *seen_creators.entry(creator_address.clone()).or_insert(0) += 1;
-            if let Some(tags) = features.as_ref().map(|f| &f.tags) {
-                for tag in tags {
-                    *seen_tags.entry(tag.clone()).or_insert(0) += 1;
-                }
+        match DegradedFeed::choose(cached, chrono::Utc::now(), self.max_stale) {
+            DegradedFeed::Cache(items) => {
+                debug!("Serving stale {} cache for {}", feed_type, user_address);
+                Ok(items.into_iter().skip(offset).take(limit).collect())
+            }
+            DegradedFeed::Trending => {
+                debug!("No fresh-enough cache for {}, serving trending", user_address);
+                self.get_trending(limit, offset, contract_type_filter, TrendingMode::Trending)
+                    .await
             }
-
-            scored.push(ScoredNft {
-                nft_id,
-                token_id: nft.token_id,
-                contract_address: nft.contract_address.clone(),
-                score,
-                reason,
-                contract_type,
-                creator_address,
-                tags: features.map(|f| f.tags).unwrap_or_default(),
-            });
         }
+    }
 
-        // Sort by score descending
-        scored.sort_by(|a, b| {
-            b.score
+    /// Trending NFTs, optionally for one content type, ranked by the score for
+    /// `mode` with ties broken per `REC_TRENDING_TIE_BREAKS`
+    pub async fn get_trending(
+        &self,
+        limit: usize,
+        offset: usize,
+        contract_type_filter: Option<&str>,
+        mode: TrendingMode,
+    ) -> Result<Vec<ScoredNft>> {
+        let nfts = self
+            .get_trending_candidates(contract_type_filter, limit, offset, mode)
+            .await?;
+        let candidates = self.attach_features(nfts, None).await?;
+
+        let window_scores = match mode.window_column() {
+            Some(column) => Some(self.get_window_scores(column, &candidates).await?),
+            None => None,
+        };
+
+        let mut ranked =
+            Self::rank_trending(candidates, window_scores.as_ref(), &self.trending_tie_breaks);
+        ranked.truncate(limit);
+        Ok(ranked)
+    }
+
+    /// Hot or rising scores for the candidates, keyed by NFT id
+    async fn get_window_scores(
+        &self,
+        column: &str,
+        candidates: &[(CandidateNft, Option<NftFeatures>)],
+    ) -> Result<HashMap<String, f32>> {
+        let ids: Vec<Uuid> = candidates
+            .iter()
+            .filter_map(|(nft, _)| nft.id.as_deref().and_then(|id| Uuid::parse_str(id).ok()))
+            .collect();
+
+        let rows: Vec<(Uuid, f32)> = sqlx::query_as(&format!(
+            "SELECT nft_id, {} FROM nft_trending_windows WHERE nft_id = ANY($1)",
+            column
+        ))
+        .bind(&ids)
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(id, score)| (id.to_string(), score)).collect())
+    }
+
+    /// Sort candidates by trending score descending, breaking ties with
+    /// `tie_breaks` in order. `window_scores`, when given, replaces the
+    /// features' trending score (missing entries score 0).
+    fn rank_trending(
+        candidates: Vec<(CandidateNft, Option<NftFeatures>)>,
+        window_scores: Option<&HashMap<String, f32>>,
+        tie_breaks: &[TrendingTieBreak],
+    ) -> Vec<ScoredNft> {
+        let finite = |v: f32| if v.is_finite() { v } else { 0.0 };
+
+        let mut ranked: Vec<(ScoredNft, f32, Option<chrono::DateTime<chrono::Utc>>)> = candidates
+            .into_iter()
+            .filter_map(|(nft, features)| {
+                let nft_id = nft.id?;
+                let trending = finite(match window_scores {
+                    Some(scores) => scores.get(&nft_id).copied().unwrap_or(0.0),
+                    None => features.as_ref().map(|f| f.trending_score).unwrap_or(0.0),
+                });
+                let engagement = finite(features.as_ref().map(|f| f.engagement_score).unwrap_or(0.0));
+                let created_at = nft.created_at.as_deref().and_then(parse_created_at);
+                let scored = ScoredNft {
+                    nft_id,
+                    token_id: nft.token_id,
+                    contract_address: nft.contract_address,
+                    score: trending,
+                    reason: RecommendationReason::Trending {
+                        trending_score: trending,
+                    },
+                    reasons: Vec::new(),
+                    contract_type: nft.contract_type.unwrap_or_default(),
+                    creator_address: nft.creator_address,
+                    tags: features.map(|f| f.tags).unwrap_or_default(),
+                    creator_username: nft.creator_username,
+                };
+                Some((scored, engagement, created_at))
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            tie_breaks.iter().fold(b.0.score.total_cmp(&a.0.score), |ord, tie_break| {
+                ord.then_with(|| match tie_break {
+                    TrendingTieBreak::Engagement => b.1.total_cmp(&a.1),
+                    TrendingTieBreak::Recency => b.2.cmp(&a.2),
+                })
+            })
+        });
+
+        ranked.into_iter().map(|(scored, _, _)| scored).collect()
+    }
+
+    /// Score candidates in parallel and return them sorted by score descending.
+    /// Candidates without an id or with a non-finite score are skipped so one bad
+    /// row can't poison the sort or take down the whole feed.
+    fn score_candidates_parallel(
+        candidates: Vec<(CandidateNft, Option<NftFeatures>)>,
+        prefs: &UserPreferences,
+        weights: &ScoringWeights,
+    ) -> Vec<ScoredNft> {
+        use rayon::prelude::*;
+        use super::metrics::PerformanceTimer;
+        let _scoring_timer = PerformanceTimer::new("parallel_scoring");
+
+        // Ralf Jung: Parallel iteration with proper ownership semantics
+        // Process in chunks to balance parallelism overhead
+        let chunk_size = (candidates.len() / rayon::current_num_threads()).max(50);
+
+        let mut scored: Vec<ScoredNft> = candidates
+            .into_par_iter()
+            .chunks(chunk_size)
+            .flat_map(|chunk| {
+                let mut local_scored = Vec::with_capacity(chunk.len());
+
+                for (nft, features) in chunk {
+                    let nft_id = match &nft.id {
+                        Some(id) => id.clone(),
+                        None => continue,
+                    };
+                    let contract_type = nft.contract_type.clone().unwrap_or_default();
+                    let created_at = nft.created_at.clone().unwrap_or_default();
+
+                    let ctx = ScoringContext {
+                        prefs,
+                        contract_type: &contract_type,
+                        creator_address: &nft.creator_address,
+                        created_at: &created_at,
+                        features: &features,
+                        seen_creators: &HashMap::new(), // Per-chunk tracking
+                        seen_tags: &HashMap::new(),
+                    };
+
+                    let Some((score, reason, reasons)) = Self::score_checked(&ctx, weights, &nft_id) else {
+                        continue;
+                    };
+
+                    local_scored.push(ScoredNft {
+                        nft_id,
+                        token_id: nft.token_id,
+                        contract_address: nft.contract_address.clone(),
+                        score,
+                        reason,
+                        reasons,
+                        contract_type,
+                        creator_address: nft.creator_address.clone(),
+                        tags: features.map(|f| f.tags).unwrap_or_default(),
+                        creator_username: nft.creator_username.clone(),
+                    });
+                }
+
+                local_scored
+            })
+            .collect();
+
+        // Alex Crichton: Unstable sort for speed (we don't need stable order)
+        scored.sort_unstable_by(|a, b| {
+            b.score
                 .partial_cmp(&a.score)
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
+        scored
+    }
+
+    /// Score a single candidate, returning `None` if scoring panics or yields
+    /// a non-finite value (NaN/inf from corrupt feature rows).
+    fn score_checked(
+        ctx: &ScoringContext<'_>,
+        weights: &ScoringWeights,
+        nft_id: &str,
+    ) -> Option<(f32, RecommendationReason, Vec<RecommendationReason>)> {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            Self::calculate_score_static(ctx, weights)
+        }));
+
+        match result {
+            Ok((score, reason, reasons)) if score.is_finite() => Some((score, reason, reasons)),
+            Ok((score, _, _)) => {
+                warn!("Skipping candidate {} with non-finite score {}", nft_id, score);
+                None
+            }
+            Err(_) => {
+                warn!("Skipping candidate {} after scoring panicked", nft_id);
+                None
+            }
+        }
+    }
+
+    /// Get personalized recommendations for a user
+    /// This is the main method called by the Elixir GraphQL API
+    pub async fn get_recommendations(
+        &self,
+        user_address: &str,
+        limit: usize,
+        contract_type_filter: Option<&str>,
+        exclude_seen: bool,
+    ) -> Result<Vec<ScoredNft>> {
+        if !self.is_enabled() {
+            return self.get_fallback_feed(limit, 0, contract_type_filter).await;
+        }
+
+        // Check cache first
+        let feed_type = cache_feed_type("personalized", contract_type_filter);
+        if let Some(cached) = get_cached_recommendations(&self.pool, user_address, &feed_type).await? {
+            let total = cached.len();
+            if total >= limit {
+                return Ok(cached.into_iter().take(limit).collect());
+            }
+        }
+
+        let key = format!(
+            "{}:personalized:{}:{}:{}",
+            user_address.to_lowercase(),
+            limit,
+            contract_type_filter.unwrap_or(""),
+            exclude_seen
+        );
+        self.single_flight(
+            &key,
+            self.compute_recommendations(user_address, limit, contract_type_filter, exclude_seen),
+        )
+        .await
+    }
+
+    /// Run `compute` unless a computation for `key` is already in flight, in
+    /// which case wait for that one and return its result instead
+    pub async fn single_flight<Fut>(&self, key: &str, compute: Fut) -> Result<Vec<ScoredNft>>
+    where
+        Fut: std::future::Future<Output = Result<Vec<ScoredNft>>>,
+    {
+        self.in_flight
+            .run(key, || async { compute.await.map_err(Arc::new) })
+            .await
+            .map_err(|e| anyhow::anyhow!("{:#}", e))
+    }
+
+    /// Score, rank and cache personalized recommendations, bypassing the cache
+    async fn compute_recommendations(
+        &self,
+        user_address: &str,
+        limit: usize,
+        contract_type_filter: Option<&str>,
+        exclude_seen: bool,
+    ) -> Result<Vec<ScoredNft>> {
+        let prefs = self.scoring_preferences(
+            super::preferences::get_or_create_preferences(&self.pool, user_address).await?,
+        );
+
+        // Get candidate NFTs (more than needed for diversity)
+        let candidates = self
+            .get_candidates(contract_type_filter, self.candidates.personalized(limit), 0, user_address)
+            .await?;
+
+        // Score each candidate
+        let mut scored: Vec<ScoredNft> = Vec::with_capacity(candidates.len());
+        let mut seen_creators: HashMap<String, usize> = HashMap::new();
+        let mut seen_tags: HashMap<String, usize> = HashMap::new();
+
+        for (nft, features) in candidates {
+            let nft_id = match &nft.id {
+                Some(id) => id.clone(),
+                None => continue,
+            };
+
+            // Skip if user has already seen this NFT and exclude_seen is true
+            if exclude_seen && self.has_user_seen_nft(user_address, &nft_id).await? {
+                continue;
+            }
+
+            let contract_type = nft.contract_type.clone().unwrap_or_default();
+            let creator_address = nft.creator_address.clone();
+            let created_at = nft.created_at.clone().unwrap_or_default();
+
+            let ctx = ScoringContext {
+                prefs: &prefs,
+                contract_type: &contract_type,
+                creator_address: &creator_address,
+                created_at: &created_at,
+                features: &features,
+                seen_creators: &seen_creators,
+                seen_tags: &seen_tags,
+            };
+
+            let Some((score, reason, reasons)) = Self::score_checked(&ctx, &self.weights, &nft_id) else {
+                continue;
+            };
+
+            // Track seen creators/tags for diversity
+            *seen_creators.entry(creator_address.clone()).or_insert(0) += 1;
+            if let Some(tags) = features.as_ref().map(|f| &f.tags) {
+                for tag in tags {
+                    *seen_tags.entry(tag.clone()).or_insert(0) += 1;
+                }
+            }
+
+            scored.push(ScoredNft {
+                nft_id,
+                token_id: nft.token_id,
+                contract_address: nft.contract_address.clone(),
+                score,
+                reason,
+                reasons,
+                contract_type,
+                creator_address,
+                tags: features.map(|f| f.tags).unwrap_or_default(),
+                creator_username: nft.creator_username.clone(),
+            });
+        }
+
+        self.boost_saved_by_similar(&prefs, &mut scored).await?;
+
+        // Sort by score descending, rejected NFTs pushed down
+        self.penalize_rejected(user_address, &mut scored).await?;
+
+        let scored = Self::filter_min_score(scored, self.min_score, limit);
+
         // Apply diversity and discovery
-        let result = self.apply_diversity_shuffle(scored, limit);
+        let result = clamp_exposed_scores(self.apply_diversity_shuffle(scored, limit, user_address));
 
         // Cache for 10 minutes
-        let _ = cache_recommendations(&self.pool, user_address, "personalized", &result, 10).await;
+        let feed_type = cache_feed_type("personalized", contract_type_filter);
+        let _ = cache_recommendations(&self.pool, user_address, &feed_type, &result, 10).await;
 
         debug!(
             "Generated {} personalized recommendations for user {}",
@@ -309,6 +1320,88 @@ impl RecommendationEngine {
         Ok(result)
     }
 
+    /// Score the personalized candidates for `user_address` with a per-factor
+    /// breakdown, best first. Read-only: nothing is cached and no preferences
+    /// are created for unknown users.
+    pub async fn explain(&self, user_address: &str, limit: usize) -> Result<Vec<ExplainedNft>> {
+        let prefs = self.scoring_preferences(
+            super::preferences::get_preferences(&self.pool, user_address)
+                .await?
+                .unwrap_or_else(|| UserPreferences {
+                    user_address: user_address.to_lowercase(),
+                    ..Default::default()
+                }),
+        );
+
+        let candidates = self
+            .get_candidates(None, self.candidates.personalized(limit), 0, user_address)
+            .await?;
+
+        let mut explained: Vec<ExplainedNft> = Vec::with_capacity(candidates.len());
+        let mut seen_creators: HashMap<String, usize> = HashMap::new();
+        let mut seen_tags: HashMap<String, usize> = HashMap::new();
+
+        for (nft, features) in candidates {
+            let Some(nft_id) = nft.id.clone() else {
+                continue;
+            };
+
+            let contract_type = nft.contract_type.clone().unwrap_or_default();
+            let created_at = nft.created_at.clone().unwrap_or_default();
+
+            let ctx = ScoringContext {
+                prefs: &prefs,
+                contract_type: &contract_type,
+                creator_address: &nft.creator_address,
+                created_at: &created_at,
+                features: &features,
+                seen_creators: &seen_creators,
+                seen_tags: &seen_tags,
+            };
+
+            let (breakdown, reason, _) = Self::score_breakdown(&ctx, &self.weights);
+            let score = breakdown.total();
+            if !score.is_finite() {
+                continue;
+            }
+
+            *seen_creators.entry(nft.creator_address.clone()).or_insert(0) += 1;
+            if let Some(tags) = features.as_ref().map(|f| &f.tags) {
+                for tag in tags {
+                    *seen_tags.entry(tag.clone()).or_insert(0) += 1;
+                }
+            }
+
+            explained.push(ExplainedNft {
+                nft_id,
+                score,
+                reason,
+                breakdown,
+                saved_by_similar: 0.0,
+                rejected_penalty: 0.0,
+            });
+        }
+
+        let nft_ids: Vec<String> = explained.iter().map(|item| item.nft_id.clone()).collect();
+        let saves = self.similar_saves_for(&prefs, &nft_ids).await?;
+        let rejected = self.rejected_nfts(user_address).await?;
+        adjust_explained(
+            &mut explained,
+            &saves,
+            self.saved_by_similar_boost,
+            &rejected,
+            self.rejected_penalty,
+        );
+
+        explained.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        explained.truncate(limit);
+        Ok(explained)
+    }
+
     /// Get feed from followed users only
     pub async fn get_following_feed(
         &self,
@@ -330,6 +1423,8 @@ impl RecommendationEngine {
 
         // Score them (simpler scoring for following feed - mostly chronological)
         let mut scored: Vec<ScoredNft> = Vec::new();
+        let ids: Vec<String> = nfts.iter().filter_map(|n| n.id.clone()).collect();
+        let mut features_by_id = super::features::get_features_batch(&self.read_pool, &ids).await?;
 
         for nft in nfts {
             let nft_id = match &nft.id {
@@ -339,7 +1434,7 @@ impl RecommendationEngine {
             let contract_type = nft.contract_type.clone().unwrap_or_default();
             let created_at = nft.created_at.clone().unwrap_or_default();
 
-            let features = self.get_nft_features(&nft_id).await?;
+            let features = features_by_id.remove(&nft_id);
 
             // For following feed, score is mainly recency + engagement
             let recency_score = Self::compute_recency_score(&created_at);
@@ -355,9 +1450,11 @@ impl RecommendationEngine {
                 reason: RecommendationReason::Following {
                     followee: nft.creator_address.clone(),
                 },
+                reasons: Vec::new(),
                 contract_type,
                 creator_address: nft.creator_address.clone(),
                 tags: features.map(|f| f.tags).unwrap_or_default(),
+                creator_username: nft.creator_username.clone(),
             });
         }
 
@@ -371,9 +1468,6 @@ impl RecommendationEngine {
         Ok(scored)
     }
 
-    /// Context used for scoring a single NFT
-
-
     // ---- Scoring helpers (pure functions) ----
     
     /// ByteGraph-inspired content type affinity scoring with dynamic boosting
@@ -438,10 +1532,12 @@ impl RecommendationEngine {
         prefs: &UserPreferences,
         seen_creators: &HashMap<String, usize>,
         seen_tags: &HashMap<String, usize>,
-    ) -> (f32, Option<RecommendationReason>) {
+        breakdown: &mut ScoreBreakdown,
+    ) -> (f32, Option<RecommendationReason>, Vec<(f32, RecommendationReason)>) {
         let mut total = 0.0f32;
         let mut primary: Option<RecommendationReason> = None;
         let mut max_score = 0.0f32;
+        let mut contributions = Vec::new();
 
         // ByteGraph-style tag matching with exponential boost for multiple matches
         // Tags include user-provided hashtags (max 3) from metadata for personalized recommendations
@@ -482,30 +1578,45 @@ impl RecommendationEngine {
             -0.1 * weights.tag_match
         };
         total += tag_match_score.max(0.0);
+        breakdown.tag_match = tag_match_score.max(0.0);
         
-        if tag_match_score > max_score && !matching_tags.is_empty() {
-            max_score = tag_match_score;
-            primary = Some(RecommendationReason::TagMatch { matching_tags });
+        if !matching_tags.is_empty() {
+            let reason = RecommendationReason::TagMatch { matching_tags };
+            if tag_match_score > max_score {
+                max_score = tag_match_score;
+                primary = Some(reason.clone());
+            }
+            contributions.push((tag_match_score, reason));
         }
 
         // Trending (reduced weight in ByteGraph-style - personalization trumps trending)
         let trending_contrib = f.trending_score * weights.trending;
         total += trending_contrib;
-        if f.trending_score > 0.7 && trending_contrib > max_score {
-            max_score = trending_contrib;
-            primary = Some(RecommendationReason::Trending { trending_score: f.trending_score });
+        breakdown.trending = trending_contrib;
+        if f.trending_score > 0.7 {
+            let reason = RecommendationReason::Trending { trending_score: f.trending_score };
+            if trending_contrib > max_score {
+                max_score = trending_contrib;
+                primary = Some(reason.clone());
+            }
+            contributions.push((trending_contrib, reason));
         }
 
         // Engagement
         let engagement_contrib = f.engagement_score * weights.engagement;
         total += engagement_contrib;
-        if f.engagement_score > 0.8 && engagement_contrib > max_score {
-            let _max_score = engagement_contrib;  // Final assignment, intentionally unused
-            primary = Some(RecommendationReason::HighEngagement { engagement_score: f.engagement_score });
+        breakdown.engagement = engagement_contrib;
+        if f.engagement_score > 0.8 {
+            let reason = RecommendationReason::HighEngagement { engagement_score: f.engagement_score };
+            if engagement_contrib > max_score {
+                primary = Some(reason.clone());
+            }
+            contributions.push((engagement_contrib, reason));
         }
 
         // Quality
         total += f.quality_score * weights.quality;
+        breakdown.quality = f.quality_score * weights.quality;
 
         // ByteGraph diversity penalties with diminishing returns
         let creator_count = seen_creators.get(&f.contract_address).copied().unwrap_or(0);
@@ -513,6 +1624,7 @@ impl RecommendationEngine {
             // Logarithmic penalty: more same-creator content = exponentially less appealing
             let penalty_multiplier = (creator_count as f32).ln() / 2.0;
             total -= weights.diversity_penalty * penalty_multiplier * 0.15;
+            breakdown.diversity_penalty += weights.diversity_penalty * penalty_multiplier * 0.15;
         }
 
         // Tag oversaturation with smart thresholding
@@ -523,46 +1635,60 @@ impl RecommendationEngine {
             // Square root penalty for smoother degradation
             let penalty = (tag_oversaturation - 4.0).sqrt() * 0.03;
             total -= weights.diversity_penalty * penalty;
+            breakdown.diversity_penalty += weights.diversity_penalty * penalty;
         }
 
-        (total, primary)
+        (total, primary, contributions)
     }
 
-    fn calculate_score(&self, ctx: &ScoringContext<'_>) -> (f32, RecommendationReason) {
-        Self::calculate_score_static(ctx, &self.weights)
+    /// Static version for parallel processing (Niko Matsakis optimization)
+    /// Allows Rayon to process scores without self reference.
+    /// Returns the score, the primary reason and the top contributing reasons.
+    fn calculate_score_static(
+        ctx: &ScoringContext<'_>,
+        weights: &ScoringWeights,
+    ) -> (f32, RecommendationReason, Vec<RecommendationReason>) {
+        let (breakdown, primary_reason, reasons) = Self::score_breakdown(ctx, weights);
+        (breakdown.ranking_score(weights.negative_grace), primary_reason, reasons)
     }
 
-    /// Static version for parallel processing (Niko Matsakis optimization)
-    /// Allows Rayon to process scores without self reference
-    fn calculate_score_static(ctx: &ScoringContext<'_>, weights: &ScoringWeights) -> (f32, RecommendationReason) {
-        let mut score = 0.0;
+    /// Per-factor scoring behind `calculate_score_static`
+    fn score_breakdown(
+        ctx: &ScoringContext<'_>,
+        weights: &ScoringWeights,
+    ) -> (ScoreBreakdown, RecommendationReason, Vec<RecommendationReason>) {
+        let mut breakdown = ScoreBreakdown::default();
         let mut primary_reason = RecommendationReason::Discovery;
         let mut max_reason_score = 0.0f32;
+        let mut contributions = Vec::new();
 
         // 1. Content type affinity
         let (type_score, type_reason) = Self::compute_type_affinity_score(weights, ctx.contract_type, ctx.prefs);
-        score += type_score;
+        breakdown.content_type = type_score;
         if let Some(r) = type_reason {
             if type_score > max_reason_score {
                 max_reason_score = type_score;
-                primary_reason = r;
+                primary_reason = r.clone();
             }
+            contributions.push((type_score, r));
         }
 
         // 2. Creator affinity
         let (creator_score, creator_reason) = Self::compute_creator_affinity_score(weights, ctx.creator_address, ctx.prefs);
-        score += creator_score;
+        breakdown.creator = creator_score;
         if let Some(r) = creator_reason {
             if creator_score > max_reason_score {
                 max_reason_score = creator_score;
-                primary_reason = r;
+                primary_reason = r.clone();
             }
+            contributions.push((creator_score, r));
         }
 
         // Feature-based scores
         if let Some(ref f) = ctx.features {
-            let (feature_score, feature_reason) = Self::compute_feature_scores(weights, f, ctx.prefs, ctx.seen_creators, ctx.seen_tags);
-            score += feature_score;
+            let (feature_score, feature_reason, feature_contributions) =
+                Self::compute_feature_scores(weights, f, ctx.prefs, ctx.seen_creators, ctx.seen_tags, &mut breakdown);
+            contributions.extend(feature_contributions);
             if let Some(r) = feature_reason {
                 if feature_score > max_reason_score {
                     let _max_reason_score = feature_score;  // Final assignment, intentionally unused
@@ -573,36 +1699,61 @@ impl RecommendationEngine {
 
         // 8. Recency bonus
         let recency = Self::compute_recency_score(ctx.created_at);
-        score += recency * weights.recency;
-
-        // Clamp score to 0-1
-        score = score.clamp(0.0, 1.0);
+        breakdown.recency = recency * weights.recency;
 
-        (score, primary_reason)
+        let reasons = rank_reasons(&primary_reason, contributions, MAX_VERBOSE_REASONS);
+        (breakdown, primary_reason, reasons)
     }
 
     fn compute_recency_score(created_at: &str) -> f32 {
         // Parse timestamp and calculate decay
         // Newer = higher score
-        match chrono::DateTime::parse_from_rfc3339(created_at) {
-            Ok(dt) => {
-                let age_hours =
-                    (chrono::Utc::now() - dt.with_timezone(&chrono::Utc)).num_hours() as f32;
+        match parse_created_at(created_at) {
+            Some(dt) => {
+                let age_hours = (chrono::Utc::now() - dt).num_hours() as f32;
                 // Exponential decay: half-life of 24 hours
                 (-age_hours / 24.0).exp()
             }
-            Err(_) => 0.5, // Default if parse fails
+            None => 0.5, // Default if parse fails
+        }
+    }
+
+    /// Drop candidates scoring below `min_score`, keeping them all instead
+    /// when fewer than `limit / 2` would survive
+    fn filter_min_score(scored: Vec<ScoredNft>, min_score: f32, limit: usize) -> Vec<ScoredNft> {
+        let passing = scored.iter().filter(|s| s.score >= min_score).count();
+        if passing == scored.len() || passing < limit / 2 {
+            return scored;
         }
+        scored.into_iter().filter(|s| s.score >= min_score).collect()
     }
 
-    /// Apply slight randomization to top results for discovery
-    fn apply_diversity_shuffle(&self, scored: Vec<ScoredNft>, limit: usize) -> Vec<ScoredNft> {
-        Self::apply_diversity_shuffle_static(scored, limit)
+    /// Apply slight randomization to top results for discovery, after
+    /// reranking them for novelty when the MMR flag is on for the user
+    fn apply_diversity_shuffle(
+        &self,
+        scored: Vec<ScoredNft>,
+        limit: usize,
+        user_address: &str,
+    ) -> Vec<ScoredNft> {
+        let scored = if self.flag_enabled(super::flags::MMR_RERANKER, user_address) {
+            mmr_rerank(scored, limit, MMR_LAMBDA)
+        } else {
+            scored
+        };
+        Self::apply_diversity_shuffle_static(scored, limit, self.shuffle.seed_now(user_address))
     }
 
-    /// Static version for use in parallel contexts (Andrew Gallant optimization)
-    fn apply_diversity_shuffle_static(mut scored: Vec<ScoredNft>, limit: usize) -> Vec<ScoredNft> {
+    /// Static version for use in parallel contexts (Andrew Gallant optimization).
+    /// Discovery picks are drawn from an RNG seeded with `seed`, so the same
+    /// seed always yields the same selection.
+    fn apply_diversity_shuffle_static(
+        mut scored: Vec<ScoredNft>,
+        limit: usize,
+        seed: u64,
+    ) -> Vec<ScoredNft> {
         use rand::seq::SliceRandom;
+        use rand::SeedableRng;
 
         if scored.len() <= limit {
             return scored;
@@ -612,205 +1763,1830 @@ impl RecommendationEngine {
         let deterministic_count = (limit as f32 * 0.8) as usize;
         let shuffle_count = limit - deterministic_count;
 
-        let mut result: Vec<ScoredNft> = scored.drain(..deterministic_count).collect();
+        let mut result: Vec<ScoredNft> = scored.drain(..deterministic_count).collect();
+
+        // From remaining, pick some randomly for discovery
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let remaining: Vec<_> = scored.into_iter().take(shuffle_count * 3).collect();
+
+        if !remaining.is_empty() {
+            let chosen: Vec<_> = remaining
+                .choose_multiple(&mut rng, shuffle_count.min(remaining.len()))
+                .cloned()
+                .collect();
+            result.extend(chosen);
+        }
+
+        result
+    }
+
+    /// Boost the items in `scored` that users with taste similar to `prefs`
+    /// have saved
+    async fn boost_saved_by_similar(&self, prefs: &UserPreferences, scored: &mut [ScoredNft]) -> Result<()> {
+        let nft_ids: Vec<String> = scored.iter().map(|item| item.nft_id.clone()).collect();
+        let saves = self.similar_saves_for(prefs, &nft_ids).await?;
+        apply_saved_by_similar(scored, &saves, self.saved_by_similar_boost);
+        Ok(())
+    }
+
+    /// Which of `nft_ids` users with taste similar to `prefs` saved; nothing
+    /// is looked up while the boost is off
+    async fn similar_saves_for(
+        &self,
+        prefs: &UserPreferences,
+        nft_ids: &[String],
+    ) -> Result<HashMap<String, SimilarSaves>> {
+        if self.saved_by_similar_boost <= 0.0 || nft_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let saver_tastes = get_saver_tastes(&self.read_pool, &prefs.user_address, nft_ids).await?;
+        let taste = super::preferences::compute_taste_vector(prefs, &self.taste_space);
+        Ok(similar_saves(&taste, saver_tastes, SIMILAR_SAVER_MIN_SIMILARITY))
+    }
+
+    /// Push NFTs the user explicitly rejected down `scored`, which ends up
+    /// sorted by score
+    async fn penalize_rejected(&self, user_address: &str, scored: &mut [ScoredNft]) -> Result<()> {
+        let rejected = self.rejected_nfts(user_address).await?;
+        apply_rejection_penalty(scored, &rejected, self.rejected_penalty);
+        Ok(())
+    }
+
+    /// NFTs the user unliked or unsaved; nothing is looked up while the
+    /// penalty is off
+    async fn rejected_nfts(&self, user_address: &str) -> Result<HashSet<String>> {
+        if self.rejected_penalty <= 0.0 {
+            return Ok(HashSet::new());
+        }
+        get_rejected_nfts(&self.read_pool, user_address).await
+    }
+
+    /// Check if user has already seen/interacted with an NFT
+    async fn has_user_seen_nft(&self, user_address: &str, nft_id: &str) -> Result<bool> {
+        let result: Option<bool> = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM user_interactions 
+                WHERE user_address = $1 
+                AND nft_id = $2 
+                AND interaction_type IN ('view', 'like', 'purchase', 'save')
+                AND created_at > NOW() - INTERVAL '30 days'
+            )
+            "#,
+        )
+        .bind(user_address.to_lowercase())
+        .bind(nft_id)
+        .fetch_optional(&self.read_pool)
+        .await?;
+
+        Ok(result.unwrap_or(false))
+    }
+
+
+
+    // Database query helpers
+
+
+    async fn get_candidates(
+        &self,
+        contract_type_filter: Option<&str>,
+        limit: usize,
+        offset: usize,
+        user_address: &str,
+    ) -> Result<Vec<(CandidateNft, Option<NftFeatures>)>> {
+        let candidates = fetch_candidates(&self.read_pool, contract_type_filter, limit, offset).await?;
+        self.adjust_candidate_quality(candidates, Some(user_address)).await
+    }
+
+    /// Up to `limit` NFTs strictly older than `cursor` in `(creation_time, id)`
+    /// order, newest first
+    async fn get_candidates_before(
+        &self,
+        contract_type_filter: Option<&str>,
+        limit: usize,
+        cursor: Option<&FeedCursor>,
+    ) -> Result<Vec<KeysetCandidate>> {
+        let rows = sqlx::query_as::<_, KeysetCandidate>(
+            r#"
+            SELECT n.id::text, n.token_id, n.contract_address, n.contract_type::text,
+                   n.creator_address, n.creation_time::text as created_at,
+                   NULLIF(cp.username, '') AS creator_username,
+                   n.creation_time AS cursor_time, n.id AS cursor_id
+            FROM nfts n
+            LEFT JOIN creator_profiles cp ON cp.address = LOWER(n.creator_address)
+            WHERE n.is_deleted = false
+            AND n.is_original = true
+            AND ($1::text IS NULL OR n.contract_type::text = $1)
+            AND ($2::timestamp IS NULL OR (n.creation_time, n.id) < ($2, $3))
+            ORDER BY n.creation_time DESC, n.id DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(contract_type_filter)
+        .bind(cursor.map(|c| c.created_at))
+        .bind(cursor.map(|c| c.id))
+        .bind(limit as i64)
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Enhanced-feed candidates drawn from each source per the configured blend.
+    /// Collaborative candidates need the `collaborative_filtering` flag, and
+    /// following candidates are skipped when followed creators are excluded.
+    async fn get_blended_candidates(
+        &self,
+        user_address: &str,
+        contract_type_filter: Option<&str>,
+        total: usize,
+        offset: usize,
+    ) -> Result<Vec<(CandidateNft, Option<NftFeatures>)>> {
+        let quotas: HashMap<_, _> = self.blend.quotas(total).into_iter().collect();
+        let offsets: HashMap<_, _> = self.blend.quotas(offset).into_iter().collect();
+        let quota = |source| quotas.get(&source).copied().unwrap_or(0);
+        let skip = |source| offsets.get(&source).copied().unwrap_or(0);
+
+        let mut sources = HashMap::new();
+        // Fetched in full so it can backfill sources that come up short
+        sources.insert(
+            CandidateSource::Personalized,
+            self.get_candidates(
+                contract_type_filter,
+                total,
+                skip(CandidateSource::Personalized),
+                user_address,
+            )
+            .await?,
+        );
+
+        if quota(CandidateSource::Trending) > 0 {
+            let nfts = self
+                .get_trending_candidates(
+                    contract_type_filter,
+                    quota(CandidateSource::Trending),
+                    skip(CandidateSource::Trending),
+                    TrendingMode::Trending,
+                )
+                .await?;
+            sources.insert(CandidateSource::Trending, self.attach_features(nfts, Some(user_address)).await?);
+        }
+
+        if quota(CandidateSource::Collaborative) > 0
+            && self.flag_enabled(super::flags::COLLABORATIVE_FILTERING, user_address)
+        {
+            let nfts = self
+                .get_collaborative_candidates(
+                    user_address,
+                    contract_type_filter,
+                    quota(CandidateSource::Collaborative),
+                    skip(CandidateSource::Collaborative),
+                )
+                .await?;
+            sources.insert(
+                CandidateSource::Collaborative,
+                self.attach_features(nfts, Some(user_address)).await?,
+            );
+        }
+
+        if quota(CandidateSource::Following) > 0 && !self.exclude_followed_creators {
+            let followed = self.get_following_addresses(user_address).await?;
+            if !followed.is_empty() {
+                let nfts = self
+                    .get_nfts_from_creators(
+                        &followed,
+                        quota(CandidateSource::Following),
+                        skip(CandidateSource::Following),
+                    )
+                    .await?
+                    .into_iter()
+                    .filter(|n| {
+                        contract_type_filter
+                            .map_or(true, |ct| n.contract_type.as_deref() == Some(ct))
+                    })
+                    .collect();
+                sources.insert(
+                    CandidateSource::Following,
+                    self.attach_features(nfts, Some(user_address)).await?,
+                );
+            }
+        }
+
+        Ok(self.blend.blend(total, sources))
+    }
+
+    /// Candidates with the highest scores for `mode`, newest first among
+    /// equals (so NFTs not scored yet still fill the page)
+    async fn get_trending_candidates(
+        &self,
+        contract_type_filter: Option<&str>,
+        limit: usize,
+        offset: usize,
+        mode: TrendingMode,
+    ) -> Result<Vec<CandidateNft>> {
+        let (join, score) = match mode.window_column() {
+            Some(column) => (
+                "LEFT JOIN nft_trending_windows w ON w.nft_id = n.id",
+                format!("w.{}", column),
+            ),
+            None => ("LEFT JOIN nft_features f ON f.nft_id = n.id", "f.trending_score".to_string()),
+        };
+        let nfts = sqlx::query_as::<_, CandidateNft>(&format!(
+            r#"
+            SELECT n.id::text, n.token_id, n.contract_address, n.contract_type::text,
+                   n.creator_address, n.creation_time::text as created_at, NULLIF(cp.username, '') AS creator_username
+            FROM nfts n
+            LEFT JOIN creator_profiles cp ON cp.address = LOWER(n.creator_address)
+            {}
+            WHERE n.is_deleted = false
+            AND n.is_original = true
+            AND ($1::text IS NULL OR n.contract_type::text = $1)
+            ORDER BY COALESCE({}, 0) DESC, n.creation_time DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            join, score
+        ))
+        .bind(contract_type_filter)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        Ok(nfts)
+    }
+
+    /// Candidates engaged with by users who engaged with the same NFTs as
+    /// `user_address`, most shared peers first
+    async fn get_collaborative_candidates(
+        &self,
+        user_address: &str,
+        contract_type_filter: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<CandidateNft>> {
+        let nfts = sqlx::query_as::<_, CandidateNft>(
+            r#"
+            WITH mine AS (
+                SELECT DISTINCT nft_id FROM user_interactions
+                WHERE user_address = $1
+                AND interaction_type IN ('like', 'purchase', 'comment', 'save')
+            ),
+            peers AS (
+                SELECT DISTINCT i.user_address FROM user_interactions i
+                JOIN mine m ON m.nft_id = i.nft_id
+                WHERE i.user_address <> $1
+                AND i.interaction_type IN ('like', 'purchase', 'comment', 'save')
+                LIMIT 500
+            ),
+            picks AS (
+                SELECT i.nft_id, COUNT(DISTINCT i.user_address) AS peer_count
+                FROM user_interactions i
+                JOIN peers p ON p.user_address = i.user_address
+                WHERE i.interaction_type IN ('like', 'purchase', 'comment', 'save')
+                AND i.nft_id NOT IN (SELECT nft_id FROM mine)
+                GROUP BY i.nft_id
+            )
+            SELECT n.id::text, n.token_id, n.contract_address, n.contract_type::text,
+                   n.creator_address, n.creation_time::text as created_at, NULLIF(cp.username, '') AS creator_username
+            FROM picks
+            JOIN nfts n ON n.id = picks.nft_id
+            LEFT JOIN creator_profiles cp ON cp.address = LOWER(n.creator_address)
+            WHERE n.is_deleted = false
+            AND n.is_original = true
+            AND ($2::text IS NULL OR n.contract_type::text = $2)
+            ORDER BY picks.peer_count DESC
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(user_address.to_lowercase())
+        .bind(contract_type_filter)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        Ok(nfts)
+    }
+
+    /// Candidates in the user's preferred content types or carrying tags that
+    /// co-occur with their preferred tags
+    async fn get_broadened_candidates(
+        &self,
+        prefs: &UserPreferences,
+        contract_type_filter: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<(CandidateNft, Option<NftFeatures>)>> {
+        let user_tags: Vec<String> = prefs
+            .tag_preferences
+            .iter()
+            .filter(|(_, w)| **w > 0.6)
+            .map(|(t, _)| t.to_lowercase())
+            .collect();
+
+        let co_tags = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT LOWER(t)
+            FROM nft_features f, UNNEST(f.tags) AS t
+            WHERE f.tags && $1
+            AND NOT (LOWER(t) = ANY($1))
+            GROUP BY LOWER(t)
+            ORDER BY COUNT(*) DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(&user_tags)
+        .bind(self.niche.co_tag_limit as i64)
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        let nfts = sqlx::query_as::<_, CandidateNft>(
+            r#"
+            SELECT n.id::text, n.token_id, n.contract_address, n.contract_type::text,
+                   n.creator_address, n.creation_time::text as created_at, NULLIF(cp.username, '') AS creator_username
+            FROM nfts n
+            LEFT JOIN nft_features f ON f.nft_id = n.id
+            LEFT JOIN creator_profiles cp ON cp.address = LOWER(n.creator_address)
+            WHERE n.is_deleted = false
+            AND n.is_original = true
+            AND ($3::text IS NULL OR n.contract_type::text = $3)
+            AND (n.contract_type::text = ANY($1) OR f.tags && $2)
+            ORDER BY n.creation_time DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(preferred_content_types(prefs))
+        .bind(&co_tags)
+        .bind(contract_type_filter)
+        .bind(limit as i64)
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        self.attach_features(nfts, Some(&prefs.user_address)).await
+    }
+
+    /// Load features for candidates, applying new-creator quality blending
+    /// (when the cold-start flag is on for `user_address`) and the badge boost
+    async fn attach_features(
+        &self,
+        nfts: Vec<CandidateNft>,
+        user_address: Option<&str>,
+    ) -> Result<Vec<(CandidateNft, Option<NftFeatures>)>> {
+        // One round-trip for every candidate's features
+        let ids: Vec<String> = nfts.iter().filter_map(|n| n.id.clone()).collect();
+        let mut features_by_id = super::features::get_features_batch(&self.read_pool, &ids).await?;
+
+        let candidates = nfts
+            .into_iter()
+            .filter_map(|nft| {
+                let features = features_by_id.remove(nft.id.as_ref()?);
+                Some((nft, features))
+            })
+            .collect();
+        self.adjust_candidate_quality(candidates, user_address).await
+    }
+
+    /// Fold creator cold-start and badge adjustments into each candidate's
+    /// quality score
+    async fn adjust_candidate_quality(
+        &self,
+        mut candidates: Vec<(CandidateNft, Option<NftFeatures>)>,
+        user_address: Option<&str>,
+    ) -> Result<Vec<(CandidateNft, Option<NftFeatures>)>> {
+        let cold_start = self.cold_start_enabled(user_address);
+        let creators: Vec<String> = candidates
+            .iter()
+            .map(|(n, _)| n.creator_address.to_lowercase())
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+        let creator_interactions = self.get_creator_interaction_counts(&creators).await?;
+        let creator_badges = if self.badge_quality_boost > 0.0 {
+            super::badges::get_badge_counts(&self.read_pool, &creators).await?
+        } else {
+            HashMap::new()
+        };
+
+        for (nft, features) in candidates.iter_mut() {
+            if let Some(f) = features.as_mut() {
+                let interactions = creator_interactions
+                    .get(&nft.creator_address.to_lowercase())
+                    .copied()
+                    .unwrap_or(0);
+                if cold_start {
+                    f.quality_score = self.cold_start.effective_quality(f.quality_score, interactions);
+                }
+                let badges = creator_badges
+                    .get(&nft.creator_address.to_lowercase())
+                    .copied()
+                    .unwrap_or(0);
+                f.quality_score = super::badges::badge_quality(f.quality_score, badges, self.badge_quality_boost);
+            }
+        }
+
+        Ok(candidates)
+    }
+
+    async fn get_creator_interaction_counts(&self, creators: &[String]) -> Result<HashMap<String, u64>> {
+        if creators.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT creator, COUNT(*)
+            FROM (
+                SELECT COALESCE(NULLIF(i.nft_creator_address, ''), LOWER(n.creator_address)) AS creator
+                FROM user_interactions i
+                LEFT JOIN nfts n ON n.id = i.nft_id
+            ) resolved
+            WHERE creator = ANY($1)
+            GROUP BY creator
+            "#,
+        )
+        .bind(creators)
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(c, n)| (c, n.max(0) as u64)).collect())
+    }
+
+    async fn get_following_addresses(&self, user_address: &str) -> Result<Vec<String>> {
+        let rows = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT u.address 
+            FROM follows f
+            JOIN social_users u ON u.id = f.followee_id
+            JOIN social_users follower ON follower.id = f.follower_id
+            WHERE follower.address = $1 AND f.is_active = true
+            UNION
+            SELECT target_address FROM user_follows
+            WHERE follower_address = $1 AND is_active = true
+            "#,
+        )
+        .bind(user_address.to_lowercase())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    async fn get_nfts_from_creators(
+        &self,
+        creators: &[String],
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<CandidateNft>> {
+        let nfts = sqlx::query_as::<_, CandidateNft>(
+            r#"
+            SELECT n.id::text, n.token_id, n.contract_address, n.contract_type::text,
+                   n.creator_address, n.creation_time::text as created_at,
+                   NULLIF(cp.username, '') AS creator_username
+            FROM nfts n
+            LEFT JOIN creator_profiles cp ON cp.address = LOWER(n.creator_address)
+            WHERE n.is_deleted = false
+            AND n.is_original = true
+            AND n.creator_address = ANY($1)
+            ORDER BY n.creation_time DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(creators)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        Ok(nfts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::random_address;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_compute_type_affinity_score_high_pref() {
+        let weights = ScoringWeights::default();
+        let mut prefs = UserPreferences::default();
+        prefs.art_affinity = 0.8;
+
+        let (score, reason) = RecommendationEngine::compute_type_affinity_score(&weights, "art", &prefs);
+        assert!(score > 0.0);
+        match reason {
+            Some(RecommendationReason::ContentTypeMatch { content_type }) => {
+                assert_eq!(content_type, "art")
+            }
+            _ => panic!("expected content type match reason"),
+        }
+    }
+
+    #[test]
+    fn test_compute_feature_scores_tag_match() {
+        let weights = ScoringWeights::default();
+        let mut prefs = UserPreferences::default();
+        prefs.tag_preferences.insert("landscape".to_string(), 0.8);
+
+        let f = NftFeatures {
+            nft_id: "1".to_string(),
+            contract_address: "0xabc".to_string(),
+            token_id: 1,
+            tags: vec!["landscape".to_string()],
+            primary_color: None,
+            style: None,
+            mood: None,
+            genre: None,
+            engagement_score: 0.0,
+            trending_score: 0.0,
+            quality_score: 0.0,
+        };
+
+        let seen_creators: HashMap<String, usize> = HashMap::new();
+        let seen_tags: HashMap<String, usize> = HashMap::new();
+
+        let (score, reason, _) = RecommendationEngine::compute_feature_scores(&weights, &f, &prefs, &seen_creators, &seen_tags, &mut ScoreBreakdown::default());
+        assert!(score > 0.0);
+        match reason {
+            Some(RecommendationReason::TagMatch { matching_tags }) => {
+                assert_eq!(matching_tags, vec!["landscape".to_string()]);
+            }
+            _ => panic!("expected TagMatch reason"),
+        }
+    }
+
+    #[test]
+    fn test_compute_recency_score_recent_vs_old() {
+        let now = chrono::Utc::now();
+        let recent = now.to_rfc3339();
+        let old = (now - chrono::Duration::days(10)).to_rfc3339();
+
+        let r1 = RecommendationEngine::compute_recency_score(&recent);
+        let r2 = RecommendationEngine::compute_recency_score(&old);
+        assert!(r1 > r2);
+
+        // As the candidate queries render `creation_time::text`
+        let as_text = |dt: chrono::DateTime<chrono::Utc>| dt.format("%Y-%m-%d %H:%M:%S%.6f").to_string();
+        let t1 = RecommendationEngine::compute_recency_score(&as_text(now));
+        let t2 = RecommendationEngine::compute_recency_score(&format!("{}+00", as_text(now - chrono::Duration::days(2))));
+        assert!((t1 - 1.0).abs() < 0.05);
+        assert!((t2 - (-2.0f32).exp()).abs() < 0.01);
+    }
+
+    fn candidate(id: &str, trending_score: f32) -> (CandidateNft, Option<NftFeatures>) {
+        let nft = CandidateNft {
+            id: Some(id.to_string()),
+            token_id: 1,
+            contract_address: "0xabc".to_string(),
+            contract_type: Some("art".to_string()),
+            creator_address: "0xcreator".to_string(),
+            created_at: Some(chrono::Utc::now().to_rfc3339()),
+            creator_username: None,
+        };
+        let features = NftFeatures {
+            nft_id: id.to_string(),
+            contract_address: "0xabc".to_string(),
+            token_id: 1,
+            tags: vec!["landscape".to_string()],
+            primary_color: None,
+            style: None,
+            mood: None,
+            genre: None,
+            engagement_score: 0.5,
+            trending_score,
+            quality_score: 0.5,
+        };
+        (nft, Some(features))
+    }
+
+    #[test]
+    fn test_nan_score_candidate_is_skipped() {
+        let weights = ScoringWeights::default();
+        let prefs = UserPreferences::default();
+        let candidates = vec![candidate("good-1", 0.4), candidate("bad", f32::NAN), candidate("good-2", 0.9)];
+
+        let scored = RecommendationEngine::score_candidates_parallel(candidates, &prefs, &weights);
+
+        assert_eq!(scored.len(), 2);
+        assert!(scored.iter().all(|s| s.score.is_finite()));
+        assert!(scored.iter().all(|s| s.nft_id != "bad"));
+        assert!(scored[0].score >= scored[1].score);
+    }
+
+    #[test]
+    fn test_new_creator_gets_baseline_quality() {
+        let cold_start = CreatorColdStart {
+            baseline_quality: 0.5,
+            interaction_threshold: 20,
+        };
+
+        // Brand-new creator with no reputation
+        assert_eq!(cold_start.effective_quality(0.0, 0), 0.5);
+        // Halfway to the threshold blends baseline and real quality
+        assert!((cold_start.effective_quality(0.1, 10) - 0.3).abs() < 1e-6);
+        // Established creators are scored on real quality
+        assert_eq!(cold_start.effective_quality(0.1, 20), 0.1);
+        // A strong new creator is never pulled down to the baseline
+        assert_eq!(cold_start.effective_quality(0.9, 0), 0.9);
+    }
+
+    #[test]
+    fn test_mmr_rerank_spreads_creators() {
+        let item = |id: &str, creator: &str, score: f32| ScoredNft {
+            nft_id: id.to_string(),
+            token_id: 1,
+            contract_address: "0xabc".to_string(),
+            score,
+            reason: RecommendationReason::Discovery,
+            reasons: Vec::new(),
+            contract_type: "art".to_string(),
+            creator_address: creator.to_string(),
+            tags: vec![],
+            creator_username: None,
+        };
+        let scored = vec![item("a1", "0xa", 0.9), item("a2", "0xA", 0.85), item("b1", "0xb", 0.8)];
+        let ids = |items: Vec<ScoredNft>| items.into_iter().map(|s| s.nft_id).collect::<Vec<_>>();
+
+        // The second pick goes to another creator; the rest keep their order
+        assert_eq!(ids(mmr_rerank(scored.clone(), 2, MMR_LAMBDA)), vec!["a1", "b1", "a2"]);
+        // Lambda 1.0 is plain score order
+        assert_eq!(ids(mmr_rerank(scored, 3, 1.0)), vec!["a1", "a2", "b1"]);
+    }
+
+    #[tokio::test]
+    async fn test_flags_gate_cold_start_and_mmr() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let engine = RecommendationEngine::new(pool, ScoringWeights::default());
+
+        // Cold start stays on for everyone by default, MMR is off
+        assert!(engine.cold_start_enabled(Some("0x1")));
+        assert!(engine.cold_start_enabled(None));
+        assert!(!engine.flag_enabled(crate::recommendation::flags::MMR_RERANKER, "0x1"));
+
+        let engine = engine.with_flags(FeatureFlags::parse("cold_start=50,mmr_reranker=100"));
+        assert!(engine.flag_enabled(crate::recommendation::flags::MMR_RERANKER, "0x1"));
+        // Partial rollouts leave unpersonalized reads out
+        assert!(!engine.cold_start_enabled(None));
+        let enabled = (0..200)
+            .filter(|i| engine.cold_start_enabled(Some(&format!("0x{:040x}", i))))
+            .count();
+        assert!((50..150).contains(&enabled), "enabled = {}", enabled);
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_discovery_picks() {
+        let candidates: Vec<_> = (0..40)
+            .map(|i| candidate(&format!("nft-{}", i), i as f32 / 40.0))
+            .collect();
+        let scored = RecommendationEngine::score_candidates_parallel(
+            candidates,
+            &UserPreferences::default(),
+            &ScoringWeights::default(),
+        );
+        let ids = |items: Vec<ScoredNft>| items.into_iter().map(|s| s.nft_id).collect::<Vec<_>>();
+
+        let shuffle = DiscoveryShuffle::default();
+        let seed = shuffle.seed("0xUser", 1_700_000_000);
+        let first = ids(RecommendationEngine::apply_diversity_shuffle_static(scored.clone(), 10, seed));
+        let second = ids(RecommendationEngine::apply_diversity_shuffle_static(scored.clone(), 10, seed));
+        assert_eq!(first, second);
+
+        // Stable within the window and across address casing, varies across users and days
+        assert_eq!(seed, shuffle.seed("0xuser", 1_700_000_000 + 60));
+        assert_ne!(seed, shuffle.seed("0xother", 1_700_000_000));
+        assert_ne!(seed, shuffle.seed("0xuser", 1_700_000_000 + 86_400));
+
+        let pinned = DiscoveryShuffle { fixed_seed: Some(7), ..Default::default() };
+        assert_eq!(pinned.seed("0xa", 0), pinned.seed("0xb", 1_000_000));
+    }
+
+    #[test]
+    fn test_trending_ties_break_by_engagement_then_recency() {
+        let with = |id: &str, trending: f32, engagement: f32, created_at: &str| {
+            let (mut nft, features) = candidate(id, trending);
+            nft.created_at = Some(created_at.to_string());
+            let mut features = features.unwrap();
+            features.engagement_score = engagement;
+            (nft, Some(features))
+        };
+        let candidates = vec![
+            with("low-engagement", 0.5, 0.1, "2024-01-03 00:00:00"),
+            with("high-engagement", 0.5, 0.9, "2024-01-01 00:00:00"),
+            with("older", 0.5, 0.4, "2024-01-01 00:00:00"),
+            with("newer", 0.5, 0.4, "2024-01-02T00:00:00Z"),
+            with("top", 0.8, 0.0, "2023-01-01 00:00:00"),
+        ];
+
+        let ids = |ranked: Vec<ScoredNft>| ranked.into_iter().map(|s| s.nft_id).collect::<Vec<_>>();
+        let ranked = RecommendationEngine::rank_trending(
+            candidates.clone(),
+            None,
+            &[TrendingTieBreak::Engagement, TrendingTieBreak::Recency],
+        );
+        assert_eq!(ids(ranked), vec!["top", "high-engagement", "newer", "older", "low-engagement"]);
+
+        // Recency only
+        let ranked = RecommendationEngine::rank_trending(candidates, None, &TrendingTieBreak::parse_list("recency"));
+        assert_eq!(ids(ranked)[..2], ["top", "low-engagement"]);
+    }
+
+    #[test]
+    fn test_rapidly_rising_nft_ranks_high_in_rising_not_hot() {
+        use super::super::features::{hot_score, rising_score};
+
+        // (id, engagement in the window before, engagement in the latest window)
+        let windows = [("steady-giant", 100.0, 90.0), ("breakout", 1.0, 20.0), ("quiet", 2.0, 2.0)];
+        let candidates: Vec<_> = windows.iter().map(|(id, _, _)| candidate(id, 0.0)).collect();
+        let scores = |f: &dyn Fn(f64, f64) -> f32| -> HashMap<String, f32> {
+            windows.iter().map(|(id, prev, recent)| (id.to_string(), f(*recent, *prev))).collect()
+        };
+        let hot = scores(&|recent, _| hot_score(recent));
+        let rising = scores(&|recent, prev| rising_score(recent, prev));
+
+        let ids = |ranked: Vec<ScoredNft>| ranked.into_iter().map(|s| s.nft_id).collect::<Vec<_>>();
+        let by_hot = ids(RecommendationEngine::rank_trending(candidates.clone(), Some(&hot), &[]));
+        let by_rising = ids(RecommendationEngine::rank_trending(candidates, Some(&rising), &[]));
+
+        assert_eq!(by_hot, vec!["steady-giant", "breakout", "quiet"]);
+        assert_eq!(by_rising[0], "breakout");
+        // Falling engagement doesn't count as rising
+        assert_eq!(rising["steady-giant"], 0.0);
+    }
+
+    #[test]
+    fn test_music_trending_within_type_ranks_globally() {
+        use super::super::features::trending_scores;
+
+        // (id, content type, decayed engagement): snaps dwarf music in volume
+        let rows = [
+            ("snap-a", "snap", 40.0),
+            ("snap-b", "snap", 38.0),
+            ("snap-c", "snap", 42.0),
+            ("snap-d", "snap", 40.0),
+            ("music-breakout", "music", 12.0),
+            ("music-a", "music", 2.0),
+            ("music-b", "music", 1.0),
+        ];
+        let engagement: Vec<_> = rows.iter().map(|(_, t, raw)| (Some(t.to_string()), *raw)).collect();
+        let candidates: Vec<_> = rows.iter().map(|(id, _, _)| candidate(id, 0.0)).collect();
+        let ranked = |normalize: bool| {
+            let scores: HashMap<String, f32> = rows
+                .iter()
+                .zip(trending_scores(&engagement, normalize))
+                .map(|((id, _, _), score)| (id.to_string(), score))
+                .collect();
+            RecommendationEngine::rank_trending(candidates.clone(), Some(&scores), &[])
+                .into_iter()
+                .map(|s| s.nft_id)
+                .collect::<Vec<_>>()
+        };
+
+        // Raw volume buries the music breakout below every snap
+        assert_eq!(ranked(false).iter().position(|id| id == "music-breakout"), Some(4));
+        // Relative to its own type it beats the average snap
+        let normalized = ranked(true);
+        let position = |id: &str| normalized.iter().position(|n| n == id).unwrap();
+        assert!(position("music-breakout") < position("snap-a"));
+        assert!(position("music-breakout") < position("snap-b"));
+        assert!(position("music-a") > position("snap-c"));
+    }
+
+    #[test]
+    fn test_min_score_drops_low_relevance_when_enough_survive() {
+        let candidates: Vec<_> = (0..6).map(|i| candidate(&format!("nft-{}", i), 0.0)).collect();
+        let mut scored = RecommendationEngine::score_candidates_parallel(
+            candidates,
+            &UserPreferences::default(),
+            &ScoringWeights::default(),
+        );
+        // Straddle a 0.1 threshold: three above, three below
+        for (item, score) in scored.iter_mut().zip([0.9, 0.5, 0.1, 0.09, 0.05, 0.01]) {
+            item.score = score;
+        }
+        let ids = |items: Vec<ScoredNft>| items.into_iter().map(|s| s.nft_id).collect::<Vec<_>>();
+
+        let filtered = RecommendationEngine::filter_min_score(scored.clone(), 0.1, 6);
+        assert_eq!(ids(filtered), vec!["nft-0", "nft-1", "nft-2"]);
+
+        // Fewer than limit / 2 would survive: keep everything
+        let unfiltered = RecommendationEngine::filter_min_score(scored, 0.1, 8);
+        assert_eq!(unfiltered.len(), 6);
+    }
+
+    #[test]
+    fn test_niche_user_gets_full_feed_via_broadening() {
+        let with_tags = |id: &str, tags: &[&str]| {
+            let (nft, features) = candidate(id, 0.1);
+            let mut features = features.unwrap();
+            features.tags = tags.iter().map(|t| t.to_string()).collect();
+            (nft, Some(features))
+        };
+        let mut prefs = UserPreferences::default();
+        prefs.tag_preferences.insert("rare-tag".to_string(), 0.9);
+        let weights = ScoringWeights::default();
+        let limit = 10;
+
+        // Only a handful of candidates, one of which has the rare tag
+        let candidates = vec![
+            with_tags("rare", &["rare-tag"]),
+            with_tags("generic-1", &["landscape"]),
+            with_tags("generic-2", &["portrait"]),
+        ];
+        let scored = RecommendationEngine::score_candidates_parallel(candidates, &prefs, &weights);
+        let niche = NicheBroadening::default();
+        assert!(niche.needs_broadening(&prefs, &scored, limit));
+        assert_eq!(RecommendationEngine::apply_diversity_shuffle_static(scored.clone(), limit, 1).len(), 3);
+
+        // Broadened candidates from co-occurring tags fill the feed
+        let broadened: Vec<_> = (0..20)
+            .map(|i| with_tags(&format!("co-{}", i), &["co-tag"]))
+            .chain(std::iter::once(with_tags("rare", &["rare-tag"])))
+            .collect();
+        let broadened = RecommendationEngine::score_candidates_parallel(broadened, &prefs, &weights);
+        let merged = NicheBroadening::merge(scored, broadened);
+        let feed = RecommendationEngine::apply_diversity_shuffle_static(merged, limit, 1);
+
+        assert_eq!(feed.len(), limit);
+        assert_eq!(feed[0].nft_id, "rare");
+        assert!(matches!(feed[0].reason, RecommendationReason::TagMatch { .. }));
+        assert_eq!(feed.iter().filter(|s| s.nft_id == "rare").count(), 1);
+        assert!(feed[1..]
+            .iter()
+            .filter(|s| s.nft_id.starts_with("co-"))
+            .all(|s| matches!(s.reason, RecommendationReason::Discovery)));
+    }
+
+    #[test]
+    fn test_followed_creators_excluded_from_enhanced_feed() {
+        let by = |id: &str, creator: &str| {
+            let (mut nft, features) = candidate(id, 0.5);
+            nft.creator_address = creator.to_string();
+            (nft, features)
+        };
+        let candidates = vec![
+            by("followed-1", "0xFollowed"),
+            by("other", "0xother"),
+            by("followed-2", "0xfollowed"),
+        ];
+
+        // Flag off: nothing to exclude
+        assert_eq!(exclude_creators(candidates.clone(), &[]).len(), 3);
+
+        let followed = vec!["0xFOLLOWED".to_string()];
+        let scored = RecommendationEngine::score_candidates_parallel(
+            exclude_creators(candidates, &followed),
+            &UserPreferences::default(),
+            &ScoringWeights::default(),
+        );
+        let feed = RecommendationEngine::apply_diversity_shuffle_static(scored, 10, 1);
+        let ids: Vec<_> = feed.iter().map(|s| s.nft_id.as_str()).collect();
+        assert_eq!(ids, vec!["other"]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_unblocking_restores_creator_to_feed() {
+        let pool = crate::database::test_pool().await;
+
+        use super::super::blocks::{block_user, get_blocked_users, unblock_user};
+        let user = "0x00000000000000000000000000000000000b10c1";
+        let creator = "0x00000000000000000000000000000000000b10c2";
+        let by = |id: &str, creator: &str| {
+            let (mut nft, features) = candidate(id, 0.5);
+            nft.creator_address = creator.to_string();
+            (nft, features)
+        };
+        let candidates = vec![by("blocked-creator", creator), by("other", "0xother")];
+        let feed_ids = |blocked: Vec<String>| {
+            exclude_creators(candidates.clone(), &blocked)
+                .into_iter()
+                .filter_map(|(nft, _)| nft.id)
+                .collect::<Vec<_>>()
+        };
+
+        block_user(&mut pool.acquire().await.unwrap(), user, creator).await.unwrap();
+        assert_eq!(feed_ids(get_blocked_users(&pool, user).await.unwrap()), vec!["other"]);
+
+        // A feed cached while blocked is dropped on unblock
+        sqlx::query("INSERT INTO recommendation_cache (user_address, feed_type, expires_at) VALUES ($1, 'enhanced', NOW() + INTERVAL '1 hour')")
+            .bind(user)
+            .execute(&pool)
+            .await
+            .unwrap();
+        unblock_user(&mut pool.acquire().await.unwrap(), user, creator).await.unwrap();
+        assert!(get_cached_recommendations(&pool, user, "enhanced").await.unwrap().is_none());
+        assert_eq!(
+            feed_ids(get_blocked_users(&pool, user).await.unwrap()),
+            vec!["blocked-creator", "other"]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_features_batch_matches_many_candidates() {
+        let pool = crate::database::test_pool().await;
+
+        let ids: Vec<Uuid> = (0..50).map(|_| Uuid::new_v4()).collect();
+        let token_ids: Vec<i64> = (0..50).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO nft_features (nft_id, contract_address, token_id)
+            SELECT * FROM UNNEST($1::uuid[], $2::text[], $3::bigint[])
+            "#,
+        )
+        .bind(&ids)
+        .bind(vec!["0xbatch".to_string(); ids.len()])
+        .bind(&token_ids)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // Uppercase ids, plus ones without features or that aren't UUIDs
+        let mut requested: Vec<String> = ids.iter().map(|id| id.to_string().to_uppercase()).collect();
+        requested.push(Uuid::new_v4().to_string());
+        requested.push("not-a-uuid".to_string());
+
+        let features = super::super::features::get_features_batch(&pool, &requested).await.unwrap();
+        assert_eq!(features.len(), ids.len());
+        for (i, id) in requested.iter().take(ids.len()).enumerate() {
+            assert_eq!(features[id].token_id, i as i64);
+            assert_eq!(features[id].nft_id, ids[i].to_string());
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_cached_feed_records_reason_histogram() {
+        let pool = crate::database::test_pool().await;
+
+        let user = "0x0000000000000000000000000000000000a7a115";
+        let item = |id: &str, reason: RecommendationReason| ScoredNft {
+            nft_id: id.to_string(),
+            token_id: 1,
+            contract_address: "0xabc".to_string(),
+            score: 0.5,
+            reason,
+            reasons: Vec::new(),
+            contract_type: "art".to_string(),
+            creator_address: "0xcreator".to_string(),
+            tags: vec![],
+            creator_username: None,
+        };
+        let feed = vec![
+            item("a", RecommendationReason::TagMatch { matching_tags: vec!["calm".to_string()] }),
+            item("b", RecommendationReason::TagMatch { matching_tags: vec!["sea".to_string()] }),
+            item("c", RecommendationReason::Trending { trending_score: 0.9 }),
+            item("d", RecommendationReason::Discovery),
+        ];
+
+        cache_recommendations(&pool, user, "personalized", &feed, 10).await.unwrap();
+
+        let (counts, total) = sqlx::query_as::<_, (serde_json::Value, i32)>(
+            "SELECT reason_counts, total FROM recommendation_reason_analytics WHERE user_address = $1 ORDER BY created_at DESC LIMIT 1",
+        )
+        .bind(user)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(counts, serde_json::json!({ "tag_match": 2, "trending": 1, "discovery": 1 }));
+        assert_eq!(total, 4);
+
+        for table in ["recommendation_reason_analytics", "recommendation_cache"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE user_address = $1", table))
+                .bind(user)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_disabled_engine_serves_trending_fallback() {
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        let contract_type = format!("ks{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let (cold, hot) = (Uuid::new_v4(), Uuid::new_v4());
+        for (id, trending) in [(cold, 0.1f32), (hot, 0.9)] {
+            sqlx::query(
+                "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address) VALUES ($1, 1, '0xabc', $2, '0xcreator')",
+            )
+            .bind(id)
+            .bind(&contract_type)
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO nft_features (nft_id, contract_address, token_id, trending_score) VALUES ($1, '0xabc', 1, $2)",
+            )
+            .bind(id)
+            .bind(trending)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let engine = RecommendationEngine::new(pool.clone(), ScoringWeights::default());
+        // Toggled through a clone, the way a shared handle would
+        engine.clone().set_enabled(false);
+        assert!(!engine.is_enabled());
+
+        let user = format!("0x{}", Uuid::new_v4().simple());
+        let ids = |feed: Vec<ScoredNft>| feed.into_iter().map(|s| s.nft_id).collect::<Vec<_>>();
+        let expected = vec![hot.to_string(), cold.to_string()];
+        assert_eq!(ids(engine.get_enhanced_feed(&user, 10, 0, Some(&contract_type)).await.unwrap()), expected);
+        assert_eq!(ids(engine.get_recommendations(&user, 10, Some(&contract_type), false).await.unwrap()), expected);
+
+        // The fallback never touches the user's preference profile
+        let prefs: Option<i64> = sqlx::query_scalar("SELECT 1::bigint FROM user_preferences WHERE user_address = $1")
+            .bind(&user)
+            .fetch_optional(&pool)
+            .await
+            .unwrap();
+        assert!(prefs.is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_trending_ranks_older_high_scorers_above_newer_nfts() {
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let contract_type = format!("ks{}", &Uuid::new_v4().simple().to_string()[..8]);
+        // Yesterday's hit, then more unscored mints than the page over-fetch
+        let hit = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address, creation_time) VALUES ($1, 1, '0xabc', $2, '0xcreator', NOW() - INTERVAL '1 day')",
+        )
+        .bind(hit)
+        .bind(&contract_type)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO nft_features (nft_id, contract_address, token_id, trending_score) VALUES ($1, '0xabc', 1, 0.9)",
+        )
+        .bind(hit)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO nft_trending_windows (nft_id, hot_score, rising_score) VALUES ($1, 0.8, 0.7)",
+        )
+        .bind(hit)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        for token_id in 2..12i64 {
+            sqlx::query(
+                "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address) VALUES ($1, $2, '0xabc', $3, '0xcreator')",
+            )
+            .bind(Uuid::new_v4())
+            .bind(token_id)
+            .bind(&contract_type)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+        tx.commit().await.unwrap();
+
+        let engine = RecommendationEngine::new(pool.clone(), ScoringWeights::default());
+        for mode in [TrendingMode::Trending, TrendingMode::Hot, TrendingMode::Rising] {
+            let top = engine.get_trending(2, 0, Some(&contract_type), mode).await.unwrap();
+            assert_eq!(top.len(), 2);
+            assert_eq!(top[0].nft_id, hit.to_string(), "{:?}", mode);
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_candidates_and_features_come_back_in_one_query() {
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let contract_type = format!("ks{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let (featured, bare) = (Uuid::new_v4(), Uuid::new_v4());
+        for id in [featured, bare] {
+            sqlx::query(
+                "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address) VALUES ($1, 1, '0xabc', $2, '0xcreator')",
+            )
+            .bind(id)
+            .bind(&contract_type)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO nft_features (nft_id, contract_address, token_id, tags, trending_score) VALUES ($1, '0xabc', 1, ARRAY['landscape'], 0.7)",
+        )
+        .bind(featured)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        // The connection is handed over once, so the whole page (features
+        // included) has to come back from a single statement
+        let page = fetch_candidates(&mut *tx, Some(&contract_type), 10, 0).await.unwrap();
+        assert_eq!(page.len(), 2);
+        let features: HashMap<String, Option<NftFeatures>> = page
+            .into_iter()
+            .map(|(nft, features)| (nft.id.unwrap(), features))
+            .collect();
+        let f = features[&featured.to_string()].as_ref().expect("features joined");
+        assert_eq!(f.tags, vec!["landscape".to_string()]);
+        assert!((f.trending_score - 0.7).abs() < 1e-6);
+        assert!(features[&bare.to_string()].is_none());
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_cache_prune_evicts_oldest_computed_first() {
+        let pool = crate::database::test_pool().await;
+
+        // Rolled back at the end, and isolated so other tests' cache writes
+        // don't shift the cap while this one runs
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let users: Vec<String> = (0..4).map(|_| random_address()).collect();
+        // Computed long before anything else in the table; the last one has expired
+        for (i, (user, expired)) in users.iter().zip([false, false, false, true]).enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO recommendation_cache (user_address, feed_type, computed_at, expires_at)
+                VALUES ($1, 'enhanced', TIMESTAMP '2000-01-01' + make_interval(days => $2),
+                        CASE WHEN $3 THEN NOW() - INTERVAL '1 minute' ELSE NOW() + INTERVAL '1 hour' END)
+                "#,
+            )
+            .bind(user)
+            .bind(i as i32)
+            .bind(expired)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+
+        let live: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM recommendation_cache WHERE expires_at >= NOW()")
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        prune_recommendation_cache(&mut *tx, Some(live - 2)).await.unwrap();
+
+        let kept: Vec<String> = sqlx::query_scalar("SELECT user_address FROM recommendation_cache WHERE user_address = ANY($1)")
+            .bind(&users)
+            .fetch_all(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(kept, vec![users[2].clone()]);
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_recent_engagement_trends_above_older() {
+        use super::super::features::{decayed_engagement, trending_scores, TrendingConfig};
+
+        let pool = crate::database::test_pool().await;
+
+        let config = TrendingConfig {
+            half_life: std::time::Duration::from_secs(6 * 3600),
+            normalize_by_type: false,
+        };
+        let mut tx = pool.begin().await.unwrap();
+        let (fresh, stale) = (Uuid::new_v4(), Uuid::new_v4());
+        // Same activity, one batch an hour old and one a day old; the view
+        // doesn't count toward trending
+        for (nft, hours_ago, kind) in [
+            (fresh, 1, "like"),
+            (fresh, 1, "comment"),
+            (fresh, 1, "share"),
+            (stale, 24, "like"),
+            (stale, 24, "comment"),
+            (stale, 24, "share"),
+            (stale, 1, "view"),
+        ] {
+            sqlx::query(
+                r#"
+                INSERT INTO user_interactions (id, user_address, nft_id, interaction_type, created_at)
+                VALUES (gen_random_uuid(), '0xtrender', $1, $2, NOW() - make_interval(hours => $3))
+                "#,
+            )
+            .bind(nft)
+            .bind(kind)
+            .bind(hours_ago)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+
+        let rows = decayed_engagement(&mut *tx, &config).await.unwrap();
+        tx.rollback().await.unwrap();
+
+        let engagement: HashMap<Uuid, f64> = rows.iter().map(|(id, _, raw)| (*id, *raw)).collect();
+        // Three events an hour old, and three four half-lives old
+        assert!((engagement[&fresh] - 3.0 * 0.5f64.powf(1.0 / 6.0)).abs() < 0.01, "{:?}", engagement);
+        assert!((engagement[&stale] - 3.0 / 16.0).abs() < 0.01, "{:?}", engagement);
+
+        let scores = trending_scores(&[(None, engagement[&fresh]), (None, engagement[&stale])], false);
+        assert!(scores[0] > scores[1]);
+    }
+
+    #[test]
+    fn test_breakdown_components_sum_to_score() {
+        let weights = ScoringWeights::default();
+        let mut prefs = UserPreferences {
+            art_affinity: 0.9,
+            ..Default::default()
+        };
+        prefs.tag_preferences.insert("landscape".to_string(), 0.9);
+        prefs.creator_preferences.insert("0xcreator".to_string(), 0.8);
+        let features = Some(NftFeatures {
+            nft_id: "nft-1".to_string(),
+            contract_address: "0xcontract".to_string(),
+            token_id: 1,
+            tags: vec!["landscape".to_string()],
+            primary_color: None,
+            style: None,
+            mood: None,
+            genre: None,
+            engagement_score: 0.9,
+            trending_score: 0.8,
+            quality_score: 0.7,
+        });
+        // Enough repetition that both diversity penalties apply
+        let seen_creators = HashMap::from([("0xcontract".to_string(), 5)]);
+        let seen_tags = HashMap::from([("landscape".to_string(), 9)]);
+        let ctx = ScoringContext {
+            prefs: &prefs,
+            contract_type: "art",
+            creator_address: "0xcreator",
+            created_at: "",
+            features: &features,
+            seen_creators: &seen_creators,
+            seen_tags: &seen_tags,
+        };
+
+        let (score, _, _) = RecommendationEngine::calculate_score_static(&ctx, &weights);
+        let (b, _, _) = RecommendationEngine::score_breakdown(&ctx, &weights);
+        assert!(b.diversity_penalty > 0.0);
+        let sum = b.tag_match
+            + b.creator
+            + b.content_type
+            + b.trending
+            + b.engagement
+            + b.quality
+            + b.recency
+            - b.diversity_penalty;
+        assert!((sum.clamp(0.0, 1.0) - score).abs() < 1e-6, "{b:?} vs {score}");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_unliked_nft_is_penalized_below_untouched_equivalent() {
+        let pool = crate::database::test_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+
+        let user = random_address();
+        let (unliked, untouched, reliked) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let history = [
+            (unliked, "like", 0),
+            (unliked, "unlike", 1),
+            (reliked, "unlike", 0),
+            (reliked, "like", 1),
+        ];
+        for (nft_id, interaction_type, minutes) in history {
+            sqlx::query(
+                r#"
+                INSERT INTO user_interactions (user_address, nft_id, interaction_type, created_at)
+                VALUES ($1, $2, $3, TIMESTAMP '2024-01-01' + make_interval(mins => $4))
+                "#,
+            )
+            .bind(&user)
+            .bind(nft_id)
+            .bind(interaction_type)
+            .bind(minutes)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+
+        let rejected = get_rejected_nfts(&mut *tx, &user).await.unwrap();
+        assert_eq!(rejected, HashSet::from([unliked.to_string()]));
+        tx.rollback().await.unwrap();
+
+        let item = |id: Uuid| ScoredNft {
+            nft_id: id.to_string(),
+            token_id: 1,
+            contract_address: "0xabc".to_string(),
+            score: 0.8,
+            reason: RecommendationReason::Discovery,
+            reasons: Vec::new(),
+            contract_type: "art".to_string(),
+            creator_address: "0xcreator".to_string(),
+            tags: Vec::new(),
+            creator_username: None,
+        };
+        let mut scored = vec![item(unliked), item(untouched)];
+        apply_rejection_penalty(&mut scored, &rejected, 0.9);
+
+        assert_eq!(scored[0].nft_id, untouched.to_string());
+        assert_eq!(scored[1].nft_id, unliked.to_string());
+        assert!(scored[1].score < scored[0].score * 0.2, "{scored:?}");
+    }
+
+    #[test]
+    fn test_negative_grace_keeps_penalized_items_apart() {
+        let prefs = UserPreferences::default();
+        let features = Some(NftFeatures {
+            nft_id: "nft-1".to_string(),
+            contract_address: "0xcontract".to_string(),
+            token_id: 1,
+            tags: Vec::new(),
+            primary_color: None,
+            style: None,
+            mood: None,
+            genre: None,
+            engagement_score: 0.0,
+            trending_score: 0.0,
+            quality_score: 0.0,
+        });
+        let seen_tags: HashMap<String, usize> = HashMap::new();
+        let score_with = |weights: &ScoringWeights, repeats: usize| {
+            let seen_creators = HashMap::from([("0xcontract".to_string(), repeats)]);
+            let ctx = ScoringContext {
+                prefs: &prefs,
+                contract_type: "art",
+                creator_address: "0xcreator",
+                created_at: "",
+                features: &features,
+                seen_creators: &seen_creators,
+                seen_tags: &seen_tags,
+            };
+            RecommendationEngine::calculate_score_static(&ctx, weights).0
+        };
+
+        // Both items are pushed well below zero by the diversity penalty
+        let clamped = ScoringWeights {
+            diversity_penalty: 10.0,
+            ..ScoringWeights::default()
+        };
+        assert_eq!(score_with(&clamped, 3), 0.0);
+        assert_eq!(score_with(&clamped, 9), 0.0);
 
-        // From remaining, pick some randomly for discovery
-        let mut rng = rand::thread_rng();
-        let remaining: Vec<_> = scored.into_iter().take(shuffle_count * 3).collect();
+        let graced = ScoringWeights {
+            negative_grace: 5.0,
+            ..clamped
+        };
+        let (less, more) = (score_with(&graced, 3), score_with(&graced, 9));
+        assert!(more < less && less < 0.0, "{less} vs {more}");
 
-        if !remaining.is_empty() {
-            let chosen: Vec<_> = remaining
-                .choose_multiple(&mut rng, shuffle_count.min(remaining.len()))
-                .cloned()
-                .collect();
-            result.extend(chosen);
-        }
+        // Callers still only see 0-1
+        let item = |nft_id: &str, score: f32| ScoredNft {
+            nft_id: nft_id.to_string(),
+            token_id: 1,
+            contract_address: "0xcontract".to_string(),
+            score,
+            reason: RecommendationReason::Discovery,
+            reasons: Vec::new(),
+            contract_type: "art".to_string(),
+            creator_address: "0xcreator".to_string(),
+            tags: Vec::new(),
+            creator_username: None,
+        };
+        let exposed = clamp_exposed_scores(vec![item("a", less), item("b", more)]);
+        assert!(exposed.iter().all(|i| i.score == 0.0));
+        assert_eq!(exposed[0].nft_id, "a");
+    }
 
-        result
+    #[test]
+    fn test_candidate_pool_applies_multiplier_under_ceiling() {
+        let pool = CandidatePool::default();
+        assert_eq!(pool.personalized(20), 80);
+        assert_eq!(pool.enhanced(20), 60);
+        // Large pages hit the ceiling instead of scaling without bound
+        assert_eq!(pool.personalized(500), 1000);
+        assert_eq!(pool.enhanced(usize::MAX), 1000);
+        // Small pages dig deeper so they still fill after diversity filtering
+        assert_eq!(pool.enhanced(10), 50);
+
+        let tight = CandidatePool {
+            max_candidates: 50,
+            personalized_multiplier: 2,
+            enhanced_multiplier: 10,
+            small_page_multiplier: 1,
+        };
+        assert_eq!(tight.personalized(20), 40);
+        assert_eq!(tight.enhanced(20), 50);
+        assert_eq!(tight.enhanced(10), 10);
     }
 
-    /// Check if user has already seen/interacted with an NFT
-    async fn has_user_seen_nft(&self, user_address: &str, nft_id: &str) -> Result<bool> {
-        let result: Option<bool> = sqlx::query_scalar(
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_reads_use_replica_and_writes_use_primary() {
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        // Stand-in replica: its own `nfts` table shadows the primary's, and
+        // its sessions are read-only so any write sent there fails
+        let schema = format!("replica_{}", &Uuid::new_v4().simple().to_string()[..8]);
+        sqlx::query(&format!("CREATE SCHEMA {schema}")).execute(&pool).await.unwrap();
+        sqlx::query(&format!("CREATE TABLE {schema}.nfts (LIKE public.nfts INCLUDING ALL)"))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let contract_type = format!("ks{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let replica_only = Uuid::new_v4();
+        sqlx::query(&format!(
+            "INSERT INTO {schema}.nfts (id, token_id, contract_address, contract_type, creator_address) VALUES ($1, 1, '0xabc', $2, '0xcreator')"
+        ))
+        .bind(replica_only)
+        .bind(&contract_type)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let replica_options = (*pool.connect_options())
+            .clone()
+            .options([
+                ("search_path", format!("{schema},public")),
+                ("default_transaction_read_only", "on".to_string()),
+            ]);
+        let replica = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(2)
+            .connect_with(replica_options)
+            .await
+            .unwrap();
+
+        let engine = RecommendationEngine::new(pool.clone(), ScoringWeights::default())
+            .with_read_pool(replica.clone());
+        let user = random_address();
+        let result = engine
+            .get_recommendations(&user, 10, Some(&contract_type), false)
+            .await;
+
+        // Preferences and the cache land on the primary
+        let stored: (i64, i64) = sqlx::query_as(
             r#"
-            SELECT EXISTS(
-                SELECT 1 FROM user_interactions 
-                WHERE user_address = $1 
-                AND nft_id = $2 
-                AND interaction_type IN ('view', 'like', 'purchase', 'save')
-                AND created_at > NOW() - INTERVAL '30 days'
-            )
+            SELECT (SELECT COUNT(*) FROM user_preferences WHERE user_address = $1),
+                   (SELECT COUNT(*) FROM recommendation_cache WHERE user_address = $1)
             "#,
         )
-        .bind(user_address.to_lowercase())
-        .bind(nft_id)
-        .fetch_optional(&self.pool)
-        .await?;
-
-        Ok(result.unwrap_or(false))
+        .bind(&user)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM recommendation_cache WHERE user_address = $1").bind(&user).execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM user_preferences WHERE user_address = $1").bind(&user).execute(&pool).await.unwrap();
+        replica.close().await;
+        sqlx::query(&format!("DROP SCHEMA {schema} CASCADE")).execute(&pool).await.unwrap();
+
+        // Only the replica has this NFT, so finding it means candidates were read there
+        let items = result.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].nft_id, replica_only.to_string());
+        assert_eq!(stored, (1, 1));
     }
 
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_nft_saved_by_similar_user_surfaces_with_reason() {
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        let prefs = |user_address: &str, art: f32, music: f32| UserPreferences {
+            user_address: user_address.to_string(),
+            art_affinity: art,
+            music_affinity: music,
+            tag_preferences: HashMap::from([("landscape".to_string(), art)]),
+            ..Default::default()
+        };
+        let (user, similar, dissimilar, unsaver) = (random_address(), random_address(), random_address(), random_address());
+        for peer_prefs in [
+            prefs(&user, 0.9, 0.5),
+            prefs(&similar, 0.8, 0.5),
+            prefs(&dissimilar, 0.1, 0.9),
+            prefs(&unsaver, 0.9, 0.5),
+        ] {
+            super::super::preferences::import_preferences(&pool, &peer_prefs, &TasteSpace::default()).await.unwrap();
+        }
 
-
-    // Database query helpers
-
-
-    async fn get_candidates(
-        &self,
-        contract_type_filter: Option<&str>,
-        limit: usize,
-        offset: usize,
-    ) -> Result<Vec<(CandidateNft, Option<NftFeatures>)>> {
-        // ByteGraph optimization: Fetch candidates with smart distribution
-        // If no filter, we fetch based on recency with slight randomization
-        let nfts: Vec<CandidateNft> = if let Some(ct) = contract_type_filter {
-            sqlx::query_as::<_, CandidateNft>(
-                r#"
-                SELECT id::text, token_id, contract_address, contract_type::text, 
-                       creator_address, creation_time::text as created_at
-                FROM nfts 
-                WHERE is_deleted = false 
-                AND is_original = true
-                AND contract_type = $1
-                ORDER BY 
-                    creation_time DESC,
-                    random() * 0.1  -- Add slight randomness for discovery
-                LIMIT $2 OFFSET $3
-                "#,
+        // Four NFTs alike but for who saved them: the similar user saved one,
+        // the dissimilar user another, and a second similar user saved a
+        // third but took it back
+        let contract_type = format!("sv{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let (saved_by_similar, saved_by_dissimilar, unsaved, untouched) =
+            (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        for id in [saved_by_similar, saved_by_dissimilar, unsaved, untouched] {
+            sqlx::query(
+                "INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address) VALUES ($1, 1, '0xabc', $2, $3)",
             )
-            .bind(ct)
-            .bind(limit as i64)
-            .bind(offset as i64)
-            .fetch_all(&self.pool)
-            .await?
-        } else {
-            // Mixed strategy: 70% recent, 30% engagement-based
-            sqlx::query_as::<_, CandidateNft>(
+            .bind(id)
+            .bind(&contract_type)
+            .bind(random_address())
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let history = [
+            (&similar, saved_by_similar, "save", 0),
+            (&dissimilar, saved_by_dissimilar, "save", 0),
+            (&unsaver, unsaved, "save", 0),
+            (&unsaver, unsaved, "unsave", 1),
+        ];
+        for (peer, nft_id, interaction_type, minutes) in history {
+            sqlx::query(
                 r#"
-                WITH recent_nfts AS (
-                    SELECT id, token_id, contract_address, contract_type::text as contract_type, 
-                           creator_address, creation_time,
-                           likes_count, buys_count
-                    FROM nfts 
-                    WHERE is_deleted = false 
-                    AND is_original = true
-                    AND creation_time > NOW() - INTERVAL '30 days'
-                ),
-                scored_nfts AS (
-                    SELECT id, token_id, contract_address, contract_type,
-                           creator_address, creation_time::text as created_at,
-                        EXTRACT(EPOCH FROM (NOW() - creation_time)) / 3600.0 as age_hours,
-                        (likes_count + buys_count * 2) as engagement
-                    FROM recent_nfts
-                )
-                SELECT id::text, token_id, contract_address, contract_type, 
-                       creator_address, created_at
-                FROM scored_nfts
-                ORDER BY 
-                    -- Blend recency and engagement
-                    (1.0 / (1.0 + age_hours / 24.0)) * 0.7 + 
-                    (engagement / 10.0) * 0.3 DESC,
-                    random() * 0.05  -- Tiny randomization for diversity
-                LIMIT $1 OFFSET $2
+                INSERT INTO user_interactions (user_address, nft_id, interaction_type, created_at)
+                VALUES ($1, $2, $3, TIMESTAMP '2024-01-01' + make_interval(mins => $4))
                 "#,
             )
-            .bind(limit as i64)
-            .bind(offset as i64)
-            .fetch_all(&self.pool)
-            .await?
-        };
+            .bind(peer.as_str())
+            .bind(nft_id)
+            .bind(interaction_type)
+            .bind(minutes)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
 
-        // Fetch features in parallel for better performance
-        let mut results = Vec::with_capacity(nfts.len());
-        for nft in nfts {
-            let nft_id = match &nft.id {
-                Some(id) => id.clone(),
-                None => continue,
-            };
-            let features = self.get_nft_features(&nft_id).await?;
-            results.push((nft, features));
+        let engine = RecommendationEngine::new(pool.clone(), ScoringWeights::default());
+        let feed = engine
+            .get_recommendations(&user, 10, Some(&contract_type), false)
+            .await
+            .unwrap();
+
+        let item = |id: Uuid| feed.iter().find(|item| item.nft_id == id.to_string()).unwrap();
+        let boosted = item(saved_by_similar);
+        assert!(matches!(
+            boosted.reason,
+            RecommendationReason::SavedBySimilar { similar_users: 1 }
+        ));
+        for other in [saved_by_dissimilar, unsaved, untouched] {
+            assert!(boosted.score > item(other).score);
+            assert_ne!(item(other).reason.kind(), "saved_by_similar");
         }
 
-        Ok(results)
+        let peers = [&user, &similar, &dissimilar, &unsaver].map(|peer| peer.as_str());
+        for table in ["user_interactions", "user_preferences", "recommendation_cache"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE user_address = ANY($1)", table))
+                .bind(&peers[..])
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("DELETE FROM nfts WHERE contract_type = $1")
+            .bind(&contract_type)
+            .execute(&pool)
+            .await
+            .unwrap();
     }
 
-    async fn get_nft_features(&self, nft_id: &str) -> Result<Option<NftFeatures>> {
-        super::features::get_features(&self.pool, nft_id).await
+    #[test]
+    fn test_explain_reports_saved_by_similar_boost_and_rejected_penalty() {
+        let explained = |id: &str| ExplainedNft {
+            nft_id: id.to_string(),
+            score: 0.5,
+            reason: RecommendationReason::Discovery,
+            breakdown: ScoreBreakdown::default(),
+            saved_by_similar: 0.0,
+            rejected_penalty: 0.0,
+        };
+        let mut items = vec![explained("saved"), explained("rejected"), explained("plain")];
+        let saves = HashMap::from([(
+            "saved".to_string(),
+            SimilarSaves {
+                similar_users: 2,
+                max_similarity: 0.8,
+            },
+        )]);
+        let rejected = HashSet::from(["rejected".to_string()]);
+
+        adjust_explained(&mut items, &saves, 0.1, &rejected, 0.9);
+
+        assert!((items[0].saved_by_similar - 0.08).abs() < 1e-6);
+        assert!((items[0].score - 0.58).abs() < 1e-6);
+        assert!(matches!(
+            items[0].reason,
+            RecommendationReason::SavedBySimilar { similar_users: 2 }
+        ));
+        assert!((items[1].rejected_penalty - 0.45).abs() < 1e-6);
+        assert!((items[1].score - 0.05).abs() < 1e-6);
+        assert_eq!((items[2].score, items[2].saved_by_similar, items[2].rejected_penalty), (0.5, 0.0, 0.0));
     }
 
-    async fn get_following_addresses(&self, user_address: &str) -> Result<Vec<String>> {
-        let rows = sqlx::query_scalar::<_, String>(
-            r#"
-            SELECT u.address 
-            FROM follows f
-            JOIN social_users u ON u.id = f.followee_id
-            JOIN social_users follower ON follower.id = f.follower_id
-            WHERE follower.address = $1 AND f.is_active = true
-            "#,
-        )
-        .bind(user_address.to_lowercase())
-        .fetch_all(&self.pool)
-        .await?;
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_filtered_and_unfiltered_feeds_cache_apart() {
+        let pool = crate::database::test_pool().await;
 
-        Ok(rows)
+        let user = random_address();
+        let item = |id: &str, contract_type: &str| ScoredNft {
+            nft_id: id.to_string(),
+            token_id: 1,
+            contract_address: "0xabc".to_string(),
+            score: 0.5,
+            reason: RecommendationReason::Discovery,
+            reasons: Vec::new(),
+            contract_type: contract_type.to_string(),
+            creator_address: "0xcreator".to_string(),
+            tags: vec![],
+            creator_username: None,
+        };
+
+        let music = cache_feed_type("personalized", Some("Music"));
+        let unfiltered = cache_feed_type("personalized", None);
+        assert_eq!(music, "personalized:music");
+        assert_eq!(unfiltered, "personalized");
+        assert_eq!(cache_feed_type("personalized", Some(" ")), "personalized");
+
+        cache_recommendations(&pool, &user, &music, &[item("song", "music")], 10).await.unwrap();
+        cache_recommendations(&pool, &user, &unfiltered, &[item("song", "music"), item("painting", "art")], 10)
+            .await
+            .unwrap();
+
+        let ids = |feed: Option<Vec<ScoredNft>>| feed.unwrap().into_iter().map(|i| i.nft_id).collect::<Vec<_>>();
+        assert_eq!(ids(get_cached_recommendations(&pool, &user, &music).await.unwrap()), vec!["song"]);
+        assert_eq!(
+            ids(get_cached_recommendations(&pool, &user, &unfiltered).await.unwrap()),
+            vec!["song", "painting"]
+        );
+        assert!(get_cached_recommendations(&pool, &user, &cache_feed_type("personalized", Some("art")))
+            .await
+            .unwrap()
+            .is_none());
+
+        for table in ["recommendation_reason_analytics", "recommendation_cache"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE user_address = $1", table))
+                .bind(&user)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
     }
 
-    async fn get_nfts_from_creators(
-        &self,
-        creators: &[String],
-        limit: usize,
-        offset: usize,
-    ) -> Result<Vec<CandidateNft>> {
-        let nfts = sqlx::query_as::<_, CandidateNft>(
-            r#"
-            SELECT id::text, token_id, contract_address, contract_type::text, 
-                   creator_address, creation_time::text as created_at
-            FROM nfts 
-            WHERE is_deleted = false 
-            AND is_original = true
-            AND creator_address = ANY($1)
-            ORDER BY creation_time DESC
-            LIMIT $2 OFFSET $3
-            "#,
-        )
-        .bind(creators)
-        .bind(limit as i64)
-        .bind(offset as i64)
-        .fetch_all(&self.pool)
-        .await?;
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_cursor_pages_do_not_repeat_after_new_mints() {
+        let pool = crate::database::test_pool().await;
+        crate::database::ensure_nfts_table(&pool).await;
+
+        let contract_type = format!("cur{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let mint = |minutes_ago: i64| {
+            let pool = pool.clone();
+            let contract_type = contract_type.clone();
+            async move {
+                let id = Uuid::new_v4();
+                sqlx::query(
+                    r#"
+                    INSERT INTO nfts (id, token_id, contract_address, contract_type, creator_address, creation_time)
+                    VALUES ($1, 1, '0xabc', $2, '0xcreator', NOW() - make_interval(mins => $3::int))
+                    "#,
+                )
+                .bind(id)
+                .bind(&contract_type)
+                .bind(minutes_ago as i32)
+                .execute(&pool)
+                .await
+                .unwrap();
+                id.to_string()
+            }
+        };
+        let mut minted = Vec::new();
+        for minutes_ago in [10, 20, 30, 30, 40] {
+            minted.push(mint(minutes_ago).await);
+        }
 
-        Ok(nfts)
+        let engine = RecommendationEngine::new(pool.clone(), ScoringWeights::default());
+        let user = random_address();
+        let ids = |items: Vec<ScoredNft>| items.into_iter().map(|s| s.nft_id).collect::<Vec<_>>();
+
+        let (first, cursor) = engine
+            .get_enhanced_feed_cursor(&user, 2, Some(&contract_type), None)
+            .await
+            .unwrap();
+        let cursor = cursor.unwrap();
+        assert_eq!(FeedCursor::decode(&cursor.encode()), Some(cursor));
+
+        // A mint between pages would shift an offset-based page by one
+        let fresh = mint(0).await;
+
+        let mut served = ids(first);
+        let mut cursor = Some(cursor);
+        while let Some(c) = cursor {
+            let (page, next) = engine
+                .get_enhanced_feed_cursor(&user, 2, Some(&contract_type), Some(&c))
+                .await
+                .unwrap();
+            served.extend(ids(page));
+            cursor = next;
+        }
+
+        let mut unique = served.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), served.len(), "duplicates in {:?}", served);
+        minted.sort();
+        assert_eq!(unique, minted);
+        assert!(!served.contains(&fresh));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashMap;
+    #[test]
+    fn test_candidate_pool_follows_source_blend() {
+        let blend = SourceBlend::default();
+        let pool = |prefix: &str, n: usize| {
+            (0..n)
+                .map(|i| candidate(&format!("{}-{}", prefix, i), 0.5))
+                .collect::<Vec<_>>()
+        };
+        let sources = HashMap::from([
+            (CandidateSource::Personalized, pool("p", 100)),
+            (CandidateSource::Trending, pool("t", 100)),
+            (CandidateSource::Collaborative, pool("c", 100)),
+            (CandidateSource::Following, pool("f", 100)),
+        ]);
+
+        let blended = blend.blend(100, sources);
+        assert_eq!(blended.len(), 100);
+        let count = |prefix: &str| {
+            blended
+                .iter()
+                .filter(|(n, _)| n.id.as_deref().unwrap().starts_with(prefix))
+                .count()
+        };
+        assert_eq!(count("p-"), 50);
+        assert_eq!(count("t-"), 20);
+        assert_eq!(count("c-"), 20);
+        assert_eq!(count("f-"), 10);
+
+        for total in [0, 1, 7, 33] {
+            let quotas = blend.quotas(total);
+            assert_eq!(quotas.iter().map(|(_, q)| q).sum::<usize>(), total);
+        }
+
+        // A source that comes up short is backfilled from the others
+        let sources = HashMap::from([
+            (CandidateSource::Personalized, pool("p", 100)),
+            (CandidateSource::Trending, pool("t", 5)),
+        ]);
+        let blended = blend.blend(100, sources);
+        assert_eq!(blended.len(), 100);
+        assert_eq!(
+            blended
+                .iter()
+                .filter(|(n, _)| n.id.as_deref().unwrap().starts_with("t-"))
+                .count(),
+            5
+        );
+    }
 
     #[test]
-    fn test_compute_type_affinity_score_high_pref() {
-        let weights = ScoringWeights::default();
-        let mut prefs = UserPreferences::default();
-        prefs.art_affinity = 0.8;
+    fn test_degraded_mode_skips_too_old_cache() {
+        let now = chrono::Utc::now();
+        let max_stale = std::time::Duration::from_secs(3600);
+        let cached = |age_secs: i64| {
+            let (nft, _) = candidate("cached", 0.5);
+            let item = ScoredNft {
+                nft_id: nft.id.unwrap(),
+                token_id: nft.token_id,
+                contract_address: nft.contract_address,
+                score: 0.5,
+                reason: RecommendationReason::Trending { trending_score: 0.5 },
+                reasons: Vec::new(),
+                contract_type: "art".to_string(),
+                creator_address: nft.creator_address,
+                tags: vec![],
+                creator_username: None,
+            };
+            Some((now - chrono::Duration::seconds(age_secs), vec![item]))
+        };
 
-        let (score, reason) = RecommendationEngine::compute_type_affinity_score(&weights, "art", &prefs);
-        assert!(score > 0.0);
-        match reason {
-            Some(RecommendationReason::ContentTypeMatch { content_type }) => {
-                assert_eq!(content_type, "art")
-            }
-            _ => panic!("expected content type match reason"),
+        match DegradedFeed::choose(cached(600), now, max_stale) {
+            DegradedFeed::Cache(items) => assert_eq!(items[0].nft_id, "cached"),
+            other => panic!("expected cache, got {:?}", other),
         }
+        assert!(matches!(DegradedFeed::choose(cached(6 * 3600), now, max_stale), DegradedFeed::Trending));
+        assert!(matches!(DegradedFeed::choose(None, now, max_stale), DegradedFeed::Trending));
     }
 
     #[test]
-    fn test_compute_feature_scores_tag_match() {
+    fn test_verbose_reasons_ordered_by_contribution() {
         let weights = ScoringWeights::default();
         let mut prefs = UserPreferences::default();
-        prefs.tag_preferences.insert("landscape".to_string(), 0.8);
+        prefs.art_affinity = 0.9;
+        prefs.creator_preferences.insert("0xcreator".to_string(), 0.9);
+        prefs.tag_preferences.insert("landscape".to_string(), 0.95);
 
-        let f = NftFeatures {
+        let features = Some(NftFeatures {
             nft_id: "1".to_string(),
             contract_address: "0xabc".to_string(),
             token_id: 1,
@@ -820,33 +3596,78 @@ mod tests {
             mood: None,
             genre: None,
             engagement_score: 0.0,
-            trending_score: 0.0,
+            trending_score: 0.9,
             quality_score: 0.0,
-        };
-
+        });
         let seen_creators: HashMap<String, usize> = HashMap::new();
         let seen_tags: HashMap<String, usize> = HashMap::new();
+        let ctx = ScoringContext {
+            prefs: &prefs,
+            contract_type: "art",
+            creator_address: "0xcreator",
+            created_at: "",
+            features: &features,
+            seen_creators: &seen_creators,
+            seen_tags: &seen_tags,
+        };
 
-        let (score, reason) = RecommendationEngine::compute_feature_scores(&weights, &f, &prefs, &seen_creators, &seen_tags);
-        assert!(score > 0.0);
-        match reason {
-            Some(RecommendationReason::TagMatch { matching_tags }) => {
-                assert_eq!(matching_tags, vec!["landscape".to_string()]);
-            }
-            _ => panic!("expected TagMatch reason"),
+        let (_, reason, reasons) = RecommendationEngine::calculate_score_static(&ctx, &weights);
+        assert_eq!(reasons.len(), MAX_VERBOSE_REASONS);
+        assert_eq!(reasons[0].kind(), reason.kind());
+
+        let mut contribution: HashMap<&str, f32> = HashMap::new();
+        let (type_score, _) = RecommendationEngine::compute_type_affinity_score(&weights, "art", &prefs);
+        contribution.insert("content_type_match", type_score);
+        let (creator_score, _) = RecommendationEngine::compute_creator_affinity_score(&weights, "0xcreator", &prefs);
+        contribution.insert("creator_affinity", creator_score);
+        let (_, _, feature_contributions) = RecommendationEngine::compute_feature_scores(
+            &weights,
+            features.as_ref().unwrap(),
+            &prefs,
+            &seen_creators,
+            &seen_tags,
+            &mut ScoreBreakdown::default(),
+        );
+        for (score, r) in feature_contributions {
+            contribution.insert(r.kind(), score);
         }
+
+        let scores: Vec<f32> = reasons[1..].iter().map(|r| contribution[r.kind()]).collect();
+        assert!(scores.windows(2).all(|w| w[0] >= w[1]), "not ordered: {scores:?}");
+        assert!(scores.iter().all(|s| *s <= contribution[reason.kind()]));
     }
 
     #[test]
-    fn test_compute_recency_score_recent_vs_old() {
-        let now = chrono::Utc::now();
-        let recent = now.to_rfc3339();
-        let old = (now - chrono::Duration::days(10)).to_rfc3339();
+    fn test_reasons_omitted_when_empty() {
+        let item = ScoredNft {
+            nft_id: "1".to_string(),
+            token_id: 1,
+            contract_address: "0xabc".to_string(),
+            score: 0.5,
+            reason: RecommendationReason::Discovery,
+            reasons: Vec::new(),
+            contract_type: "art".to_string(),
+            creator_address: "0xcreator".to_string(),
+            tags: Vec::new(),
+            creator_username: None,
+        };
+        let json = serde_json::to_value(&item).unwrap();
+        assert!(json.get("reasons").is_none());
+    }
+}
 
-        let r1 = RecommendationEngine::compute_recency_score(&recent);
-        let r2 = RecommendationEngine::compute_recency_score(&old);
-        assert!(r1 > r2);
+/// Parse a candidate's `created_at`, which is RFC 3339 or Postgres'
+/// `timestamp::text` format
+fn parse_created_at(created_at: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(created_at) {
+        return Some(dt.with_timezone(&chrono::Utc));
     }
+    if let Ok(dt) = chrono::DateTime::parse_from_str(created_at, "%Y-%m-%d %H:%M:%S%.f%#z") {
+        return Some(dt.with_timezone(&chrono::Utc));
+    }
+    chrono::NaiveDateTime::parse_from_str(created_at, "%Y-%m-%d %H:%M:%S%.f")
+        .ok()
+        .map(|dt| dt.and_utc())
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -857,6 +3678,123 @@ struct CandidateNft {
     contract_type: Option<String>,
     creator_address: String,
     created_at: Option<String>,
+    /// From `creator_profiles`, joined on by the candidate queries
+    #[sqlx(default)]
+    creator_username: Option<String>,
+}
+
+/// Candidate plus the keyset position it was read at
+#[derive(Debug, sqlx::FromRow)]
+struct KeysetCandidate {
+    #[sqlx(flatten)]
+    nft: CandidateNft,
+    cursor_time: chrono::NaiveDateTime,
+    cursor_id: Uuid,
+}
+
+/// Candidate row with its `nft_features` columns joined on
+#[derive(Debug, sqlx::FromRow)]
+struct CandidateRow {
+    #[sqlx(flatten)]
+    nft: CandidateNft,
+    #[sqlx(flatten)]
+    features: super::features::JoinedFeaturesRow,
+}
+
+/// A page of candidates with their features, read in a single query.
+///
+/// Takes one executor by value, so the page (features included) costs one
+/// round trip rather than one per candidate.
+async fn fetch_candidates<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    contract_type_filter: Option<&str>,
+    limit: usize,
+    offset: usize,
+) -> Result<Vec<(CandidateNft, Option<NftFeatures>)>> {
+    // ByteGraph optimization: Fetch candidates with smart distribution
+    // If no filter, we fetch based on recency with slight randomization
+    let rows: Vec<CandidateRow> = if let Some(ct) = contract_type_filter {
+        sqlx::query_as::<_, CandidateRow>(
+            r#"
+            SELECT n.id::text, n.token_id, n.contract_address, n.contract_type::text,
+                   n.creator_address, n.creation_time::text as created_at, NULLIF(cp.username, '') AS creator_username,
+                   f.nft_id AS feature_nft_id, f.tags, f.primary_color, f.style, f.mood,
+                   f.genre, f.engagement_score, f.trending_score, f.quality_score
+            FROM nfts n
+            LEFT JOIN nft_features f ON f.nft_id = n.id
+            LEFT JOIN creator_profiles cp ON cp.address = LOWER(n.creator_address)
+            WHERE n.is_deleted = false
+            AND n.is_original = true
+            AND n.contract_type = $1
+            ORDER BY
+                n.creation_time DESC,
+                random() * 0.1  -- Add slight randomness for discovery
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(ct)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(executor)
+        .await?
+    } else {
+        // Mixed strategy: 70% recent, 30% engagement-based
+        sqlx::query_as::<_, CandidateRow>(
+            r#"
+            WITH recent_nfts AS (
+                SELECT id, token_id, contract_address, contract_type::text as contract_type,
+                       creator_address, creation_time,
+                       likes_count, buys_count
+                FROM nfts
+                WHERE is_deleted = false
+                AND is_original = true
+                AND creation_time > NOW() - INTERVAL '30 days'
+            ),
+            scored_nfts AS (
+                SELECT id, token_id, contract_address, contract_type,
+                       creator_address, creation_time::text as created_at,
+                    EXTRACT(EPOCH FROM (NOW() - creation_time)) / 3600.0 as age_hours,
+                    (likes_count + buys_count * 2) as engagement
+                FROM recent_nfts
+            )
+            SELECT s.id::text, s.token_id, s.contract_address, s.contract_type,
+                   s.creator_address, s.created_at, NULLIF(cp.username, '') AS creator_username,
+                   f.nft_id AS feature_nft_id, f.tags, f.primary_color, f.style, f.mood,
+                   f.genre, f.engagement_score, f.trending_score, f.quality_score
+            FROM scored_nfts s
+            LEFT JOIN nft_features f ON f.nft_id = s.id
+            LEFT JOIN creator_profiles cp ON cp.address = LOWER(s.creator_address)
+            ORDER BY
+                -- Blend recency and engagement
+                (1.0 / (1.0 + s.age_hours / 24.0)) * 0.7 +
+                (s.engagement / 10.0) * 0.3 DESC,
+                random() * 0.05  -- Tiny randomization for diversity
+            LIMIT $1 OFFSET $2
+            "#,
+        )
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(executor)
+        .await?
+    };
+
+    Ok(rows
+        .into_iter()
+        .filter(|row| row.nft.id.is_some())
+        .map(|row| {
+            let features = row.features.into_features(&row.nft.contract_address, row.nft.token_id);
+            (row.nft, features)
+        })
+        .collect())
+}
+
+/// Cache key for `feed` narrowed to `contract_type_filter`, e.g.
+/// `personalized:music`, so filtered and unfiltered feeds are cached apart
+pub fn cache_feed_type(feed: &str, contract_type_filter: Option<&str>) -> String {
+    match contract_type_filter.map(str::trim).filter(|ct| !ct.is_empty()) {
+        Some(contract_type) => format!("{}:{}", feed, contract_type.to_lowercase()),
+        None => feed.to_string(),
+    }
 }
 
 /// Cache recommendations for faster serving
@@ -890,9 +3828,175 @@ pub async fn cache_recommendations(
     .execute(pool)
     .await?;
 
+    // Best-effort: analytics must never fail the cache path
+    if let Err(e) = record_reason_analytics(pool, user_address, feed_type, recommendations).await {
+        warn!("Failed to record recommendation reasons for {}: {:?}", user_address, e);
+    }
+
+    Ok(())
+}
+
+/// How many recommendations each primary reason accounts for
+pub fn reason_histogram(recommendations: &[ScoredNft]) -> BTreeMap<&'static str, u32> {
+    let mut counts = BTreeMap::new();
+    for rec in recommendations {
+        *counts.entry(rec.reason.kind()).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Store the reason histogram of one generated feed
+async fn record_reason_analytics(
+    pool: &PgPool,
+    user_address: &str,
+    feed_type: &str,
+    recommendations: &[ScoredNft],
+) -> Result<()> {
+    if recommendations.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO recommendation_reason_analytics (user_address, feed_type, reason_counts, total)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(user_address.to_lowercase())
+    .bind(feed_type)
+    .bind(serde_json::to_value(reason_histogram(recommendations))?)
+    .bind(recommendations.len() as i32)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Latest cache entry regardless of expiry, with when it was computed
+async fn get_cache_entry(
+    pool: &PgPool,
+    user_address: &str,
+    feed_type: &str,
+) -> Result<Option<(chrono::DateTime<chrono::Utc>, Vec<ScoredNft>)>> {
+    let row = sqlx::query_as::<_, (chrono::NaiveDateTime, serde_json::Value)>(
+        r#"
+        SELECT computed_at, recommendations
+        FROM recommendation_cache
+        WHERE user_address = $1
+        AND feed_type = $2
+        "#,
+    )
+    .bind(user_address.to_lowercase())
+    .bind(feed_type)
+    .fetch_optional(pool)
+    .await?;
+
+    match row {
+        Some((computed_at, value)) => Ok(Some((computed_at.and_utc(), serde_json::from_value(value)?))),
+        None => Ok(None),
+    }
+}
+
+/// Drop every cached feed for a user so the next request recomputes it
+pub async fn invalidate_cached_recommendations<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    user_address: &str,
+) -> Result<()> {
+    sqlx::query("DELETE FROM recommendation_cache WHERE user_address = $1")
+        .bind(user_address.to_lowercase())
+        .execute(executor)
+        .await?;
     Ok(())
 }
 
+/// NFTs `user_address` rejected: ones whose latest like/unlike is an unlike,
+/// or whose latest save/unsave is an unsave
+pub async fn get_rejected_nfts<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    user_address: &str,
+) -> Result<HashSet<String>> {
+    let rejected: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT nft_id::text FROM (
+            SELECT DISTINCT ON (nft_id, interaction_type IN ('like', 'unlike'))
+                nft_id, interaction_type
+            FROM user_interactions
+            WHERE user_address = $1
+              AND interaction_type IN ('like', 'unlike', 'save', 'unsave')
+            ORDER BY nft_id, interaction_type IN ('like', 'unlike'), created_at DESC
+        ) latest
+        WHERE interaction_type IN ('unlike', 'unsave')
+        "#,
+    )
+    .bind(user_address.to_lowercase())
+    .fetch_all(executor)
+    .await?;
+    Ok(rejected.into_iter().collect())
+}
+
+/// `(nft_id, taste_vector)` for each user other than `user_address` whose
+/// latest save or unsave of one of `nft_ids` was a save, where that user
+/// has a stored taste vector
+pub async fn get_saver_tastes<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    user_address: &str,
+    nft_ids: &[String],
+) -> Result<Vec<(String, Vec<f32>)>> {
+    let nft_ids: Vec<Uuid> = nft_ids.iter().filter_map(|id| id.parse().ok()).collect();
+    let rows = sqlx::query_as::<_, (String, Vec<f32>)>(
+        r#"
+        SELECT latest.nft_id::text, p.taste_vector FROM (
+            SELECT DISTINCT ON (nft_id, user_address) nft_id, user_address, interaction_type
+            FROM user_interactions
+            WHERE nft_id = ANY($2)
+              AND user_address <> $1
+              AND interaction_type IN ('save', 'unsave')
+            ORDER BY nft_id, user_address, created_at DESC
+        ) latest
+        JOIN user_preferences p ON p.user_address = latest.user_address
+        WHERE latest.interaction_type = 'save' AND p.taste_vector IS NOT NULL
+        "#,
+    )
+    .bind(user_address.to_lowercase())
+    .bind(&nft_ids)
+    .fetch_all(executor)
+    .await?;
+    Ok(rows)
+}
+
+/// Drop expired cache entries, then, with `max_rows` set, the least recently
+/// computed live entries beyond it. Returns how many entries were removed.
+pub async fn prune_recommendation_cache<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    max_rows: Option<i64>,
+) -> Result<u64> {
+    let removed: i64 = sqlx::query_scalar(
+        r#"
+        WITH expired AS (
+            DELETE FROM recommendation_cache
+            WHERE expires_at < NOW()
+            RETURNING 1
+        ),
+        overflow AS (
+            DELETE FROM recommendation_cache
+            WHERE $1::bigint IS NOT NULL
+            AND id IN (
+                SELECT id FROM recommendation_cache
+                WHERE expires_at >= NOW()
+                ORDER BY computed_at DESC
+                OFFSET $1
+            )
+            RETURNING 1
+        )
+        SELECT (SELECT COUNT(*) FROM expired) + (SELECT COUNT(*) FROM overflow)
+        "#,
+    )
+    .bind(max_rows)
+    .fetch_one(executor)
+    .await?;
+    Ok(removed as u64)
+}
+
 /// Get cached recommendations if valid
 pub async fn get_cached_recommendations(
     pool: &PgPool,
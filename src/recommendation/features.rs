@@ -7,15 +7,23 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::PgPool;
-use std::collections::HashSet;
-use tracing::info;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::{info, warn};
 use uuid::Uuid;
 
+use crate::config::MetadataFetchConfig;
+pub use crate::config::TrendingConfig;
+
 /// Extracted features from an NFT
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NftFeatures {
     pub nft_id: String,
     pub contract_address: String,
+    #[serde(with = "crate::serde_helpers::string_int")]
     pub token_id: i64,
     pub tags: Vec<String>,
     pub primary_color: Option<String>,
@@ -43,6 +51,207 @@ struct FeaturesRow {
     quality_score: f32,
 }
 
+/// Feature columns from a `LEFT JOIN nft_features`; all null when the NFT
+/// has no features row yet
+#[derive(Debug, sqlx::FromRow)]
+pub(crate) struct JoinedFeaturesRow {
+    feature_nft_id: Option<Uuid>,
+    tags: Option<Vec<String>>,
+    primary_color: Option<String>,
+    style: Option<String>,
+    mood: Option<String>,
+    genre: Option<String>,
+    engagement_score: Option<f32>,
+    trending_score: Option<f32>,
+    quality_score: Option<f32>,
+}
+
+impl JoinedFeaturesRow {
+    /// Features for the joined NFT, if it had a features row
+    pub(crate) fn into_features(self, contract_address: &str, token_id: i64) -> Option<NftFeatures> {
+        let nft_id = self.feature_nft_id?;
+        Some(NftFeatures {
+            nft_id: nft_id.to_string(),
+            contract_address: contract_address.to_string(),
+            token_id,
+            tags: self.tags.unwrap_or_default(),
+            primary_color: self.primary_color,
+            style: self.style,
+            mood: self.mood,
+            genre: self.genre,
+            engagement_score: self.engagement_score.unwrap_or(0.0),
+            trending_score: self.trending_score.unwrap_or(0.0),
+            quality_score: self.quality_score.unwrap_or(0.5),
+        })
+    }
+}
+
+/// Fetches mint metadata documents for their tags, within the limits of a
+/// [`MetadataFetchConfig`]. Clones share the client and the fetch budget.
+#[derive(Clone)]
+pub struct MetadataFetcher {
+    client: reqwest::Client,
+    config: Arc<MetadataFetchConfig>,
+    permits: Arc<Semaphore>,
+}
+
+impl MetadataFetcher {
+    pub fn new(config: &MetadataFetchConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            // A redirect could lead off the allowed hosts
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            config: Arc::new(config.clone()),
+            permits: Arc::new(Semaphore::new(config.max_concurrent.max(1))),
+        }
+    }
+
+    /// A slot for one fetch, or `None` while `max_concurrent` fetches are in
+    /// flight
+    pub fn try_acquire(&self) -> Option<OwnedSemaphorePermit> {
+        self.permits.clone().try_acquire_owned().ok()
+    }
+
+    /// URL to fetch `uri` from: `ipfs://` URIs go through the gateway,
+    /// `https://` URIs are used as-is on an allowed host that isn't a
+    /// private or loopback address. Anything else isn't fetched.
+    fn metadata_url(&self, uri: &str) -> Option<String> {
+        let uri = uri.trim();
+        if let Some(path) = uri.strip_prefix("ipfs://") {
+            let path = path.trim_start_matches("ipfs/");
+            return (!path.is_empty()).then(|| format!("{}{}", self.config.ipfs_gateway, path));
+        }
+
+        let url = reqwest::Url::parse(uri).ok()?;
+        if url.scheme() != "https" {
+            return None;
+        }
+        // IPv6 hosts come bracketed; the parser has already lowercased
+        // names and normalized numeric IPv4 forms
+        let host = url.host_str()?.trim_start_matches('[').trim_end_matches(']');
+        if host.parse::<IpAddr>().is_ok_and(is_internal_ip) {
+            return None;
+        }
+        let allowed = self
+            .config
+            .allowed_hosts
+            .iter()
+            .any(|h| h.trim_start_matches('[').trim_end_matches(']') == host);
+        allowed.then(|| url.to_string())
+    }
+
+    /// Fetch the metadata JSON at `uri` and extract its tags. A refused,
+    /// failed, timed out or oversized fetch, or a body that isn't JSON,
+    /// yields no tags rather than an error, so callers never fail on a
+    /// creator's metadata.
+    pub async fn extract_tags_from_metadata(&self, uri: &str) -> Vec<String> {
+        let Some(url) = self.metadata_url(uri) else {
+            warn!("Not fetching metadata from {}: scheme or host not allowed", uri);
+            return Vec::new();
+        };
+
+        let response = match self.client.get(&url).send().await.and_then(|r| r.error_for_status()) {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to fetch metadata {}: {}", url, e);
+                return Vec::new();
+            }
+        };
+        let body = match read_capped(response, self.config.max_bytes).await {
+            Ok(Some(body)) => body,
+            Ok(None) => {
+                warn!("Metadata at {} is over {} bytes", url, self.config.max_bytes);
+                return Vec::new();
+            }
+            Err(e) => {
+                warn!("Failed to read metadata {}: {}", url, e);
+                return Vec::new();
+            }
+        };
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(metadata) => metadata_tags(&metadata),
+            Err(e) => {
+                warn!("Malformed metadata at {}: {}", url, e);
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Loopback, private, link-local and unspecified addresses, which metadata
+/// URIs must not reach
+fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            v4.is_loopback() || v4.is_private() || v4.is_link_local() || v4.is_unspecified() || v4.is_broadcast()
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80 // link-local
+                || v6.to_ipv4_mapped().is_some_and(|v4| is_internal_ip(v4.into()))
+        }
+    }
+}
+
+/// The response body, or `None` once it passes `max_bytes`
+async fn read_capped(mut response: reqwest::Response, max_bytes: usize) -> reqwest::Result<Option<Vec<u8>>> {
+    if response.content_length().is_some_and(|len| len > max_bytes as u64) {
+        return Ok(None);
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_bytes {
+            return Ok(None);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(Some(body))
+}
+
+/// Normalized tags from a metadata document's `attributes` values, `tags`
+/// and `category`. Lists or comma-separated strings are accepted; tags are
+/// trimmed, lowercased, deduplicated and sorted.
+pub fn metadata_tags(metadata: &Value) -> Vec<String> {
+    fn collect(value: &Value, tags: &mut BTreeSet<String>) {
+        match value {
+            Value::String(s) => {
+                for tag in s.split(',') {
+                    let tag = tag.trim().to_lowercase();
+                    if !tag.is_empty() {
+                        tags.insert(tag);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|item| collect(item, tags)),
+            _ => {}
+        }
+    }
+
+    let mut tags = BTreeSet::new();
+    if let Some(attributes) = metadata.get("attributes").and_then(|v| v.as_array()) {
+        for attr in attributes {
+            // OpenSea style `{trait_type, value}`, or a bare string
+            match attr.get("value") {
+                Some(value) => collect(value, &mut tags),
+                None => collect(attr, &mut tags),
+            }
+        }
+    }
+    for key in ["tags", "category"] {
+        if let Some(value) = metadata.get(key) {
+            collect(value, &mut tags);
+        }
+    }
+    tags.into_iter().collect()
+}
+
 // Keyword dictionaries for feature extraction
 // These will be used when processing new NFT metadata
 
@@ -390,6 +599,7 @@ pub async fn save_features(pool: &PgPool, features: &NftFeatures) -> Result<()>
 }
 
 /// Get features for an NFT
+#[allow(dead_code)]
 pub async fn get_features(pool: &PgPool, nft_id: &str) -> Result<Option<NftFeatures>> {
     let result = sqlx::query_as::<_, FeaturesRow>(
         r#"
@@ -403,19 +613,62 @@ pub async fn get_features(pool: &PgPool, nft_id: &str) -> Result<Option<NftFeatu
     .fetch_optional(pool)
     .await?;
 
-    Ok(result.map(|row| NftFeatures {
-        nft_id: row.nft_id.to_string(),
-        contract_address: row.contract_address,
-        token_id: row.token_id,
-        tags: row.tags.unwrap_or_default(),
-        primary_color: row.primary_color,
-        style: row.style,
-        mood: row.mood,
-        genre: row.genre,
-        engagement_score: row.engagement_score,
-        trending_score: row.trending_score,
-        quality_score: row.quality_score,
-    }))
+    Ok(result.map(NftFeatures::from))
+}
+
+/// Get features for many NFTs in one query, keyed by NFT id. Ids that aren't
+/// UUIDs or have no features are simply absent from the map.
+pub async fn get_features_batch(pool: &PgPool, nft_ids: &[String]) -> Result<HashMap<String, NftFeatures>> {
+    let ids: Vec<Uuid> = nft_ids.iter().filter_map(|id| Uuid::parse_str(id).ok()).collect();
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let rows = sqlx::query_as::<_, FeaturesRow>(
+        r#"
+        SELECT nft_id, contract_address, token_id, tags, primary_color,
+               style, mood, genre, engagement_score, trending_score, quality_score
+        FROM nft_features
+        WHERE nft_id = ANY($1)
+        "#,
+    )
+    .bind(&ids)
+    .fetch_all(pool)
+    .await?;
+
+    // Key by the caller's spelling of each id so lookups match
+    let requested: HashMap<Uuid, &String> = nft_ids
+        .iter()
+        .filter_map(|id| Uuid::parse_str(id).ok().map(|uuid| (uuid, id)))
+        .collect();
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let key = requested
+                .get(&row.nft_id)
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| row.nft_id.to_string());
+            (key, NftFeatures::from(row))
+        })
+        .collect())
+}
+
+impl From<FeaturesRow> for NftFeatures {
+    fn from(row: FeaturesRow) -> Self {
+        Self {
+            nft_id: row.nft_id.to_string(),
+            contract_address: row.contract_address,
+            token_id: row.token_id,
+            tags: row.tags.unwrap_or_default(),
+            primary_color: row.primary_color,
+            style: row.style,
+            mood: row.mood,
+            genre: row.genre,
+            engagement_score: row.engagement_score,
+            trending_score: row.trending_score,
+            quality_score: row.quality_score,
+        }
+    }
 }
 
 /// Update engagement scores for all NFTs (run periodically)
@@ -445,33 +698,192 @@ pub async fn update_engagement_scores(pool: &PgPool) -> Result<u64> {
     Ok(result.rows_affected())
 }
 
+/// Half-lives of history that count toward trending; older engagement would
+/// add under 0.5% of its weight
+const TRENDING_WINDOW_HALF_LIVES: u32 = 8;
+
+impl TrendingConfig {
+    /// How far back engagement is counted
+    pub fn window(&self) -> Duration {
+        self.half_life * TRENDING_WINDOW_HALF_LIVES
+    }
+}
+
+/// Per-NFT `(content type, decayed engagement)`: likes, comments and shares
+/// within the window, each halved in weight every `half_life` of age
+pub(crate) async fn decayed_engagement<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    config: &TrendingConfig,
+) -> Result<Vec<(Uuid, Option<String>, f64)>> {
+    let rows = sqlx::query_as(
+        r#"
+        SELECT
+            nft_id,
+            MAX(nft_contract_type),
+            SUM(POWER(0.5, EXTRACT(EPOCH FROM (NOW() - created_at))::FLOAT8 / $1))::FLOAT8
+        FROM user_interactions
+        WHERE interaction_type IN ('like', 'comment', 'share')
+        AND created_at > NOW() - make_interval(secs => $2)
+        GROUP BY nft_id
+        "#,
+    )
+    .bind(config.half_life.as_secs_f64())
+    .bind(config.window().as_secs_f64())
+    .fetch_all(executor)
+    .await?;
+    Ok(rows)
+}
+
 /// Update trending scores based on recent activity (run hourly)
-pub async fn update_trending_scores(pool: &PgPool) -> Result<u64> {
+///
+/// With `normalize_by_type`, each NFT's engagement is measured against the
+/// average for its content type, so a music NFT trending within music isn't
+/// buried by the sheer volume of snaps.
+pub async fn update_trending_scores(pool: &PgPool, config: &TrendingConfig) -> Result<u64> {
     // Trending = recent engagement with time decay
+    let rows = decayed_engagement(pool, config).await?;
+
+    let ids: Vec<Uuid> = rows.iter().map(|(id, _, _)| *id).collect();
+    let engagement: Vec<(Option<String>, f64)> = rows
+        .into_iter()
+        .map(|(_, content_type, score)| (content_type, score))
+        .collect();
+    let scores = trending_scores(&engagement, config.normalize_by_type);
+
+    let mut tx = pool.begin().await?;
+    // NFTs with no recent engagement drop back to zero
+    let reset = sqlx::query(
+        "UPDATE nft_features SET trending_score = 0, updated_at = NOW() WHERE NOT (nft_id = ANY($1))",
+    )
+    .bind(&ids)
+    .execute(&mut *tx)
+    .await?;
     let result = sqlx::query(
         r#"
         UPDATE nft_features f SET
-            trending_score = COALESCE((
-                SELECT SUM(
-                    CASE interaction_type 
-                        WHEN 'like' THEN 1.0
-                        WHEN 'purchase' THEN 3.0
-                        WHEN 'view' THEN 0.1
-                        ELSE 0.5
-                    END * EXP(-EXTRACT(EPOCH FROM (NOW() - created_at)) / 86400.0)
-                )
-                FROM user_interactions i
-                WHERE i.nft_id = f.nft_id
-                AND i.created_at > NOW() - INTERVAL '7 days'
-            ), 0) / 100.0,
+            trending_score = u.score,
             updated_at = NOW()
+        FROM UNNEST($1::uuid[], $2::real[]) AS u(nft_id, score)
+        WHERE f.nft_id = u.nft_id
         "#,
     )
-    .execute(pool)
+    .bind(&ids)
+    .bind(&scores)
+    .execute(&mut *tx)
     .await?;
+    tx.commit().await?;
+
+    let updated = reset.rows_affected() + result.rows_affected();
+    info!("📈 Updated trending scores for {} NFTs", updated);
+    Ok(updated)
+}
+
+/// Trending scores for `(content type, decayed engagement)` rows, on the
+/// `/ 100` scale of `hot_score`. Normalizing rescales each type so its
+/// average matches the overall average; NFTs without a type share a group.
+pub fn trending_scores(engagement: &[(Option<String>, f64)], normalize_by_type: bool) -> Vec<f32> {
+    let scale = |raw: f64| (raw.max(0.0) / 100.0) as f32;
+    if !normalize_by_type || engagement.is_empty() {
+        return engagement.iter().map(|(_, raw)| scale(*raw)).collect();
+    }
+
+    let mut totals: HashMap<Option<&str>, (f64, usize)> = HashMap::new();
+    for (content_type, raw) in engagement {
+        let entry = totals.entry(content_type.as_deref()).or_default();
+        entry.0 += raw.max(0.0);
+        entry.1 += 1;
+    }
+    let overall = engagement.iter().map(|(_, raw)| raw.max(0.0)).sum::<f64>() / engagement.len() as f64;
+
+    engagement
+        .iter()
+        .map(|(content_type, raw)| {
+            let (total, count) = totals[&content_type.as_deref()];
+            let type_average = total / count as f64;
+            if type_average > 0.0 {
+                scale(raw * overall / type_average)
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// "Hot now" score from weighted engagement in the latest window, on the
+/// same scale as `trending_score`
+pub fn hot_score(recent: f64) -> f32 {
+    (recent.max(0.0) / 100.0) as f32
+}
+
+/// "Rising" score: growth of weighted engagement from the previous window to
+/// the latest one, relative to where it started. Falling engagement scores 0.
+pub fn rising_score(recent: f64, previous: f64) -> f32 {
+    let (recent, previous) = (recent.max(0.0), previous.max(0.0));
+    ((recent - previous) / (previous + 1.0)).max(0.0) as f32
+}
+
+/// Update hot-now and rising scores from engagement in the latest `window`
+/// and the one before it (run with the other score updates)
+pub async fn update_trending_windows(pool: &PgPool, window: std::time::Duration) -> Result<u64> {
+    let window_secs = window.as_secs_f64().max(1.0);
+
+    let rows: Vec<(Uuid, f64, f64)> = sqlx::query_as(
+        r#"
+        SELECT
+            nft_id,
+            COALESCE(SUM(w) FILTER (WHERE created_at > NOW() - make_interval(secs => $1)), 0)::FLOAT8,
+            COALESCE(SUM(w) FILTER (WHERE created_at <= NOW() - make_interval(secs => $1)), 0)::FLOAT8
+        FROM (
+            SELECT nft_id, created_at,
+                CASE interaction_type
+                    WHEN 'like' THEN 1.0
+                    WHEN 'purchase' THEN 3.0
+                    WHEN 'view' THEN 0.1
+                    ELSE 0.5
+                END AS w
+            FROM user_interactions
+            WHERE created_at > NOW() - make_interval(secs => $1 * 2)
+        ) i
+        GROUP BY nft_id
+        "#,
+    )
+    .bind(window_secs)
+    .fetch_all(pool)
+    .await?;
+
+    let ids: Vec<Uuid> = rows.iter().map(|(id, _, _)| *id).collect();
+    let hot: Vec<f32> = rows.iter().map(|(_, recent, _)| hot_score(*recent)).collect();
+    let rising: Vec<f32> = rows
+        .iter()
+        .map(|(_, recent, previous)| rising_score(*recent, *previous))
+        .collect();
+
+    let mut tx = pool.begin().await?;
+    // NFTs with no engagement in either window drop back to zero
+    sqlx::query("DELETE FROM nft_trending_windows WHERE NOT (nft_id = ANY($1))")
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?;
+    let result = sqlx::query(
+        r#"
+        INSERT INTO nft_trending_windows (nft_id, hot_score, rising_score, updated_at)
+        SELECT u.nft_id, u.hot, u.rising, NOW()
+        FROM UNNEST($1::uuid[], $2::real[], $3::real[]) AS u(nft_id, hot, rising)
+        ON CONFLICT (nft_id) DO UPDATE SET
+            hot_score = EXCLUDED.hot_score,
+            rising_score = EXCLUDED.rising_score,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(&ids)
+    .bind(&hot)
+    .bind(&rising)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
 
     info!(
-        "📈 Updated trending scores for {} NFTs",
+        "🚀 Updated hot/rising scores for {} NFTs",
         result.rows_affected()
     );
     Ok(result.rows_affected())
@@ -496,3 +908,82 @@ pub fn calculate_tag_similarity(tags1: &[String], tags2: &[String]) -> f32 {
         intersection as f32 / union as f32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+
+    #[tokio::test]
+    async fn test_metadata_attributes_become_tags() {
+        let app = axum::Router::new()
+            .route(
+                "/ipfs/meta.json",
+                get(|| async {
+                    axum::Json(serde_json::json!({
+                        "name": "Dusk",
+                        "attributes": [
+                            {"trait_type": "Mood", "value": "Calm"},
+                            {"trait_type": "Scene", "value": " Beach "},
+                            {"trait_type": "Edition", "value": 3}
+                        ],
+                        "tags": "sunset, calm",
+                        "category": "Photography"
+                    }))
+                }),
+            )
+            .route("/ipfs/broken.json", get(|| async { "{not json" }))
+            .route("/ipfs/big.json", get(|| async { format!("{{\"tags\": \"{}\"}}", "a".repeat(4096)) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        // The operator's gateway is trusted even on a local address
+        let fetcher = MetadataFetcher::new(&MetadataFetchConfig {
+            ipfs_gateway: format!("http://{}/ipfs/", addr),
+            max_bytes: 1024,
+            ..MetadataFetchConfig::default()
+        });
+        let tags = fetcher.extract_tags_from_metadata("ipfs://meta.json").await;
+        assert_eq!(tags, vec!["beach", "calm", "photography", "sunset"]);
+
+        // Malformed JSON, missing or oversized documents and unsupported
+        // schemes yield no tags
+        assert!(fetcher.extract_tags_from_metadata("ipfs://broken.json").await.is_empty());
+        assert!(fetcher.extract_tags_from_metadata("ipfs://missing.json").await.is_empty());
+        assert!(fetcher.extract_tags_from_metadata("ipfs://big.json").await.is_empty());
+        assert!(fetcher.extract_tags_from_metadata("ar://abc").await.is_empty());
+
+        // A URI straight at the local server is refused
+        let direct = format!("http://{}/ipfs/meta.json", addr);
+        assert!(fetcher.extract_tags_from_metadata(&direct).await.is_empty());
+    }
+
+    #[test]
+    fn test_metadata_url_allows_ipfs_and_listed_https_hosts_only() {
+        let fetcher = MetadataFetcher::new(&MetadataFetchConfig {
+            allowed_hosts: vec!["meta.example.com".into(), "127.0.0.1".into(), "[::1]".into()],
+            ..MetadataFetchConfig::default()
+        });
+
+        assert_eq!(
+            fetcher.metadata_url("ipfs://bafy/metadata.json").unwrap(),
+            "https://ipfs.io/ipfs/bafy/metadata.json"
+        );
+        assert_eq!(
+            fetcher.metadata_url("https://META.example.com/1.json").unwrap(),
+            "https://meta.example.com/1.json"
+        );
+        assert!(fetcher.metadata_url("http://meta.example.com/1.json").is_none());
+        assert!(fetcher.metadata_url("https://other.example.com/1.json").is_none());
+        assert!(fetcher.metadata_url("file:///etc/passwd").is_none());
+
+        // Internal addresses stay off limits even when listed
+        assert!(fetcher.metadata_url("https://127.0.0.1/1.json").is_none());
+        assert!(fetcher.metadata_url("https://2130706433/1.json").is_none());
+        assert!(fetcher.metadata_url("https://[::1]/1.json").is_none());
+        assert!(is_internal_ip("169.254.169.254".parse().unwrap()));
+        assert!(is_internal_ip("::ffff:10.0.0.1".parse().unwrap()));
+        assert!(!is_internal_ip("93.184.216.34".parse().unwrap()));
+    }
+}
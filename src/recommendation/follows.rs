@@ -0,0 +1,94 @@
+//! User Follows
+//!
+//! `Follow` and `Unfollow` events toggle `user_follows.is_active` for a
+//! follower/target pair, which the following feed reads alongside the social
+//! app's `follows` table.
+
+use anyhow::Result;
+use sqlx::PgExecutor;
+
+use crate::address::normalize_address;
+
+/// Record that `follower` follows `target`. Replays are harmless.
+pub async fn follow_user<'e>(
+    executor: impl PgExecutor<'e>,
+    follower: &str,
+    target: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_follows (follower_address, target_address, is_active)
+        VALUES ($1, $2, true)
+        ON CONFLICT (follower_address, target_address) DO UPDATE SET
+            is_active = true,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(normalize_address(follower)?)
+    .bind(normalize_address(target)?)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Mark a follow inactive. A no-op when `follower` never followed `target`.
+pub async fn unfollow_user<'e>(
+    executor: impl PgExecutor<'e>,
+    follower: &str,
+    target: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE user_follows SET is_active = false, updated_at = NOW()
+        WHERE follower_address = $1 AND target_address = $2
+        "#,
+    )
+    .bind(normalize_address(follower)?)
+    .bind(normalize_address(target)?)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_follow_then_unfollow_leaves_follow_inactive() {
+        let pool = crate::database::test_pool().await;
+
+        let follower = "0x00000000000000000000000000000000000f0110";
+        let target = "0x00000000000000000000000000000000000F0111";
+        let state = || async {
+            sqlx::query_scalar::<_, bool>(
+                "SELECT is_active FROM user_follows WHERE follower_address = $1 AND target_address = $2",
+            )
+            .bind(follower)
+            .bind(target.to_lowercase())
+            .fetch_optional(&pool)
+            .await
+            .unwrap()
+        };
+        sqlx::query("DELETE FROM user_follows WHERE follower_address = $1")
+            .bind(follower)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Unfollow before any follow creates nothing
+        unfollow_user(&pool, follower, target).await.unwrap();
+        assert_eq!(state().await, None);
+
+        // Replayed follow events upsert the same row
+        follow_user(&pool, follower, target).await.unwrap();
+        follow_user(&pool, follower, target).await.unwrap();
+        assert_eq!(state().await, Some(true));
+
+        unfollow_user(&pool, follower, target).await.unwrap();
+        assert_eq!(state().await, Some(false));
+    }
+}
@@ -25,10 +25,19 @@
 //! - Recency (5%): Newer content bonus
 //! - Diversity penalty (5%): Avoid too much from same creator/tags
 
+pub mod badges;
+pub mod blocks;
 pub mod engine;
 pub mod features;
+pub mod flags;
+pub mod follows;
 pub mod graph_client;
 pub mod preferences;
+pub mod privacy;
+pub mod profiles;
+pub mod reputation;
+pub mod shares;
+pub mod single_flight;
 pub mod updater;
 pub mod metrics;
 
@@ -5,9 +5,14 @@
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgExecutor, PgPool};
 use std::collections::HashMap;
-use tracing::info;
+use tracing::{debug, info, warn};
+
+use super::privacy::{increment_engagement, is_opted_out};
+use crate::address::normalize_address;
+pub use crate::config::DecayRates;
+use crate::kafka::{EventPublisher, UserActionEvent};
 
 /// Interaction types we track
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -78,6 +83,18 @@ impl Default for UserPreferences {
     }
 }
 
+/// Cached taste vectors are refreshed once the current taste falls below
+/// this cosine similarity to them
+const TASTE_REFRESH_SIMILARITY: f32 = 0.99;
+
+pub use crate::config::TasteSpace;
+
+impl TasteSpace {
+    pub fn dimensions(&self) -> usize {
+        4 + self.tags.len()
+    }
+}
+
 /// Interaction event for recording
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InteractionEvent {
@@ -97,33 +114,196 @@ const PURCHASE_WEIGHT: f32 = 3.0; // Purchases are strongest signal
 const VIEW_WEIGHT: f32 = 0.1; // Views are weak signal
 const LONG_VIEW_WEIGHT: f32 = 0.3; // Long views (>5s) are stronger
 const UNLIKE_WEIGHT: f32 = -0.5; // Negative signal
-const DECAY_FACTOR: f32 = 0.95; // Daily decay for old preferences
+const TIP_WEIGHT: f32 = 2.0; // Tips endorse the creator rather than one NFT
 const LONG_VIEW_THRESHOLD_MS: i64 = 5000;
+/// Share of the first-contact bonus kept with each repeat interaction
+const FIRST_CONTACT_DECAY: f32 = 0.5;
 
-/// Records a user interaction and updates preferences
-pub async fn record_interaction(pool: &PgPool, event: InteractionEvent) -> Result<()> {
-    // 1. Insert interaction record
-    insert_interaction(pool, &event).await?;
+/// Canonicalize an interaction's user and creator addresses so they match
+/// what is stored. An invalid user address is an error; an invalid creator
+/// address is dropped, since the interaction still says something about the user.
+pub fn normalize_interaction(interaction: &mut InteractionEvent) -> crate::error::Result<()> {
+    interaction.user_address = normalize_address(&interaction.user_address)?;
+    interaction.nft_creator_address = interaction
+        .nft_creator_address
+        .as_deref()
+        .and_then(|creator| normalize_address(creator).ok());
+    Ok(())
+}
 
-    // 2. Update user preferences based on interaction
-    update_preferences_from_interaction(pool, &event).await?;
+/// Records a user interaction and updates preferences; false if the user
+/// opted out of tracking. A first positive interaction with a creator or tag
+/// weighs `1 + first_contact_boost` times as much. The cached taste vector
+/// is computed over `taste_space`.
+pub async fn record_interaction(
+    pool: &PgPool,
+    event: InteractionEvent,
+    first_contact_boost: f32,
+    taste_space: &TasteSpace,
+) -> Result<bool> {
+    record_weighted_interaction(pool, event, None, first_contact_boost, taste_space).await
+}
+
+/// Records a user interaction whose preference weight is scaled by `quality`
+/// (e.g. a spammy comment). The quality is stored with the interaction.
+///
+/// Returns false when the user opted out of tracking: only the anonymous
+/// engagement counter is bumped and nothing is stored against the user.
+pub async fn record_weighted_interaction(
+    pool: &PgPool,
+    event: InteractionEvent,
+    quality: Option<f32>,
+    first_contact_boost: f32,
+    taste_space: &TasteSpace,
+) -> Result<bool> {
+    let mut conn = pool.acquire().await?;
+    record_weighted_interaction_on(&mut conn, event, quality, first_contact_boost, taste_space)
+        .await
+}
+
+/// `record_weighted_interaction` on an existing connection, so the write can
+/// be part of a caller's transaction
+pub async fn record_weighted_interaction_on(
+    conn: &mut PgConnection,
+    mut event: InteractionEvent,
+    quality: Option<f32>,
+    first_contact_boost: f32,
+    taste_space: &TasteSpace,
+) -> Result<bool> {
+    normalize_interaction(&mut event)?;
+
+    // 1. Count the interaction anonymously
+    increment_engagement(
+        &mut *conn,
+        &event.nft_id,
+        &event.interaction_type.to_string(),
+    )
+    .await?;
+
+    if is_opted_out(&mut *conn, &event.user_address).await? {
+        debug!(
+            "User {} opted out of tracking; skipping personalized record",
+            event.user_address
+        );
+        return Ok(false);
+    }
+
+    // 2. Insert interaction record, after counting the ones before it
+    let prior = if first_contact_boost > 0.0 {
+        prior_contacts(&mut *conn, &event).await?
+    } else {
+        PriorContacts::default()
+    };
+    insert_interaction(&mut *conn, &event, quality).await?;
+
+    // 3. Update user preferences based on interaction
+    update_preferences_from_interaction(
+        conn,
+        &event,
+        quality.unwrap_or(1.0),
+        &prior,
+        first_contact_boost,
+        taste_space,
+    )
+    .await?;
 
     info!(
         "📊 Recorded {} interaction: user={}, nft={}",
         event.interaction_type, event.user_address, event.nft_id
     );
 
-    Ok(())
+    Ok(true)
+}
+
+/// Strengthen `sender`'s affinity for `recipient` after a tip, scaled by the
+/// sender's reputation weight. False if the sender opted out or tipped
+/// themselves.
+pub async fn record_tip_on(
+    conn: &mut PgConnection,
+    sender: &str,
+    recipient: &str,
+    reputation_weight: f32,
+) -> Result<bool> {
+    if sender.eq_ignore_ascii_case(recipient) || is_opted_out(&mut *conn, sender).await? {
+        return Ok(false);
+    }
+
+    let mut prefs = load_or_create_preferences(&mut *conn, sender).await?;
+    add_creator_preference(
+        &mut prefs.creator_preferences,
+        &normalize_address(recipient)?,
+        TIP_WEIGHT * reputation_weight * 0.1,
+    );
+    save_preferences(conn, &prefs, None).await?;
+
+    info!(
+        "📊 Recorded tip: user={}, creator={}, weight={:.2}",
+        sender, recipient, reputation_weight
+    );
+    Ok(true)
 }
 
-async fn insert_interaction(pool: &PgPool, event: &InteractionEvent) -> Result<()> {
+/// Publishes recorded interactions, with the preference weight they were
+/// applied at, as `UserActionEvent`s so downstream consumers (notifications,
+/// live counters) see the processed signal rather than raw chain events
+pub struct InteractionEcho<P: EventPublisher> {
+    publisher: P,
+    topic: String,
+}
+
+impl<P: EventPublisher> InteractionEcho<P> {
+    pub fn new(publisher: P, topic: impl Into<String>) -> Self {
+        Self {
+            publisher,
+            topic: topic.into(),
+        }
+    }
+
+    /// Publish `event`, keyed by user. Failures are logged, never returned:
+    /// the interaction is already recorded.
+    pub async fn publish(&self, event: &InteractionEvent, quality: Option<f32>) {
+        let action = interaction_action(event, quality);
+        if let Err(e) = self
+            .publisher
+            .send_event(&self.topic, &event.user_address, &action)
+            .await
+        {
+            warn!(
+                "Failed to echo {} interaction for {}: {:?}",
+                event.interaction_type, event.user_address, e
+            );
+        }
+    }
+}
+
+/// Describe a recorded interaction as a `UserActionEvent`
+pub fn interaction_action(event: &InteractionEvent, quality: Option<f32>) -> UserActionEvent {
+    UserActionEvent {
+        action_type: event.interaction_type.to_string(),
+        user_address: event.user_address.clone(),
+        nft_id: Some(event.nft_id.clone()),
+        contract_type: event.nft_contract_type.clone(),
+        timestamp: chrono::Utc::now().timestamp(),
+        metadata: Some(serde_json::json!({
+            "weight": interaction_weight(event) * quality.unwrap_or(1.0),
+            "creator_address": event.nft_creator_address,
+            "source": event.source,
+        })),
+    }
+}
+
+async fn insert_interaction<'e>(
+    executor: impl PgExecutor<'e>,
+    event: &InteractionEvent,
+    quality: Option<f32>,
+) -> Result<()> {
     sqlx::query(
         r#"
         INSERT INTO user_interactions 
             (id, user_address, nft_id, interaction_type, view_duration_ms, source,
-             nft_contract_type, nft_creator_address, nft_tags, created_at)
+             nft_contract_type, nft_creator_address, nft_tags, signal_quality, created_at)
         VALUES 
-            (gen_random_uuid(), $1, $2::uuid, $3, $4, $5, $6, $7, $8, NOW())
+            (gen_random_uuid(), $1, $2::uuid, $3, $4, $5, $6, $7, $8, $9, NOW())
         "#,
     )
     .bind(&event.user_address)
@@ -134,36 +314,143 @@ async fn insert_interaction(pool: &PgPool, event: &InteractionEvent) -> Result<(
     .bind(&event.nft_contract_type)
     .bind(&event.nft_creator_address)
     .bind(&event.nft_tags)
-    .execute(pool)
+    .bind(quality)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
+/// How often a user had interacted with an event's creator and each of its
+/// tags before the event
+#[derive(Debug, Default)]
+struct PriorContacts {
+    creator: i64,
+    tags: HashMap<String, i64>,
+}
+
+async fn prior_contacts(
+    conn: &mut PgConnection,
+    event: &InteractionEvent,
+) -> Result<PriorContacts> {
+    let creator = match &event.nft_creator_address {
+        Some(creator) => {
+            sqlx::query_scalar::<_, i64>(
+                r#"
+                SELECT COUNT(*)
+                FROM user_interactions i
+                LEFT JOIN nfts n ON n.id = i.nft_id
+                WHERE i.user_address = $1
+                AND COALESCE(NULLIF(i.nft_creator_address, ''), LOWER(n.creator_address)) = $2
+                "#,
+            )
+            .bind(&event.user_address)
+            .bind(creator)
+            .fetch_one(&mut *conn)
+            .await?
+        }
+        None => 0,
+    };
+
+    let tags = if event.nft_tags.is_empty() {
+        HashMap::new()
+    } else {
+        sqlx::query_as::<_, (String, i64)>(
+            r#"
+            SELECT tag, COUNT(*)
+            FROM user_interactions, UNNEST(nft_tags) AS tag
+            WHERE user_address = $1 AND tag = ANY($2)
+            GROUP BY tag
+            "#,
+        )
+        .bind(&event.user_address)
+        .bind(&event.nft_tags)
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .collect()
+    };
+
+    Ok(PriorContacts { creator, tags })
+}
+
+/// Move `creator`'s preference by `delta` (starting from the neutral 0.5).
+/// Stored preferences are uncapped; scoring applies [`cap_creator_share`].
+pub fn add_creator_preference(creators: &mut HashMap<String, f32>, creator: &str, delta: f32) {
+    let current = creators.get(creator).copied().unwrap_or(0.5);
+    creators.insert(creator.to_string(), (current + delta).clamp(0.0, 1.0));
+}
+
+/// Lower the strongest creator preferences so none holds more than
+/// `max_share` of the total preference above neutral, so one favourite can't
+/// take over the feed. The capped creators
+/// end up level, at exactly `max_share` each. Needs at least two liked
+/// creators, and leaves the map alone when `max_share` is outside 0-1 or
+/// can only be met by capping everyone.
+pub fn cap_creator_share(creators: &mut HashMap<String, f32>, max_share: f32) {
+    if !(max_share > 0.0 && max_share < 1.0) {
+        return;
+    }
+    let mut liked: Vec<(String, f32)> = creators
+        .iter()
+        .filter(|(_, weight)| **weight > 0.5)
+        .map(|(creator, weight)| (creator.clone(), weight - 0.5))
+        .collect();
+    if liked.len() < 2 {
+        return;
+    }
+    liked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    // With the top `k` capped at `c`: c = max_share * (k * c + rest)
+    let mut rest: f32 = liked.iter().map(|(_, excess)| excess).sum();
+    for k in 1..liked.len() {
+        rest -= liked[k - 1].1;
+        let capped_share = max_share * k as f32;
+        if capped_share >= 1.0 {
+            return;
+        }
+        let cap = max_share * rest / (1.0 - capped_share);
+        if liked[k - 1].1 <= cap {
+            return;
+        }
+        // Rounding slack, so a creator sitting right at the cap isn't
+        // counted as over it
+        if liked[k].1 <= cap + 1e-6 {
+            for (creator, _) in &liked[..k] {
+                creators.insert(creator.clone(), 0.5 + cap);
+            }
+            return;
+        }
+    }
+}
+
+/// Weight multiplier for an interaction after `prior` earlier ones with the
+/// same creator or tag: `1 + boost` the first time, the bonus halving with
+/// each repeat
+pub fn first_contact_multiplier(prior: i64, boost: f32) -> f32 {
+    1.0 + boost * FIRST_CONTACT_DECAY.powi(prior.clamp(0, 64) as i32)
+}
+
 async fn update_preferences_from_interaction(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     event: &InteractionEvent,
+    quality: f32,
+    prior: &PriorContacts,
+    first_contact_boost: f32,
+    taste_space: &TasteSpace,
 ) -> Result<()> {
-    // Calculate weight based on interaction type
-    let weight = match event.interaction_type {
-        InteractionType::Like => LIKE_WEIGHT,
-        InteractionType::Comment => LIKE_WEIGHT * 0.8,
-        InteractionType::Purchase => PURCHASE_WEIGHT,
-        InteractionType::View => {
-            if event.view_duration_ms.unwrap_or(0) > LONG_VIEW_THRESHOLD_MS {
-                LONG_VIEW_WEIGHT
-            } else {
-                VIEW_WEIGHT
-            }
+    let weight = interaction_weight(event) * quality;
+    // Only positive signals are boosted; a first unlike says little new
+    let boosted = |prior: i64| {
+        if weight > 0.0 {
+            weight * first_contact_multiplier(prior, first_contact_boost)
+        } else {
+            weight
         }
-        InteractionType::Unlike => UNLIKE_WEIGHT,
-        InteractionType::Unsave => UNLIKE_WEIGHT * 0.5,
-        InteractionType::Share => LIKE_WEIGHT * 0.5,
-        InteractionType::Save => LIKE_WEIGHT * 0.7,
     };
 
     // Get or create user preferences
-    let mut prefs = get_or_create_preferences(pool, &event.user_address).await?;
+    let mut prefs = load_or_create_preferences(&mut *conn, &event.user_address).await?;
 
     // Update content type affinity
     if let Some(ref contract_type) = event.nft_contract_type {
@@ -173,19 +460,19 @@ async fn update_preferences_from_interaction(
     // Update tag preferences
     for tag in &event.nft_tags {
         let current = prefs.tag_preferences.get(tag).copied().unwrap_or(0.5);
+        let weight = boosted(prior.tags.get(tag).copied().unwrap_or(0));
         let new_value = (current + weight * 0.1).clamp(0.0, 1.0);
         prefs.tag_preferences.insert(tag.clone(), new_value);
     }
 
-    // Update creator preferences
+    // Update creator preferences, keyed by the normalized address the way
+    // scoring looks creators up
     if let Some(ref creator) = event.nft_creator_address {
-        let current = prefs
-            .creator_preferences
-            .get(creator)
-            .copied()
-            .unwrap_or(0.5);
-        let new_value = (current + weight * 0.1).clamp(0.0, 1.0);
-        prefs.creator_preferences.insert(creator.clone(), new_value);
+        add_creator_preference(
+            &mut prefs.creator_preferences,
+            creator,
+            boosted(prior.creator) * 0.1,
+        );
     }
 
     // Update behavioral stats
@@ -197,12 +484,46 @@ async fn update_preferences_from_interaction(
         _ => {}
     }
 
+    // Refresh the cached taste vector only when the taste has moved
+    let taste = compute_taste_vector(&prefs, taste_space);
+    let cached = load_taste_vector(&mut *conn, &prefs.user_address).await?;
+    let refreshed = taste_changed(cached.as_deref(), &taste).then_some(taste);
+
     // Save updated preferences
-    save_preferences(pool, &prefs).await?;
+    save_preferences(&mut *conn, &prefs, refreshed.as_deref()).await?;
+
+    // Note what kind of signal the content type affinity now rests on
+    let class = SignalClass::of(&event.interaction_type).filter(|_| weight > 0.0);
+    if let (Some(contract_type), Some(class)) = (&event.nft_contract_type, class) {
+        let contract_type = contract_type.to_lowercase();
+        if matches!(contract_type.as_str(), "snap" | "art" | "music" | "flix") {
+            record_affinity_signal(conn, &prefs.user_address, &contract_type, class).await?;
+        }
+    }
 
     Ok(())
 }
 
+/// Base preference weight of an interaction, before any quality scaling
+pub fn interaction_weight(event: &InteractionEvent) -> f32 {
+    match event.interaction_type {
+        InteractionType::Like => LIKE_WEIGHT,
+        InteractionType::Comment => LIKE_WEIGHT * 0.8,
+        InteractionType::Purchase => PURCHASE_WEIGHT,
+        InteractionType::View => {
+            if event.view_duration_ms.unwrap_or(0) > LONG_VIEW_THRESHOLD_MS {
+                LONG_VIEW_WEIGHT
+            } else {
+                VIEW_WEIGHT
+            }
+        }
+        InteractionType::Unlike => UNLIKE_WEIGHT,
+        InteractionType::Unsave => UNLIKE_WEIGHT * 0.5,
+        InteractionType::Share => LIKE_WEIGHT * 0.5,
+        InteractionType::Save => LIKE_WEIGHT * 0.7,
+    }
+}
+
 fn update_content_affinity(prefs: &mut UserPreferences, contract_type: &str, weight: f32) {
     let delta = weight * 0.05; // Small increments
 
@@ -234,9 +555,83 @@ pub async fn get_or_create_preferences(
     pool: &PgPool,
     user_address: &str,
 ) -> Result<UserPreferences> {
-    let normalized = user_address.to_lowercase();
+    let mut conn = pool.acquire().await?;
+    load_or_create_preferences(&mut conn, user_address).await
+}
+
+/// Stored preferences for `user_address`, without creating any
+pub async fn get_preferences(pool: &PgPool, user_address: &str) -> Result<Option<UserPreferences>> {
+    load_preferences(pool, user_address).await
+}
+
+/// A user's full learned profile, for data export. A user with no stored
+/// profile gets the defaults; nothing is created.
+#[allow(dead_code)]
+pub async fn export_preferences<'e>(
+    executor: impl PgExecutor<'e>,
+    user_address: &str,
+) -> Result<UserPreferences> {
+    let user_address = normalize_address(user_address)?;
+    Ok(load_preferences(executor, &user_address)
+        .await?
+        .unwrap_or_else(|| UserPreferences {
+            user_address,
+            ..Default::default()
+        }))
+}
+
+/// Replace the stored profile for `prefs.user_address` wholesale, creating
+/// it if needed (account migration). The taste vector is recomputed from the
+/// imported profile over `taste_space`.
+#[allow(dead_code)]
+pub async fn import_preferences<'e>(
+    executor: impl PgExecutor<'e>,
+    prefs: &UserPreferences,
+    taste_space: &TasteSpace,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_preferences
+            (id, user_address, snap_affinity, art_affinity, music_affinity, flix_affinity,
+             tag_preferences, creator_preferences, total_likes, total_purchases, total_views,
+             taste_vector, last_activity_at, inserted_at, updated_at)
+        VALUES
+            (gen_random_uuid(), $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW(), NOW(), NOW())
+        ON CONFLICT (user_address) DO UPDATE SET
+            snap_affinity = EXCLUDED.snap_affinity,
+            art_affinity = EXCLUDED.art_affinity,
+            music_affinity = EXCLUDED.music_affinity,
+            flix_affinity = EXCLUDED.flix_affinity,
+            tag_preferences = EXCLUDED.tag_preferences,
+            creator_preferences = EXCLUDED.creator_preferences,
+            total_likes = EXCLUDED.total_likes,
+            total_purchases = EXCLUDED.total_purchases,
+            total_views = EXCLUDED.total_views,
+            taste_vector = EXCLUDED.taste_vector,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(normalize_address(&prefs.user_address)?)
+    .bind(prefs.snap_affinity)
+    .bind(prefs.art_affinity)
+    .bind(prefs.music_affinity)
+    .bind(prefs.flix_affinity)
+    .bind(serde_json::to_value(&prefs.tag_preferences)?)
+    .bind(serde_json::to_value(&prefs.creator_preferences)?)
+    .bind(prefs.total_likes)
+    .bind(prefs.total_purchases)
+    .bind(prefs.total_views)
+    .bind(compute_taste_vector(prefs, taste_space))
+    .execute(executor)
+    .await?;
+    Ok(())
+}
 
-    let result = sqlx::query_as::<_, PreferencesRow>(
+async fn load_preferences<'e>(
+    executor: impl PgExecutor<'e>,
+    user_address: &str,
+) -> Result<Option<UserPreferences>> {
+    let row = sqlx::query_as::<_, PreferencesRow>(
         r#"
         SELECT 
             user_address, snap_affinity, art_affinity, music_affinity, flix_affinity,
@@ -245,24 +640,32 @@ pub async fn get_or_create_preferences(
         WHERE user_address = $1
         "#,
     )
-    .bind(&normalized)
-    .fetch_optional(pool)
+    .bind(normalize_address(user_address)?)
+    .fetch_optional(executor)
     .await?;
 
-    match result {
-        Some(row) => Ok(UserPreferences {
-            user_address: row.user_address,
-            snap_affinity: row.snap_affinity,
-            art_affinity: row.art_affinity,
-            music_affinity: row.music_affinity,
-            flix_affinity: row.flix_affinity,
-            tag_preferences: serde_json::from_value(row.tag_preferences).unwrap_or_default(),
-            creator_preferences: serde_json::from_value(row.creator_preferences)
-                .unwrap_or_default(),
-            total_likes: row.total_likes,
-            total_purchases: row.total_purchases,
-            total_views: row.total_views,
-        }),
+    Ok(row.map(|row| UserPreferences {
+        user_address: row.user_address,
+        snap_affinity: row.snap_affinity,
+        art_affinity: row.art_affinity,
+        music_affinity: row.music_affinity,
+        flix_affinity: row.flix_affinity,
+        tag_preferences: serde_json::from_value(row.tag_preferences).unwrap_or_default(),
+        creator_preferences: serde_json::from_value(row.creator_preferences).unwrap_or_default(),
+        total_likes: row.total_likes,
+        total_purchases: row.total_purchases,
+        total_views: row.total_views,
+    }))
+}
+
+async fn load_or_create_preferences(
+    conn: &mut PgConnection,
+    user_address: &str,
+) -> Result<UserPreferences> {
+    let normalized = normalize_address(user_address)?;
+
+    match load_preferences(&mut *conn, user_address).await? {
+        Some(prefs) => Ok(prefs),
         None => {
             // Create new preferences with defaults
             let prefs = UserPreferences {
@@ -294,7 +697,7 @@ pub async fn get_or_create_preferences(
             .bind(prefs.total_likes)
             .bind(prefs.total_purchases)
             .bind(prefs.total_views)
-            .execute(pool)
+            .execute(&mut *conn)
             .await?;
 
             Ok(prefs)
@@ -302,7 +705,12 @@ pub async fn get_or_create_preferences(
     }
 }
 
-async fn save_preferences(pool: &PgPool, prefs: &UserPreferences) -> Result<()> {
+/// Save `prefs`, replacing the cached taste vector when `taste` is given
+async fn save_preferences(
+    conn: &mut PgConnection,
+    prefs: &UserPreferences,
+    taste: Option<&[f32]>,
+) -> Result<()> {
     let tag_prefs_json = serde_json::to_value(&prefs.tag_preferences)?;
     let creator_prefs_json = serde_json::to_value(&prefs.creator_preferences)?;
 
@@ -318,6 +726,7 @@ async fn save_preferences(pool: &PgPool, prefs: &UserPreferences) -> Result<()>
             total_likes = $8,
             total_purchases = $9,
             total_views = $10,
+            taste_vector = COALESCE($11, taste_vector),
             last_activity_at = NOW(),
             updated_at = NOW()
         WHERE user_address = $1
@@ -333,27 +742,166 @@ async fn save_preferences(pool: &PgPool, prefs: &UserPreferences) -> Result<()>
     .bind(prefs.total_likes)
     .bind(prefs.total_purchases)
     .bind(prefs.total_views)
-    .execute(pool)
+    .bind(taste)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// `prefs` as a fixed-length vector over `space`. Each dimension is centered
+/// on the neutral 0.5, so untouched preferences contribute nothing and daily
+/// decay (which shrinks toward 0.5) leaves the direction unchanged.
+pub fn compute_taste_vector(prefs: &UserPreferences, space: &TasteSpace) -> Vec<f32> {
+    let mut vector = Vec::with_capacity(space.dimensions());
+    vector.extend([
+        prefs.snap_affinity,
+        prefs.art_affinity,
+        prefs.music_affinity,
+        prefs.flix_affinity,
+    ]);
+    vector.extend(
+        space
+            .tags
+            .iter()
+            .map(|tag| prefs.tag_preferences.get(tag).copied().unwrap_or(0.5)),
+    );
+    vector.iter_mut().for_each(|v| *v -= 0.5);
+    vector
+}
+
+/// Cosine similarity of two taste vectors (0.0 if either is all-neutral or
+/// they come from different spaces)
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let (norm_a, norm_b) = (norm(a), norm(b));
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Whether `current` has moved far enough from the `cached` vector to store it
+fn taste_changed(cached: Option<&[f32]>, current: &[f32]) -> bool {
+    match cached {
+        Some(cached) if cached.len() == current.len() => {
+            cached != current && cosine_similarity(cached, current) < TASTE_REFRESH_SIMILARITY
+        }
+        _ => true,
+    }
+}
+
+async fn load_taste_vector<'e>(
+    executor: impl PgExecutor<'e>,
+    user_address: &str,
+) -> Result<Option<Vec<f32>>> {
+    let vector = sqlx::query_scalar::<_, Option<Vec<f32>>>(
+        "SELECT taste_vector FROM user_preferences WHERE user_address = $1",
+    )
+    .bind(normalize_address(user_address)?)
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(vector.flatten())
+}
+
+/// A user's cached taste vector, if they have one yet
+#[allow(dead_code)]
+pub async fn get_taste_vector(pool: &PgPool, user_address: &str) -> Result<Option<Vec<f32>>> {
+    load_taste_vector(pool, user_address).await
+}
+
+/// Strength class of the signal behind a content type affinity, which sets
+/// how fast the affinity decays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SignalClass {
+    View,
+    Engagement,
+    Purchase,
+}
+
+impl SignalClass {
+    /// Class of a positive interaction; negative ones have none
+    pub fn of(interaction_type: &InteractionType) -> Option<Self> {
+        match interaction_type {
+            InteractionType::Purchase => Some(Self::Purchase),
+            InteractionType::View => Some(Self::View),
+            InteractionType::Like
+            | InteractionType::Comment
+            | InteractionType::Share
+            | InteractionType::Save => Some(Self::Engagement),
+            InteractionType::Unlike | InteractionType::Unsave => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::View => "view",
+            Self::Engagement => "engagement",
+            Self::Purchase => "purchase",
+        }
+    }
+}
+
+/// Remember `class` as the signal behind the user's `contract_type`
+/// affinity, unless a stronger one is already recorded
+async fn record_affinity_signal<'e>(
+    executor: impl PgExecutor<'e>,
+    user_address: &str,
+    contract_type: &str,
+    class: SignalClass,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE user_preferences
+        SET affinity_signals = affinity_signals || jsonb_build_object($2::text, $3::text)
+        WHERE user_address = $1
+          AND CASE affinity_signals->>$2
+                WHEN 'purchase' THEN 3 WHEN 'engagement' THEN 2 WHEN 'view' THEN 1 ELSE 0
+              END < $4
+        "#,
+    )
+    .bind(user_address)
+    .bind(contract_type)
+    .bind(class.as_str())
+    .bind(class as i32 + 1)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
 /// Apply time decay to all preferences (run daily via cron)
-pub async fn apply_preference_decay(pool: &PgPool) -> Result<u64> {
+pub async fn apply_preference_decay(pool: &PgPool, rates: &DecayRates) -> Result<u64> {
+    decay_preferences(pool, rates).await
+}
+
+/// One day of decay toward neutral for users inactive for a day, each
+/// content type affinity at the rate of its signal class
+async fn decay_preferences<'e>(executor: impl PgExecutor<'e>, rates: &DecayRates) -> Result<u64> {
     let result = sqlx::query(
         r#"
         UPDATE user_preferences SET
-            snap_affinity = 0.5 + (snap_affinity - 0.5) * $1,
-            art_affinity = 0.5 + (art_affinity - 0.5) * $1,
-            music_affinity = 0.5 + (music_affinity - 0.5) * $1,
-            flix_affinity = 0.5 + (flix_affinity - 0.5) * $1,
+            snap_affinity = 0.5 + (snap_affinity - 0.5) * CASE affinity_signals->>'snap'
+                WHEN 'purchase' THEN $1 WHEN 'view' THEN $3 ELSE $2 END,
+            art_affinity = 0.5 + (art_affinity - 0.5) * CASE affinity_signals->>'art'
+                WHEN 'purchase' THEN $1 WHEN 'view' THEN $3 ELSE $2 END,
+            music_affinity = 0.5 + (music_affinity - 0.5) * CASE affinity_signals->>'music'
+                WHEN 'purchase' THEN $1 WHEN 'view' THEN $3 ELSE $2 END,
+            flix_affinity = 0.5 + (flix_affinity - 0.5) * CASE affinity_signals->>'flix'
+                WHEN 'purchase' THEN $1 WHEN 'view' THEN $3 ELSE $2 END,
             updated_at = NOW()
         WHERE last_activity_at < NOW() - INTERVAL '1 day'
         "#,
     )
-    .bind(DECAY_FACTOR as f64)
-    .execute(pool)
+    .bind(rates.purchase as f64)
+    .bind(rates.engagement as f64)
+    .bind(rates.view as f64)
+    .execute(executor)
     .await?;
 
     info!(
@@ -362,3 +910,428 @@ pub async fn apply_preference_decay(pool: &PgPool) -> Result<u64> {
     );
     Ok(result.rows_affected())
 }
+
+/// Fill in `nft_creator_address` from `nfts` for interactions recorded
+/// without one, `batch_size` rows at a time. Returns the rows updated.
+pub async fn backfill_interaction_creators(pool: &PgPool, batch_size: i64) -> Result<u64> {
+    let mut total = 0;
+
+    loop {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_interactions i
+            SET nft_creator_address = LOWER(n.creator_address)
+            FROM nfts n
+            WHERE n.id = i.nft_id
+            AND i.id IN (
+                SELECT i2.id
+                FROM user_interactions i2
+                JOIN nfts n2 ON n2.id = i2.nft_id
+                WHERE COALESCE(i2.nft_creator_address, '') = ''
+                AND COALESCE(n2.creator_address, '') <> ''
+                LIMIT $1
+            )
+            "#,
+        )
+        .bind(batch_size)
+        .execute(pool)
+        .await?;
+
+        total += result.rows_affected();
+        if result.rows_affected() < batch_size.max(1) as u64 {
+            break;
+        }
+    }
+
+    info!("🧩 Backfilled creator on {} interactions", total);
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::random_address;
+
+    #[test]
+    fn test_identical_preferences_share_a_taste_vector() {
+        let space = TasteSpace {
+            tags: vec!["ambient".to_string(), "portrait".to_string()],
+        };
+        let prefs = |user: &str| {
+            let mut prefs = UserPreferences {
+                user_address: user.to_string(),
+                music_affinity: 0.8,
+                snap_affinity: 0.3,
+                ..Default::default()
+            };
+            prefs.tag_preferences.insert("ambient".to_string(), 0.9);
+            prefs.tag_preferences.insert("off-space".to_string(), 0.9);
+            prefs
+        };
+
+        let a = compute_taste_vector(&prefs("0xa"), &space);
+        let b = compute_taste_vector(&prefs("0xb"), &space);
+        assert_eq!(a.len(), space.dimensions());
+        assert_eq!(a, b);
+        assert!(cosine_similarity(&a, &b) > 0.999);
+
+        // An unrelated taste is far less similar and would refresh the cache
+        let mut other = prefs("0xc");
+        other.music_affinity = 0.2;
+        other.art_affinity = 0.9;
+        other.tag_preferences.insert("ambient".to_string(), 0.1);
+        let c = compute_taste_vector(&other, &space);
+        assert!(cosine_similarity(&a, &c) < 0.5);
+        assert!(taste_changed(Some(&a), &c));
+        assert!(!taste_changed(Some(&a), &b));
+        assert!(taste_changed(None, &a));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_interaction_without_creator_resolves_on_read() {
+        let pool = crate::database::test_pool().await;
+        let Some((nft_id, creator)) = sqlx::query_as::<_, (uuid::Uuid, String)>(
+            "SELECT id, creator_address FROM nfts WHERE creator_address <> '' LIMIT 1",
+        )
+        .fetch_optional(&pool)
+        .await
+        .ok()
+        .flatten() else {
+            return;
+        };
+
+        let mut event = InteractionEvent {
+            user_address: "0x00000000000000000000000000000000000c4ea7".to_string(),
+            nft_id: nft_id.to_string(),
+            interaction_type: InteractionType::View,
+            view_duration_ms: None,
+            source: Some("test".to_string()),
+            nft_contract_type: None,
+            nft_creator_address: None,
+            nft_tags: vec![],
+        };
+        insert_interaction(&pool, &event, None).await.unwrap();
+
+        // The creatorless view still counts as prior contact with the creator
+        event.nft_creator_address = Some(creator.to_lowercase());
+        let mut conn = pool.acquire().await.unwrap();
+        assert_eq!(prior_contacts(&mut conn, &event).await.unwrap().creator, 1);
+
+        sqlx::query("DELETE FROM user_interactions WHERE user_address = $1")
+            .bind(&event.user_address)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recorded_like_is_echoed_as_user_action() {
+        use crate::kafka::InMemoryPublisher;
+
+        let publisher = InMemoryPublisher::new();
+        let echo = InteractionEcho::new(publisher.clone(), "recommendations");
+        let like = InteractionEvent {
+            user_address: "0xliker".to_string(),
+            nft_id: "nft-1".to_string(),
+            interaction_type: InteractionType::Like,
+            view_duration_ms: None,
+            source: Some("blockchain".to_string()),
+            nft_contract_type: Some("art".to_string()),
+            nft_creator_address: Some("0xcreator".to_string()),
+            nft_tags: vec![],
+        };
+
+        echo.publish(&like, Some(0.5)).await;
+
+        let sent = publisher.messages();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].topic, "recommendations");
+        assert_eq!(sent[0].key, "0xliker");
+        let action = &sent[0].payload;
+        assert_eq!(action["action_type"], "like");
+        assert_eq!(action["user_address"], "0xliker");
+        assert_eq!(action["nft_id"], "nft-1");
+        assert_eq!(action["contract_type"], "art");
+        assert_eq!(action["metadata"]["weight"], LIKE_WEIGHT as f64 * 0.5);
+        assert_eq!(action["metadata"]["creator_address"], "0xcreator");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_opted_out_like_only_counts_anonymously() {
+        use crate::recommendation::privacy::{get_engagement_counts, set_privacy, PrivacySettings};
+
+        let pool = crate::database::test_pool().await;
+
+        let user = "0x00000000000000000000000000000000000061de";
+        let nft_id = uuid::Uuid::new_v4().to_string();
+        set_privacy(
+            &pool,
+            user,
+            &PrivacySettings {
+                opt_out_tracking: true,
+            },
+        )
+        .await
+        .unwrap();
+
+        let like = InteractionEvent {
+            user_address: user.to_string(),
+            nft_id: nft_id.clone(),
+            interaction_type: InteractionType::Like,
+            view_duration_ms: None,
+            source: Some("test".to_string()),
+            nft_contract_type: Some("art".to_string()),
+            nft_creator_address: Some("0xcreator".to_string()),
+            nft_tags: vec!["calm".to_string()],
+        };
+        assert!(
+            !record_interaction(&pool, like, 0.5, &TasteSpace::default())
+                .await
+                .unwrap()
+        );
+
+        let stored = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM user_interactions WHERE user_address = $1",
+        )
+        .bind(user)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(stored, 0);
+        let prefs = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM user_preferences WHERE user_address = $1",
+        )
+        .bind(user)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(prefs, 0);
+        assert_eq!(
+            get_engagement_counts(&pool, &nft_id)
+                .await
+                .unwrap()
+                .get("like"),
+            Some(&1)
+        );
+
+        sqlx::query("DELETE FROM nft_engagement_counts WHERE nft_id = $1::uuid")
+            .bind(&nft_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM user_privacy WHERE user_address = $1")
+            .bind(user)
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_preferences_export_import_round_trip() {
+        let pool = crate::database::test_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+
+        let user = random_address();
+
+        // Unknown users export the defaults without being created
+        let exported = export_preferences(&mut *tx, &user).await.unwrap();
+        assert_eq!(exported.user_address, user);
+        assert_eq!(
+            exported.art_affinity,
+            UserPreferences::default().art_affinity
+        );
+        assert!(load_preferences(&mut *tx, &user).await.unwrap().is_none());
+
+        let mut prefs = exported;
+        prefs.art_affinity = 0.9;
+        prefs.total_likes = 7;
+        prefs.tag_preferences.insert("ambient".to_string(), 0.8);
+        prefs
+            .creator_preferences
+            .insert("0xcreator".to_string(), 0.6);
+        import_preferences(&mut *tx, &prefs, &TasteSpace::default())
+            .await
+            .unwrap();
+
+        let round_trip = export_preferences(&mut *tx, &user).await.unwrap();
+        assert_eq!(
+            serde_json::to_value(&round_trip).unwrap(),
+            serde_json::to_value(&prefs).unwrap()
+        );
+
+        // A second import replaces the profile wholesale
+        prefs.tag_preferences.clear();
+        prefs.music_affinity = 0.1;
+        import_preferences(&mut *tx, &prefs, &TasteSpace::default())
+            .await
+            .unwrap();
+        let replaced = export_preferences(&mut *tx, &user).await.unwrap();
+        assert!(replaced.tag_preferences.is_empty());
+        assert_eq!(replaced.music_affinity, 0.1);
+        assert_eq!(replaced.creator_preferences["0xcreator"], 0.6);
+        assert_eq!(
+            load_taste_vector(&mut *tx, &user).await.unwrap(),
+            Some(compute_taste_vector(&prefs, &TasteSpace::default()))
+        );
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[test]
+    fn test_first_contact_multiplier_fades_with_prior_contacts() {
+        assert_eq!(first_contact_multiplier(0, 0.5), 1.5);
+        assert_eq!(first_contact_multiplier(1, 0.5), 1.25);
+        assert_eq!(first_contact_multiplier(3, 0.0), 1.0);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_first_interaction_with_creator_outweighs_tenth() {
+        let pool = crate::database::test_pool().await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let user = "0x00000000000000000000000000000000000f1257";
+        let creator = random_address();
+        let creator_pref = |prefs: &UserPreferences| prefs.creator_preferences[&creator];
+
+        let boost = 0.5;
+        let mut gains = Vec::new();
+        let mut last = 0.5;
+        for _ in 0..10 {
+            let view = InteractionEvent {
+                user_address: user.to_string(),
+                nft_id: uuid::Uuid::new_v4().to_string(),
+                interaction_type: InteractionType::View,
+                view_duration_ms: None,
+                source: Some("feed".to_string()),
+                nft_contract_type: None,
+                nft_creator_address: Some(creator.clone()),
+                nft_tags: vec![],
+            };
+            assert!(record_weighted_interaction_on(
+                &mut tx,
+                view,
+                None,
+                boost,
+                &TasteSpace::default()
+            )
+            .await
+            .unwrap());
+            let current = creator_pref(&load_or_create_preferences(&mut tx, user).await.unwrap());
+            gains.push(current - last);
+            last = current;
+        }
+
+        assert!((gains[0] - VIEW_WEIGHT * 0.1 * first_contact_multiplier(0, boost)).abs() < 1e-4);
+        assert!(
+            gains[0] > gains[9],
+            "first {} vs tenth {}",
+            gains[0],
+            gains[9]
+        );
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_purchase_affinity_decays_slower_than_view_affinity() {
+        let pool = crate::database::test_pool().await;
+
+        let mut tx = pool.begin().await.unwrap();
+        let user = "0x00000000000000000000000000000000000dec47";
+        for (interaction_type, contract_type) in [
+            (InteractionType::Purchase, "art"),
+            (InteractionType::View, "music"),
+            // A later weaker signal doesn't downgrade the class
+            (InteractionType::View, "art"),
+        ] {
+            let event = InteractionEvent {
+                user_address: user.to_string(),
+                nft_id: uuid::Uuid::new_v4().to_string(),
+                interaction_type,
+                view_duration_ms: None,
+                source: Some("test".to_string()),
+                nft_contract_type: Some(contract_type.to_string()),
+                nft_creator_address: None,
+                nft_tags: vec![],
+            };
+            record_weighted_interaction_on(&mut tx, event, None, 0.5, &TasteSpace::default())
+                .await
+                .unwrap();
+        }
+
+        // Same starting strength, inactive long enough to decay
+        sqlx::query(
+            "UPDATE user_preferences SET art_affinity = 0.9, music_affinity = 0.9, last_activity_at = NOW() - INTERVAL '2 days' WHERE user_address = $1",
+        )
+        .bind(user)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+
+        let rates = DecayRates {
+            purchase: 0.99,
+            engagement: 0.95,
+            view: 0.8,
+        };
+        for _day in 0..5 {
+            decay_preferences(&mut *tx, &rates).await.unwrap();
+        }
+
+        let prefs = load_or_create_preferences(&mut tx, user).await.unwrap();
+        let expected = |rate: f32| 0.5 + 0.4 * rate.powi(5);
+        assert!(
+            (prefs.art_affinity - expected(0.99)).abs() < 1e-4,
+            "art {}",
+            prefs.art_affinity
+        );
+        assert!(
+            (prefs.music_affinity - expected(0.8)).abs() < 1e-4,
+            "music {}",
+            prefs.music_affinity
+        );
+        assert!(prefs.art_affinity > prefs.music_affinity);
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[test]
+    fn test_whale_creator_is_capped_to_max_share() {
+        let mut creators = HashMap::new();
+        let share = |creators: &HashMap<String, f32>, creator: &str| {
+            let excess = |w: f32| (w - 0.5).max(0.0);
+            excess(creators[creator]) / creators.values().map(|w| excess(*w)).sum::<f32>()
+        };
+
+        // Fifty likes for one creator, a few for two others
+        for _ in 0..50 {
+            add_creator_preference(&mut creators, "0xwhale", 0.1);
+        }
+        add_creator_preference(&mut creators, "0xsecond", 0.1);
+        add_creator_preference(&mut creators, "0xthird", 0.1);
+
+        // Stored preferences keep the whale's full weight; the cap applies
+        // to the copy scoring reads
+        assert_eq!(creators["0xwhale"], 1.0);
+        let mut capped = creators.clone();
+        cap_creator_share(&mut capped, 0.4);
+        assert!((share(&capped, "0xwhale") - 0.4).abs() < 1e-4, "{capped:?}");
+        assert!(capped["0xwhale"] > capped["0xsecond"]);
+        assert!((capped["0xsecond"] - 0.6).abs() < 1e-4);
+        assert!((capped["0xthird"] - 0.6).abs() < 1e-4);
+
+        // A single liked creator has no one to be compared with
+        let mut solo = HashMap::from([("0xonly".to_string(), 1.0)]);
+        cap_creator_share(&mut solo, 0.4);
+        assert_eq!(solo["0xonly"], 1.0);
+
+        // Uncapped, the whale keeps its full weight
+        let mut uncapped = creators.clone();
+        cap_creator_share(&mut uncapped, 1.0);
+        assert_eq!(uncapped, creators);
+    }
+}
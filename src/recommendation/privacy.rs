@@ -0,0 +1,84 @@
+//! User Privacy
+//!
+//! Users can opt out of personalized interaction tracking. Their interactions
+//! still count toward the anonymous per-NFT totals in `nft_engagement_counts`
+//! but are never stored against their address or folded into preferences.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use std::collections::BTreeMap;
+
+use crate::address::normalize_address;
+
+/// A user's tracking preference
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrivacySettings {
+    pub opt_out_tracking: bool,
+}
+
+/// Set whether `user` is opted out of personalized tracking
+pub async fn set_privacy(pool: &PgPool, user: &str, settings: &PrivacySettings) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_privacy (user_address, opt_out_tracking)
+        VALUES ($1, $2)
+        ON CONFLICT (user_address) DO UPDATE SET
+            opt_out_tracking = EXCLUDED.opt_out_tracking,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(normalize_address(user)?)
+    .bind(settings.opt_out_tracking)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Whether `user` opted out of tracking. Users with no row are tracked.
+pub async fn is_opted_out<'e>(executor: impl PgExecutor<'e>, user: &str) -> Result<bool> {
+    let opted_out = sqlx::query_scalar::<_, bool>(
+        "SELECT opt_out_tracking FROM user_privacy WHERE user_address = $1",
+    )
+    .bind(normalize_address(user)?)
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(opted_out.unwrap_or(false))
+}
+
+/// Bump the anonymous counter for one interaction on `nft_id`
+pub async fn increment_engagement<'e>(
+    executor: impl PgExecutor<'e>,
+    nft_id: &str,
+    interaction_type: &str,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO nft_engagement_counts (nft_id, interaction_type, count)
+        VALUES ($1::uuid, $2, 1)
+        ON CONFLICT (nft_id, interaction_type) DO UPDATE SET
+            count = nft_engagement_counts.count + 1,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(nft_id)
+    .bind(interaction_type)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Anonymous counts on `nft_id` by interaction type, opted-out users included
+pub async fn get_engagement_counts(pool: &PgPool, nft_id: &str) -> Result<BTreeMap<String, i64>> {
+    let counts = sqlx::query_as::<_, (String, i64)>(
+        "SELECT interaction_type, count FROM nft_engagement_counts WHERE nft_id = $1::uuid",
+    )
+    .bind(nft_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(counts.into_iter().collect())
+}
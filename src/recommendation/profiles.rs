@@ -0,0 +1,46 @@
+//! Creator Profiles
+//!
+//! Display info from on-chain profile events, so feed items can carry the
+//! creator's username. `ProfileUpdated` (legacy) only sets the username;
+//! `ProfileUpdatedExtended` sets every field.
+
+use anyhow::Result;
+use sqlx::PgExecutor;
+
+/// Profile fields from a profile event. `None` leaves the stored value alone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CreatorProfile {
+    pub address: String,
+    pub username: Option<String>,
+    pub bio: Option<String>,
+    pub website: Option<String>,
+    pub profile_hash: Option<String>,
+}
+
+/// Insert or update a creator's profile
+pub async fn upsert_creator_profile<'e>(
+    executor: impl PgExecutor<'e>,
+    profile: &CreatorProfile,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO creator_profiles (address, username, bio, website, profile_hash)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (address) DO UPDATE SET
+            username = COALESCE(EXCLUDED.username, creator_profiles.username),
+            bio = COALESCE(EXCLUDED.bio, creator_profiles.bio),
+            website = COALESCE(EXCLUDED.website, creator_profiles.website),
+            profile_hash = COALESCE(EXCLUDED.profile_hash, creator_profiles.profile_hash),
+            updated_at = NOW()
+        "#,
+    )
+    .bind(profile.address.to_lowercase())
+    .bind(profile.username.as_deref().filter(|u| !u.is_empty()))
+    .bind(&profile.bio)
+    .bind(&profile.website)
+    .bind(&profile.profile_hash)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
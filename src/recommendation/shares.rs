@@ -0,0 +1,164 @@
+//! Share Relationships
+//!
+//! A `ContentShared` event is a weak signal that the recipient may care about
+//! the sharer's taste. Each share adds a little sharer -> recipient affinity;
+//! when the recipient later engages with the shared NFT the share is credited
+//! and the affinity grows much more.
+
+use anyhow::Result;
+use sqlx::{PgConnection, PgPool};
+use tracing::debug;
+
+use crate::address::normalize_address;
+
+/// Affinity added by each share
+const SHARE_AFFINITY: f32 = 0.05;
+/// Affinity added when the recipient engages with a shared NFT
+const ENGAGED_SHARE_AFFINITY: f32 = 0.25;
+
+/// Sharer -> recipient affinity after `shares` shares, `engaged_shares` of
+/// which the recipient went on to engage with
+pub fn share_affinity(shares: i32, engaged_shares: i32) -> f32 {
+    (shares as f32 * SHARE_AFFINITY + engaged_shares as f32 * ENGAGED_SHARE_AFFINITY).clamp(0.0, 1.0)
+}
+
+/// Record a share and bump the sharer -> recipient affinity. The bump is an
+/// increment, so run this in the transaction that claims the share event.
+pub async fn record_share(conn: &mut PgConnection, sharer: &str, recipient: &str, nft_id: &str) -> Result<()> {
+    let sharer = normalize_address(sharer)?;
+    let recipient = normalize_address(recipient)?;
+
+    sqlx::query(
+        "INSERT INTO content_shares (sharer_address, recipient_address, nft_id) VALUES ($1, $2, $3::uuid)",
+    )
+    .bind(&sharer)
+    .bind(&recipient)
+    .bind(nft_id)
+    .execute(&mut *conn)
+    .await?;
+
+    bump_affinity(conn, &sharer, &recipient, 1, 0).await
+}
+
+/// Credit any uncredited shares of `nft_id` to `user` now that they engaged
+/// with it. Returns the sharers whose affinity was strengthened.
+pub async fn credit_engagement(conn: &mut PgConnection, user: &str, nft_id: &str) -> Result<Vec<String>> {
+    let recipient = normalize_address(user)?;
+
+    let sharers = sqlx::query_scalar::<_, String>(
+        r#"
+        UPDATE content_shares
+        SET engaged_at = NOW()
+        WHERE recipient_address = $1
+        AND nft_id = $2::uuid
+        AND engaged_at IS NULL
+        RETURNING sharer_address
+        "#,
+    )
+    .bind(&recipient)
+    .bind(nft_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for sharer in &sharers {
+        bump_affinity(conn, sharer, &recipient, 0, 1).await?;
+        debug!("🤝 {} engaged with content shared by {}", recipient, sharer);
+    }
+
+    Ok(sharers)
+}
+
+/// Current sharer -> recipient affinity (0.0 if they have no share history)
+#[allow(dead_code)]
+pub async fn get_share_affinity(pool: &PgPool, sharer: &str, recipient: &str) -> Result<f32> {
+    let affinity = sqlx::query_scalar::<_, f32>(
+        "SELECT affinity FROM share_affinity WHERE sharer_address = $1 AND recipient_address = $2",
+    )
+    .bind(normalize_address(sharer)?)
+    .bind(normalize_address(recipient)?)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(affinity.unwrap_or(0.0))
+}
+
+async fn bump_affinity(
+    conn: &mut PgConnection,
+    sharer: &str,
+    recipient: &str,
+    shares: i32,
+    engaged_shares: i32,
+) -> Result<()> {
+    let (total_shares, total_engaged) = sqlx::query_as::<_, (i32, i32)>(
+        r#"
+        INSERT INTO share_affinity (sharer_address, recipient_address, shares, engaged_shares)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (sharer_address, recipient_address) DO UPDATE
+        SET shares = share_affinity.shares + EXCLUDED.shares,
+            engaged_shares = share_affinity.engaged_shares + EXCLUDED.engaged_shares,
+            updated_at = NOW()
+        RETURNING shares, engaged_shares
+        "#,
+    )
+    .bind(sharer)
+    .bind(recipient)
+    .bind(shares)
+    .bind(engaged_shares)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        "UPDATE share_affinity SET affinity = $3 WHERE sharer_address = $1 AND recipient_address = $2",
+    )
+    .bind(sharer)
+    .bind(recipient)
+    .bind(share_affinity(total_shares, total_engaged))
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engagement_outweighs_share_alone() {
+        assert_eq!(share_affinity(0, 0), 0.0);
+        assert!(share_affinity(1, 1) > share_affinity(1, 0));
+        assert!(share_affinity(1, 1) > share_affinity(5, 0));
+        assert_eq!(share_affinity(100, 100), 1.0);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_share_then_like_strengthens_affinity() {
+        let pool = crate::database::test_pool().await;
+
+        let sharer = "0x00000000000000000000000000000000000005a1";
+        let recipient = "0x00000000000000000000000000000000000005a2";
+        let nft_id = uuid::Uuid::new_v4().to_string();
+
+        let mut conn = pool.acquire().await.unwrap();
+        record_share(&mut conn, sharer, recipient, &nft_id).await.unwrap();
+        let after_share = get_share_affinity(&pool, sharer, recipient).await.unwrap();
+        assert!(after_share > 0.0);
+
+        // The recipient likes the shared NFT
+        assert_eq!(credit_engagement(&mut conn, recipient, &nft_id).await.unwrap(), vec![sharer]);
+        let after_like = get_share_affinity(&pool, sharer, recipient).await.unwrap();
+        assert!(after_like > after_share);
+
+        // A share is only credited once
+        assert!(credit_engagement(&mut conn, recipient, &nft_id).await.unwrap().is_empty());
+
+        for table in ["content_shares", "share_affinity"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE sharer_address = $1", table))
+                .bind(sharer)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+    }
+}
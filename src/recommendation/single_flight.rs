@@ -0,0 +1,93 @@
+//! Single-flight deduplication of feed recomputations, so a burst of
+//! requests against a cold cache computes the feed once instead of once
+//! per request
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::OnceCell;
+
+/// In-flight computations by key. Callers that arrive while a computation
+/// for their key is running wait for it and share its result; once it
+/// finishes the key is released and the next caller computes afresh.
+pub struct SingleFlight<V> {
+    calls: Mutex<HashMap<String, Arc<OnceCell<V>>>>,
+}
+
+impl<V> Default for SingleFlight<V> {
+    fn default() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<V: Clone> SingleFlight<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `compute` for `key`, or wait for the run already in flight.
+    /// If the leading caller is cancelled, one of the waiters takes over.
+    pub async fn run<F, Fut>(&self, key: &str, compute: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let cell = {
+            let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+            calls.entry(key.to_string()).or_default().clone()
+        };
+
+        let value = cell.get_or_init(compute).await.clone();
+
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        if calls.get(key).is_some_and(|current| Arc::ptr_eq(current, &cell)) {
+            calls.remove(key);
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_cold_requests_compute_once() {
+        let flight = Arc::new(SingleFlight::<Vec<String>>::new());
+        let computations = Arc::new(AtomicUsize::new(0));
+
+        let requests: Vec<_> = (0..10)
+            .map(|_| {
+                let flight = flight.clone();
+                let computations = computations.clone();
+                tokio::spawn(async move {
+                    flight
+                        .run("0xuser:personalized", || async move {
+                            computations.fetch_add(1, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            vec!["nft-1".to_string(), "nft-2".to_string()]
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        for request in requests {
+            assert_eq!(request.await.unwrap(), vec!["nft-1", "nft-2"]);
+        }
+        assert_eq!(computations.load(Ordering::SeqCst), 1);
+
+        // Once released, the next request recomputes
+        flight.run("0xuser:personalized", || async {
+            computations.fetch_add(1, Ordering::SeqCst);
+            Vec::new()
+        })
+        .await;
+        assert_eq!(computations.load(Ordering::SeqCst), 2);
+    }
+}
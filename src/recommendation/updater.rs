@@ -1,10 +1,53 @@
+use crate::kafka::{EventPublisher, UserActionEvent};
 use crate::recommendation::engine::RecommendationEngine;
 use chrono::{Duration as ChronoDuration, Utc};
 use sqlx::PgPool;
 use tracing::{error, info, warn};
 
-/// Update recommendations for all active users
-pub async fn update_all_recommendations(pool: &PgPool) -> anyhow::Result<()> {
+/// `action_type` of the event published after a user's feed is refreshed
+pub const RECOMMENDATIONS_REFRESHED: &str = "recommendations_refreshed";
+
+/// Tells downstream consumers (the Elixir app's GraphQL caches) that a
+/// user's recommendations were regenerated
+pub struct RefreshNotifier<P: EventPublisher> {
+    publisher: P,
+    topic: String,
+}
+
+impl<P: EventPublisher> RefreshNotifier<P> {
+    pub fn new(publisher: P, topic: impl Into<String>) -> Self {
+        Self {
+            publisher,
+            topic: topic.into(),
+        }
+    }
+
+    /// Publish a `recommendations_refreshed` event keyed by user. Failures
+    /// are logged, never returned: the feed is already refreshed.
+    pub async fn notify(&self, user_address: &str) {
+        let event = UserActionEvent {
+            action_type: RECOMMENDATIONS_REFRESHED.to_string(),
+            user_address: user_address.to_string(),
+            nft_id: None,
+            contract_type: None,
+            timestamp: Utc::now().timestamp(),
+            metadata: None,
+        };
+        if let Err(e) = self.publisher.send_event(&self.topic, user_address, &event).await {
+            warn!("Failed to publish refresh for {}: {:?}", user_address, e);
+        }
+    }
+}
+
+/// Update recommendations for all active users with `engine`, at most
+/// `concurrency` at a time, notifying `notifier` of each user whose feed
+/// was refreshed
+pub async fn update_all_recommendations<P: EventPublisher>(
+    pool: &PgPool,
+    engine: &RecommendationEngine,
+    concurrency: usize,
+    notifier: &RefreshNotifier<P>,
+) -> anyhow::Result<()> {
     // 1. Identify active users (interacted in last 7 days)
     // We look at interactions, or just users who have logged in/connected
     // For now, let's use the social_users table if it has last_seen, or just interactions.
@@ -59,16 +102,13 @@ pub async fn update_all_recommendations(pool: &PgPool) -> anyhow::Result<()> {
         users_to_update.len()
     );
 
-    // Limit concurrency to 10 simultaneous updates to prevent DB saturation
+    // Limit simultaneous updates to prevent DB saturation
     // (Alex Crichton / Niko Matsakis style: explicit concurrency control)
-    const CONCURRENCY_LIMIT: usize = 10;
 
     // Use a JoinSet to manage concurrent tasks and collect results
     let mut set = tokio::task::JoinSet::new();
-    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(CONCURRENCY_LIMIT));
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(concurrency.max(1)));
 
-    // Shared engine instance (cheap to clone as it just holds a pool)
-    let engine = RecommendationEngine::new(pool.clone());
     let graph_client = std::sync::Arc::new(crate::recommendation::graph_client::GraphClient::new());
 
     for user_address in users_to_update.clone() {
@@ -103,7 +143,10 @@ pub async fn update_all_recommendations(pool: &PgPool) -> anyhow::Result<()> {
         match res {
             Ok((addr, rec_res, follow_res, graph_res)) => {
                 match rec_res {
-                    Ok(_) => success_count += 1,
+                    Ok(_) => {
+                        success_count += 1;
+                        notifier.notify(&addr).await;
+                    }
                     Err(e) => warn!("Failed to generate recommendations for {}: {}", addr, e),
                 }
 
@@ -127,3 +170,28 @@ pub async fn update_all_recommendations(pool: &PgPool) -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kafka::InMemoryPublisher;
+
+    #[tokio::test]
+    async fn test_refresh_publishes_one_event_per_user() {
+        let publisher = InMemoryPublisher::new();
+        let notifier = RefreshNotifier::new(publisher.clone(), "recommendations");
+
+        for user in ["0xaaa", "0xbbb"] {
+            notifier.notify(user).await;
+        }
+
+        let messages = publisher.messages();
+        assert_eq!(messages.len(), 2);
+        for (message, user) in messages.iter().zip(["0xaaa", "0xbbb"]) {
+            assert_eq!(message.topic, "recommendations");
+            assert_eq!(message.key, user);
+            assert_eq!(message.payload["action_type"], RECOMMENDATIONS_REFRESHED);
+            assert_eq!(message.payload["user_address"], user);
+        }
+    }
+}
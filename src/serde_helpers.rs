@@ -0,0 +1,78 @@
+//! Serde helpers for API payloads
+
+/// Serialize an integer as a JSON string so JavaScript clients don't lose
+/// precision above 2^53. Deserializes from either a string or a number, so
+/// payloads written before the change still load.
+///
+/// ```
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Nft {
+///     #[serde(with = "theragraph::serde_helpers::string_int")]
+///     token_id: i64,
+/// }
+///
+/// let json = serde_json::to_string(&Nft { token_id: 1 << 60 }).unwrap();
+/// assert_eq!(json, r#"{"token_id":"1152921504606846976"}"#);
+/// let old: Nft = serde_json::from_str(r#"{"token_id":7}"#).unwrap();
+/// assert_eq!(old.token_id, 7);
+/// ```
+pub mod string_int {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::fmt::Display;
+    use std::str::FromStr;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrInt<T> {
+        String(String),
+        Int(T),
+    }
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr + Deserialize<'de>,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        match StringOrInt::<T>::deserialize(deserializer)? {
+            StringOrInt::String(s) => s.parse().map_err(de::Error::custom),
+            StringOrInt::Int(n) => Ok(n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Amount {
+        #[serde(with = "super::string_int")]
+        value: i64,
+    }
+
+    #[test]
+    fn test_large_int_serializes_as_string_and_round_trips() {
+        let value = (1i64 << 53) + 1;
+        let json = serde_json::to_value(Amount { value }).unwrap();
+        assert_eq!(
+            json["value"],
+            serde_json::Value::String("9007199254740993".to_string())
+        );
+
+        let back: Amount = serde_json::from_value(json).unwrap();
+        assert_eq!(back.value, value);
+
+        // Older payloads with plain numbers still load
+        let legacy: Amount = serde_json::from_str(r#"{"value": 42}"#).unwrap();
+        assert_eq!(legacy.value, 42);
+        assert!(serde_json::from_str::<Amount>(r#"{"value": "abc"}"#).is_err());
+    }
+}